@@ -20,7 +20,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let response = client
         .submit_flight_plan(FlightPlanRequest {
-            flight_plan_id: "".to_string(),
+            flight_plan_id: "FP-EXAMPLE".to_string(),
             data: "".to_string(),
         })
         .await?;
@@ -28,7 +28,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let response = client
         .request_flight_release(FlightReleaseRequest {
-            flight_plan_id: "".to_string(),
+            flight_plan_id: "FP-EXAMPLE".to_string(),
             data: "".to_string(),
         })
         .await?;
@@ -37,5 +37,51 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         response.into_inner()
     );
 
+    let response = client
+        .activate_flight_plan(FlightPlanActivateRequest {
+            flight_plan_id: "FP-EXAMPLE".to_string(),
+            departed_at: Some(std::time::SystemTime::now().into()),
+        })
+        .await?;
+    println!("activate_flight_plan RESPONSE={:?}", response.into_inner());
+
+    let response = client.get_release_keys(ReleaseKeysRequest {}).await?;
+    println!("get_release_keys RESPONSE={:?}", response.into_inner());
+
+    let response = client
+        .close_flight_plan(FlightPlanCloseRequest {
+            flight_plan_id: "FP-EXAMPLE".to_string(),
+            landed_at: Some(std::time::SystemTime::now().into()),
+        })
+        .await?;
+    println!("close_flight_plan RESPONSE={:?}", response.into_inner());
+
+    let response = client
+        .get_flight_plan_status(FlightPlanStatusRequest {
+            flight_plan_id: "FP-EXAMPLE".to_string(),
+        })
+        .await?;
+    println!(
+        "get_flight_plan_status RESPONSE={:?}",
+        response.into_inner()
+    );
+
+    let response = client.list_restrictions(RestrictionsRequest {}).await?;
+    println!("list_restrictions RESPONSE={:?}", response.into_inner());
+
+    let response = client.get_merge_report(MergeReportRequest {}).await?;
+    println!("get_merge_report RESPONSE={:?}", response.into_inner());
+
+    let response = client.list_waypoints(WaypointsRequest {}).await?;
+    println!("list_waypoints RESPONSE={:?}", response.into_inner());
+
+    let response = client
+        .get_waypoint_quality_report(WaypointQualityRequest {})
+        .await?;
+    println!(
+        "get_waypoint_quality_report RESPONSE={:?}",
+        response.into_inner()
+    );
+
     Ok(())
 }
@@ -28,6 +28,22 @@ cfg_if::cfg_if! {
                 let grpc_service = ServerImpl {
                     mq_channel: None,
                     region,
+                    restrictions: std::sync::Arc::new(
+                        svc_compliance::restrictions::RestrictionStore::default(),
+                    ),
+                    waypoints: std::sync::Arc::new(
+                        svc_compliance::waypoints::WaypointStore::default(),
+                    ),
+                    health: std::sync::Arc::new(
+                        svc_compliance::health::HealthState::default(),
+                    ),
+                    flight_plans: std::sync::Arc::new(
+                        svc_compliance::flight_plans::FlightPlanStore::default(),
+                    ),
+                    release: svc_compliance::releases::ReleaseConfig::default(),
+                    safety_buffers:
+                        svc_compliance::restrictions::buffer::BufferConfig::default(),
+                    release_signer: None,
                 };
 
                 lib_common::grpc::mock::start_mock_server(
@@ -102,6 +118,129 @@ impl service::Client<RpcServiceClient<Channel>> for ComplianceClient {
             .request_flight_release(request)
             .await
     }
+
+    async fn cancel_flight_plan(
+        &self,
+        request: FlightPlanCancelRequest,
+    ) -> Result<tonic::Response<FlightPlanCancelResponse>, tonic::Status> {
+        grpc_info!("(cancel_flight_plan) {} client.", self.get_name());
+        grpc_debug!("(cancel_flight_plan) request: {:?}", request);
+        self.get_client().await?.cancel_flight_plan(request).await
+    }
+
+    async fn amend_flight_plan(
+        &self,
+        request: FlightPlanRequest,
+    ) -> Result<tonic::Response<FlightPlanResponse>, tonic::Status> {
+        grpc_info!("(amend_flight_plan) {} client.", self.get_name());
+        grpc_debug!("(amend_flight_plan) request: {:?}", request);
+        self.get_client().await?.amend_flight_plan(request).await
+    }
+
+    async fn activate_flight_plan(
+        &self,
+        request: FlightPlanActivateRequest,
+    ) -> Result<tonic::Response<FlightPlanActivateResponse>, tonic::Status> {
+        grpc_info!("(activate_flight_plan) {} client.", self.get_name());
+        grpc_debug!("(activate_flight_plan) request: {:?}", request);
+        self.get_client().await?.activate_flight_plan(request).await
+    }
+
+    async fn get_release_keys(
+        &self,
+        request: ReleaseKeysRequest,
+    ) -> Result<tonic::Response<ReleaseKeys>, tonic::Status> {
+        grpc_info!("(get_release_keys) {} client.", self.get_name());
+        grpc_debug!("(get_release_keys) request: {:?}", request);
+        self.get_client().await?.get_release_keys(request).await
+    }
+
+    async fn close_flight_plan(
+        &self,
+        request: FlightPlanCloseRequest,
+    ) -> Result<tonic::Response<FlightPlanCloseResponse>, tonic::Status> {
+        grpc_info!("(close_flight_plan) {} client.", self.get_name());
+        grpc_debug!("(close_flight_plan) request: {:?}", request);
+        self.get_client().await?.close_flight_plan(request).await
+    }
+
+    async fn get_flight_plan_status(
+        &self,
+        request: FlightPlanStatusRequest,
+    ) -> Result<tonic::Response<FlightPlanStatus>, tonic::Status> {
+        grpc_info!("(get_flight_plan_status) {} client.", self.get_name());
+        grpc_debug!("(get_flight_plan_status) request: {:?}", request);
+        self.get_client()
+            .await?
+            .get_flight_plan_status(request)
+            .await
+    }
+
+    async fn create_restriction(
+        &self,
+        request: ManualRestriction,
+    ) -> Result<tonic::Response<ManualRestrictionResponse>, tonic::Status> {
+        grpc_warn!("(create_restriction) {} client.", self.get_name());
+        grpc_debug!("(create_restriction) request: {:?}", request);
+        self.get_client().await?.create_restriction(request).await
+    }
+
+    async fn update_restriction(
+        &self,
+        request: ManualRestriction,
+    ) -> Result<tonic::Response<ManualRestrictionResponse>, tonic::Status> {
+        grpc_warn!("(update_restriction) {} client.", self.get_name());
+        grpc_debug!("(update_restriction) request: {:?}", request);
+        self.get_client().await?.update_restriction(request).await
+    }
+
+    async fn delete_restriction(
+        &self,
+        request: DeleteRestrictionRequest,
+    ) -> Result<tonic::Response<ManualRestrictionResponse>, tonic::Status> {
+        grpc_warn!("(delete_restriction) {} client.", self.get_name());
+        grpc_debug!("(delete_restriction) request: {:?}", request);
+        self.get_client().await?.delete_restriction(request).await
+    }
+
+    async fn list_restrictions(
+        &self,
+        request: RestrictionsRequest,
+    ) -> Result<tonic::Response<RestrictionsResponse>, tonic::Status> {
+        grpc_info!("(list_restrictions) {} client.", self.get_name());
+        grpc_debug!("(list_restrictions) request: {:?}", request);
+        self.get_client().await?.list_restrictions(request).await
+    }
+
+    async fn get_merge_report(
+        &self,
+        request: MergeReportRequest,
+    ) -> Result<tonic::Response<MergeReport>, tonic::Status> {
+        grpc_info!("(get_merge_report) {} client.", self.get_name());
+        grpc_debug!("(get_merge_report) request: {:?}", request);
+        self.get_client().await?.get_merge_report(request).await
+    }
+
+    async fn list_waypoints(
+        &self,
+        request: WaypointsRequest,
+    ) -> Result<tonic::Response<WaypointsResponse>, tonic::Status> {
+        grpc_info!("(list_waypoints) {} client.", self.get_name());
+        grpc_debug!("(list_waypoints) request: {:?}", request);
+        self.get_client().await?.list_waypoints(request).await
+    }
+
+    async fn get_waypoint_quality_report(
+        &self,
+        request: WaypointQualityRequest,
+    ) -> Result<tonic::Response<WaypointQualityReport>, tonic::Status> {
+        grpc_info!("(get_waypoint_quality_report) {} client.", self.get_name());
+        grpc_debug!("(get_waypoint_quality_report) request: {:?}", request);
+        self.get_client()
+            .await?
+            .get_waypoint_quality_report(request)
+            .await
+    }
 }
 
 #[cfg(feature = "stub_client")]
@@ -128,6 +267,8 @@ impl service::Client<RpcServiceClient<Channel>> for ComplianceClient {
             flight_plan_id: request.flight_plan_id,
             submitted: true,
             result: None,
+            authority_reference: None,
+            pending: false,
         }))
     }
 
@@ -141,6 +282,181 @@ impl service::Client<RpcServiceClient<Channel>> for ComplianceClient {
             flight_plan_id: request.flight_plan_id,
             released: true,
             result: None,
+            release_token: None,
+        }))
+    }
+
+    async fn cancel_flight_plan(
+        &self,
+        request: FlightPlanCancelRequest,
+    ) -> Result<tonic::Response<FlightPlanCancelResponse>, tonic::Status> {
+        grpc_warn!("(cancel_flight_plan MOCK) {} client.", self.get_name());
+        grpc_debug!("(cancel_flight_plan MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(FlightPlanCancelResponse {
+            flight_plan_id: request.flight_plan_id,
+            cancelled: true,
+            result: None,
+        }))
+    }
+
+    async fn amend_flight_plan(
+        &self,
+        request: FlightPlanRequest,
+    ) -> Result<tonic::Response<FlightPlanResponse>, tonic::Status> {
+        grpc_warn!("(amend_flight_plan MOCK) {} client.", self.get_name());
+        grpc_debug!("(amend_flight_plan MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(FlightPlanResponse {
+            flight_plan_id: request.flight_plan_id,
+            submitted: true,
+            result: None,
+            authority_reference: None,
+            pending: false,
+        }))
+    }
+
+    async fn activate_flight_plan(
+        &self,
+        request: FlightPlanActivateRequest,
+    ) -> Result<tonic::Response<FlightPlanActivateResponse>, tonic::Status> {
+        grpc_warn!("(activate_flight_plan MOCK) {} client.", self.get_name());
+        grpc_debug!("(activate_flight_plan MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(FlightPlanActivateResponse {
+            flight_plan_id: request.flight_plan_id,
+            activated: true,
+            result: None,
+        }))
+    }
+
+    async fn get_release_keys(
+        &self,
+        request: ReleaseKeysRequest,
+    ) -> Result<tonic::Response<ReleaseKeys>, tonic::Status> {
+        grpc_warn!("(get_release_keys MOCK) {} client.", self.get_name());
+        grpc_debug!("(get_release_keys MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(ReleaseKeys {
+            issuer: "svc-compliance".to_string(),
+            keys: vec![],
+        }))
+    }
+
+    async fn close_flight_plan(
+        &self,
+        request: FlightPlanCloseRequest,
+    ) -> Result<tonic::Response<FlightPlanCloseResponse>, tonic::Status> {
+        grpc_warn!("(close_flight_plan MOCK) {} client.", self.get_name());
+        grpc_debug!("(close_flight_plan MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(FlightPlanCloseResponse {
+            flight_plan_id: request.flight_plan_id,
+            closed: true,
+            result: None,
+        }))
+    }
+
+    async fn get_flight_plan_status(
+        &self,
+        request: FlightPlanStatusRequest,
+    ) -> Result<tonic::Response<FlightPlanStatus>, tonic::Status> {
+        grpc_warn!("(get_flight_plan_status MOCK) {} client.", self.get_name());
+        grpc_debug!("(get_flight_plan_status MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(FlightPlanStatus {
+            flight_plan_id: request.flight_plan_id,
+            state: "accepted".to_string(),
+            authority_reference: None,
+            authority_message: None,
+            release: None,
+            history: vec![],
+        }))
+    }
+
+    async fn create_restriction(
+        &self,
+        request: ManualRestriction,
+    ) -> Result<tonic::Response<ManualRestrictionResponse>, tonic::Status> {
+        grpc_warn!("(create_restriction MOCK) {} client.", self.get_name());
+        grpc_debug!("(create_restriction MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(ManualRestrictionResponse {
+            identifier: request.identifier,
+            success: true,
+            result: None,
+        }))
+    }
+
+    async fn update_restriction(
+        &self,
+        request: ManualRestriction,
+    ) -> Result<tonic::Response<ManualRestrictionResponse>, tonic::Status> {
+        grpc_warn!("(update_restriction MOCK) {} client.", self.get_name());
+        grpc_debug!("(update_restriction MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(ManualRestrictionResponse {
+            identifier: request.identifier,
+            success: true,
+            result: None,
+        }))
+    }
+
+    async fn delete_restriction(
+        &self,
+        request: DeleteRestrictionRequest,
+    ) -> Result<tonic::Response<ManualRestrictionResponse>, tonic::Status> {
+        grpc_warn!("(delete_restriction MOCK) {} client.", self.get_name());
+        grpc_debug!("(delete_restriction MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(ManualRestrictionResponse {
+            identifier: request.identifier,
+            success: true,
+            result: None,
+        }))
+    }
+
+    async fn list_restrictions(
+        &self,
+        request: RestrictionsRequest,
+    ) -> Result<tonic::Response<RestrictionsResponse>, tonic::Status> {
+        grpc_warn!("(list_restrictions MOCK) {} client.", self.get_name());
+        grpc_debug!("(list_restrictions MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(RestrictionsResponse {
+            restrictions: vec![],
+        }))
+    }
+
+    async fn get_merge_report(
+        &self,
+        request: MergeReportRequest,
+    ) -> Result<tonic::Response<MergeReport>, tonic::Status> {
+        grpc_warn!("(get_merge_report MOCK) {} client.", self.get_name());
+        grpc_debug!("(get_merge_report MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(MergeReport {
+            timestamp: None,
+            feeds: vec![],
+            merged: 0,
+            conflicts: vec![],
+        }))
+    }
+
+    async fn list_waypoints(
+        &self,
+        request: WaypointsRequest,
+    ) -> Result<tonic::Response<WaypointsResponse>, tonic::Status> {
+        grpc_warn!("(list_waypoints MOCK) {} client.", self.get_name());
+        grpc_debug!("(list_waypoints MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(WaypointsResponse {
+            waypoints: vec![],
+        }))
+    }
+
+    async fn get_waypoint_quality_report(
+        &self,
+        request: WaypointQualityRequest,
+    ) -> Result<tonic::Response<WaypointQualityReport>, tonic::Status> {
+        grpc_warn!(
+            "(get_waypoint_quality_report MOCK) {} client.",
+            self.get_name()
+        );
+        grpc_debug!("(get_waypoint_quality_report MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(WaypointQualityReport {
+            timestamp: None,
+            received: 0,
+            accepted: 0,
+            issues: vec![],
         }))
     }
 }
@@ -202,6 +518,68 @@ mod tests {
         assert_eq!(result.submitted, true);
     }
 
+    #[tokio::test]
+    async fn test_client_list_restrictions() {
+        let name = "compliance";
+        let (server_host, server_port) =
+            lib_common::grpc::get_endpoint_from_env("GRPC_HOST", "GRPC_PORT");
+
+        let client = ComplianceClient::new_client(&server_host, server_port, name);
+
+        let result = client.list_restrictions(RestrictionsRequest {}).await;
+
+        assert!(result.is_ok());
+        let result: RestrictionsResponse = result.unwrap().into_inner();
+        println!("{:?}", result);
+    }
+
+    #[tokio::test]
+    async fn test_client_get_merge_report() {
+        let name = "compliance";
+        let (server_host, server_port) =
+            lib_common::grpc::get_endpoint_from_env("GRPC_HOST", "GRPC_PORT");
+
+        let client = ComplianceClient::new_client(&server_host, server_port, name);
+
+        let result = client.get_merge_report(MergeReportRequest {}).await;
+
+        assert!(result.is_ok());
+        let result: MergeReport = result.unwrap().into_inner();
+        println!("{:?}", result);
+    }
+
+    #[tokio::test]
+    async fn test_client_list_waypoints() {
+        let name = "compliance";
+        let (server_host, server_port) =
+            lib_common::grpc::get_endpoint_from_env("GRPC_HOST", "GRPC_PORT");
+
+        let client = ComplianceClient::new_client(&server_host, server_port, name);
+
+        let result = client.list_waypoints(WaypointsRequest {}).await;
+
+        assert!(result.is_ok());
+        let result: WaypointsResponse = result.unwrap().into_inner();
+        println!("{:?}", result);
+    }
+
+    #[tokio::test]
+    async fn test_client_get_waypoint_quality_report() {
+        let name = "compliance";
+        let (server_host, server_port) =
+            lib_common::grpc::get_endpoint_from_env("GRPC_HOST", "GRPC_PORT");
+
+        let client = ComplianceClient::new_client(&server_host, server_port, name);
+
+        let result = client
+            .get_waypoint_quality_report(WaypointQualityRequest {})
+            .await;
+
+        assert!(result.is_ok());
+        let result: WaypointQualityReport = result.unwrap().into_inner();
+        println!("{:?}", result);
+    }
+
     #[tokio::test]
     async fn test_grpc_request_flight_release() {
         let name = "compliance";
@@ -222,4 +600,89 @@ mod tests {
         println!("{:?}", result);
         assert_eq!(result.released, true);
     }
+
+    #[tokio::test]
+    async fn test_client_get_flight_plan_status() {
+        let name = "compliance";
+        let (server_host, server_port) =
+            lib_common::grpc::get_endpoint_from_env("GRPC_HOST", "GRPC_PORT");
+
+        let client = ComplianceClient::new_client(&server_host, server_port, name);
+
+        let result = client
+            .get_flight_plan_status(FlightPlanStatusRequest {
+                flight_plan_id: "FP-1".to_string(),
+            })
+            .await;
+
+        assert!(result.is_ok());
+        let result: FlightPlanStatus = result.unwrap().into_inner();
+        println!("{:?}", result);
+        assert_eq!(result.flight_plan_id, "FP-1");
+    }
+
+    #[tokio::test]
+    async fn test_client_cancel_amend_close_flight_plan() {
+        let name = "compliance";
+        let (server_host, server_port) =
+            lib_common::grpc::get_endpoint_from_env("GRPC_HOST", "GRPC_PORT");
+
+        let client = ComplianceClient::new_client(&server_host, server_port, name);
+
+        let result = client
+            .amend_flight_plan(FlightPlanRequest {
+                flight_plan_id: "FP-1".to_string(),
+                data: "".to_string(),
+            })
+            .await;
+        println!("{:?}", result);
+        assert_eq!(result.unwrap().into_inner().submitted, true);
+
+        let result = client
+            .cancel_flight_plan(FlightPlanCancelRequest {
+                flight_plan_id: "FP-1".to_string(),
+                reason: None,
+            })
+            .await;
+        println!("{:?}", result);
+        assert_eq!(result.unwrap().into_inner().cancelled, true);
+
+        let result = client
+            .close_flight_plan(FlightPlanCloseRequest {
+                flight_plan_id: "FP-1".to_string(),
+                landed_at: Some(std::time::SystemTime::now().into()),
+            })
+            .await;
+        println!("{:?}", result);
+        assert_eq!(result.unwrap().into_inner().closed, true);
+    }
+
+    #[tokio::test]
+    async fn test_client_activate_flight_plan() {
+        let name = "compliance";
+        let (server_host, server_port) =
+            lib_common::grpc::get_endpoint_from_env("GRPC_HOST", "GRPC_PORT");
+
+        let client = ComplianceClient::new_client(&server_host, server_port, name);
+        let result = client
+            .activate_flight_plan(FlightPlanActivateRequest {
+                flight_plan_id: "FP-1".to_string(),
+                departed_at: Some(std::time::SystemTime::now().into()),
+            })
+            .await;
+        println!("{:?}", result);
+        assert_eq!(result.unwrap().into_inner().activated, true);
+    }
+
+    #[tokio::test]
+    async fn test_client_get_release_keys() {
+        let name = "compliance";
+        let (server_host, server_port) =
+            lib_common::grpc::get_endpoint_from_env("GRPC_HOST", "GRPC_PORT");
+
+        let client = ComplianceClient::new_client(&server_host, server_port, name);
+        let result = client.get_release_keys(ReleaseKeysRequest {}).await;
+        println!("{:?}", result);
+        assert_eq!(result.unwrap().into_inner().issuer, "svc-compliance");
+    }
 }
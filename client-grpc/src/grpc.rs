@@ -1,3 +1,15 @@
+/// Coordinates
+#[derive(Copy)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Coordinates {
+    /// Latitude in degrees
+    #[prost(double, tag = "1")]
+    pub latitude: f64,
+    /// Longitude in degrees
+    #[prost(double, tag = "2")]
+    pub longitude: f64,
+}
 /// FlightPlanRequest
 #[derive(serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -23,6 +35,13 @@ pub struct FlightPlanResponse {
     /// Optional error or warning message
     #[prost(string, optional, tag = "3")]
     pub result: ::core::option::Option<::prost::alloc::string::String>,
+    /// Reference number assigned by the authority, if any
+    #[prost(string, optional, tag = "4")]
+    pub authority_reference: ::core::option::Option<::prost::alloc::string::String>,
+    /// The authority decides later, the decision is published on AMQP
+    /// and can be tracked with the authority reference
+    #[prost(bool, tag = "5")]
+    pub pending: bool,
 }
 /// FlightReleaseRequest
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -48,6 +67,456 @@ pub struct FlightReleaseResponse {
     /// Optional error or warning message
     #[prost(string, optional, tag = "3")]
     pub result: ::core::option::Option<::prost::alloc::string::String>,
+    /// Signed token proving the release, if tokens are issued
+    #[prost(string, optional, tag = "4")]
+    pub release_token: ::core::option::Option<::prost::alloc::string::String>,
+}
+/// FlightPlanCancelRequest
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FlightPlanCancelRequest {
+    /// Flight Plan Id
+    #[prost(string, tag = "1")]
+    pub flight_plan_id: ::prost::alloc::string::String,
+    /// Why the flight plan is cancelled
+    #[prost(string, optional, tag = "2")]
+    pub reason: ::core::option::Option<::prost::alloc::string::String>,
+}
+/// FlightPlanCancelResponse
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FlightPlanCancelResponse {
+    /// Flight Plan Id
+    #[prost(string, tag = "1")]
+    pub flight_plan_id: ::prost::alloc::string::String,
+    /// Status result for cancelled
+    #[prost(bool, tag = "2")]
+    pub cancelled: bool,
+    /// Optional error or warning message
+    #[prost(string, optional, tag = "3")]
+    pub result: ::core::option::Option<::prost::alloc::string::String>,
+}
+/// FlightPlanActivateRequest
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FlightPlanActivateRequest {
+    /// Flight Plan Id
+    #[prost(string, tag = "1")]
+    pub flight_plan_id: ::prost::alloc::string::String,
+    /// Actual takeoff time
+    #[prost(message, optional, tag = "2")]
+    pub departed_at: ::core::option::Option<::prost_types::Timestamp>,
+}
+/// FlightPlanActivateResponse
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FlightPlanActivateResponse {
+    /// Flight Plan Id
+    #[prost(string, tag = "1")]
+    pub flight_plan_id: ::prost::alloc::string::String,
+    /// Status result for activated
+    #[prost(bool, tag = "2")]
+    pub activated: bool,
+    /// Optional error or warning message
+    #[prost(string, optional, tag = "3")]
+    pub result: ::core::option::Option<::prost::alloc::string::String>,
+}
+/// FlightPlanCloseRequest
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FlightPlanCloseRequest {
+    /// Flight Plan Id
+    #[prost(string, tag = "1")]
+    pub flight_plan_id: ::prost::alloc::string::String,
+    /// Actual landing time
+    #[prost(message, optional, tag = "2")]
+    pub landed_at: ::core::option::Option<::prost_types::Timestamp>,
+}
+/// FlightPlanCloseResponse
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FlightPlanCloseResponse {
+    /// Flight Plan Id
+    #[prost(string, tag = "1")]
+    pub flight_plan_id: ::prost::alloc::string::String,
+    /// Status result for closed
+    #[prost(bool, tag = "2")]
+    pub closed: bool,
+    /// Optional error or warning message
+    #[prost(string, optional, tag = "3")]
+    pub result: ::core::option::Option<::prost::alloc::string::String>,
+}
+/// FlightPlanStatusRequest
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FlightPlanStatusRequest {
+    /// Flight Plan Id
+    #[prost(string, tag = "1")]
+    pub flight_plan_id: ::prost::alloc::string::String,
+}
+/// FlightPlanTransition
+/// A state entered by a flight plan
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FlightPlanTransition {
+    /// State entered (draft, submitted, pending_authority, accepted, rejected,
+    /// released, active, closed, cancelled or expired)
+    #[prost(string, tag = "1")]
+    pub state: ::prost::alloc::string::String,
+    /// When the state was entered
+    #[prost(message, optional, tag = "2")]
+    pub timestamp: ::core::option::Option<::prost_types::Timestamp>,
+    /// Why the state was entered, if known
+    #[prost(string, optional, tag = "3")]
+    pub reason: ::core::option::Option<::prost::alloc::string::String>,
+}
+/// ReleaseStatus
+/// Release granted for a flight plan
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReleaseStatus {
+    /// When the release was granted
+    #[prost(message, optional, tag = "1")]
+    pub released_at: ::core::option::Option<::prost_types::Timestamp>,
+    /// When the release stops being valid, if ever
+    #[prost(message, optional, tag = "2")]
+    pub valid_until: ::core::option::Option<::prost_types::Timestamp>,
+    /// When the release was revoked, if it was
+    #[prost(message, optional, tag = "3")]
+    pub revoked_at: ::core::option::Option<::prost_types::Timestamp>,
+    /// Why the release was revoked
+    #[prost(string, optional, tag = "4")]
+    pub revoked_reason: ::core::option::Option<::prost::alloc::string::String>,
+    /// Signed token proving the release, if one was issued
+    #[prost(string, optional, tag = "5")]
+    pub token: ::core::option::Option<::prost::alloc::string::String>,
+}
+/// ReleaseKeysRequest
+///
+/// No arguments
+#[derive(Eq, Copy)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReleaseKeysRequest {}
+/// ReleaseKey
+/// Public key release tokens are signed with
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReleaseKey {
+    /// Identifier of the key, the `kid` header of the tokens
+    #[prost(string, tag = "1")]
+    pub key_id: ::prost::alloc::string::String,
+    /// Signature algorithm, always `EdDSA`
+    #[prost(string, tag = "2")]
+    pub algorithm: ::prost::alloc::string::String,
+    /// Base64url encoded Ed25519 public key
+    #[prost(string, tag = "3")]
+    pub public_key: ::prost::alloc::string::String,
+    /// Whether new tokens are signed with this key
+    #[prost(bool, tag = "4")]
+    pub signing: bool,
+}
+/// ReleaseKeys
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReleaseKeys {
+    /// Issuer of the tokens, the `iss` claim
+    #[prost(string, tag = "1")]
+    pub issuer: ::prost::alloc::string::String,
+    /// Keys by identifier
+    #[prost(message, repeated, tag = "2")]
+    pub keys: ::prost::alloc::vec::Vec<ReleaseKey>,
+}
+/// FlightPlanStatus
+/// Lifecycle status of a flight plan
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FlightPlanStatus {
+    /// Flight Plan Id
+    #[prost(string, tag = "1")]
+    pub flight_plan_id: ::prost::alloc::string::String,
+    /// Current state (see FlightPlanTransition)
+    #[prost(string, tag = "2")]
+    pub state: ::prost::alloc::string::String,
+    /// Reference number assigned by the authority, if any
+    #[prost(string, optional, tag = "3")]
+    pub authority_reference: ::core::option::Option<::prost::alloc::string::String>,
+    /// Latest message of the authority, if any
+    #[prost(string, optional, tag = "4")]
+    pub authority_message: ::core::option::Option<::prost::alloc::string::String>,
+    /// The release, if one was granted
+    #[prost(message, optional, tag = "5")]
+    pub release: ::core::option::Option<ReleaseStatus>,
+    /// Every state entered, oldest first
+    #[prost(message, repeated, tag = "6")]
+    pub history: ::prost::alloc::vec::Vec<FlightPlanTransition>,
+}
+/// ManualRestriction
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ManualRestriction {
+    /// Restriction identifier, generated on creation if empty
+    #[prost(string, tag = "1")]
+    pub identifier: ::prost::alloc::string::String,
+    /// Boundary vertices of the restriction
+    #[prost(message, repeated, tag = "2")]
+    pub vertices: ::prost::alloc::vec::Vec<Coordinates>,
+    /// Minimum altitude in meters
+    #[prost(float, tag = "3")]
+    pub altitude_meters_min: f32,
+    /// Maximum altitude in meters
+    #[prost(float, tag = "4")]
+    pub altitude_meters_max: f32,
+    /// Start of the restriction, active immediately if not provided
+    #[prost(message, optional, tag = "5")]
+    pub time_start: ::core::option::Option<::prost_types::Timestamp>,
+    /// Expiry of the restriction
+    #[prost(message, optional, tag = "6")]
+    pub time_end: ::core::option::Option<::prost_types::Timestamp>,
+    /// Reason for the restriction
+    #[prost(string, tag = "7")]
+    pub reason: ::prost::alloc::string::String,
+    /// Operator creating or changing the restriction
+    #[prost(string, tag = "8")]
+    pub author: ::prost::alloc::string::String,
+}
+/// ManualRestrictionResponse
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ManualRestrictionResponse {
+    /// Restriction identifier
+    #[prost(string, tag = "1")]
+    pub identifier: ::prost::alloc::string::String,
+    /// Status result of the change
+    #[prost(bool, tag = "2")]
+    pub success: bool,
+    /// Optional error or warning message
+    #[prost(string, optional, tag = "3")]
+    pub result: ::core::option::Option<::prost::alloc::string::String>,
+}
+/// DeleteRestrictionRequest
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteRestrictionRequest {
+    /// Restriction identifier
+    #[prost(string, tag = "1")]
+    pub identifier: ::prost::alloc::string::String,
+    /// Operator deleting the restriction
+    #[prost(string, tag = "2")]
+    pub author: ::prost::alloc::string::String,
+}
+/// RestrictionsRequest
+///
+/// No arguments
+#[derive(Copy)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RestrictionsRequest {}
+/// Restriction
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Restriction {
+    /// Restriction identifier
+    #[prost(string, tag = "1")]
+    pub identifier: ::prost::alloc::string::String,
+    /// Boundary vertices of the restriction
+    #[prost(message, repeated, tag = "2")]
+    pub vertices: ::prost::alloc::vec::Vec<Coordinates>,
+    /// Minimum altitude in meters
+    #[prost(float, tag = "3")]
+    pub altitude_meters_min: f32,
+    /// Maximum altitude in meters
+    #[prost(float, tag = "4")]
+    pub altitude_meters_max: f32,
+    /// Start of the restriction
+    #[prost(message, optional, tag = "5")]
+    pub time_start: ::core::option::Option<::prost_types::Timestamp>,
+    /// End of the restriction
+    #[prost(message, optional, tag = "6")]
+    pub time_end: ::core::option::Option<::prost_types::Timestamp>,
+    /// Restriction type
+    #[prost(string, tag = "7")]
+    pub zone_type: ::prost::alloc::string::String,
+    /// Kind of source the restriction was taken from (static, file, notam or manual)
+    #[prost(string, tag = "8")]
+    pub source: ::prost::alloc::string::String,
+    /// Reason for a manual restriction
+    #[prost(string, optional, tag = "9")]
+    pub reason: ::core::option::Option<::prost::alloc::string::String>,
+    /// Operator of a manual restriction
+    #[prost(string, optional, tag = "10")]
+    pub author: ::core::option::Option<::prost::alloc::string::String>,
+    /// Feed the restriction was taken from
+    #[prost(string, tag = "11")]
+    pub feed: ::prost::alloc::string::String,
+}
+/// RestrictionsResponse
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RestrictionsResponse {
+    /// All restrictions known to the service
+    #[prost(message, repeated, tag = "1")]
+    pub restrictions: ::prost::alloc::vec::Vec<Restriction>,
+}
+/// MergeReportRequest
+///
+/// No arguments
+#[derive(Copy)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MergeReportRequest {}
+/// FeedSummary
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FeedSummary {
+    /// Kind of source providing the feed
+    #[prost(string, tag = "1")]
+    pub source: ::prost::alloc::string::String,
+    /// Name of the feed
+    #[prost(string, tag = "2")]
+    pub feed: ::prost::alloc::string::String,
+    /// Number of restrictions provided by the feed
+    #[prost(uint32, tag = "3")]
+    pub restrictions: u32,
+}
+/// Reference to a restriction within a feed
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FeedReference {
+    /// Kind of source providing the feed
+    #[prost(string, tag = "1")]
+    pub source: ::prost::alloc::string::String,
+    /// Name of the feed
+    #[prost(string, tag = "2")]
+    pub feed: ::prost::alloc::string::String,
+    /// Restriction identifier within the feed
+    #[prost(string, tag = "3")]
+    pub identifier: ::prost::alloc::string::String,
+}
+/// MergeConflict
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MergeConflict {
+    /// The restriction that was kept
+    #[prost(message, optional, tag = "1")]
+    pub kept: ::core::option::Option<FeedReference>,
+    /// The restriction that was dropped
+    #[prost(message, optional, tag = "2")]
+    pub dropped: ::core::option::Option<FeedReference>,
+    /// Why the restriction was dropped
+    #[prost(string, tag = "3")]
+    pub reason: ::prost::alloc::string::String,
+}
+/// MergeReport
+/// Outcome of merging the restriction feeds by source priority
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MergeReport {
+    /// When the merge was performed
+    #[prost(message, optional, tag = "1")]
+    pub timestamp: ::core::option::Option<::prost_types::Timestamp>,
+    /// Merged feeds in order of precedence
+    #[prost(message, repeated, tag = "2")]
+    pub feeds: ::prost::alloc::vec::Vec<FeedSummary>,
+    /// Number of restrictions accepted
+    #[prost(uint32, tag = "3")]
+    pub merged: u32,
+    /// Restrictions dropped in favor of others
+    #[prost(message, repeated, tag = "4")]
+    pub conflicts: ::prost::alloc::vec::Vec<MergeConflict>,
+}
+/// WaypointsRequest
+///
+/// No arguments
+#[derive(Copy)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WaypointsRequest {}
+/// Waypoint
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Waypoint {
+    /// Waypoint identifier
+    #[prost(string, tag = "1")]
+    pub identifier: ::prost::alloc::string::String,
+    /// Location of the waypoint
+    #[prost(message, optional, tag = "2")]
+    pub location: ::core::option::Option<Coordinates>,
+    /// Published name of the waypoint
+    #[prost(string, optional, tag = "3")]
+    pub name: ::core::option::Option<::prost::alloc::string::String>,
+    /// Waypoint type (fix, vertiport_entry or holding_point)
+    #[prost(string, tag = "4")]
+    pub waypoint_type: ::prost::alloc::string::String,
+    /// Minimum altitude in meters at this waypoint
+    #[prost(float, optional, tag = "5")]
+    pub altitude_meters_min: ::core::option::Option<f32>,
+    /// Maximum altitude in meters at this waypoint
+    #[prost(float, optional, tag = "6")]
+    pub altitude_meters_max: ::core::option::Option<f32>,
+    /// Start of the validity of the waypoint
+    #[prost(message, optional, tag = "7")]
+    pub time_start: ::core::option::Option<::prost_types::Timestamp>,
+    /// End of the validity of the waypoint
+    #[prost(message, optional, tag = "8")]
+    pub time_end: ::core::option::Option<::prost_types::Timestamp>,
+    /// Active restriction the waypoint is withheld for, if any
+    #[prost(string, optional, tag = "9")]
+    pub withheld_by: ::core::option::Option<::prost::alloc::string::String>,
+}
+/// WaypointsResponse
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WaypointsResponse {
+    /// All waypoints known to the service
+    #[prost(message, repeated, tag = "1")]
+    pub waypoints: ::prost::alloc::vec::Vec<Waypoint>,
+}
+/// WaypointQualityRequest
+///
+/// No arguments
+#[derive(Copy)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WaypointQualityRequest {}
+/// WaypointQualityIssue
+/// A quality issue found on an imported waypoint
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WaypointQualityIssue {
+    /// Identifier of the waypoint
+    #[prost(string, tag = "1")]
+    pub identifier: ::prost::alloc::string::String,
+    /// Kind of issue (invalid_identifier, identifier_collision or near_duplicate)
+    #[prost(string, tag = "2")]
+    pub kind: ::prost::alloc::string::String,
+    /// The other waypoint involved, if any
+    #[prost(string, optional, tag = "3")]
+    pub related: ::core::option::Option<::prost::alloc::string::String>,
+    /// Distance in meters to the related waypoint, for near-duplicates
+    #[prost(double, optional, tag = "4")]
+    pub distance_meters: ::core::option::Option<f64>,
+    /// What happened to the waypoint (rejected or merged)
+    #[prost(string, tag = "5")]
+    pub action: ::prost::alloc::string::String,
+}
+/// WaypointQualityReport
+/// Outcome of the quality checks on the last waypoint import
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WaypointQualityReport {
+    /// When the checks were performed
+    #[prost(message, optional, tag = "1")]
+    pub timestamp: ::core::option::Option<::prost_types::Timestamp>,
+    /// Number of waypoints received from the source
+    #[prost(uint32, tag = "2")]
+    pub received: u32,
+    /// Number of waypoints accepted
+    #[prost(uint32, tag = "3")]
+    pub accepted: u32,
+    /// Issues found, in source order
+    #[prost(message, repeated, tag = "4")]
+    pub issues: ::prost::alloc::vec::Vec<WaypointQualityIssue>,
 }
 /// ReadyRequest body
 ///
@@ -225,5 +694,337 @@ pub mod rpc_service_client {
                 .insert(GrpcMethod::new("grpc.RpcService", "requestFlightRelease"));
             self.inner.unary(req, path, codec).await
         }
+        /// cancel flight plan
+        pub async fn cancel_flight_plan(
+            &mut self,
+            request: impl tonic::IntoRequest<super::FlightPlanCancelRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::FlightPlanCancelResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/cancelFlightPlan",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "cancelFlightPlan"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// amend and resubmit flight plan
+        pub async fn amend_flight_plan(
+            &mut self,
+            request: impl tonic::IntoRequest<super::FlightPlanRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::FlightPlanResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/amendFlightPlan",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "amendFlightPlan"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// report the takeoff of a released flight plan
+        pub async fn activate_flight_plan(
+            &mut self,
+            request: impl tonic::IntoRequest<super::FlightPlanActivateRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::FlightPlanActivateResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/activateFlightPlan",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "activateFlightPlan"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// close flight plan after landing
+        pub async fn close_flight_plan(
+            &mut self,
+            request: impl tonic::IntoRequest<super::FlightPlanCloseRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::FlightPlanCloseResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/closeFlightPlan",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "closeFlightPlan"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// lifecycle status of a flight plan
+        pub async fn get_flight_plan_status(
+            &mut self,
+            request: impl tonic::IntoRequest<super::FlightPlanStatusRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::FlightPlanStatus>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/getFlightPlanStatus",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "getFlightPlanStatus"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// public keys release tokens are signed with
+        pub async fn get_release_keys(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ReleaseKeysRequest>,
+        ) -> std::result::Result<tonic::Response<super::ReleaseKeys>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/getReleaseKeys",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "getReleaseKeys"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// create a manual restriction (admin)
+        pub async fn create_restriction(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ManualRestriction>,
+        ) -> std::result::Result<
+            tonic::Response<super::ManualRestrictionResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/createRestriction",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "createRestriction"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// update a manual restriction (admin)
+        pub async fn update_restriction(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ManualRestriction>,
+        ) -> std::result::Result<
+            tonic::Response<super::ManualRestrictionResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/updateRestriction",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "updateRestriction"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// delete a manual restriction (admin)
+        pub async fn delete_restriction(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DeleteRestrictionRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ManualRestrictionResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/deleteRestriction",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "deleteRestriction"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// list all known restrictions (admin)
+        pub async fn list_restrictions(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RestrictionsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RestrictionsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/listRestrictions",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "listRestrictions"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// report of the last restriction feed merge (admin)
+        pub async fn get_merge_report(
+            &mut self,
+            request: impl tonic::IntoRequest<super::MergeReportRequest>,
+        ) -> std::result::Result<tonic::Response<super::MergeReport>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/getMergeReport",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "getMergeReport"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// list all known waypoints
+        pub async fn list_waypoints(
+            &mut self,
+            request: impl tonic::IntoRequest<super::WaypointsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::WaypointsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/listWaypoints",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "listWaypoints"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// report of the last waypoint import quality checks (admin)
+        pub async fn get_waypoint_quality_report(
+            &mut self,
+            request: impl tonic::IntoRequest<super::WaypointQualityRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::WaypointQualityReport>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/getWaypointQualityReport",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "getWaypointQualityReport"));
+            self.inner.unary(req, path, codec).await
+        }
     }
 }
@@ -2,6 +2,7 @@
 
 pub mod client;
 pub mod prelude;
+pub mod release_token;
 pub mod service;
 
 use client::*;
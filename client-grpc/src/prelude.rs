@@ -1,6 +1,7 @@
 //! Re-export of used objects
 
 pub use super::client as compliance;
+pub use super::release_token;
 pub use super::service::Client as ComplianceServiceClient;
 pub use compliance::ComplianceClient;
 
@@ -0,0 +1,261 @@
+//! Verification of release tokens
+//!
+//! Releases granted by the service come with a compact JWS signed with
+//!  Ed25519. [`verify_release_token`] checks a token against the keys
+//!  returned by `getReleaseKeys`, without contacting the service, and
+//!  returns its claims. Compare [`ReleaseClaims::route_hash`] with
+//!  [`route_hash`] of the route to be flown to make sure the release covers
+//!  it.
+
+use crate::client::ReleaseKey;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ed25519_dalek::{Signature, VerifyingKey};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Errors from verifying a release token
+#[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenError {
+    /// The token is not a compact JWS with the expected header and claims
+    #[error("error: Malformed release token.")]
+    Malformed,
+
+    /// The token is not signed with EdDSA
+    #[error("error: Unsupported release token algorithm.")]
+    UnsupportedAlgorithm,
+
+    /// The token is signed with a key that is not known
+    #[error("error: Unknown release token key.")]
+    UnknownKey,
+
+    /// The signature does not match
+    #[error("error: Invalid release token signature.")]
+    InvalidSignature,
+
+    /// The release is not valid yet
+    #[error("error: Release token not valid yet.")]
+    NotYetValid,
+
+    /// The release ran out
+    #[error("error: Release token expired.")]
+    Expired,
+}
+
+/// Header of a release token
+#[derive(Debug, Clone, Deserialize)]
+struct TokenHeader {
+    /// Signature algorithm
+    alg: String,
+
+    /// Identifier of the signing key
+    kid: String,
+}
+
+/// Claims of a release token
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ReleaseClaims {
+    /// Issuer of the token
+    pub iss: String,
+
+    /// Identifier of the released flight plan
+    pub sub: String,
+
+    /// Identifier of the released aircraft
+    pub aircraft: String,
+
+    /// Hash of the route, see [`route_hash`]
+    pub route_hash: String,
+
+    /// When the release was granted, in unix seconds
+    pub iat: i64,
+
+    /// When the release starts being valid, in unix seconds
+    pub nbf: i64,
+
+    /// When the release stops being valid, in unix seconds
+    pub exp: i64,
+}
+
+/// Returns the hash of a route of `(latitude, longitude)` points, as put in
+///  the release token by the service
+pub fn route_hash(route: &[(f64, f64)]) -> String {
+    let route = route
+        .iter()
+        .map(|(latitude, longitude)| format!("{:.7},{:.7}", latitude, longitude))
+        .collect::<Vec<String>>()
+        .join(";");
+
+    hex::encode(Sha256::digest(route.as_bytes()))
+}
+
+/// Verifies a release token with the published keys, and returns its claims
+///  if the release is valid at the provided time
+pub fn verify_release_token(
+    token: &str,
+    keys: &[ReleaseKey],
+    now: SystemTime,
+) -> Result<ReleaseClaims, TokenError> {
+    let mut parts = token.split('.');
+    let (Some(header), Some(claims), Some(signature), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(TokenError::Malformed);
+    };
+
+    let decoded = URL_SAFE_NO_PAD
+        .decode(header)
+        .map_err(|_| TokenError::Malformed)?;
+    let header: TokenHeader =
+        serde_json::from_slice(&decoded).map_err(|_| TokenError::Malformed)?;
+    if header.alg != "EdDSA" {
+        return Err(TokenError::UnsupportedAlgorithm);
+    }
+
+    let key = keys
+        .iter()
+        .find(|key| key.key_id == header.kid && key.algorithm == "EdDSA")
+        .ok_or(TokenError::UnknownKey)?;
+    let key: [u8; 32] = URL_SAFE_NO_PAD
+        .decode(&key.public_key)
+        .ok()
+        .and_then(|key| key.try_into().ok())
+        .ok_or(TokenError::UnknownKey)?;
+    let key = VerifyingKey::from_bytes(&key).map_err(|_| TokenError::UnknownKey)?;
+
+    let signature: [u8; 64] = URL_SAFE_NO_PAD
+        .decode(signature)
+        .ok()
+        .and_then(|signature| signature.try_into().ok())
+        .ok_or(TokenError::Malformed)?;
+    let signing_input = &token[..token.rfind('.').unwrap_or_default()];
+    key.verify_strict(signing_input.as_bytes(), &Signature::from_bytes(&signature))
+        .map_err(|_| TokenError::InvalidSignature)?;
+
+    let decoded = URL_SAFE_NO_PAD
+        .decode(claims)
+        .map_err(|_| TokenError::Malformed)?;
+    let claims: ReleaseClaims =
+        serde_json::from_slice(&decoded).map_err(|_| TokenError::Malformed)?;
+
+    let now = match now.duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => elapsed.as_secs() as i64,
+        Err(_) => return Err(TokenError::NotYetValid),
+    };
+    if now < claims.nbf {
+        return Err(TokenError::NotYetValid);
+    }
+    if now >= claims.exp {
+        return Err(TokenError::Expired);
+    }
+
+    Ok(claims)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+    use std::time::Duration;
+
+    const RELEASED_AT: u64 = 1700000000;
+
+    fn sign(key: &SigningKey, header: &str, claims: &str) -> String {
+        let signing_input = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(header),
+            URL_SAFE_NO_PAD.encode(claims)
+        );
+        let signature = key.sign(signing_input.as_bytes());
+        format!(
+            "{signing_input}.{}",
+            URL_SAFE_NO_PAD.encode(signature.to_bytes())
+        )
+    }
+
+    fn release_key(key_id: &str, key: &SigningKey) -> ReleaseKey {
+        ReleaseKey {
+            key_id: key_id.to_string(),
+            algorithm: "EdDSA".to_string(),
+            public_key: URL_SAFE_NO_PAD.encode(key.verifying_key().as_bytes()),
+            signing: true,
+        }
+    }
+
+    #[test]
+    fn test_route_hash() {
+        // sha256("52.0000000,4.0000000;52.1000000,4.1000000"), also checked
+        //  by the server
+        assert_eq!(
+            route_hash(&[(52.0, 4.0), (52.1, 4.1)]),
+            "aa44255c85140a372b4f9059c58c76b809f1d657e14100527309df5bdb5e541e"
+        );
+    }
+
+    #[test]
+    fn test_verify_release_token() {
+        let key = SigningKey::from_bytes(&[2; 32]);
+        let keys = [
+            release_key("key-1", &SigningKey::from_bytes(&[1; 32])),
+            release_key("key-2", &key),
+        ];
+        let header = r#"{"alg":"EdDSA","typ":"JWT","kid":"key-2"}"#;
+        let claims = format!(
+            r#"{{"iss":"svc-compliance","sub":"FP-1","aircraft":"A-1","route_hash":"{}","iat":{RELEASED_AT},"nbf":{RELEASED_AT},"exp":{}}}"#,
+            route_hash(&[(52.0, 4.0), (52.1, 4.1)]),
+            RELEASED_AT + 900
+        );
+        let token = sign(&key, header, &claims);
+        let at = |seconds: u64| UNIX_EPOCH + Duration::from_secs(seconds);
+
+        let claims = verify_release_token(&token, &keys, at(RELEASED_AT + 60)).unwrap();
+        assert_eq!(claims.sub, "FP-1");
+        assert_eq!(claims.route_hash, route_hash(&[(52.0, 4.0), (52.1, 4.1)]));
+
+        // Only while the release is valid
+        assert_eq!(
+            verify_release_token(&token, &keys, at(RELEASED_AT - 1)).unwrap_err(),
+            TokenError::NotYetValid
+        );
+        assert_eq!(
+            verify_release_token(&token, &keys, at(RELEASED_AT + 900)).unwrap_err(),
+            TokenError::Expired
+        );
+
+        // Only with the key it names
+        assert_eq!(
+            verify_release_token(&token, &keys[..1], at(RELEASED_AT)).unwrap_err(),
+            TokenError::UnknownKey
+        );
+        let forged = sign(
+            &SigningKey::from_bytes(&[3; 32]),
+            header,
+            &claims_json(&token),
+        );
+        assert_eq!(
+            verify_release_token(&forged, &keys, at(RELEASED_AT)).unwrap_err(),
+            TokenError::InvalidSignature
+        );
+
+        let unsigned = sign(
+            &key,
+            r#"{"alg":"none","kid":"key-2"}"#,
+            &claims_json(&token),
+        );
+        assert_eq!(
+            verify_release_token(&unsigned, &keys, at(RELEASED_AT)).unwrap_err(),
+            TokenError::UnsupportedAlgorithm
+        );
+        assert_eq!(
+            verify_release_token("header.claims", &keys, at(RELEASED_AT)).unwrap_err(),
+            TokenError::Malformed
+        );
+    }
+
+    /// Returns the claims of a token as JSON
+    fn claims_json(token: &str) -> String {
+        let claims = token.split('.').nth(1).unwrap();
+        String::from_utf8(URL_SAFE_NO_PAD.decode(claims).unwrap()).unwrap()
+    }
+}
@@ -103,4 +103,431 @@ where
         &self,
         request: super::FlightReleaseRequest,
     ) -> Result<tonic::Response<super::FlightReleaseResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`FlightPlanCancelResponse`](super::FlightPlanCancelResponse)
+    /// Takes a [`FlightPlanCancelRequest`](super::FlightPlanCancelRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::NotFound`](tonic::Code::NotFound) if
+    /// the flight plan was never submitted, or with
+    /// [`Code::FailedPrecondition`](tonic::Code::FailedPrecondition) if it is
+    /// active, closed or already cancelled.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_compliance_client_grpc::prelude::*;
+    /// use tonic::transport::Channel;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = ComplianceClient::new_client(&host, port, "compliance");
+    ///     let response = client
+    ///         .cancel_flight_plan(compliance::FlightPlanCancelRequest {
+    ///             flight_plan_id: "FP-1".to_string(),
+    ///             reason: Some("weather".to_string()),
+    ///         })
+    ///         .await?;
+    ///     println!("cancel_flight_plan RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn cancel_flight_plan(
+        &self,
+        request: super::FlightPlanCancelRequest,
+    ) -> Result<tonic::Response<super::FlightPlanCancelResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`FlightPlanResponse`](super::FlightPlanResponse)
+    /// Takes a [`FlightPlanRequest`](super::FlightPlanRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::NotFound`](tonic::Code::NotFound) if
+    /// the flight plan was never submitted, or with
+    /// [`Code::FailedPrecondition`](tonic::Code::FailedPrecondition) unless it
+    /// is pending or accepted.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_compliance_client_grpc::prelude::*;
+    /// use tonic::transport::Channel;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = ComplianceClient::new_client(&host, port, "compliance");
+    ///     let response = client
+    ///         .amend_flight_plan(compliance::FlightPlanRequest {
+    ///             flight_plan_id: "FP-1".to_string(),
+    ///             data: "".to_string(),
+    ///         })
+    ///         .await?;
+    ///     println!("amend_flight_plan RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn amend_flight_plan(
+        &self,
+        request: super::FlightPlanRequest,
+    ) -> Result<tonic::Response<super::FlightPlanResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`FlightPlanActivateResponse`](super::FlightPlanActivateResponse)
+    /// Takes a [`FlightPlanActivateRequest`](super::FlightPlanActivateRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with
+    /// [`Code::InvalidArgument`](tonic::Code::InvalidArgument) if the takeoff
+    /// time is missing or in the future, or with
+    /// [`Code::FailedPrecondition`](tonic::Code::FailedPrecondition) unless the
+    /// flight plan is released and took off before its release ran out.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_compliance_client_grpc::prelude::*;
+    /// use tonic::transport::Channel;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = ComplianceClient::new_client(&host, port, "compliance");
+    ///     let response = client
+    ///         .activate_flight_plan(compliance::FlightPlanActivateRequest {
+    ///             flight_plan_id: "FP-1".to_string(),
+    ///             departed_at: Some(std::time::SystemTime::now().into()),
+    ///         })
+    ///         .await?;
+    ///     println!("activate_flight_plan RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn activate_flight_plan(
+        &self,
+        request: super::FlightPlanActivateRequest,
+    ) -> Result<tonic::Response<super::FlightPlanActivateResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing the [`ReleaseKeys`](super::ReleaseKeys)
+    ///  release tokens are verified with, see
+    ///  [`verify_release_token`](crate::release_token::verify_release_token)
+    /// Takes a [`ReleaseKeysRequest`](super::ReleaseKeysRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with
+    /// [`Code::FailedPrecondition`](tonic::Code::FailedPrecondition) if the
+    /// service does not issue release tokens.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_compliance_client_grpc::prelude::*;
+    /// use tonic::transport::Channel;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = ComplianceClient::new_client(&host, port, "compliance");
+    ///     let response = client
+    ///         .get_release_keys(compliance::ReleaseKeysRequest {})
+    ///         .await?;
+    ///     println!("get_release_keys RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn get_release_keys(
+        &self,
+        request: super::ReleaseKeysRequest,
+    ) -> Result<tonic::Response<super::ReleaseKeys>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`FlightPlanCloseResponse`](super::FlightPlanCloseResponse)
+    /// Takes a [`FlightPlanCloseRequest`](super::FlightPlanCloseRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with
+    /// [`Code::InvalidArgument`](tonic::Code::InvalidArgument) if the landing
+    /// time is missing or in the future, or with
+    /// [`Code::FailedPrecondition`](tonic::Code::FailedPrecondition) unless the
+    /// flight plan is released or active.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_compliance_client_grpc::prelude::*;
+    /// use tonic::transport::Channel;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = ComplianceClient::new_client(&host, port, "compliance");
+    ///     let response = client
+    ///         .close_flight_plan(compliance::FlightPlanCloseRequest {
+    ///             flight_plan_id: "FP-1".to_string(),
+    ///             landed_at: Some(std::time::SystemTime::now().into()),
+    ///         })
+    ///         .await?;
+    ///     println!("close_flight_plan RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn close_flight_plan(
+        &self,
+        request: super::FlightPlanCloseRequest,
+    ) -> Result<tonic::Response<super::FlightPlanCloseResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`FlightPlanStatus`](super::FlightPlanStatus)
+    /// Takes a [`FlightPlanStatusRequest`](super::FlightPlanStatusRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::NotFound`](tonic::Code::NotFound) if
+    /// the flight plan was never submitted.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_compliance_client_grpc::prelude::*;
+    /// use tonic::transport::Channel;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = ComplianceClient::new_client(&host, port, "compliance");
+    ///     let response = client
+    ///         .get_flight_plan_status(compliance::FlightPlanStatusRequest {
+    ///             flight_plan_id: "FP-1".to_string(),
+    ///         })
+    ///         .await?;
+    ///     println!("get_flight_plan_status RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn get_flight_plan_status(
+        &self,
+        request: super::FlightPlanStatusRequest,
+    ) -> Result<tonic::Response<super::FlightPlanStatus>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`ManualRestrictionResponse`](super::ManualRestrictionResponse)
+    /// Takes a [`ManualRestriction`](super::ManualRestriction).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::InvalidArgument`](tonic::Code::InvalidArgument) if
+    /// the restriction is invalid, or [`Code::AlreadyExists`](tonic::Code::AlreadyExists) if
+    /// a restriction with the same identifier exists.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_compliance_client_grpc::prelude::*;
+    /// use tonic::transport::Channel;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = ComplianceClient::new_client(&host, port, "compliance");
+    ///     let response = client
+    ///         .create_restriction(compliance::ManualRestriction {
+    ///             identifier: "".to_string(),
+    ///             vertices: vec![],
+    ///             altitude_meters_min: 0.0,
+    ///             altitude_meters_max: 500.0,
+    ///             time_start: None,
+    ///             time_end: None,
+    ///             reason: "Accident scene".to_string(),
+    ///             author: "operator".to_string(),
+    ///         })
+    ///         .await?;
+    ///     println!("create_restriction RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn create_restriction(
+        &self,
+        request: super::ManualRestriction,
+    ) -> Result<tonic::Response<super::ManualRestrictionResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`ManualRestrictionResponse`](super::ManualRestrictionResponse)
+    /// Takes a [`ManualRestriction`](super::ManualRestriction).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::InvalidArgument`](tonic::Code::InvalidArgument) if
+    /// the restriction is invalid, or [`Code::NotFound`](tonic::Code::NotFound) if
+    /// no active manual restriction with this identifier exists.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_compliance_client_grpc::prelude::*;
+    /// use tonic::transport::Channel;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = ComplianceClient::new_client(&host, port, "compliance");
+    ///     let response = client
+    ///         .update_restriction(compliance::ManualRestriction {
+    ///             identifier: "ARROW-NL-MANUAL-1".to_string(),
+    ///             vertices: vec![],
+    ///             altitude_meters_min: 0.0,
+    ///             altitude_meters_max: 500.0,
+    ///             time_start: None,
+    ///             time_end: None,
+    ///             reason: "Accident scene".to_string(),
+    ///             author: "operator".to_string(),
+    ///         })
+    ///         .await?;
+    ///     println!("update_restriction RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn update_restriction(
+        &self,
+        request: super::ManualRestriction,
+    ) -> Result<tonic::Response<super::ManualRestrictionResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`ManualRestrictionResponse`](super::ManualRestrictionResponse)
+    /// Takes a [`DeleteRestrictionRequest`](super::DeleteRestrictionRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::NotFound`](tonic::Code::NotFound) if
+    /// no active manual restriction with this identifier exists.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_compliance_client_grpc::prelude::*;
+    /// use tonic::transport::Channel;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = ComplianceClient::new_client(&host, port, "compliance");
+    ///     let response = client
+    ///         .delete_restriction(compliance::DeleteRestrictionRequest {
+    ///             identifier: "ARROW-NL-MANUAL-1".to_string(),
+    ///             author: "operator".to_string(),
+    ///         })
+    ///         .await?;
+    ///     println!("delete_restriction RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn delete_restriction(
+        &self,
+        request: super::DeleteRestrictionRequest,
+    ) -> Result<tonic::Response<super::ManualRestrictionResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`RestrictionsResponse`](super::RestrictionsResponse)
+    /// Takes a [`RestrictionsRequest`](super::RestrictionsRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::Unknown`](tonic::Code::Unknown) if
+    /// the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_compliance_client_grpc::prelude::*;
+    /// use tonic::transport::Channel;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = ComplianceClient::new_client(&host, port, "compliance");
+    ///     let response = client
+    ///         .list_restrictions(compliance::RestrictionsRequest {})
+    ///         .await?;
+    ///     println!("list_restrictions RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn list_restrictions(
+        &self,
+        request: super::RestrictionsRequest,
+    ) -> Result<tonic::Response<super::RestrictionsResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`MergeReport`](super::MergeReport)
+    /// Takes a [`MergeReportRequest`](super::MergeReportRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::Unknown`](tonic::Code::Unknown) if
+    /// the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_compliance_client_grpc::prelude::*;
+    /// use tonic::transport::Channel;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = ComplianceClient::new_client(&host, port, "compliance");
+    ///     let response = client
+    ///         .get_merge_report(compliance::MergeReportRequest {})
+    ///         .await?;
+    ///     println!("get_merge_report RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn get_merge_report(
+        &self,
+        request: super::MergeReportRequest,
+    ) -> Result<tonic::Response<super::MergeReport>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`WaypointsResponse`](super::WaypointsResponse)
+    /// Takes a [`WaypointsRequest`](super::WaypointsRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::Unknown`](tonic::Code::Unknown) if
+    /// the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_compliance_client_grpc::prelude::*;
+    /// use tonic::transport::Channel;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = ComplianceClient::new_client(&host, port, "compliance");
+    ///     let response = client
+    ///         .list_waypoints(compliance::WaypointsRequest {})
+    ///         .await?;
+    ///     println!("list_waypoints RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn list_waypoints(
+        &self,
+        request: super::WaypointsRequest,
+    ) -> Result<tonic::Response<super::WaypointsResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`WaypointQualityReport`](super::WaypointQualityReport)
+    /// Takes a [`WaypointQualityRequest`](super::WaypointQualityRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`Code::Unknown`](tonic::Code::Unknown) if
+    /// the server is not ready.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_compliance_client_grpc::prelude::*;
+    /// use tonic::transport::Channel;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = ComplianceClient::new_client(&host, port, "compliance");
+    ///     let response = client
+    ///         .get_waypoint_quality_report(compliance::WaypointQualityRequest {})
+    ///         .await?;
+    ///     println!("get_waypoint_quality_report RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn get_waypoint_quality_report(
+        &self,
+        request: super::WaypointQualityRequest,
+    ) -> Result<tonic::Response<super::WaypointQualityReport>, tonic::Status>;
 }
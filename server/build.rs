@@ -10,7 +10,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .type_attribute("ReadyResponse", "#[derive(Eq, Copy)]")
         .type_attribute("Coordinates", "#[derive(Copy)]")
         .type_attribute("RestrictionsRequest", "#[derive(Copy)]")
+        .type_attribute("MergeReportRequest", "#[derive(Copy)]")
         .type_attribute("WaypointsRequest", "#[derive(Copy)]")
+        .type_attribute("WaypointQualityRequest", "#[derive(Copy)]")
+        .type_attribute("ReleaseKeysRequest", "#[derive(Eq, Copy)]")
         .type_attribute("FlightPlanRequest", "#[derive(serde::Serialize)]");
 
     let client_config = server_config.clone();
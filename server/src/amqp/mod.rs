@@ -4,7 +4,10 @@
 pub mod macros;
 pub mod pool;
 use crate::config::Config;
+use crate::flight_plans::FlightPlanState;
+use chrono::{DateTime, Utc};
 use lapin::{options::BasicPublishOptions, BasicProperties};
+use serde::Serialize;
 
 /// Name of the AMQP exchange for flightplan messages
 pub const EXCHANGE_NAME_FLIGHTPLAN: &str = "flightplan";
@@ -15,6 +18,61 @@ pub const QUEUE_NAME_CARGO: &str = "cargo";
 /// Routing key for CARGO messages
 pub const ROUTING_KEY_CARGO: &str = "cargo";
 
+/// Routing key for cancelled flight plans
+pub const ROUTING_KEY_CANCELLED: &str = "flightplan.cancelled";
+
+/// Routing key for amended flight plans
+pub const ROUTING_KEY_AMENDED: &str = "flightplan.amended";
+
+/// Routing key for closed flight plans
+pub const ROUTING_KEY_CLOSED: &str = "flightplan.closed";
+
+/// Routing key for decisions of the authority received after the submission
+pub const ROUTING_KEY_DECIDED: &str = "flightplan.decided";
+
+/// Routing key for flight plans that took off
+pub const ROUTING_KEY_ACTIVATED: &str = "flightplan.activated";
+
+/// Routing key for flight plans whose release expired before takeoff
+pub const ROUTING_KEY_EXPIRED: &str = "flightplan.expired";
+
+/// Routing key for flight plans whose release was revoked by a new
+///  restriction
+pub const ROUTING_KEY_REVOKED: &str = "flightplan.revoked";
+
+/// Change of a flight plan published to the flightplan exchange
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FlightPlanEvent {
+    /// Identifier of the flight plan
+    pub flight_plan_id: String,
+
+    /// State of the flight plan after the change
+    pub state: FlightPlanState,
+
+    /// When the change happened
+    pub timestamp: DateTime<Utc>,
+
+    /// Why the change happened, if known
+    pub reason: Option<String>,
+
+    /// JSON data of an amended flight plan
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+
+    /// Actual landing time of a closed flight plan
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub landed_at: Option<DateTime<Utc>>,
+
+    /// Actual takeoff time of an activated flight plan
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub departed_at: Option<DateTime<Utc>>,
+
+    /// Identifiers of the restrictions crossing the route of a flight plan
+    ///  whose release was revoked
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub zones: Vec<String>,
+}
+
 /// Custom Error type for MQ errors
 #[derive(thiserror::Error, Debug, Copy, Clone)]
 pub enum AMQPError {
@@ -91,6 +149,49 @@ cfg_if::cfg_if! {
     }
 }
 
+/// Publishes a change of a flight plan to the flightplan exchange, logging
+///  failures
+///
+/// Nothing is published without a channel.
+pub async fn publish_flight_plan_event(
+    mq_channel: &Option<lapin::Channel>,
+    routing_key: &str,
+    event: &FlightPlanEvent,
+) {
+    let Some(mq_channel) = mq_channel else {
+        return;
+    };
+
+    let Ok(payload) = serde_json::to_vec(event) else {
+        amqp_error!("(publish_flight_plan_event) Could not serialize flight plan event.");
+        return;
+    };
+
+    let result = mq_channel
+        .basic_publish(
+            EXCHANGE_NAME_FLIGHTPLAN,
+            routing_key,
+            BasicPublishOptions::default(),
+            &payload,
+            BasicProperties::default(),
+        )
+        .await;
+
+    match result {
+        Ok(_) => amqp_info!(
+            "(publish_flight_plan_event) Flight plan {} event '{}' pushed to RabbitMQ.",
+            event.flight_plan_id,
+            routing_key
+        ),
+        Err(e) => amqp_error!(
+            "(publish_flight_plan_event) Flight plan {} event '{}' push to RabbitMQ failed: {}",
+            event.flight_plan_id,
+            routing_key,
+            e
+        ),
+    }
+}
+
 /// Initializes the AMQP connection. Creates the flightplan exchange and queues.
 #[cfg(not(tarpaulin_include))]
 pub async fn init_mq(config: Config) -> Result<lapin::Channel, AMQPError> {
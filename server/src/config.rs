@@ -2,11 +2,23 @@
 //!
 //! Define and implement config options for module
 
+use crate::decisions::DecisionConfig;
+use crate::gis::{RetryConfig, StartupConfig};
+use crate::health::HealthConfig;
+use crate::releases::token::TokenConfig;
+use crate::releases::ReleaseConfig;
+use crate::restrictions::buffer::BufferConfig;
+use crate::restrictions::simplify::SimplifyConfig;
+use crate::sources::SourceConfig;
+use crate::storage::StorageConfig;
+use crate::waypoints::quality::QualityConfig;
+use crate::webhook::WebhookConfig;
 use anyhow::Result;
 use config::{ConfigError, Environment};
 use dotenv::dotenv;
 use lapin::ConnectionProperties;
 use serde::Deserialize;
+use std::collections::HashMap;
 
 /// struct holding configuration options
 #[derive(Debug, Deserialize, Clone)]
@@ -20,6 +32,14 @@ pub struct Config {
     /// svc-gis port
     pub gis_port_grpc: u16,
 
+    /// retries and batching of updates pushed to svc-gis
+    #[serde(default)]
+    pub gis_retry: RetryConfig,
+
+    /// wait for svc-gis to be ready on startup
+    #[serde(default)]
+    pub gis_startup: StartupConfig,
+
     /// interval in seconds to refresh no-fly zones
     pub interval_seconds_refresh_zones: u16,
 
@@ -29,6 +49,61 @@ pub struct Config {
     /// path to log configuration YAML file
     pub log_config: String,
 
+    /// path to the file persisting manually entered restrictions
+    pub manual_restrictions_file: String,
+
+    /// path to the file persisting the identifiers items were first published under
+    pub identifier_registry_file: String,
+
+    /// comma separated restriction sources, highest priority first
+    pub restriction_source_priority: String,
+
+    /// directory holding the snapshots of acquired restrictions and waypoints
+    pub snapshot_dir: String,
+
+    /// maximum age in seconds of snapshots reloaded on boot
+    pub snapshot_max_age_seconds: u32,
+
+    /// restriction and waypoint feeds, by name
+    #[serde(default)]
+    pub sources: HashMap<String, SourceConfig>,
+
+    /// safety buffers applied around restriction zones
+    #[serde(default)]
+    pub safety_buffers: BufferConfig,
+
+    /// simplification of restriction polygons pushed to svc-gis
+    #[serde(default)]
+    pub simplification: SimplifyConfig,
+
+    /// quality checks on imported waypoints
+    #[serde(default)]
+    pub waypoint_quality: QualityConfig,
+
+    /// readiness thresholds
+    #[serde(default)]
+    pub health: HealthConfig,
+
+    /// storage of flight plans, releases and the audit trail
+    #[serde(default)]
+    pub storage: StorageConfig,
+
+    /// Polling of pending decisions of the authority
+    #[serde(default)]
+    pub decisions: DecisionConfig,
+
+    /// Release checks and validity
+    #[serde(default)]
+    pub release: ReleaseConfig,
+
+    /// Signing of release tokens
+    #[serde(default)]
+    pub release_tokens: TokenConfig,
+
+    /// Receiver of decisions notified by the authority
+    #[serde(default)]
+    pub webhook: WebhookConfig,
+
     /// AMQP Settings
     pub amqp: deadpool_lapin::Config,
 }
@@ -47,9 +122,26 @@ impl Config {
             docker_port_grpc: 50051,
             gis_host_grpc: String::from("svc-gis"),
             gis_port_grpc: 50051,
+            gis_retry: RetryConfig::default(),
+            gis_startup: StartupConfig::default(),
             interval_seconds_refresh_zones: 30,
             interval_seconds_refresh_waypoints: 30,
             log_config: String::from("log4rs.yaml"),
+            manual_restrictions_file: String::from("manual_restrictions.json"),
+            identifier_registry_file: String::from("identifier_registry.json"),
+            restriction_source_priority: String::from("manual,notam,file,static"),
+            snapshot_dir: String::from("snapshots"),
+            snapshot_max_age_seconds: 21600,
+            sources: HashMap::new(),
+            safety_buffers: BufferConfig::default(),
+            simplification: SimplifyConfig::default(),
+            waypoint_quality: QualityConfig::default(),
+            health: HealthConfig::default(),
+            storage: StorageConfig::default(),
+            decisions: DecisionConfig::default(),
+            release: ReleaseConfig::default(),
+            release_tokens: TokenConfig::default(),
+            webhook: WebhookConfig::default(),
             amqp: deadpool_lapin::Config {
                 url: None,
                 pool: None,
@@ -67,6 +159,23 @@ impl Config {
         config::Config::builder()
             .set_default("docker_port_grpc", default_config.docker_port_grpc)?
             .set_default("log_config", default_config.log_config)?
+            .set_default(
+                "manual_restrictions_file",
+                default_config.manual_restrictions_file,
+            )?
+            .set_default(
+                "identifier_registry_file",
+                default_config.identifier_registry_file,
+            )?
+            .set_default(
+                "restriction_source_priority",
+                default_config.restriction_source_priority,
+            )?
+            .set_default("snapshot_dir", default_config.snapshot_dir)?
+            .set_default(
+                "snapshot_max_age_seconds",
+                default_config.snapshot_max_age_seconds,
+            )?
             .set_default(
                 "interval_seconds_refresh_zones",
                 default_config.interval_seconds_refresh_zones,
@@ -84,6 +193,11 @@ impl Config {
 #[cfg(test)]
 mod tests {
     use super::Config;
+    use crate::restrictions::buffer::BufferMode;
+    use crate::restrictions::merge::SourceKind;
+    use crate::sources::SourceData;
+    use crate::storage::StorageBackend;
+    use crate::waypoints::quality::NearDuplicateAction;
 
     #[tokio::test]
     async fn test_config_from_default() {
@@ -98,6 +212,37 @@ mod tests {
         assert_eq!(config.interval_seconds_refresh_zones, 30);
         assert_eq!(config.interval_seconds_refresh_waypoints, 30);
         assert_eq!(config.log_config, String::from("log4rs.yaml"));
+        assert_eq!(
+            config.manual_restrictions_file,
+            String::from("manual_restrictions.json")
+        );
+        assert_eq!(
+            config.identifier_registry_file,
+            String::from("identifier_registry.json")
+        );
+        assert_eq!(
+            config.restriction_source_priority,
+            String::from("manual,notam,file,static")
+        );
+        assert_eq!(config.snapshot_dir, String::from("snapshots"));
+        assert_eq!(config.snapshot_max_age_seconds, 21600);
+        assert!(config.sources.is_empty());
+        assert!(config.safety_buffers.margins.is_empty());
+        assert_eq!(config.simplification.tolerance_meters, 10.0);
+        assert_eq!(config.simplification.max_vertices, 500);
+        assert_eq!(config.waypoint_quality.min_separation_meters, 10.0);
+        assert_eq!(config.gis_retry.max_attempts, 4);
+        assert_eq!(config.gis_startup.timeout_seconds, 60);
+        assert_eq!(config.health.max_restrictions_age_seconds, 300);
+        assert!(config.health.require_amqp);
+        assert_eq!(config.storage.backend, StorageBackend::Sqlite);
+        assert_eq!(config.storage.path, String::from("compliance.db"));
+        assert_eq!(config.decisions.poll_interval_seconds, 60);
+        assert_eq!(config.release.validity_minutes, 15);
+        assert!(config.release_tokens.keys.is_empty());
+        assert!(!config.webhook.enabled);
+        assert_eq!(config.webhook.port, 8080);
+        assert!(config.webhook.keys.is_empty());
         assert!(config.amqp.url.is_none());
         assert!(config.amqp.pool.is_none());
 
@@ -115,6 +260,44 @@ mod tests {
         std::env::set_var("INTERVAL_SECONDS_REFRESH_ZONES", "40");
         std::env::set_var("INTERVAL_SECONDS_REFRESH_WAYPOINTS", "40");
         std::env::set_var("LOG_CONFIG", "config_file.yaml");
+        std::env::set_var("MANUAL_RESTRICTIONS_FILE", "/tmp/manual.json");
+        std::env::set_var("IDENTIFIER_REGISTRY_FILE", "/tmp/identifiers.json");
+        std::env::set_var("STORAGE__PATH", "/tmp/compliance.db");
+        std::env::set_var("DECISIONS__POLL_INTERVAL_SECONDS", "15");
+        std::env::set_var("RELEASE__VALIDITY_MINUTES", "5");
+        std::env::set_var("RELEASE_TOKENS__SIGNING_KEY_ID", "key-2");
+        std::env::set_var(
+            "RELEASE_TOKENS__KEYS__KEY-2",
+            "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=",
+        );
+        std::env::set_var("WEBHOOK__ENABLED", "true");
+        std::env::set_var("WEBHOOK__KEYS__AUTHORITY-1", "webhook-secret");
+        std::env::set_var("RESTRICTION_SOURCE_PRIORITY", "notam,manual");
+        std::env::set_var("SNAPSHOT_DIR", "/tmp/snapshots");
+        std::env::set_var("SNAPSHOT_MAX_AGE_SECONDS", "3600");
+        std::env::set_var("SAFETY_BUFFERS__MODE", "replace");
+        std::env::set_var(
+            "SAFETY_BUFFERS__MARGINS__NL__RESTRICTION__LATERAL_METERS",
+            "100",
+        );
+        std::env::set_var("SIMPLIFICATION__MAX_VERTICES", "64");
+        std::env::set_var("SOURCES__EVENTS__KIND", "file");
+        std::env::set_var("SOURCES__EVENTS__DATA", "restrictions");
+        std::env::set_var("SOURCES__EVENTS__PATH", "/tmp/events.json");
+        std::env::set_var("SOURCES__EVENTS__COORDINATES", "EPSG:28992");
+        std::env::set_var("SOURCES__EVENTS__INTERVAL_SECONDS", "300");
+        std::env::set_var("SOURCES__NOTAMS__KIND", "notam");
+        std::env::set_var("SOURCES__NOTAMS__DATA", "restrictions");
+        std::env::set_var("SOURCES__NOTAMS__URL", "https://notams.example/feed");
+        std::env::set_var("SOURCES__NOTAMS__BEARER_TOKEN", "secret");
+        std::env::set_var("SOURCES__NOTAMS__MAX_BYTES", "1048576");
+        std::env::set_var("WAYPOINT_QUALITY__MIN_SEPARATION_METERS", "25");
+        std::env::set_var("WAYPOINT_QUALITY__NEAR_DUPLICATES", "reject");
+        std::env::set_var("GIS_RETRY__MAX_ATTEMPTS", "6");
+        std::env::set_var("GIS_RETRY__BATCH_SIZE", "100");
+        std::env::set_var("GIS_STARTUP__TIMEOUT_SECONDS", "120");
+        std::env::set_var("HEALTH__MAX_RESTRICTIONS_AGE_SECONDS", "120");
+        std::env::set_var("HEALTH__REQUIRE_AMQP", "false");
         std::env::set_var("AMQP__URL", "amqp://test_rabbitmq:5672");
         std::env::set_var("AMQP__POOL__MAX_SIZE", "32");
 
@@ -128,6 +311,69 @@ mod tests {
         assert_eq!(config.interval_seconds_refresh_zones, 40);
         assert_eq!(config.interval_seconds_refresh_waypoints, 40);
         assert_eq!(config.log_config, String::from("config_file.yaml"));
+        assert_eq!(
+            config.manual_restrictions_file,
+            String::from("/tmp/manual.json")
+        );
+        assert_eq!(
+            config.identifier_registry_file,
+            String::from("/tmp/identifiers.json")
+        );
+        assert_eq!(
+            config.restriction_source_priority,
+            String::from("notam,manual")
+        );
+        assert_eq!(config.snapshot_dir, String::from("/tmp/snapshots"));
+        assert_eq!(config.snapshot_max_age_seconds, 3600);
+        assert_eq!(config.safety_buffers.mode, BufferMode::Replace);
+        assert_eq!(
+            config.safety_buffers.margins["nl"]["restriction"].lateral_meters,
+            100.0
+        );
+        assert_eq!(config.simplification.max_vertices, 64);
+        let events = &config.sources["events"];
+        assert_eq!(events.kind, SourceKind::File);
+        assert_eq!(events.data, SourceData::Restrictions);
+        assert_eq!(events.path, Some(String::from("/tmp/events.json")));
+        assert_eq!(events.coordinates, String::from("EPSG:28992"));
+        assert_eq!(events.interval_seconds, Some(300));
+        assert_eq!(events.retry_seconds, None);
+        let notams = &config.sources["notams"];
+        assert_eq!(notams.path, None);
+        assert_eq!(
+            notams.url,
+            Some(String::from("https://notams.example/feed"))
+        );
+        assert_eq!(notams.bearer_token, Some(String::from("secret")));
+        assert_eq!(notams.max_bytes, Some(1048576));
+        assert_eq!(config.simplification.tolerance_meters, 10.0);
+        assert_eq!(config.waypoint_quality.min_separation_meters, 25.0);
+        assert_eq!(
+            config.waypoint_quality.near_duplicates,
+            NearDuplicateAction::Reject
+        );
+        assert_eq!(config.gis_retry.max_attempts, 6);
+        assert_eq!(config.gis_retry.batch_size, 100);
+        assert_eq!(config.gis_retry.initial_backoff_ms, 500);
+        assert_eq!(config.gis_startup.timeout_seconds, 120);
+        assert_eq!(config.gis_startup.poll_interval_ms, 1000);
+        assert_eq!(config.health.max_restrictions_age_seconds, 120);
+        assert_eq!(config.health.max_waypoints_age_seconds, 300);
+        assert!(!config.health.require_amqp);
+        assert_eq!(config.storage.path, String::from("/tmp/compliance.db"));
+        assert_eq!(config.decisions.poll_interval_seconds, 15);
+        assert_eq!(config.release.validity_minutes, 5);
+        assert_eq!(config.release.earliest_minutes_before_departure, 30);
+        assert_eq!(
+            config.release_tokens.signing_key_id,
+            Some(String::from("key-2"))
+        );
+        assert!(config.release_tokens.keys.contains_key("key-2"));
+        assert!(config.webhook.enabled);
+        assert_eq!(
+            config.webhook.keys.get("authority-1"),
+            Some(&String::from("webhook-secret"))
+        );
         assert_eq!(
             config.amqp.url,
             Some(String::from("amqp://test_rabbitmq:5672"))
@@ -0,0 +1,348 @@
+//! Decisions of the authority received after the submission
+//!
+//! Authorities can take minutes or hours to decide on a flight plan, so a
+//!  submission or amendment may be answered with a pending result and a
+//!  tracking reference. The flight plan then awaits the authority until the
+//!  [`DecisionResolver`] resolves it, either from [`poll_loop`] asking the
+//!  region or from a decision delivered to the service. The outcome is
+//!  recorded like any other answer of the authority and published on the
+//!  flightplan exchange, so svc-scheduler can react to it.
+
+#[macro_use]
+pub mod macros;
+
+use crate::amqp::{self, FlightPlanEvent};
+use crate::flight_plans::{FlightPlanError, FlightPlanRecord, FlightPlanState, FlightPlanStore};
+use crate::region::{AuthorityDecision, RegionInterface};
+use crate::storage::{AuthorityRequest, AuthorityResponse};
+use chrono::Utc;
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// Polling of pending decisions
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct DecisionConfig {
+    /// Interval in seconds at which the authority is asked for pending
+    ///  decisions
+    pub poll_interval_seconds: u16,
+}
+
+impl Default for DecisionConfig {
+    fn default() -> Self {
+        DecisionConfig {
+            poll_interval_seconds: 60,
+        }
+    }
+}
+
+/// Resolves flight plans awaiting a decision of the authority
+#[derive(Debug)]
+pub struct DecisionResolver {
+    /// Tracked flight plans
+    flight_plans: Arc<FlightPlanStore>,
+
+    /// AMQP channel the outcomes are published on
+    mq_channel: Option<lapin::Channel>,
+}
+
+impl DecisionResolver {
+    /// Create a new resolver
+    pub fn new(flight_plans: Arc<FlightPlanStore>, mq_channel: Option<lapin::Channel>) -> Self {
+        Self {
+            flight_plans,
+            mq_channel,
+        }
+    }
+
+    /// Returns the pending answer of the authority to the latest submission
+    ///  of a flight plan, or to its amendment if one awaits the authority
+    async fn pending_response(
+        &self,
+        plan: &FlightPlanRecord,
+    ) -> Result<Option<AuthorityResponse>, FlightPlanError> {
+        let request = pending_request(plan);
+        let responses = self
+            .flight_plans
+            .storage()
+            .authority_responses(&plan.flight_plan_id)
+            .await?;
+
+        Ok(responses
+            .into_iter()
+            .rev()
+            .find(|response| response.pending && response.request == request))
+    }
+
+    /// Returns the identifier of the flight plan awaiting a decision under
+    ///  the provided reference of the authority
+    pub async fn find_by_reference(
+        &self,
+        reference: &str,
+    ) -> Result<Option<String>, FlightPlanError> {
+        for plan in self.flight_plans.awaiting_authority().await {
+            let pending = self.pending_response(&plan).await?;
+            if pending.and_then(|response| response.reference).as_deref() == Some(reference) {
+                return Ok(Some(plan.flight_plan_id));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Records the decision of the authority on a flight plan awaiting it
+    ///
+    /// The flight plan is accepted or rejected, or its amendment applied or
+    ///  dropped, and the outcome published. Flight plans that are not
+    ///  awaiting a decision, for example because it was already resolved,
+    ///  are left unchanged.
+    pub async fn resolve(
+        &self,
+        flight_plan_id: &str,
+        decision: AuthorityDecision,
+    ) -> Result<FlightPlanRecord, FlightPlanError> {
+        let Some(plan) = self.flight_plans.get(flight_plan_id).await else {
+            return Err(FlightPlanError::NotFound(flight_plan_id.to_string()));
+        };
+
+        let (state, reason) = match decision.granted {
+            true => (FlightPlanState::Accepted, None),
+            false => (FlightPlanState::Rejected, decision.message.clone()),
+        };
+
+        let request = pending_request(&plan);
+        if request == AuthorityRequest::Submission
+            && plan.state != FlightPlanState::PendingAuthority
+        {
+            return Err(FlightPlanError::InvalidTransition {
+                id: flight_plan_id.to_string(),
+                from: plan.state,
+                to: state,
+            });
+        }
+
+        let pending = self.pending_response(&plan).await?;
+        let plan = match request {
+            AuthorityRequest::Amendment => {
+                self.flight_plans
+                    .resolve_amendment(flight_plan_id, decision.granted, Utc::now())
+                    .await?
+            }
+            _ => {
+                self.flight_plans
+                    .transition(flight_plan_id, state, Utc::now(), reason)
+                    .await?
+            }
+        };
+
+        decisions_info!(
+            "(resolve) The {} of flight plan {} was decided by the authority, which is now {}.",
+            request,
+            flight_plan_id,
+            plan.state
+        );
+
+        let reference = decision
+            .reference
+            .clone()
+            .or_else(|| pending.and_then(|response| response.reference));
+        let response = AuthorityResponse {
+            flight_plan_id: flight_plan_id.to_string(),
+            request,
+            received_at: plan.updated_at(),
+            granted: decision.granted,
+            message: decision.message.clone(),
+            reference,
+            pending: false,
+        };
+
+        if let Err(e) = self
+            .flight_plans
+            .storage()
+            .add_authority_response(&response)
+            .await
+        {
+            decisions_error!(
+                "(resolve) Could not record the decision on {}: {}",
+                flight_plan_id,
+                e
+            );
+        }
+
+        amqp::publish_flight_plan_event(
+            &self.mq_channel,
+            amqp::ROUTING_KEY_DECIDED,
+            &FlightPlanEvent {
+                flight_plan_id: flight_plan_id.to_string(),
+                state: plan.state,
+                timestamp: plan.updated_at(),
+                reason: decision.message,
+                data: (request == AuthorityRequest::Amendment && decision.granted)
+                    .then(|| plan.data.clone()),
+                landed_at: None,
+                departed_at: None,
+                zones: vec![],
+            },
+        )
+        .await;
+
+        Ok(plan)
+    }
+
+    /// Asks the region for the decisions on every flight plan awaiting one,
+    ///  returning the number of flight plans resolved
+    pub async fn poll(&self, region: &(dyn RegionInterface + Send + Sync)) -> usize {
+        let mut resolved = 0;
+        for plan in self.flight_plans.awaiting_authority().await {
+            let flight_plan_id = &plan.flight_plan_id;
+            let reference = match self.pending_response(&plan).await {
+                // The amendment is still being submitted
+                Ok(None) if plan.amendment.is_some() => continue,
+                Ok(response) => response.and_then(|response| response.reference),
+                Err(e) => {
+                    decisions_error!(
+                        "(poll) Could not read the pending response for {}: {}",
+                        flight_plan_id,
+                        e
+                    );
+                    continue;
+                }
+            };
+
+            let decision = match region
+                .poll_decision(flight_plan_id, reference.as_deref())
+                .await
+            {
+                Ok(Some(decision)) => decision,
+                Ok(None) => continue,
+                Err(e) => {
+                    decisions_warn!(
+                        "(poll) Could not poll the decision on {}: {}",
+                        flight_plan_id,
+                        e
+                    );
+                    continue;
+                }
+            };
+
+            match self.resolve(flight_plan_id, decision).await {
+                Ok(_) => resolved += 1,
+                Err(e) => decisions_warn!("(poll) Could not resolve {}: {}", flight_plan_id, e),
+            }
+        }
+
+        resolved
+    }
+}
+
+/// Returns the request of a flight plan awaiting a decision of the
+///  authority
+fn pending_request(plan: &FlightPlanRecord) -> AuthorityRequest {
+    match plan.amendment {
+        Some(_) => AuthorityRequest::Amendment,
+        None => AuthorityRequest::Submission,
+    }
+}
+
+/// Periodically asks the region for pending decisions
+///
+/// The first poll happens right away, so decisions made while the service
+///  was down are picked up at startup.
+pub async fn poll_loop(
+    resolver: Arc<DecisionResolver>,
+    region: Box<dyn RegionInterface + Send + Sync>,
+    config: DecisionConfig,
+) {
+    decisions_info!(
+        "(poll_loop) Starting loop with interval: {} seconds.",
+        config.poll_interval_seconds
+    );
+
+    let interval = std::time::Duration::from_secs(config.poll_interval_seconds as u64);
+    loop {
+        let resolved = resolver.poll(region.as_ref()).await;
+        if resolved > 0 {
+            decisions_info!("(poll_loop) Resolved {} pending decision(s).", resolved);
+        }
+
+        tokio::time::sleep(interval).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::region::RegionImpl;
+    use crate::test_util::pending_plan;
+
+    #[tokio::test]
+    async fn test_resolve() {
+        crate::get_log_handle().await;
+        ut_info!("(test_resolve) Start.");
+
+        let flight_plans = Arc::new(FlightPlanStore::default());
+        let resolver = DecisionResolver::new(flight_plans.clone(), None);
+        let rejection = AuthorityDecision {
+            granted: false,
+            message: Some("airspace closed".to_string()),
+            reference: None,
+        };
+
+        assert!(matches!(
+            resolver.resolve("FP-1", rejection.clone()).await,
+            Err(FlightPlanError::NotFound(_))
+        ));
+
+        pending_plan(&flight_plans, "FP-1", "REF-1").await;
+        assert_eq!(
+            resolver.find_by_reference("REF-1").await.unwrap(),
+            Some("FP-1".to_string())
+        );
+        assert_eq!(resolver.find_by_reference("REF-2").await.unwrap(), None);
+
+        let plan = resolver.resolve("FP-1", rejection.clone()).await.unwrap();
+        assert_eq!(plan.state, FlightPlanState::Rejected);
+        assert_eq!(
+            plan.history.last().unwrap().reason,
+            Some("airspace closed".to_string())
+        );
+
+        let responses = flight_plans
+            .storage()
+            .authority_responses("FP-1")
+            .await
+            .unwrap();
+        assert_eq!(responses.len(), 2);
+        assert!(!responses[1].pending);
+        assert!(!responses[1].granted);
+        assert_eq!(responses[1].reference, Some("REF-1".to_string()));
+
+        // Resolved once
+        assert_eq!(resolver.find_by_reference("REF-1").await.unwrap(), None);
+        assert!(matches!(
+            resolver.resolve("FP-1", rejection).await,
+            Err(FlightPlanError::InvalidTransition { .. })
+        ));
+
+        ut_info!("(test_resolve) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_poll() {
+        crate::get_log_handle().await;
+        ut_info!("(test_poll) Start.");
+
+        let flight_plans = Arc::new(FlightPlanStore::default());
+        let resolver = DecisionResolver::new(flight_plans.clone(), None);
+        let region = RegionImpl::default();
+        assert_eq!(resolver.poll(&region).await, 0);
+
+        pending_plan(&flight_plans, "FP-1", "REF-1").await;
+        assert_eq!(resolver.poll(&region).await, 1);
+        let plan = flight_plans.get("FP-1").await.unwrap();
+        assert_eq!(plan.state, FlightPlanState::Accepted);
+        assert_eq!(resolver.poll(&region).await, 0);
+
+        ut_info!("(test_poll) Success.");
+    }
+}
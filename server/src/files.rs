@@ -0,0 +1,37 @@
+//! Writing the files this service keeps across restarts
+
+use std::path::Path;
+
+/// Replaces the contents of the provided file
+///
+/// The contents are written to a temporary file next to it, which is then
+///  moved in place. A crash during the write leaves the previous file, never
+///  a truncated one.
+pub async fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    tokio::fs::write(&tmp, contents).await?;
+    tokio::fs::rename(&tmp, path).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_write_atomic() {
+        let path = std::env::temp_dir().join(format!(
+            "svc-compliance-files-{}.json",
+            uuid::Uuid::new_v4()
+        ));
+
+        write_atomic(&path, b"first").await.unwrap();
+        write_atomic(&path, b"second").await.unwrap();
+        assert_eq!(tokio::fs::read(&path).await.unwrap(), b"second");
+        assert!(!path.with_extension("tmp").exists());
+
+        let missing = path.with_extension("missing").join("file.json");
+        assert!(write_atomic(&missing, b"first").await.is_err());
+
+        let _ = tokio::fs::remove_file(&path).await;
+    }
+}
@@ -0,0 +1,4 @@
+//! log macro's for flight plan logging
+
+use lib_common::log_macros;
+log_macros!("flight_plans");
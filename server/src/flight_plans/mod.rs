@@ -0,0 +1,980 @@
+//! Lifecycle of submitted flight plans
+//!
+//! Every submitted flight plan is tracked through explicit states, from its
+//!  submission to the authority until it is closed, cancelled or expired.
+//!  Only the transitions listed by [`FlightPlanState::can_transition_to`] are
+//!  accepted, and each one is recorded with its time.
+//!
+//! ```text
+//! draft -> submitted -> [pending_authority ->] accepted -> released -> active -> closed
+//!                                           \-> rejected
+//! ```
+//!
+//! Plans can be cancelled until they are active, and released plans expire
+//!  when they do not take off before their release runs out. Pending and
+//!  accepted plans keep their data and state while an amendment awaits the
+//!  authority, and go back to submitted once it is granted. Released plans
+//!  can be closed without being reported active, or go back to accepted when
+//!  a new restriction revokes their release. The plans are persisted to the
+//!  [`Storage`](crate::storage::Storage), with every transition recorded in
+//!  its audit trail, so a release can still be refused after a restart for a
+//!  plan that was never accepted.
+//!
+//! Each plan keeps a [`fingerprint`] of its data, so a retried submission of
+//!  the same plan is recognized instead of being filed twice with the
+//!  authority.
+
+#[macro_use]
+pub mod macros;
+
+use crate::storage::memory::MemoryStorage;
+use crate::storage::{AuditRecord, ReleaseRecord, Storage, StorageError};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+
+/// Errors returned by the flight plan store
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum FlightPlanError {
+    /// No flight plan with this identifier exists
+    #[error("error: Flight plan {0} not found.")]
+    NotFound(String),
+
+    /// The flight plan can not move to the requested state
+    #[error("error: Flight plan {id} can not go from {from} to {to}.")]
+    InvalidTransition {
+        /// Identifier of the flight plan
+        id: String,
+
+        /// The current state
+        from: FlightPlanState,
+
+        /// The requested state
+        to: FlightPlanState,
+    },
+
+    /// The provided flight plan is invalid
+    #[error("error: Invalid flight plan: {0}.")]
+    InvalidArgument(String),
+
+    /// The flight plan was already submitted with the same data
+    #[error("error: Flight plan {0} was already submitted.")]
+    AlreadySubmitted(String),
+
+    /// The flight plan was already submitted with different data
+    #[error("error: Flight plan {0} was already submitted with different data.")]
+    Conflict(String),
+
+    /// The flight plans could not be persisted or loaded
+    #[error("error: Could not persist flight plans: {0}.")]
+    Persistence(String),
+}
+
+/// State of a flight plan
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlightPlanState {
+    /// Recorded, not submitted to the authority yet
+    Draft,
+
+    /// Submitted to the authority
+    Submitted,
+
+    /// Awaiting a decision of the authority
+    PendingAuthority,
+
+    /// Accepted by the authority
+    Accepted,
+
+    /// Rejected by the authority
+    Rejected,
+
+    /// Released for flight
+    Released,
+
+    /// In flight
+    Active,
+
+    /// Flown and closed
+    Closed,
+
+    /// Cancelled before it was closed
+    Cancelled,
+
+    /// Not flown in time
+    Expired,
+}
+
+impl fmt::Display for FlightPlanState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            FlightPlanState::Draft => "draft",
+            FlightPlanState::Submitted => "submitted",
+            FlightPlanState::PendingAuthority => "pending_authority",
+            FlightPlanState::Accepted => "accepted",
+            FlightPlanState::Rejected => "rejected",
+            FlightPlanState::Released => "released",
+            FlightPlanState::Active => "active",
+            FlightPlanState::Closed => "closed",
+            FlightPlanState::Cancelled => "cancelled",
+            FlightPlanState::Expired => "expired",
+        };
+
+        write!(f, "{name}")
+    }
+}
+
+impl FlightPlanState {
+    /// Returns true if no transition leaves this state
+    pub fn is_final(self) -> bool {
+        matches!(
+            self,
+            FlightPlanState::Rejected
+                | FlightPlanState::Closed
+                | FlightPlanState::Cancelled
+                | FlightPlanState::Expired
+        )
+    }
+
+    /// Returns true if a flight plan in this state can move to the provided
+    ///  state
+    pub fn can_transition_to(self, next: FlightPlanState) -> bool {
+        use FlightPlanState::*;
+
+        matches!(
+            (self, next),
+            (Draft, Submitted)
+                | (Submitted, PendingAuthority | Accepted | Rejected)
+                | (PendingAuthority, Accepted | Rejected)
+                | (PendingAuthority | Accepted, Submitted)
+                | (Accepted, Released)
+                | (Released, Accepted)
+                | (Released, Active)
+                | (Released | Active, Closed)
+                | (
+                    Draft | Submitted | PendingAuthority | Accepted | Released,
+                    Cancelled
+                )
+                | (Submitted | PendingAuthority | Accepted | Released, Expired)
+        )
+    }
+}
+
+/// Returns the fingerprint of the data of a flight plan, the hexadecimal
+///  SHA-256 digest of the data as submitted
+pub fn fingerprint(data: &str) -> String {
+    Sha256::digest(data.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// A recorded change of state
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Transition {
+    /// The new state
+    pub state: FlightPlanState,
+
+    /// When the state was entered
+    pub at: DateTime<Utc>,
+
+    /// Why the state was entered, if known
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// A tracked flight plan
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlightPlanRecord {
+    /// Identifier of the flight plan
+    pub flight_plan_id: String,
+
+    /// JSON data of the flight plan as submitted
+    pub data: String,
+
+    /// Fingerprint of the data, see [`fingerprint`]
+    #[serde(default)]
+    pub fingerprint: String,
+
+    /// The current state
+    pub state: FlightPlanState,
+
+    /// Every state entered, oldest first
+    pub history: Vec<Transition>,
+
+    /// JSON data of an amendment awaiting the authority, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amendment: Option<String>,
+}
+
+impl FlightPlanRecord {
+    /// Create a new draft
+    pub fn new(flight_plan_id: &str, data: &str, at: DateTime<Utc>) -> Self {
+        Self {
+            flight_plan_id: flight_plan_id.to_string(),
+            data: data.to_string(),
+            fingerprint: fingerprint(data),
+            state: FlightPlanState::Draft,
+            history: vec![Transition {
+                state: FlightPlanState::Draft,
+                at,
+                reason: None,
+            }],
+            amendment: None,
+        }
+    }
+
+    /// Returns when the flight plan was created
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.history.first().map(|t| t.at).unwrap_or_default()
+    }
+
+    /// Returns when the flight plan last changed state
+    pub fn updated_at(&self) -> DateTime<Utc> {
+        self.history.last().map(|t| t.at).unwrap_or_default()
+    }
+
+    /// Returns when the flight plan entered the provided state, if it did
+    pub fn entered(&self, state: FlightPlanState) -> Option<DateTime<Utc>> {
+        self.history.iter().find(|t| t.state == state).map(|t| t.at)
+    }
+
+    /// Replaces the data of the flight plan
+    pub fn set_data(&mut self, data: &str) {
+        self.data = data.to_string();
+        self.fingerprint = fingerprint(data);
+    }
+
+    /// Moves the flight plan to the provided state
+    ///
+    /// A pending amendment is dropped when the flight plan moves to a state
+    ///  it can no longer be amended from, so it no longer awaits the
+    ///  authority.
+    pub fn transition(
+        &mut self,
+        state: FlightPlanState,
+        at: DateTime<Utc>,
+        reason: Option<String>,
+    ) -> Result<(), FlightPlanError> {
+        if !self.state.can_transition_to(state) {
+            return Err(FlightPlanError::InvalidTransition {
+                id: self.flight_plan_id.clone(),
+                from: self.state,
+                to: state,
+            });
+        }
+
+        self.state = state;
+        self.history.push(Transition { state, at, reason });
+        if !state.can_transition_to(FlightPlanState::Submitted) {
+            self.amendment = None;
+        }
+
+        Ok(())
+    }
+}
+
+/// Tracked flight plans, by identifier
+#[derive(Debug)]
+pub struct FlightPlanStore {
+    /// Flight plans by identifier
+    plans: RwLock<HashMap<String, FlightPlanRecord>>,
+
+    /// Flight plans submitted to the authority and not answered yet
+    submitting: Mutex<HashSet<String>>,
+
+    /// Storage persisting the flight plans across restarts
+    storage: Arc<dyn Storage>,
+}
+
+impl Default for FlightPlanStore {
+    fn default() -> Self {
+        Self::new(Arc::new(MemoryStorage::default()))
+    }
+}
+
+impl From<StorageError> for FlightPlanError {
+    fn from(e: StorageError) -> Self {
+        FlightPlanError::Persistence(e.to_string())
+    }
+}
+
+impl FlightPlanStore {
+    /// Create a new store persisted to the provided storage
+    pub fn new(storage: Arc<dyn Storage>) -> Self {
+        Self {
+            plans: RwLock::new(HashMap::new()),
+            submitting: Mutex::new(HashSet::new()),
+            storage,
+        }
+    }
+
+    /// Returns the storage the flight plans are persisted to
+    pub fn storage(&self) -> &Arc<dyn Storage> {
+        &self.storage
+    }
+
+    /// Load previously persisted flight plans
+    pub async fn load(&self) -> Result<usize, FlightPlanError> {
+        let plans: HashMap<String, FlightPlanRecord> = self
+            .storage
+            .flight_plans()
+            .await
+            .map_err(|e| {
+                flight_plans_error!("(load) Could not load flight plans: {}", e);
+                e
+            })?
+            .into_iter()
+            .map(|mut record| {
+                // Recorded before fingerprints were kept
+                if record.fingerprint.is_empty() {
+                    record.fingerprint = fingerprint(&record.data);
+                }
+
+                (record.flight_plan_id.clone(), record)
+            })
+            .collect();
+
+        let count = plans.len();
+        *self.plans.write().await = plans;
+        flight_plans_info!("(load) Loaded {} flight plan(s).", count);
+        Ok(count)
+    }
+
+    /// Returns the flight plan with the provided identifier
+    pub async fn get(&self, flight_plan_id: &str) -> Option<FlightPlanRecord> {
+        self.plans.read().await.get(flight_plan_id).cloned()
+    }
+
+    /// Returns the flight plans in the provided state
+    pub async fn in_state(&self, state: FlightPlanState) -> Vec<FlightPlanRecord> {
+        self.plans
+            .read()
+            .await
+            .values()
+            .filter(|record| record.state == state)
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the flight plans awaiting a decision of the authority, on
+    ///  their submission or on an amendment
+    pub async fn awaiting_authority(&self) -> Vec<FlightPlanRecord> {
+        self.plans
+            .read()
+            .await
+            .values()
+            .filter(|record| {
+                record.state == FlightPlanState::PendingAuthority
+                    || (record.amendment.is_some()
+                        && record.state.can_transition_to(FlightPlanState::Submitted))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Records a flight plan as submitted to the authority
+    ///
+    /// A new flight plan is recorded as a draft first. A draft recorded
+    ///  before is submitted with the provided data, and so is a submitted
+    ///  flight plan the authority could not be asked about. Other flight
+    ///  plans are not submitted again: [`FlightPlanError::AlreadySubmitted`]
+    ///  is returned for the same data, and [`FlightPlanError::Conflict`] for
+    ///  different data. The submission awaits the authority until
+    ///  [`FlightPlanStore::answered`] is called.
+    pub async fn submit(
+        &self,
+        flight_plan_id: &str,
+        data: &str,
+        at: DateTime<Utc>,
+    ) -> Result<FlightPlanRecord, FlightPlanError> {
+        if flight_plan_id.trim().is_empty() {
+            return Err(FlightPlanError::InvalidArgument(
+                "a flight plan identifier must be provided".to_string(),
+            ));
+        }
+
+        let mut plans = self.plans.write().await;
+        let mut record = match plans.get(flight_plan_id) {
+            Some(record) => record.clone(),
+            None => FlightPlanRecord::new(flight_plan_id, data, at),
+        };
+
+        // Submitted plans are changed with an amendment
+        let from = record.state;
+        let mut submitting = self.submitting.lock().await;
+        if from == FlightPlanState::Submitted
+            && record.fingerprint == fingerprint(data)
+            && !submitting.contains(flight_plan_id)
+        {
+            flight_plans_info!("(submit) Flight plan {} submitted again.", flight_plan_id);
+            submitting.insert(flight_plan_id.to_string());
+            return Ok(record);
+        }
+
+        if from != FlightPlanState::Draft {
+            let id = flight_plan_id.to_string();
+            return match record.fingerprint == fingerprint(data) {
+                true => Err(FlightPlanError::AlreadySubmitted(id)),
+                false => Err(FlightPlanError::Conflict(id)),
+            };
+        }
+
+        record.set_data(data);
+        record.transition(FlightPlanState::Submitted, at, None)?;
+        flight_plans_info!("(submit) Flight plan {} submitted.", flight_plan_id);
+        let record = self.commit(&mut plans, from, record, None).await?;
+        submitting.insert(flight_plan_id.to_string());
+        Ok(record)
+    }
+
+    /// Records that the authority answered the submission of a flight plan,
+    ///  or could not be asked about it
+    pub async fn answered(&self, flight_plan_id: &str) {
+        self.submitting.lock().await.remove(flight_plan_id);
+    }
+
+    /// Records an amendment of a submitted flight plan, to be submitted to
+    ///  the authority
+    ///
+    /// The flight plan keeps its data and state until the authority decides
+    ///  on the amendment, see [`FlightPlanStore::resolve_amendment`]. Only
+    ///  one amendment of a flight plan can await the authority at a time.
+    pub async fn amend(
+        &self,
+        flight_plan_id: &str,
+        data: &str,
+    ) -> Result<FlightPlanRecord, FlightPlanError> {
+        let mut plans = self.plans.write().await;
+        let Some(record) = plans.get(flight_plan_id) else {
+            return Err(FlightPlanError::NotFound(flight_plan_id.to_string()));
+        };
+
+        if !record.state.can_transition_to(FlightPlanState::Submitted) {
+            return Err(FlightPlanError::InvalidTransition {
+                id: flight_plan_id.to_string(),
+                from: record.state,
+                to: FlightPlanState::Submitted,
+            });
+        }
+
+        if record.amendment.is_some() {
+            return Err(FlightPlanError::InvalidArgument(format!(
+                "an amendment of flight plan {flight_plan_id} already awaits the authority"
+            )));
+        }
+
+        let mut record = record.clone();
+        record.amendment = Some(data.to_string());
+        flight_plans_info!("(amend) Flight plan {} amended.", flight_plan_id);
+        self.persist(&mut plans, record).await
+    }
+
+    /// Applies the decision of the authority on the amendment of a flight
+    ///  plan
+    ///
+    /// A granted amendment replaces the data of the flight plan, which goes
+    ///  back to submitted and is accepted again. A refused one is dropped,
+    ///  leaving the flight plan as it was.
+    pub async fn resolve_amendment(
+        &self,
+        flight_plan_id: &str,
+        granted: bool,
+        at: DateTime<Utc>,
+    ) -> Result<FlightPlanRecord, FlightPlanError> {
+        let mut plans = self.plans.write().await;
+        let Some(record) = plans.get(flight_plan_id) else {
+            return Err(FlightPlanError::NotFound(flight_plan_id.to_string()));
+        };
+
+        let mut record = record.clone();
+        let Some(data) = record.amendment.take() else {
+            return Err(FlightPlanError::InvalidArgument(format!(
+                "no amendment of flight plan {flight_plan_id} awaits the authority"
+            )));
+        };
+
+        if !granted {
+            flight_plans_info!(
+                "(resolve_amendment) Amendment of flight plan {} dropped.",
+                flight_plan_id
+            );
+            return self.persist(&mut plans, record).await;
+        }
+
+        let from = record.state;
+        record.transition(FlightPlanState::Submitted, at, Some("amended".to_string()))?;
+        record.set_data(&data);
+        let mut record = self.commit(&mut plans, from, record, None).await?;
+        record.transition(FlightPlanState::Accepted, at, None)?;
+        flight_plans_info!(
+            "(resolve_amendment) Amendment of flight plan {} applied.",
+            flight_plan_id
+        );
+
+        self.commit(&mut plans, FlightPlanState::Submitted, record, None)
+            .await
+    }
+
+    /// Moves a flight plan to the provided state
+    pub async fn transition(
+        &self,
+        flight_plan_id: &str,
+        state: FlightPlanState,
+        at: DateTime<Utc>,
+        reason: Option<String>,
+    ) -> Result<FlightPlanRecord, FlightPlanError> {
+        let mut plans = self.plans.write().await;
+        let Some(record) = plans.get(flight_plan_id) else {
+            return Err(FlightPlanError::NotFound(flight_plan_id.to_string()));
+        };
+
+        let mut record = record.clone();
+        let from = record.state;
+        record.transition(state, at, reason)?;
+        flight_plans_info!(
+            "(transition) Flight plan {} went from {} to {}.",
+            flight_plan_id,
+            from,
+            state
+        );
+
+        self.commit(&mut plans, from, record, None).await
+    }
+
+    /// Moves a flight plan to the provided state if the provided function
+    ///  allows it
+    ///
+    /// The function is called with the flight plan while the store is
+    ///  locked, once the transition was found valid, so the flight plan can
+    ///  not change in between. It returns its outcome and, when it allows the
+    ///  transition, when the state was entered and why. The flight plan is
+    ///  returned when it moved.
+    pub async fn transition_if<T, E, F>(
+        &self,
+        flight_plan_id: &str,
+        state: FlightPlanState,
+        allow: F,
+    ) -> Result<(T, Option<FlightPlanRecord>), E>
+    where
+        E: From<FlightPlanError>,
+        F: FnOnce(&FlightPlanRecord) -> Result<(T, Option<(DateTime<Utc>, Option<String>)>), E>,
+    {
+        let mut plans = self.plans.write().await;
+        let Some(record) = plans.get(flight_plan_id) else {
+            return Err(FlightPlanError::NotFound(flight_plan_id.to_string()).into());
+        };
+
+        if !record.state.can_transition_to(state) {
+            return Err(FlightPlanError::InvalidTransition {
+                id: flight_plan_id.to_string(),
+                from: record.state,
+                to: state,
+            }
+            .into());
+        }
+
+        let (outcome, allowed) = allow(record)?;
+        let Some((at, reason)) = allowed else {
+            return Ok((outcome, None));
+        };
+
+        let mut record = record.clone();
+        let from = record.state;
+        record.transition(state, at, reason)?;
+        flight_plans_info!(
+            "(transition_if) Flight plan {} went from {} to {}.",
+            flight_plan_id,
+            from,
+            state
+        );
+
+        let record = self.commit(&mut plans, from, record, None).await?;
+        Ok((outcome, Some(record)))
+    }
+
+    /// Moves a released flight plan to the provided state, and saves the
+    ///  change of its release along with the transition
+    pub async fn transition_release(
+        &self,
+        flight_plan_id: &str,
+        state: FlightPlanState,
+        at: DateTime<Utc>,
+        reason: Option<String>,
+        release: &ReleaseRecord,
+    ) -> Result<FlightPlanRecord, FlightPlanError> {
+        let mut plans = self.plans.write().await;
+        let Some(record) = plans.get(flight_plan_id) else {
+            return Err(FlightPlanError::NotFound(flight_plan_id.to_string()));
+        };
+
+        let mut record = record.clone();
+        let from = record.state;
+        record.transition(state, at, reason)?;
+        flight_plans_info!(
+            "(transition_release) Flight plan {} went from {} to {}.",
+            flight_plan_id,
+            from,
+            state
+        );
+
+        self.commit(&mut plans, from, record, Some(release)).await
+    }
+
+    /// Releases a flight plan if the provided function grants it
+    ///
+    /// The function is called with the flight plan while the store is
+    ///  locked, so concurrent requests for the same flight plan are decided
+    ///  one at a time. It returns its outcome and, when it grants the
+    ///  release, the release to record. The flight plan then moves to
+    ///  released at the time of the release.
+    pub async fn release<T, E, F>(&self, flight_plan_id: &str, grant: F) -> Result<T, E>
+    where
+        E: From<FlightPlanError>,
+        F: FnOnce(&FlightPlanRecord) -> Result<(T, Option<ReleaseRecord>), E>,
+    {
+        let mut plans = self.plans.write().await;
+        let Some(record) = plans.get(flight_plan_id) else {
+            return Err(FlightPlanError::NotFound(flight_plan_id.to_string()).into());
+        };
+
+        let (outcome, release) = grant(record)?;
+        let Some(release) = release else {
+            return Ok(outcome);
+        };
+
+        let mut record = record.clone();
+        let from = record.state;
+        record.transition(FlightPlanState::Released, release.released_at, None)?;
+        flight_plans_info!("(release) Flight plan {} released.", flight_plan_id);
+        self.commit(&mut plans, from, record, Some(&release))
+            .await?;
+
+        Ok(outcome)
+    }
+
+    /// Persists a flight plan that did not change state
+    ///
+    /// The change is only kept in memory once it was persisted.
+    async fn persist(
+        &self,
+        plans: &mut HashMap<String, FlightPlanRecord>,
+        record: FlightPlanRecord,
+    ) -> Result<FlightPlanRecord, FlightPlanError> {
+        self.storage.save_flight_plan(&record).await.map_err(|e| {
+            flight_plans_error!(
+                "(persist) Could not persist flight plan {}: {}",
+                record.flight_plan_id,
+                e
+            );
+            e
+        })?;
+
+        plans.insert(record.flight_plan_id.clone(), record.clone());
+        Ok(record)
+    }
+
+    /// Persists a changed flight plan, along with its release if provided,
+    ///  and records the transition in the audit trail
+    ///
+    /// The change is only kept in memory once it was persisted.
+    async fn commit(
+        &self,
+        plans: &mut HashMap<String, FlightPlanRecord>,
+        from: FlightPlanState,
+        record: FlightPlanRecord,
+        release: Option<&ReleaseRecord>,
+    ) -> Result<FlightPlanRecord, FlightPlanError> {
+        let saved = match release {
+            Some(release) => {
+                self.storage
+                    .save_flight_plan_and_release(&record, release)
+                    .await
+            }
+            None => self.storage.save_flight_plan(&record).await,
+        };
+
+        saved.map_err(|e| {
+            flight_plans_error!(
+                "(commit) Could not persist flight plan {}: {}",
+                record.flight_plan_id,
+                e
+            );
+            e
+        })?;
+
+        plans.insert(record.flight_plan_id.clone(), record.clone());
+
+        let Some(transition) = record.history.last() else {
+            return Ok(record);
+        };
+
+        let mut detail = format!("{} -> {}", from, transition.state);
+        if let Some(reason) = &transition.reason {
+            detail = format!("{detail}: {reason}");
+        }
+
+        let audit = AuditRecord {
+            at: transition.at,
+            flight_plan_id: Some(record.flight_plan_id.clone()),
+            action: "transition".to_string(),
+            detail,
+        };
+
+        if let Err(e) = self.storage.add_audit(&audit).await {
+            flight_plans_error!("(commit) Could not record the transition: {}", e);
+        }
+
+        Ok(record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use FlightPlanState::*;
+
+    #[tokio::test]
+    async fn test_transitions() {
+        crate::get_log_handle().await;
+        ut_info!("(test_transitions) Start.");
+
+        assert!(Draft.can_transition_to(Submitted));
+        assert!(Submitted.can_transition_to(PendingAuthority));
+        assert!(PendingAuthority.can_transition_to(Accepted));
+        assert!(Accepted.can_transition_to(Released));
+        assert!(Released.can_transition_to(Active));
+        assert!(Active.can_transition_to(Closed));
+        assert!(Released.can_transition_to(Closed));
+        assert!(Accepted.can_transition_to(Submitted));
+        assert!(Accepted.can_transition_to(Cancelled));
+        assert!(Released.can_transition_to(Expired));
+        assert!(Released.can_transition_to(Accepted));
+
+        assert!(!Draft.can_transition_to(Released));
+        assert!(!Submitted.can_transition_to(Released));
+        assert!(!Rejected.can_transition_to(Accepted));
+        assert!(!Active.can_transition_to(Cancelled));
+        assert!(!Active.can_transition_to(Expired));
+        assert!(!Draft.can_transition_to(Expired));
+        assert!(!Closed.can_transition_to(Cancelled));
+        assert!(!Accepted.can_transition_to(Accepted));
+        assert!(!Submitted.can_transition_to(Submitted));
+        assert!(!Released.can_transition_to(Submitted));
+        assert!(!Accepted.can_transition_to(Closed));
+
+        ut_info!("(test_transitions) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_fingerprint() {
+        crate::get_log_handle().await;
+        ut_info!("(test_fingerprint) Start.");
+
+        assert_eq!(
+            fingerprint(""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(fingerprint("{}"), fingerprint("{}"));
+        assert_ne!(fingerprint("{}"), fingerprint("{ }"));
+
+        ut_info!("(test_fingerprint) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_store() {
+        crate::get_log_handle().await;
+        ut_info!("(test_store) Start.");
+
+        let storage: Arc<dyn Storage> = Arc::new(MemoryStorage::default());
+        let store = FlightPlanStore::new(storage.clone());
+        let at = Utc::now();
+
+        assert!(matches!(
+            store.submit("", "{}", at).await,
+            Err(FlightPlanError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            store.transition("FP-1", Accepted, at, None).await,
+            Err(FlightPlanError::NotFound(_))
+        ));
+
+        let record = store.submit("FP-1", "{}", at).await.unwrap();
+        assert_eq!(record.state, Submitted);
+        assert_eq!(record.entered(Draft), Some(at));
+        assert_eq!(record.fingerprint, fingerprint("{}"));
+        assert!(matches!(
+            store.submit("FP-1", "{}", at).await,
+            Err(FlightPlanError::AlreadySubmitted(_))
+        ));
+        assert!(matches!(
+            store.submit("FP-1", "{\"changed\":true}", at).await,
+            Err(FlightPlanError::Conflict(_))
+        ));
+
+        let later = at + chrono::Duration::try_minutes(1).unwrap();
+        store
+            .transition("FP-1", Accepted, later, Some("approved".to_string()))
+            .await
+            .unwrap();
+
+        // Invalid transitions leave the flight plan unchanged
+        assert!(matches!(
+            store.transition("FP-1", Closed, later, None).await,
+            Err(FlightPlanError::InvalidTransition { .. })
+        ));
+
+        // Accepted plans are only submitted again through an amendment
+        assert!(matches!(
+            store.submit("FP-1", "{\"amended\":true}", later).await,
+            Err(FlightPlanError::Conflict(_))
+        ));
+        assert!(matches!(
+            store.amend("FP-2", "{}").await,
+            Err(FlightPlanError::NotFound(_))
+        ));
+
+        // A refused amendment leaves the accepted plan as it was
+        let record = store.amend("FP-1", "{\"refused\":true}").await.unwrap();
+        assert_eq!(record.state, Accepted);
+        assert_eq!(record.data, "{}");
+        assert_eq!(store.awaiting_authority().await.len(), 1);
+        assert!(matches!(
+            store.amend("FP-1", "{\"amended\":true}").await,
+            Err(FlightPlanError::InvalidArgument(_))
+        ));
+        let record = store.resolve_amendment("FP-1", false, later).await.unwrap();
+        assert_eq!(record.state, Accepted);
+        assert_eq!(record.data, "{}");
+        assert_eq!(record.amendment, None);
+        assert!(store.awaiting_authority().await.is_empty());
+        assert!(matches!(
+            store.resolve_amendment("FP-1", true, later).await,
+            Err(FlightPlanError::InvalidArgument(_))
+        ));
+
+        store.amend("FP-1", "{\"amended\":true}").await.unwrap();
+        let record = store.resolve_amendment("FP-1", true, later).await.unwrap();
+        assert_eq!(record.state, Accepted);
+        assert_eq!(record.data, "{\"amended\":true}");
+
+        // Reloaded after a restart
+        let reloaded = FlightPlanStore::new(storage.clone());
+        assert_eq!(reloaded.load().await.unwrap(), 1);
+        let record = reloaded.get("FP-1").await.unwrap();
+        assert_eq!(record.state, Accepted);
+        assert_eq!(reloaded.in_state(Accepted).await.len(), 1);
+        assert!(reloaded.in_state(Submitted).await.is_empty());
+        assert_eq!(record.created_at(), at);
+        assert_eq!(record.updated_at(), later);
+        assert_eq!(record.history[2].reason, Some("approved".to_string()));
+        assert_eq!(record.history[3].reason, Some("amended".to_string()));
+        assert_eq!(record.data, "{\"amended\":true}");
+        assert_eq!(record.fingerprint, fingerprint("{\"amended\":true}"));
+
+        let audit = storage.audit(Some("FP-1")).await.unwrap();
+        assert_eq!(audit.len(), 4);
+        assert_eq!(audit[1].detail, "submitted -> accepted: approved");
+        assert_eq!(audit[2].detail, "accepted -> submitted: amended");
+
+        ut_info!("(test_store) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_release() {
+        crate::get_log_handle().await;
+        ut_info!("(test_release) Start.");
+
+        let storage: Arc<dyn Storage> = Arc::new(MemoryStorage::default());
+        let store = FlightPlanStore::new(storage.clone());
+        let at = Utc::now();
+        store.submit("FP-1", "{}", at).await.unwrap();
+        store.transition("FP-1", Accepted, at, None).await.unwrap();
+
+        let release = ReleaseRecord {
+            flight_plan_id: "FP-1".to_string(),
+            released_at: at,
+            valid_until: None,
+            revoked_at: None,
+            revoked_reason: None,
+            token: None,
+        };
+
+        // Checked and released in one go
+        let grant = |plan: &FlightPlanRecord| match plan.state {
+            Accepted => Ok((true, Some(release.clone()))),
+            _ => Ok::<_, FlightPlanError>((false, None)),
+        };
+        assert!(store.release("FP-1", grant).await.unwrap());
+        assert_eq!(store.get("FP-1").await.unwrap().state, Released);
+        assert_eq!(
+            storage.release("FP-1").await.unwrap(),
+            Some(release.clone())
+        );
+        assert!(!store.release("FP-1", grant).await.unwrap());
+        assert!(matches!(
+            store.release("FP-2", grant).await,
+            Err(FlightPlanError::NotFound(_))
+        ));
+
+        ut_info!("(test_release) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_transition_if() {
+        crate::get_log_handle().await;
+        ut_info!("(test_transition_if) Start.");
+
+        let store = FlightPlanStore::default();
+        let at = Utc::now();
+        store.submit("FP-1", "{}", at).await.unwrap();
+        store.transition("FP-1", Accepted, at, None).await.unwrap();
+        store.amend("FP-1", "{\"amended\":true}").await.unwrap();
+
+        // Not allowed, the flight plan stays as it was
+        let (outcome, record) = store
+            .transition_if("FP-1", Cancelled, |_| {
+                Ok::<_, FlightPlanError>((false, None))
+            })
+            .await
+            .unwrap();
+        assert!(!outcome);
+        assert!(record.is_none());
+        assert_eq!(store.get("FP-1").await.unwrap().state, Accepted);
+
+        // Cancelled while the amendment awaits the authority
+        let later = at + chrono::Duration::try_minutes(1).unwrap();
+        let (_, record) = store
+            .transition_if("FP-1", Cancelled, |plan| {
+                assert_eq!(plan.state, Accepted);
+                Ok::<_, FlightPlanError>(((), Some((later, None))))
+            })
+            .await
+            .unwrap();
+        let record = record.unwrap();
+        assert_eq!(record.state, Cancelled);
+        assert_eq!(record.updated_at(), later);
+        assert_eq!(record.amendment, None);
+        assert!(store.awaiting_authority().await.is_empty());
+        assert!(matches!(
+            store.resolve_amendment("FP-1", true, later).await,
+            Err(FlightPlanError::InvalidArgument(_))
+        ));
+
+        // Invalid transitions are refused before the function is called
+        assert!(matches!(
+            store
+                .transition_if("FP-1", Closed, |_| -> Result<((), _), FlightPlanError> {
+                    panic!("called for an invalid transition")
+                })
+                .await,
+            Err(FlightPlanError::InvalidTransition { .. })
+        ));
+
+        ut_info!("(test_transition_if) Success.");
+    }
+}
@@ -0,0 +1,507 @@
+//! Updates pushed to svc-gis
+//!
+//! Zones and waypoints are pushed in batches over a connection shared by the
+//!  refresh loops. A batch failing with a transient gRPC error is retried
+//!  with exponential backoff, up to the configured number of attempts; a
+//!  batch failing with a permanent error is not retried. The outcome of
+//!  every push is counted, so it can be reported.
+
+#[macro_use]
+pub mod macros;
+
+use serde::Deserialize;
+use std::fmt::{self, Display, Formatter};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use svc_gis_client_grpc::prelude::*;
+use tonic::{Code, Status};
+
+/// Retry and batching settings for updates pushed to svc-gis
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct RetryConfig {
+    /// Maximum number of attempts per batch, including the first one
+    pub max_attempts: u32,
+
+    /// Delay in milliseconds before the first retry
+    pub initial_backoff_ms: u64,
+
+    /// Maximum delay in milliseconds between retries
+    pub max_backoff_ms: u64,
+
+    /// Maximum number of items per request
+    pub batch_size: usize,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        RetryConfig {
+            max_attempts: 4,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 10_000,
+            batch_size: 500,
+        }
+    }
+}
+
+/// Settings of the wait for svc-gis on startup
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct StartupConfig {
+    /// Maximum time in seconds to wait for svc-gis to be ready
+    pub timeout_seconds: u32,
+
+    /// Interval in milliseconds between readiness checks
+    pub poll_interval_ms: u64,
+}
+
+impl Default for StartupConfig {
+    fn default() -> Self {
+        StartupConfig {
+            timeout_seconds: 60,
+            poll_interval_ms: 1000,
+        }
+    }
+}
+
+/// Whether a failed request is worth retrying
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// The request may succeed if sent again
+    Transient,
+
+    /// The request will fail again until something changes
+    Permanent,
+}
+
+/// Classifies a gRPC status code
+///
+/// Codes indicating an unavailable, overloaded or interrupted server are
+///  transient. Codes indicating a problem with the request itself, or with
+///  our credentials, are permanent, and so are unknown errors, which would
+///  otherwise be sent again for every attempt.
+pub fn classify(code: Code) -> ErrorClass {
+    match code {
+        Code::Unavailable
+        | Code::DeadlineExceeded
+        | Code::ResourceExhausted
+        | Code::Aborted
+        | Code::Cancelled => ErrorClass::Transient,
+        _ => ErrorClass::Permanent,
+    }
+}
+
+/// Returns the delay before the provided retry, starting at 1
+///
+/// The delay doubles with every retry, up to the configured maximum.
+pub fn backoff(config: &RetryConfig, retry: u32) -> Duration {
+    let factor = 2u64.saturating_pow(retry.saturating_sub(1));
+    Duration::from_millis(
+        config
+            .initial_backoff_ms
+            .saturating_mul(factor)
+            .min(config.max_backoff_ms),
+    )
+}
+
+/// Errors returned when pushing updates to svc-gis
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum PushError {
+    /// svc-gis rejected the update
+    #[error("error: svc-gis rejected the update: {0}.")]
+    Permanent(String),
+
+    /// svc-gis does not support the update yet
+    #[error("error: svc-gis does not support {0} yet.")]
+    Unsupported(String),
+
+    /// svc-gis could not be reached within the allowed attempts
+    #[error("error: svc-gis unavailable after {attempts} attempt(s): {message}.")]
+    Exhausted {
+        /// Number of attempts made
+        attempts: u32,
+
+        /// Message of the last error
+        message: String,
+    },
+}
+
+/// Counters of the pushes of one kind of item
+#[derive(Debug, Default)]
+struct PushCounters {
+    /// Batches accepted by svc-gis
+    batches: AtomicU64,
+
+    /// Requests sent again after a transient error
+    retries: AtomicU64,
+
+    /// Batches abandoned after exhausting the attempts
+    transient_failures: AtomicU64,
+
+    /// Batches rejected with a permanent error
+    permanent_failures: AtomicU64,
+}
+
+impl PushCounters {
+    /// Returns the current values of the counters
+    fn totals(&self) -> PushTotals {
+        PushTotals {
+            batches: self.batches.load(Ordering::Relaxed),
+            retries: self.retries.load(Ordering::Relaxed),
+            transient_failures: self.transient_failures.load(Ordering::Relaxed),
+            permanent_failures: self.permanent_failures.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Totals of the pushes of one kind of item since the service started
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct PushTotals {
+    /// Batches accepted by svc-gis
+    pub batches: u64,
+
+    /// Requests sent again after a transient error
+    pub retries: u64,
+
+    /// Batches abandoned after exhausting the attempts
+    pub transient_failures: u64,
+
+    /// Batches rejected with a permanent error
+    pub permanent_failures: u64,
+}
+
+impl Display for PushTotals {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} batch(es) accepted, {} retry(ies), {} transient failure(s), {} permanent failure(s)",
+            self.batches, self.retries, self.transient_failures, self.permanent_failures
+        )
+    }
+}
+
+/// Pushes zones and waypoints to svc-gis
+pub struct GisPusher {
+    /// Client reused for every request
+    client: GisClient,
+
+    /// Retry and batching settings
+    config: RetryConfig,
+
+    /// Counters of zone pushes
+    zones: PushCounters,
+
+    /// Counters of waypoint pushes
+    waypoints: PushCounters,
+}
+
+impl fmt::Debug for GisPusher {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GisPusher")
+            .field("client", &self.client.get_address())
+            .field("config", &self.config)
+            .field("zones", &self.zones)
+            .field("waypoints", &self.waypoints)
+            .finish()
+    }
+}
+
+impl GisPusher {
+    /// Create a new pusher for the svc-gis server at the provided address
+    pub fn new(host: &str, port: u16, config: RetryConfig) -> Self {
+        Self {
+            client: GisClient::new_client(host, port, "gis"),
+            config,
+            zones: PushCounters::default(),
+            waypoints: PushCounters::default(),
+        }
+    }
+
+    /// Returns the totals of the zone pushes
+    pub fn zone_totals(&self) -> PushTotals {
+        self.zones.totals()
+    }
+
+    /// Returns the totals of the waypoint pushes
+    pub fn waypoint_totals(&self) -> PushTotals {
+        self.waypoints.totals()
+    }
+
+    /// Returns true if svc-gis reports it is ready
+    pub async fn is_ready(&self) -> bool {
+        match self.client.is_ready(gis::ReadyRequest {}).await {
+            Ok(response) => response.into_inner().ready,
+            Err(e) => {
+                gis_debug!("(is_ready) {:?}: {}", e.code(), e.message());
+                false
+            }
+        }
+    }
+
+    /// Waits until svc-gis reports it is ready, up to the configured timeout
+    ///
+    /// Returns false if svc-gis was not ready in time.
+    pub async fn wait_until_ready(&self, config: &StartupConfig) -> bool {
+        let start = tokio::time::Instant::now();
+        let timeout = Duration::from_secs(config.timeout_seconds as u64);
+        let interval = Duration::from_millis(config.poll_interval_ms.max(1));
+
+        gis_info!(
+            "(wait_until_ready) Waiting up to {} seconds for svc-gis.",
+            config.timeout_seconds
+        );
+
+        loop {
+            if self.is_ready().await {
+                gis_info!(
+                    "(wait_until_ready) svc-gis ready after {} ms.",
+                    start.elapsed().as_millis()
+                );
+                return true;
+            }
+
+            if start.elapsed() + interval > timeout {
+                gis_warn!(
+                    "(wait_until_ready) svc-gis not ready after {} seconds.",
+                    config.timeout_seconds
+                );
+                return false;
+            }
+
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    /// Pushes the zones to svc-gis
+    pub async fn update_zones(&self, zones: Vec<gis::Zone>) -> Result<(), PushError> {
+        self.push("zones", &self.zones, zones, |zones| {
+            self.client.update_zones(gis::UpdateZonesRequest { zones })
+        })
+        .await
+    }
+
+    /// Pushes the waypoints to svc-gis
+    pub async fn update_waypoints(&self, waypoints: Vec<gis::Waypoint>) -> Result<(), PushError> {
+        self.push("waypoints", &self.waypoints, waypoints, |waypoints| {
+            self.client
+                .update_waypoints(gis::UpdateWaypointsRequest { waypoints })
+        })
+        .await
+    }
+
+    /// Removes the waypoints from svc-gis
+    pub async fn remove_waypoints(&self, identifiers: Vec<String>) -> Result<(), PushError> {
+        if identifiers.is_empty() {
+            return Ok(());
+        }
+
+        //
+        // TODO(R4): svc-gis has no call to remove waypoints yet. Send the
+        //  removals once it does; until then the caller keeps the waypoints
+        //  closed by zones and asks again on every refresh.
+        //
+        gis_warn!(
+            "(remove_waypoints) Cannot remove {} withheld waypoint(s) from svc-gis: {}.",
+            identifiers.len(),
+            identifiers.join(", ")
+        );
+
+        Err(PushError::Unsupported("removing waypoints".to_string()))
+    }
+
+    /// Sends the items in batches, retrying batches failing with a
+    ///  transient error
+    ///
+    /// Every batch is attempted, even after another one failed, so a single
+    ///  bad batch does not hold back the others. Returns the last error, if
+    ///  any.
+    async fn push<T, R, F, Fut>(
+        &self,
+        kind: &str,
+        counters: &PushCounters,
+        items: Vec<T>,
+        send: F,
+    ) -> Result<(), PushError>
+    where
+        T: Clone,
+        F: Fn(Vec<T>) -> Fut,
+        Fut: Future<Output = Result<tonic::Response<R>, Status>>,
+    {
+        let batch_size = self.config.batch_size.max(1);
+        let batches = items.len().div_ceil(batch_size);
+        let mut result = Ok(());
+
+        for (index, batch) in items.chunks(batch_size).enumerate() {
+            let mut attempt = 1;
+            let error = loop {
+                let status = match send(batch.to_vec()).await {
+                    Ok(_) => {
+                        gis_debug!(
+                            "(push) Batch {}/{} of {} accepted.",
+                            index + 1,
+                            batches,
+                            kind
+                        );
+                        counters.batches.fetch_add(1, Ordering::Relaxed);
+                        break None;
+                    }
+                    Err(status) => status,
+                };
+
+                if classify(status.code()) == ErrorClass::Permanent {
+                    counters.permanent_failures.fetch_add(1, Ordering::Relaxed);
+                    break Some(PushError::Permanent(status.message().to_string()));
+                }
+
+                if attempt >= self.config.max_attempts {
+                    counters.transient_failures.fetch_add(1, Ordering::Relaxed);
+                    break Some(PushError::Exhausted {
+                        attempts: attempt,
+                        message: status.message().to_string(),
+                    });
+                }
+
+                let delay = backoff(&self.config, attempt);
+                gis_warn!(
+                    "(push) Batch {}/{} of {} failed ({:?}), retrying in {} ms: {}",
+                    index + 1,
+                    batches,
+                    kind,
+                    status.code(),
+                    delay.as_millis(),
+                    status.message()
+                );
+
+                counters.retries.fetch_add(1, Ordering::Relaxed);
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            };
+
+            if let Some(e) = error {
+                gis_error!("(push) Batch {}/{} of {}: {}", index + 1, batches, kind, e);
+                result = Err(e);
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU32;
+
+    #[tokio::test]
+    async fn test_backoff() {
+        crate::get_log_handle().await;
+        ut_info!("(test_backoff) Start.");
+
+        assert_eq!(classify(Code::Unavailable), ErrorClass::Transient);
+        assert_eq!(classify(Code::DeadlineExceeded), ErrorClass::Transient);
+        assert_eq!(classify(Code::InvalidArgument), ErrorClass::Permanent);
+        assert_eq!(classify(Code::Unauthenticated), ErrorClass::Permanent);
+        assert_eq!(classify(Code::Unknown), ErrorClass::Permanent);
+
+        let config = RetryConfig::default();
+        assert_eq!(backoff(&config, 1), Duration::from_millis(500));
+        assert_eq!(backoff(&config, 3), Duration::from_millis(2000));
+        assert_eq!(backoff(&config, 10), Duration::from_millis(10_000));
+        assert_eq!(backoff(&config, u32::MAX), Duration::from_millis(10_000));
+
+        ut_info!("(test_backoff) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_push() {
+        crate::get_log_handle().await;
+        ut_info!("(test_push) Start.");
+
+        let pusher = GisPusher::new(
+            "localhost",
+            50008,
+            RetryConfig {
+                max_attempts: 3,
+                initial_backoff_ms: 1,
+                max_backoff_ms: 1,
+                batch_size: 2,
+            },
+        );
+
+        // The second request fails once, then succeeds
+        let calls = AtomicU32::new(0);
+        let result = pusher
+            .push("items", &pusher.zones, vec![1, 2, 3, 4, 5], |batch| {
+                let call = calls.fetch_add(1, Ordering::Relaxed);
+                async move {
+                    match call {
+                        1 => Err(Status::unavailable("busy")),
+                        _ => Ok(tonic::Response::new(batch.len())),
+                    }
+                }
+            })
+            .await;
+        assert!(result.is_ok());
+        assert_eq!(calls.load(Ordering::Relaxed), 4);
+        assert_eq!(
+            pusher.zone_totals(),
+            PushTotals {
+                batches: 3,
+                retries: 1,
+                ..Default::default()
+            }
+        );
+
+        // Permanent errors are not retried, transient ones until exhausted
+        let calls = AtomicU32::new(0);
+        let result = pusher
+            .push("items", &pusher.waypoints, vec![1, 2, 3], |batch| {
+                let call = calls.fetch_add(1, Ordering::Relaxed);
+                async move {
+                    match call {
+                        0 => Err(Status::invalid_argument("bad zone")),
+                        _ => Err::<tonic::Response<Vec<i32>>, _>(Status::unavailable(format!(
+                            "busy with {}",
+                            batch.len()
+                        ))),
+                    }
+                }
+            })
+            .await;
+        assert_eq!(
+            result,
+            Err(PushError::Exhausted {
+                attempts: 3,
+                message: "busy with 1".to_string()
+            })
+        );
+        assert_eq!(calls.load(Ordering::Relaxed), 4);
+        assert_eq!(
+            pusher.waypoint_totals(),
+            PushTotals {
+                batches: 0,
+                retries: 2,
+                transient_failures: 1,
+                permanent_failures: 1,
+            }
+        );
+
+        ut_info!("(test_push) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_ready() {
+        crate::get_log_handle().await;
+        ut_info!("(test_wait_until_ready) Start.");
+
+        let pusher = GisPusher::new("localhost", 50008, RetryConfig::default());
+        let config = StartupConfig {
+            timeout_seconds: 1,
+            poll_interval_ms: 10,
+        };
+        assert!(pusher.wait_until_ready(&config).await);
+
+        ut_info!("(test_wait_until_ready) Success.");
+    }
+}
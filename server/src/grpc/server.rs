@@ -7,20 +7,49 @@ mod grpc_server {
 }
 
 pub use crate::amqp::init_mq;
-use crate::region::RestrictionDetails;
+use crate::region::{RestrictionDetails, WaypointDetails};
 pub use grpc_server::rpc_service_server::{RpcService, RpcServiceServer};
+pub use grpc_server::{Coordinates, Restriction, RestrictionsRequest, RestrictionsResponse};
+pub use grpc_server::{DeleteRestrictionRequest, ManualRestriction, ManualRestrictionResponse};
+pub use grpc_server::{FeedReference, FeedSummary, MergeConflict, MergeReport, MergeReportRequest};
+pub use grpc_server::{FlightPlanActivateRequest, FlightPlanActivateResponse};
+pub use grpc_server::{FlightPlanCancelRequest, FlightPlanCancelResponse};
+pub use grpc_server::{FlightPlanCloseRequest, FlightPlanCloseResponse};
 pub use grpc_server::{FlightPlanRequest, FlightPlanResponse};
-pub use grpc_server::{FlightReleaseRequest, FlightReleaseResponse};
+pub use grpc_server::{FlightPlanStatus, FlightPlanStatusRequest, FlightPlanTransition};
+pub use grpc_server::{FlightReleaseRequest, FlightReleaseResponse, ReleaseStatus};
 pub use grpc_server::{ReadyRequest, ReadyResponse};
+pub use grpc_server::{ReleaseKey, ReleaseKeys, ReleaseKeysRequest};
+pub use grpc_server::{Waypoint, WaypointsRequest, WaypointsResponse};
+pub use grpc_server::{WaypointQualityIssue, WaypointQualityReport, WaypointQualityRequest};
 use svc_gis_client_grpc::prelude::*;
 
+use crate::amqp::{self, FlightPlanEvent};
 use crate::config::Config;
+use crate::decisions::{self, DecisionResolver};
+use crate::flight_plans::{FlightPlanError, FlightPlanRecord, FlightPlanState, FlightPlanStore};
+use crate::gis::GisPusher;
+use crate::health::{HealthState, StartupPhase};
+use crate::identifiers::IdentifierRegistry;
 use crate::region::RegionInterface;
+use crate::releases::token::{ReleaseClaims, ReleaseSigner};
+use crate::releases::{self, FlightPlanData, ReleaseConfig, ReleaseError, ReleaseMonitor};
+use crate::restrictions::buffer::{apply_buffers, BufferConfig};
+use crate::restrictions::merge::{self, SourcePriorities};
+use crate::restrictions::simplify::simplify_restrictions;
+use crate::restrictions::{manual, ListedRestriction, RestrictionError, RestrictionStore};
 use crate::shutdown_signal;
+use crate::snapshot;
+use crate::sources::{self, RestrictionSources, WaypointSources};
+use crate::storage::{self, AuthorityRequest, AuthorityResponse, ReleaseRecord, StorageError};
+use crate::waypoints::{quality, ListedWaypoint, WaypointStore};
+use crate::webhook::{self, WebhookReceiver};
 
+use chrono::{DateTime, Utc};
 use core::fmt;
 use std::collections::HashMap;
-use std::net::SocketAddr;
+use std::net::{IpAddr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
 use tonic::transport::Server;
 use tonic::{Request, Response, Status};
 
@@ -31,6 +60,27 @@ pub struct ServerImpl {
 
     /// Region interface
     pub region: Box<dyn RegionInterface + Send + Sync>,
+
+    /// Restrictions shared with the refresh loop
+    pub restrictions: Arc<RestrictionStore>,
+
+    /// Waypoints shared with the refresh loop
+    pub waypoints: Arc<WaypointStore>,
+
+    /// Readiness recorded by the refresh loops
+    pub health: Arc<HealthState>,
+
+    /// Lifecycle of submitted flight plans
+    pub flight_plans: Arc<FlightPlanStore>,
+
+    /// Release checks and validity
+    pub release: ReleaseConfig,
+
+    /// Safety margins added around the restrictions checked for a release
+    pub safety_buffers: BufferConfig,
+
+    /// Signer of release tokens, if tokens are issued
+    pub release_signer: Option<Arc<ReleaseSigner>>,
 }
 
 /// Results of updating restrictions
@@ -59,14 +109,395 @@ pub enum UpdateWaypointsStatus {
     RequestFailure,
 }
 
+/// State shared by the startup sequence and the refresh loops
+#[derive(Debug, Clone)]
+pub struct RefreshContext {
+    /// Service configuration
+    pub config: Config,
+
+    /// Region short code
+    pub region: String,
+
+    /// Restrictions shared with the gRPC server
+    pub restrictions: Arc<RestrictionStore>,
+
+    /// Waypoints shared with the gRPC server
+    pub waypoints: Arc<WaypointStore>,
+
+    /// Identifiers items are published under
+    pub registry: Arc<IdentifierRegistry>,
+
+    /// Connection to svc-gis
+    pub pusher: Arc<GisPusher>,
+
+    /// Readiness of the service
+    pub health: Arc<HealthState>,
+
+    /// Monitor revoking the releases crossed by new restrictions
+    pub releases: Arc<ReleaseMonitor>,
+}
+
+/// Returns when a refresh loop should wake up: when the next source is
+///  due, or after the default interval if there are no sources
+fn next_due(due: Option<tokio::time::Instant>, default_seconds: u16) -> tokio::time::Instant {
+    due.unwrap_or_else(|| {
+        tokio::time::Instant::now() + std::time::Duration::from_secs(default_seconds as u64)
+    })
+}
+
 impl fmt::Debug for ServerImpl {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("ServerImpl")
             .field("region", &"RegionInterface (not printable)")
+            .field("restrictions", &self.restrictions)
+            .field("waypoints", &self.waypoints)
+            .field("health", &self.health)
             .finish()
     }
 }
 
+impl From<RestrictionError> for Status {
+    fn from(e: RestrictionError) -> Self {
+        match e {
+            RestrictionError::AlreadyExists(_) => Status::already_exists(e.to_string()),
+            RestrictionError::NotFound(_) => Status::not_found(e.to_string()),
+            RestrictionError::InvalidArgument(_) => Status::invalid_argument(e.to_string()),
+            RestrictionError::Persistence(_) => Status::internal(e.to_string()),
+        }
+    }
+}
+
+impl From<FlightPlanError> for Status {
+    fn from(e: FlightPlanError) -> Self {
+        match e {
+            FlightPlanError::NotFound(_) => Status::not_found(e.to_string()),
+            FlightPlanError::InvalidTransition { .. } => Status::failed_precondition(e.to_string()),
+            FlightPlanError::InvalidArgument(_) => Status::invalid_argument(e.to_string()),
+            FlightPlanError::AlreadySubmitted(_) | FlightPlanError::Conflict(_) => {
+                Status::already_exists(e.to_string())
+            }
+            FlightPlanError::Persistence(_) => Status::internal(e.to_string()),
+        }
+    }
+}
+
+impl From<ReleaseError> for Status {
+    fn from(e: ReleaseError) -> Self {
+        Status::failed_precondition(e.to_string())
+    }
+}
+
+impl From<StorageError> for Status {
+    fn from(e: StorageError) -> Self {
+        Status::internal(e.to_string())
+    }
+}
+
+/// Records an answer of the authority, logging failures
+async fn record_authority_response(flight_plans: &FlightPlanStore, response: AuthorityResponse) {
+    if let Err(e) = flight_plans
+        .storage()
+        .add_authority_response(&response)
+        .await
+    {
+        grpc_error!(
+            "(record_authority_response) Could not record the {} response for {}: {}",
+            response.request,
+            response.flight_plan_id,
+            e
+        );
+    }
+}
+
+/// Records the decision of the authority on a submitted flight plan, which
+///  is accepted or rejected accordingly
+///
+/// A flight plan the authority decides on later awaits the decision, which
+///  is resolved by the [`DecisionResolver`](crate::decisions::DecisionResolver).
+///  A flight plan the authority could not be asked about stays submitted,
+///  so a retried submission files it again.
+async fn record_decision(
+    flight_plans: &FlightPlanStore,
+    flight_plan_id: &str,
+    request: AuthorityRequest,
+    result: Result<Response<FlightPlanResponse>, Status>,
+) -> Result<Response<FlightPlanResponse>, Status> {
+    flight_plans.answered(flight_plan_id).await;
+    let response = result?;
+
+    record_authority_response(
+        flight_plans,
+        AuthorityResponse {
+            flight_plan_id: flight_plan_id.to_string(),
+            request,
+            received_at: Utc::now(),
+            granted: response.get_ref().submitted,
+            message: response.get_ref().result.clone(),
+            reference: response.get_ref().authority_reference.clone(),
+            pending: response.get_ref().pending,
+        },
+    )
+    .await;
+
+    if response.get_ref().pending {
+        flight_plans
+            .transition(
+                flight_plan_id,
+                FlightPlanState::PendingAuthority,
+                Utc::now(),
+                None,
+            )
+            .await?;
+
+        return Ok(response);
+    }
+
+    let (state, reason) = match response.get_ref().submitted {
+        true => (FlightPlanState::Accepted, None),
+        false => (FlightPlanState::Rejected, response.get_ref().result.clone()),
+    };
+    flight_plans
+        .transition(flight_plan_id, state, Utc::now(), reason)
+        .await?;
+
+    Ok(response)
+}
+
+/// Returns the answer of the authority to the latest submission or amendment
+///  of a flight plan, for a submission that was retried
+async fn stored_decision(
+    flight_plans: &FlightPlanStore,
+    flight_plan_id: &str,
+) -> Result<Response<FlightPlanResponse>, Status> {
+    let responses = flight_plans
+        .storage()
+        .authority_responses(flight_plan_id)
+        .await?;
+
+    let Some(response) = responses.into_iter().rev().find(|response| {
+        matches!(
+            response.request,
+            AuthorityRequest::Submission | AuthorityRequest::Amendment
+        )
+    }) else {
+        // The first submission is still awaiting the authority
+        return Err(Status::aborted(format!(
+            "flight plan {flight_plan_id} is being submitted"
+        )));
+    };
+
+    Ok(Response::new(FlightPlanResponse {
+        flight_plan_id: flight_plan_id.to_string(),
+        submitted: response.granted,
+        result: response.message,
+        authority_reference: response.reference,
+        pending: response.pending,
+    }))
+}
+
+/// Converts a protobuf timestamp to a [`DateTime<Utc>`]
+fn timestamp_to_datetime(timestamp: &prost_types::Timestamp) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp(timestamp.seconds, timestamp.nanos.try_into().ok()?)
+}
+
+/// Converts a [`DateTime<Utc>`] to a protobuf timestamp
+fn datetime_to_timestamp(datetime: &DateTime<Utc>) -> prost_types::Timestamp {
+    prost_types::Timestamp {
+        seconds: datetime.timestamp(),
+        nanos: datetime.timestamp_subsec_nanos() as i32,
+    }
+}
+
+/// Converts an admin request into a [`manual::ManualRestriction`]
+fn to_manual_restriction(request: ManualRestriction) -> Result<manual::ManualRestriction, Status> {
+    let timestamp_start = match &request.time_start {
+        Some(ts) => Some(
+            timestamp_to_datetime(ts)
+                .ok_or_else(|| Status::invalid_argument("invalid start time"))?,
+        ),
+        None => None,
+    };
+
+    let timestamp_end = match &request.time_end {
+        Some(ts) => Some(
+            timestamp_to_datetime(ts).ok_or_else(|| Status::invalid_argument("invalid expiry"))?,
+        ),
+        None => None,
+    };
+
+    let now = Utc::now();
+    Ok(manual::ManualRestriction {
+        details: RestrictionDetails {
+            vertices: request
+                .vertices
+                .iter()
+                .map(|v| gis::Coordinates {
+                    latitude: v.latitude,
+                    longitude: v.longitude,
+                })
+                .collect(),
+            timestamp_start,
+            timestamp_end,
+            zone_type: gis::ZoneType::Restriction,
+            altitude_meters_min: request.altitude_meters_min,
+            altitude_meters_max: request.altitude_meters_max,
+            notam_id: None,
+        },
+        reason: request.reason,
+        author: request.author,
+        created_at: now,
+        updated_at: now,
+    })
+}
+
+/// Converts a merged restriction into its gRPC representation
+fn to_restriction(restriction: ListedRestriction) -> Restriction {
+    let ListedRestriction {
+        identifier,
+        merged,
+        manual,
+    } = restriction;
+
+    let details = merged.details;
+    Restriction {
+        identifier,
+        vertices: details
+            .vertices
+            .iter()
+            .map(|v| Coordinates {
+                latitude: v.latitude,
+                longitude: v.longitude,
+            })
+            .collect(),
+        altitude_meters_min: details.altitude_meters_min,
+        altitude_meters_max: details.altitude_meters_max,
+        time_start: details.timestamp_start.as_ref().map(datetime_to_timestamp),
+        time_end: details.timestamp_end.as_ref().map(datetime_to_timestamp),
+        zone_type: details.zone_type.as_str_name().to_string(),
+        source: merged.kind.to_string(),
+        reason: manual.as_ref().map(|m| m.reason.clone()),
+        author: manual.map(|m| m.author),
+        feed: merged.feed,
+    }
+}
+
+/// Returns true if the AMQP channel is open
+fn amqp_connected(mq_channel: &Option<lapin::Channel>) -> bool {
+    mq_channel
+        .as_ref()
+        .is_some_and(|channel| channel.status().connected())
+}
+
+/// Converts a stored waypoint into its gRPC representation
+fn to_waypoint(waypoint: ListedWaypoint) -> Waypoint {
+    let ListedWaypoint {
+        identifier,
+        details,
+        withheld_by,
+    } = waypoint;
+
+    Waypoint {
+        identifier,
+        location: Some(Coordinates {
+            latitude: details.location.latitude,
+            longitude: details.location.longitude,
+        }),
+        name: details.name,
+        waypoint_type: details.waypoint_type.as_str().to_string(),
+        altitude_meters_min: details.altitude_meters_min,
+        altitude_meters_max: details.altitude_meters_max,
+        time_start: details.timestamp_start.as_ref().map(datetime_to_timestamp),
+        time_end: details.timestamp_end.as_ref().map(datetime_to_timestamp),
+        withheld_by,
+    }
+}
+
+/// Converts a tracked flight plan to its status, with the latest reference
+///  and message of the authority
+fn to_flight_plan_status(
+    plan: FlightPlanRecord,
+    responses: &[AuthorityResponse],
+    release: Option<ReleaseRecord>,
+) -> FlightPlanStatus {
+    FlightPlanStatus {
+        flight_plan_id: plan.flight_plan_id,
+        state: plan.state.to_string(),
+        authority_reference: responses.iter().rev().find_map(|r| r.reference.clone()),
+        authority_message: responses.iter().rev().find_map(|r| r.message.clone()),
+        release: release.map(|release| ReleaseStatus {
+            released_at: Some(datetime_to_timestamp(&release.released_at)),
+            valid_until: release.valid_until.as_ref().map(datetime_to_timestamp),
+            revoked_at: release.revoked_at.as_ref().map(datetime_to_timestamp),
+            revoked_reason: release.revoked_reason,
+            token: release.token,
+        }),
+        history: plan
+            .history
+            .into_iter()
+            .map(|transition| FlightPlanTransition {
+                state: transition.state.to_string(),
+                timestamp: Some(datetime_to_timestamp(&transition.at)),
+                reason: transition.reason,
+            })
+            .collect(),
+    }
+}
+
+/// Converts a waypoint quality report into its gRPC representation
+fn to_quality_report(report: quality::QualityReport) -> WaypointQualityReport {
+    WaypointQualityReport {
+        timestamp: Some(datetime_to_timestamp(&report.timestamp)),
+        received: report.received as u32,
+        accepted: report.accepted as u32,
+        issues: report
+            .issues
+            .into_iter()
+            .map(|issue| WaypointQualityIssue {
+                identifier: issue.identifier,
+                kind: issue.kind.to_string(),
+                related: issue.related,
+                distance_meters: issue.distance_meters,
+                action: issue.action.to_string(),
+            })
+            .collect(),
+    }
+}
+
+/// Converts a feed reference into its gRPC representation
+fn to_feed_reference(reference: merge::FeedReference) -> FeedReference {
+    FeedReference {
+        source: reference.kind.to_string(),
+        feed: reference.feed,
+        identifier: reference.identifier,
+    }
+}
+
+/// Converts a merge report into its gRPC representation
+fn to_merge_report(report: merge::MergeReport) -> MergeReport {
+    MergeReport {
+        timestamp: Some(datetime_to_timestamp(&report.timestamp)),
+        feeds: report
+            .feeds
+            .into_iter()
+            .map(|feed| FeedSummary {
+                source: feed.kind.to_string(),
+                feed: feed.feed,
+                restrictions: feed.restrictions as u32,
+            })
+            .collect(),
+        merged: report.merged as u32,
+        conflicts: report
+            .conflicts
+            .into_iter()
+            .map(|conflict| MergeConflict {
+                kept: Some(to_feed_reference(conflict.kept)),
+                dropped: Some(to_feed_reference(conflict.dropped)),
+                reason: conflict.reason.to_string(),
+            })
+            .collect(),
+    }
+}
+
 #[cfg(not(feature = "stub_server"))]
 #[tonic::async_trait]
 impl RpcService for ServerImpl {
@@ -78,7 +509,17 @@ impl RpcService for ServerImpl {
         let region = self.region.get_region();
         grpc_info!("(is_ready)[{}] compliance server.", region);
         grpc_debug!("(is_ready)[{}] [{:?}].", region, request);
-        let response = ReadyResponse { ready: true };
+        let issues = self
+            .health
+            .check(Utc::now(), amqp_connected(&self.mq_channel))
+            .await;
+        for issue in issues.iter() {
+            grpc_warn!("(is_ready)[{}] Not ready: {}.", region, issue);
+        }
+
+        let response = ReadyResponse {
+            ready: issues.is_empty(),
+        };
         Ok(Response::new(response))
     }
 
@@ -90,7 +531,32 @@ impl RpcService for ServerImpl {
         grpc_info!("(submit_flight_plan)[{}] compliance server.", region);
         grpc_debug!("(submit_flight_plan)[{}] [{:?}].", region, request);
         let request = request.into_inner();
-        let response = self.region.submit_flight_plan(request.clone())?;
+        let flight_plan_id = request.flight_plan_id.clone();
+        match self
+            .flight_plans
+            .submit(&flight_plan_id, &request.data, Utc::now())
+            .await
+        {
+            Ok(_) => (),
+            Err(FlightPlanError::AlreadySubmitted(_)) => {
+                grpc_info!(
+                    "(submit_flight_plan)[{}] Flight plan {} was already submitted, returning the stored response.",
+                    region,
+                    flight_plan_id
+                );
+                return stored_decision(&self.flight_plans, &flight_plan_id).await;
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        // The authority decides right away
+        let response = record_decision(
+            &self.flight_plans,
+            &flight_plan_id,
+            AuthorityRequest::Submission,
+            self.region.submit_flight_plan(request.clone()),
+        )
+        .await?;
 
         // send flight plan to AMQP
         if let Some(mq_channel) = &self.mq_channel {
@@ -101,8 +567,8 @@ impl RpcService for ServerImpl {
 
             let result = mq_channel
                 .basic_publish(
-                    crate::amqp::EXCHANGE_NAME_FLIGHTPLAN,
-                    crate::amqp::QUEUE_NAME_CARGO,
+                    amqp::EXCHANGE_NAME_FLIGHTPLAN,
+                    amqp::QUEUE_NAME_CARGO,
                     lapin::options::BasicPublishOptions::default(),
                     &payload,
                     lapin::BasicProperties::default(),
@@ -127,20 +593,604 @@ impl RpcService for ServerImpl {
         let region = self.region.get_region();
         grpc_info!("(request_flight_release)[{}] compliance server.", region);
         grpc_debug!("(request_flight_release)[{}] [{:?}].", region, request);
-        self.region.request_flight_release(request)
+        let flight_plan_id = request.get_ref().flight_plan_id.clone();
+        if self.flight_plans.get(&flight_plan_id).await.is_none() {
+            return Err(Status::failed_precondition(format!(
+                "flight plan {flight_plan_id} was never submitted"
+            )));
+        }
+
+        // Checked with the same margins as the zones published to svc-gis
+        let restrictions = apply_buffers(
+            &self.restrictions.get_all().await,
+            &self.safety_buffers,
+            self.region.get_region(),
+        );
+
+        // Decided while the flight plan is locked, so it is released once
+        let response = self
+            .flight_plans
+            .release(&flight_plan_id, |plan| {
+                if plan.state != FlightPlanState::Accepted {
+                    return Err(Status::failed_precondition(format!(
+                        "flight plan {flight_plan_id} is {}, only accepted flight plans can be released",
+                        plan.state
+                    )));
+                }
+
+                let now = Utc::now();
+                let data = FlightPlanData::parse(&plan.data)?;
+                releases::check_preconditions(&self.release, &data, &restrictions, now)?;
+
+                let mut response = self.region.request_flight_release(request)?;
+                if !response.get_ref().released {
+                    return Ok((response, None));
+                }
+
+                // The release runs out when the aircraft does not take off in time
+                let released_at = Utc::now();
+                let valid_until = data.release_end(&self.release, released_at);
+                let token = match &self.release_signer {
+                    Some(signer) => {
+                        let claims = ReleaseClaims::new(
+                            signer.issuer(),
+                            &flight_plan_id,
+                            &data,
+                            released_at,
+                            valid_until,
+                        );
+                        Some(
+                            signer
+                                .sign(&claims)
+                                .map_err(|e| Status::internal(e.to_string()))?,
+                        )
+                    }
+                    None => None,
+                };
+
+                response.get_mut().release_token = token.clone();
+                let release = ReleaseRecord {
+                    flight_plan_id: flight_plan_id.clone(),
+                    released_at,
+                    valid_until: Some(valid_until),
+                    revoked_at: None,
+                    revoked_reason: None,
+                    token,
+                };
+
+                Ok((response, Some(release)))
+            })
+            .await?;
+
+        record_authority_response(
+            &self.flight_plans,
+            AuthorityResponse {
+                flight_plan_id: flight_plan_id.clone(),
+                request: AuthorityRequest::Release,
+                received_at: Utc::now(),
+                granted: response.get_ref().released,
+                message: response.get_ref().result.clone(),
+                reference: None,
+                pending: false,
+            },
+        )
+        .await;
+
+        Ok(response)
+    }
+
+    async fn cancel_flight_plan(
+        &self,
+        request: Request<FlightPlanCancelRequest>,
+    ) -> Result<Response<FlightPlanCancelResponse>, Status> {
+        let region = self.region.get_region();
+        grpc_info!("(cancel_flight_plan)[{}] compliance server.", region);
+        grpc_debug!("(cancel_flight_plan)[{}] [{:?}].", region, request);
+        let request = request.into_inner();
+        let flight_plan_id = request.flight_plan_id.clone();
+
+        // Decided while the flight plan is locked, so it can not move on
+        //  before it is cancelled
+        let (response, plan) = self
+            .flight_plans
+            .transition_if(&flight_plan_id, FlightPlanState::Cancelled, |_| {
+                let response = self.region.cancel_flight_plan(request.clone())?;
+                let cancelled = response
+                    .get_ref()
+                    .cancelled
+                    .then(|| (Utc::now(), request.reason.clone()));
+                Ok::<_, Status>((response, cancelled))
+            })
+            .await?;
+
+        record_authority_response(
+            &self.flight_plans,
+            AuthorityResponse {
+                flight_plan_id: flight_plan_id.clone(),
+                request: AuthorityRequest::Cancellation,
+                received_at: Utc::now(),
+                granted: response.get_ref().cancelled,
+                message: response.get_ref().result.clone(),
+                reference: None,
+                pending: false,
+            },
+        )
+        .await;
+
+        let Some(plan) = plan else {
+            return Ok(response);
+        };
+
+        // A granted release can no longer be used
+        let storage = self.flight_plans.storage();
+        if let Some(mut release) = storage.release(&flight_plan_id).await? {
+            if release.revoked_at.is_none() {
+                release.revoked_at = Some(plan.updated_at());
+                release.revoked_reason = Some("flight plan cancelled".to_string());
+                storage.save_release(&release).await?;
+            }
+        }
+
+        amqp::publish_flight_plan_event(
+            &self.mq_channel,
+            amqp::ROUTING_KEY_CANCELLED,
+            &FlightPlanEvent {
+                flight_plan_id,
+                state: plan.state,
+                timestamp: plan.updated_at(),
+                reason: request.reason,
+                data: None,
+                landed_at: None,
+                departed_at: None,
+                zones: vec![],
+            },
+        )
+        .await;
+
+        Ok(response)
+    }
+
+    async fn amend_flight_plan(
+        &self,
+        request: Request<FlightPlanRequest>,
+    ) -> Result<Response<FlightPlanResponse>, Status> {
+        let region = self.region.get_region();
+        grpc_info!("(amend_flight_plan)[{}] compliance server.", region);
+        grpc_debug!("(amend_flight_plan)[{}] [{:?}].", region, request);
+        let request = request.into_inner();
+        let flight_plan_id = request.flight_plan_id.clone();
+        self.flight_plans
+            .amend(&flight_plan_id, &request.data)
+            .await?;
+
+        // The flight plan keeps its accepted data until the authority grants
+        //  the amendment
+        let result = self.region.amend_flight_plan(request.clone());
+        let now = Utc::now();
+        let (plan, granted, reason) = match &result {
+            Ok(response) => {
+                let response = response.get_ref();
+                record_authority_response(
+                    &self.flight_plans,
+                    AuthorityResponse {
+                        flight_plan_id: flight_plan_id.clone(),
+                        request: AuthorityRequest::Amendment,
+                        received_at: now,
+                        granted: response.submitted,
+                        message: response.result.clone(),
+                        reference: response.authority_reference.clone(),
+                        pending: response.pending,
+                    },
+                )
+                .await;
+
+                // Decided later, see DecisionResolver
+                let plan = match response.pending {
+                    true => self.flight_plans.get(&flight_plan_id).await,
+                    false => Some(
+                        self.flight_plans
+                            .resolve_amendment(&flight_plan_id, response.submitted, now)
+                            .await?,
+                    ),
+                };
+
+                let granted = response.submitted && !response.pending;
+                (plan, granted, response.result.clone())
+            }
+            Err(e) => {
+                let plan = self
+                    .flight_plans
+                    .resolve_amendment(&flight_plan_id, false, now)
+                    .await?;
+                (Some(plan), false, Some(e.message().to_string()))
+            }
+        };
+
+        if let Some(plan) = plan {
+            amqp::publish_flight_plan_event(
+                &self.mq_channel,
+                amqp::ROUTING_KEY_AMENDED,
+                &FlightPlanEvent {
+                    flight_plan_id,
+                    state: plan.state,
+                    timestamp: now,
+                    reason,
+                    data: granted.then_some(request.data),
+                    landed_at: None,
+                    departed_at: None,
+                    zones: vec![],
+                },
+            )
+            .await;
+        }
+
+        result
+    }
+
+    async fn activate_flight_plan(
+        &self,
+        request: Request<FlightPlanActivateRequest>,
+    ) -> Result<Response<FlightPlanActivateResponse>, Status> {
+        let region = self.region.get_region();
+        grpc_info!("(activate_flight_plan)[{}] compliance server.", region);
+        grpc_debug!("(activate_flight_plan)[{}] [{:?}].", region, request);
+        let request = request.into_inner();
+        let flight_plan_id = request.flight_plan_id;
+        let Some(departed_at) = request.departed_at.as_ref().and_then(timestamp_to_datetime) else {
+            return Err(Status::invalid_argument("a takeoff time must be provided"));
+        };
+
+        let now = Utc::now();
+        if departed_at > now {
+            return Err(Status::invalid_argument(format!(
+                "takeoff time {} is in the future",
+                departed_at.to_rfc3339()
+            )));
+        }
+
+        let release = self.flight_plans.storage().release(&flight_plan_id).await?;
+        let Some(release) = release.filter(|release| release.revoked_at.is_none()) else {
+            return Err(Status::failed_precondition(format!(
+                "flight plan {flight_plan_id} has no valid release"
+            )));
+        };
+
+        if let Some(valid_until) = release.valid_until.filter(|until| departed_at > *until) {
+            return Err(Status::failed_precondition(format!(
+                "the release of flight plan {flight_plan_id} ran out at {}",
+                valid_until.to_rfc3339()
+            )));
+        }
+
+        // A release revoked meanwhile moved the flight plan back to accepted,
+        //  which the transition is checked against under the lock
+        let reason = format!("departed at {}", departed_at.to_rfc3339());
+        let (_, plan) = self
+            .flight_plans
+            .transition_if(&flight_plan_id, FlightPlanState::Active, |plan| {
+                let released = plan
+                    .history
+                    .iter()
+                    .rev()
+                    .find(|t| t.state == FlightPlanState::Released);
+                match released {
+                    Some(transition) if transition.at == release.released_at => {
+                        Ok(((), Some((now, Some(reason)))))
+                    }
+                    _ => Err(Status::failed_precondition(format!(
+                        "flight plan {flight_plan_id} was released again, activate it again"
+                    ))),
+                }
+            })
+            .await?;
+        let Some(plan) = plan else {
+            return Err(Status::internal(format!(
+                "flight plan {flight_plan_id} was not activated"
+            )));
+        };
+
+        amqp::publish_flight_plan_event(
+            &self.mq_channel,
+            amqp::ROUTING_KEY_ACTIVATED,
+            &FlightPlanEvent {
+                flight_plan_id: flight_plan_id.clone(),
+                state: plan.state,
+                timestamp: plan.updated_at(),
+                reason: None,
+                data: None,
+                landed_at: None,
+                departed_at: Some(departed_at),
+                zones: vec![],
+            },
+        )
+        .await;
+
+        Ok(Response::new(FlightPlanActivateResponse {
+            flight_plan_id,
+            activated: true,
+            result: None,
+        }))
+    }
+
+    async fn close_flight_plan(
+        &self,
+        request: Request<FlightPlanCloseRequest>,
+    ) -> Result<Response<FlightPlanCloseResponse>, Status> {
+        let region = self.region.get_region();
+        grpc_info!("(close_flight_plan)[{}] compliance server.", region);
+        grpc_debug!("(close_flight_plan)[{}] [{:?}].", region, request);
+        let request = request.into_inner();
+        let flight_plan_id = request.flight_plan_id.clone();
+        let Some(landed_at) = request.landed_at.as_ref().and_then(timestamp_to_datetime) else {
+            return Err(Status::invalid_argument("a landing time must be provided"));
+        };
+
+        let now = Utc::now();
+        if landed_at > now {
+            return Err(Status::invalid_argument(format!(
+                "landing time {} is in the future",
+                landed_at.to_rfc3339()
+            )));
+        }
+
+        // Decided while the flight plan is locked, so it can not move on
+        //  before it is closed
+        let reason = format!("landed at {}", landed_at.to_rfc3339());
+        let (response, plan) = self
+            .flight_plans
+            .transition_if(&flight_plan_id, FlightPlanState::Closed, |plan| {
+                // The history stays in order
+                if landed_at < plan.updated_at() {
+                    return Err(Status::invalid_argument(format!(
+                        "landing time {} is before the flight plan became {} at {}",
+                        landed_at.to_rfc3339(),
+                        plan.state,
+                        plan.updated_at().to_rfc3339()
+                    )));
+                }
+
+                let response = self.region.close_flight_plan(request)?;
+                let closed = response
+                    .get_ref()
+                    .closed
+                    .then_some((landed_at, Some(reason)));
+                Ok::<_, Status>((response, closed))
+            })
+            .await?;
+
+        record_authority_response(
+            &self.flight_plans,
+            AuthorityResponse {
+                flight_plan_id: flight_plan_id.clone(),
+                request: AuthorityRequest::Closure,
+                received_at: Utc::now(),
+                granted: response.get_ref().closed,
+                message: response.get_ref().result.clone(),
+                reference: None,
+                pending: false,
+            },
+        )
+        .await;
+
+        let Some(plan) = plan else {
+            return Ok(response);
+        };
+
+        amqp::publish_flight_plan_event(
+            &self.mq_channel,
+            amqp::ROUTING_KEY_CLOSED,
+            &FlightPlanEvent {
+                flight_plan_id,
+                state: plan.state,
+                timestamp: plan.updated_at(),
+                reason: None,
+                data: None,
+                landed_at: Some(landed_at),
+                departed_at: None,
+                zones: vec![],
+            },
+        )
+        .await;
+
+        Ok(response)
+    }
+
+    async fn get_release_keys(
+        &self,
+        request: Request<ReleaseKeysRequest>,
+    ) -> Result<Response<ReleaseKeys>, Status> {
+        let region = self.region.get_region();
+        grpc_info!("(get_release_keys)[{}] compliance server.", region);
+        grpc_debug!("(get_release_keys)[{}] [{:?}].", region, request);
+        let Some(signer) = &self.release_signer else {
+            return Err(Status::failed_precondition("release tokens are not issued"));
+        };
+
+        Ok(Response::new(ReleaseKeys {
+            issuer: signer.issuer().to_string(),
+            keys: signer
+                .public_keys()
+                .into_iter()
+                .map(|(key_id, public_key)| ReleaseKey {
+                    signing: key_id == signer.signing_key_id(),
+                    key_id,
+                    algorithm: "EdDSA".to_string(),
+                    public_key,
+                })
+                .collect(),
+        }))
+    }
+
+    async fn get_flight_plan_status(
+        &self,
+        request: Request<FlightPlanStatusRequest>,
+    ) -> Result<Response<FlightPlanStatus>, Status> {
+        let region = self.region.get_region();
+        grpc_info!("(get_flight_plan_status)[{}] compliance server.", region);
+        grpc_debug!("(get_flight_plan_status)[{}] [{:?}].", region, request);
+        let flight_plan_id = request.into_inner().flight_plan_id;
+        let Some(plan) = self.flight_plans.get(&flight_plan_id).await else {
+            return Err(FlightPlanError::NotFound(flight_plan_id).into());
+        };
+
+        let storage = self.flight_plans.storage();
+        let responses = storage.authority_responses(&flight_plan_id).await?;
+        let release = storage.release(&flight_plan_id).await?;
+        Ok(Response::new(to_flight_plan_status(
+            plan, &responses, release,
+        )))
+    }
+
+    async fn create_restriction(
+        &self,
+        request: Request<ManualRestriction>,
+    ) -> Result<Response<ManualRestrictionResponse>, Status> {
+        let region = self.region.get_region();
+        grpc_info!("(create_restriction)[{}] compliance server.", region);
+        grpc_debug!("(create_restriction)[{}] [{:?}].", region, request);
+        let request = request.into_inner();
+        let identifier = match request.identifier.trim() {
+            "" => format!(
+                "ARROW-{}-MANUAL-{}",
+                region.to_uppercase(),
+                uuid::Uuid::new_v4()
+            ),
+            identifier => identifier.to_string(),
+        };
+
+        let restriction = to_manual_restriction(request)?;
+        self.restrictions
+            .create_manual(identifier.clone(), restriction)
+            .await?;
+
+        Ok(Response::new(ManualRestrictionResponse {
+            identifier,
+            success: true,
+            result: None,
+        }))
+    }
+
+    async fn update_restriction(
+        &self,
+        request: Request<ManualRestriction>,
+    ) -> Result<Response<ManualRestrictionResponse>, Status> {
+        let region = self.region.get_region();
+        grpc_info!("(update_restriction)[{}] compliance server.", region);
+        grpc_debug!("(update_restriction)[{}] [{:?}].", region, request);
+        let request = request.into_inner();
+        let identifier = request.identifier.clone();
+        let restriction = to_manual_restriction(request)?;
+        self.restrictions
+            .update_manual(identifier.clone(), restriction)
+            .await?;
+
+        Ok(Response::new(ManualRestrictionResponse {
+            identifier,
+            success: true,
+            result: None,
+        }))
+    }
+
+    async fn delete_restriction(
+        &self,
+        request: Request<DeleteRestrictionRequest>,
+    ) -> Result<Response<ManualRestrictionResponse>, Status> {
+        let region = self.region.get_region();
+        grpc_info!("(delete_restriction)[{}] compliance server.", region);
+        grpc_debug!("(delete_restriction)[{}] [{:?}].", region, request);
+        let request = request.into_inner();
+        if request.author.trim().is_empty() {
+            return Err(Status::invalid_argument("an author must be provided"));
+        }
+
+        self.restrictions
+            .delete_manual(request.identifier.clone(), request.author)
+            .await?;
+
+        Ok(Response::new(ManualRestrictionResponse {
+            identifier: request.identifier,
+            success: true,
+            result: None,
+        }))
+    }
+
+    async fn list_restrictions(
+        &self,
+        request: Request<RestrictionsRequest>,
+    ) -> Result<Response<RestrictionsResponse>, Status> {
+        let region = self.region.get_region();
+        grpc_info!("(list_restrictions)[{}] compliance server.", region);
+        grpc_debug!("(list_restrictions)[{}] [{:?}].", region, request);
+        let restrictions = self
+            .restrictions
+            .list()
+            .await
+            .into_iter()
+            .map(to_restriction)
+            .collect();
+
+        Ok(Response::new(RestrictionsResponse { restrictions }))
+    }
+
+    async fn get_merge_report(
+        &self,
+        request: Request<MergeReportRequest>,
+    ) -> Result<Response<MergeReport>, Status> {
+        let region = self.region.get_region();
+        grpc_info!("(get_merge_report)[{}] compliance server.", region);
+        grpc_debug!("(get_merge_report)[{}] [{:?}].", region, request);
+        let (_, report) = self.restrictions.merge().await;
+        Ok(Response::new(to_merge_report(report)))
+    }
+
+    async fn list_waypoints(
+        &self,
+        request: Request<WaypointsRequest>,
+    ) -> Result<Response<WaypointsResponse>, Status> {
+        let region = self.region.get_region();
+        grpc_info!("(list_waypoints)[{}] compliance server.", region);
+        grpc_debug!("(list_waypoints)[{}] [{:?}].", region, request);
+        let waypoints = self
+            .waypoints
+            .list()
+            .await
+            .into_iter()
+            .map(to_waypoint)
+            .collect();
+
+        Ok(Response::new(WaypointsResponse { waypoints }))
+    }
+
+    async fn get_waypoint_quality_report(
+        &self,
+        request: Request<WaypointQualityRequest>,
+    ) -> Result<Response<WaypointQualityReport>, Status> {
+        let region = self.region.get_region();
+        grpc_info!(
+            "(get_waypoint_quality_report)[{}] compliance server.",
+            region
+        );
+        grpc_debug!("(get_waypoint_quality_report)[{}] [{:?}].", region, request);
+        let report = self.waypoints.quality_report().await;
+        Ok(Response::new(to_quality_report(report)))
     }
 }
 
 async fn update_waypoints(
-    host: String,
-    port: u16,
-    waypoints: &HashMap<String, gis::Coordinates>,
+    pusher: &GisPusher,
+    waypoints: &HashMap<String, WaypointDetails>,
 ) -> UpdateWaypointsStatus {
+    //
+    // TODO(R5): svc-gis only accepts an identifier and a location per
+    //  waypoint, so the altitude constraints are pushed as zones around each
+    //  fix instead, see waypoints::zones. Push the name, type and altitude
+    //  constraints with the waypoint once its interface supports them.
+    //
     let nodes: Vec<gis::Waypoint> = waypoints
         .iter()
-        .map(|(label, coordinates)| gis::Waypoint {
+        .map(|(label, details)| gis::Waypoint {
             identifier: label.clone(),
-            location: Some(*coordinates),
+            location: Some(details.location),
         })
         .collect();
 
@@ -149,48 +1199,115 @@ async fn update_waypoints(
         return UpdateWaypointsStatus::NoWaypoints;
     }
 
-    let client = GisClient::new_client(&host, port, "gis");
-    match client
-        .update_waypoints(gis::UpdateWaypointsRequest { waypoints: nodes })
-        .await
-    {
-        Ok(response) => {
-            grpc_debug!("(update_waypoints) Got response: {:?}", response);
-            UpdateWaypointsStatus::Success
-        }
+    match pusher.update_waypoints(nodes).await {
+        Ok(()) => UpdateWaypointsStatus::Success,
         Err(e) => {
-            grpc_error!("(update_waypoints) {:?}", e);
+            grpc_error!("(update_waypoints) {}", e);
+            grpc_warn!(
+                "(update_waypoints) Waypoint pushes: {}.",
+                pusher.waypoint_totals()
+            );
             UpdateWaypointsStatus::RequestFailure
         }
     }
 }
 
-/// Periodically pulls down waypoints from the regional interface and
-///  pushes them to the GIS microservice
-pub async fn waypoints_loop(config: Config, region: Box<dyn RegionInterface + Send + Sync>) {
-    let host = config.gis_host_grpc;
-    let port = config.gis_port_grpc;
+/// Refreshes the waypoint sources that are due and pushes the currently
+///  valid waypoints to the GIS microservice
+///
+/// Waypoints inside an active restriction, including its safety buffer, are
+///  withheld for as long as the restriction is active, and removed from
+///  svc-gis when they become withheld.
+///
+/// Waypoints are pushed under the identifiers resolved by the registry,
+///  over the connection shared with the restrictions loop.
+pub async fn refresh_waypoints(
+    context: &RefreshContext,
+    sources: &WaypointSources,
+) -> UpdateWaypointsStatus {
+    let config = &context.config;
+    let store = &context.waypoints;
+
+    // Failing sources keep their previous or restored waypoints
+    let status = sources.refresh(store, &config.waypoint_quality).await;
+    if status.acquired > 0 {
+        snapshot::save_waypoints(config, store).await;
+    }
+
+    // Fresh only when data was actually acquired, not when no source was due
+    if status.acquired > 0 && status.is_healthy() {
+        context.health.waypoints_refreshed(Utc::now()).await;
+    }
+
+    let active = apply_buffers(
+        &context.restrictions.get_all().await,
+        &config.safety_buffers,
+        &context.region,
+    );
+    let availability = store.get_available(Utc::now(), &active).await;
+
+    // Asked again on every refresh until svc-gis removed them
+    let mut withdrawn = HashMap::new();
+    for (identifier, details) in availability.withdrawn.iter() {
+        withdrawn.insert(context.registry.resolve(identifier).await, details.clone());
+    }
+
+    match context
+        .pusher
+        .remove_waypoints(withdrawn.keys().cloned().collect())
+        .await
+    {
+        Ok(()) => store.removed(availability.withdrawn.keys()).await,
+        Err(e) => grpc_warn!(
+            "(refresh_waypoints) Withheld waypoints stay closed by zones: {}",
+            e
+        ),
+    }
+
+    let waypoints = context.registry.publish(availability.available).await;
+    let status = update_waypoints(&context.pusher, &waypoints).await;
+    context
+        .health
+        .waypoints_pushed(status != UpdateWaypointsStatus::RequestFailure)
+        .await;
+
+    // Altitude limits and withheld waypoints reach svc-gis as zones
+    let zones_time = Utc::now();
+    let zones = store
+        .waypoint_zones(&waypoints, &withdrawn, zones_time)
+        .await;
+    if !zones.is_empty()
+        && update_restrictions(&context.pusher, &zones).await == UpdateRestrictionsStatus::Success
+    {
+        store.zones_pushed(zones_time).await;
+    }
+
+    status
+}
 
+/// Refreshes the waypoints whenever a source is due, see
+///  [`refresh_waypoints`]
+///
+/// The first refresh is performed by [`startup`].
+pub async fn waypoints_loop(context: RefreshContext, sources: Arc<WaypointSources>) {
     grpc_debug!(
         "(waypoints_loop) Starting loop with interval: {} seconds.",
-        config.interval_seconds_refresh_waypoints
+        context.config.interval_seconds_refresh_waypoints
     );
 
-    let mut cache: HashMap<String, gis::Coordinates> = HashMap::new();
-
     loop {
-        // Pull down waypoints from regional interface
-        region.acquire_waypoints(&mut cache).await;
-        update_waypoints(host.clone(), port, &cache).await;
-        std::thread::sleep(std::time::Duration::from_secs(
-            config.interval_seconds_refresh_waypoints as u64,
-        ));
+        tokio::time::sleep_until(next_due(
+            sources.next_due().await,
+            context.config.interval_seconds_refresh_waypoints,
+        ))
+        .await;
+
+        refresh_waypoints(&context, &sources).await;
     }
 }
 
 async fn update_restrictions(
-    host: String,
-    port: u16,
+    pusher: &GisPusher,
     restrictions: &HashMap<String, RestrictionDetails>,
 ) -> UpdateRestrictionsStatus {
     let mut zones: Vec<gis::Zone> = vec![];
@@ -222,37 +1339,180 @@ async fn update_restrictions(
         return UpdateRestrictionsStatus::NoRestrictions;
     }
 
-    let client = GisClient::new_client(&host, port, "gis");
-    match client.update_zones(gis::UpdateZonesRequest { zones }).await {
-        Ok(response) => {
-            grpc_debug!("(update_restrictions) Got response: {:?}", response);
-            UpdateRestrictionsStatus::Success
-        }
+    match pusher.update_zones(zones).await {
+        Ok(()) => UpdateRestrictionsStatus::Success,
         Err(e) => {
-            grpc_error!("(update_restrictions) {:?}", e);
+            grpc_error!("(update_restrictions) {}", e);
+            grpc_warn!(
+                "(update_restrictions) Zone pushes: {}.",
+                pusher.zone_totals()
+            );
             UpdateRestrictionsStatus::RequestFailure
         }
     }
 }
 
-/// Periodically pulls down restrictions from the regional interface and
-///  pushes them to the GIS microservice
-pub async fn restrictions_loop(config: Config, region: Box<dyn RegionInterface + Send + Sync>) {
-    let host = config.gis_host_grpc;
-    let port = config.gis_port_grpc;
-    let mut cache: HashMap<String, RestrictionDetails> = HashMap::new();
-
-    grpc_info!(
+/// Refreshes the restriction sources that are due, merges their feeds with
+///  the manual restrictions and pushes them to the GIS microservice
+///
+/// The configured safety buffers are applied to the merged restrictions and
+///  the resulting polygons are simplified before they are pushed.
+///
+/// Restrictions are pushed under the identifiers resolved by the registry,
+///  over the connection shared with the waypoints loop. Released and active
+///  flight plans crossed by a new or changed restriction then have their
+///  release revoked, see [`ReleaseMonitor::revoke_restricted`].
+pub async fn refresh_restrictions(
+    context: &RefreshContext,
+    sources: &RestrictionSources,
+) -> UpdateRestrictionsStatus {
+    let config = &context.config;
+    let store = &context.restrictions;
+
+    // Failing sources keep their previous or restored feed
+    let status = sources.refresh(store).await;
+    if status.acquired > 0 {
+        snapshot::save_restrictions(config, store).await;
+    }
+
+    // Fresh only when data was actually acquired, not when no source was due
+    if status.acquired > 0 && status.is_healthy() {
+        context.health.restrictions_refreshed(Utc::now()).await;
+    }
+
+    let snapshot_time = Utc::now();
+    let restrictions = simplify_restrictions(
+        apply_buffers(
+            &context.registry.publish(store.get_all().await).await,
+            &config.safety_buffers,
+            &context.region,
+        ),
+        &config.simplification,
+    )
+    .await;
+    let status = update_restrictions(&context.pusher, &restrictions).await;
+    context
+        .health
+        .restrictions_pushed(status != UpdateRestrictionsStatus::RequestFailure)
+        .await;
+    if status == UpdateRestrictionsStatus::Success {
+        store.purge_expired(snapshot_time).await;
+    }
+
+    // Checked with the same margins as the releases were granted with
+    let restricted = apply_buffers(
+        &store.get_all().await,
+        &config.safety_buffers,
+        &context.region,
+    );
+    context
+        .releases
+        .revoke_restricted(&config.release, &restricted, Utc::now())
+        .await;
+
+    status
+}
+
+/// Refreshes the restrictions whenever a source is due, see
+///  [`refresh_restrictions`]
+///
+/// The first refresh is performed by [`startup`]. Changes to the manual
+///  restrictions trigger an immediate refresh.
+pub async fn restrictions_loop(context: RefreshContext, sources: Arc<RestrictionSources>) {
+    grpc_info!(
         "(restrictions_loop) Starting loop with interval: {} seconds.",
-        config.interval_seconds_refresh_zones
+        context.config.interval_seconds_refresh_zones
     );
 
     loop {
-        region.acquire_restrictions(&mut cache).await;
-        update_restrictions(host.clone(), port, &cache).await;
-        std::thread::sleep(std::time::Duration::from_secs(
-            config.interval_seconds_refresh_zones as u64,
-        ));
+        tokio::select! {
+            _ = tokio::time::sleep_until(next_due(
+                sources.next_due().await,
+                context.config.interval_seconds_refresh_zones,
+            )) => (),
+            _ = context.restrictions.changed() => {
+                grpc_info!("(restrictions_loop) Manual restrictions changed, pushing update.");
+            }
+        }
+
+        refresh_restrictions(&context, &sources).await;
+    }
+}
+
+/// Waits for svc-gis to be ready, then performs a first refresh of the
+///  restrictions and the waypoints
+///
+/// Pushing before svc-gis is ready only produces failures, so the first push
+///  waits for it, up to the configured timeout. Restrictions are refreshed
+///  first, so waypoints inside active restrictions are withheld from the
+///  first push on. The service is not ready before this completes.
+pub async fn startup(
+    context: &RefreshContext,
+    restriction_sources: &RestrictionSources,
+    waypoint_sources: &WaypointSources,
+) {
+    let config = &context.config;
+    let health = &context.health;
+
+    health.startup(StartupPhase::WaitingForGis).await;
+    let gis_ready = context.pusher.wait_until_ready(&config.gis_startup).await;
+    if !gis_ready {
+        grpc_warn!(
+            "(startup) svc-gis not ready after {} seconds, pushing anyway.",
+            config.gis_startup.timeout_seconds
+        );
+    }
+
+    health.startup(StartupPhase::Syncing).await;
+    let restrictions_status = refresh_restrictions(context, restriction_sources).await;
+    let waypoints_status = refresh_waypoints(context, waypoint_sources).await;
+
+    // Not ready without svc-gis until it accepts the pushes of the loops
+    let phase = if gis_ready {
+        StartupPhase::Complete
+    } else {
+        StartupPhase::Degraded
+    };
+    health.startup(phase).await;
+    grpc_info!(
+        "(startup) Initial sync complete, restrictions: {:?}, waypoints: {:?}.",
+        restrictions_status,
+        waypoints_status
+    );
+}
+
+/// Periodically reports the health state to the gRPC health service
+///
+/// The service is reported as not serving until its data is fresh and
+///  accepted by svc-gis, so orchestrators do not route requests to an
+///  instance with stale airspace data.
+pub async fn health_loop(
+    health: Arc<HealthState>,
+    mut reporter: tonic_health::server::HealthReporter,
+    mq_channel: Option<lapin::Channel>,
+) {
+    let interval = std::time::Duration::from_secs(health.config().interval_seconds as u64);
+    let mut serving: Option<bool> = None;
+
+    loop {
+        let issues = health.check(Utc::now(), amqp_connected(&mq_channel)).await;
+        let ready = issues.is_empty();
+        if serving != Some(ready) {
+            if ready {
+                grpc_info!("(health_loop) Serving.");
+                reporter.set_serving::<RpcServiceServer<ServerImpl>>().await;
+            } else {
+                let issues: Vec<String> = issues.iter().map(|issue| issue.to_string()).collect();
+                grpc_warn!("(health_loop) Not serving: {}.", issues.join(", "));
+                reporter
+                    .set_not_serving::<RpcServiceServer<ServerImpl>>()
+                    .await;
+            }
+
+            serving = Some(ready);
+        }
+
+        tokio::time::sleep(interval).await;
     }
 }
 
@@ -289,25 +1549,137 @@ pub async fn grpc_server(config: Config, shutdown_rx: Option<tokio::sync::onesho
         return;
     };
 
+    let priorities = config
+        .restriction_source_priority
+        .parse::<SourcePriorities>()
+        .unwrap_or_else(|e| {
+            grpc_error!(
+                "(grpc_server) Invalid restriction source priority, using the default: {}",
+                e
+            );
+            SourcePriorities::default()
+        });
+
+    let restrictions = Arc::new(RestrictionStore::new(
+        Some(config.manual_restrictions_file.clone().into()),
+        priorities,
+    ));
+
+    if let Err(e) = restrictions.load().await {
+        grpc_error!("(grpc_server) Could not load manual restrictions: {}", e);
+    }
+
+    let region: Box<dyn RegionInterface + Send + Sync> =
+        Box::<crate::region::RegionImpl>::default();
+    let registry = Arc::new(IdentifierRegistry::new(Some(
+        config.identifier_registry_file.clone().into(),
+    )));
+
+    if let Err(e) = registry.load().await {
+        grpc_error!(
+            "(grpc_server) Could not load the identifier registry: {}",
+            e
+        );
+    }
+
+    if let Err(e) = registry.adopt(region.legacy_identifiers()).await {
+        grpc_error!("(grpc_server) Could not adopt legacy identifiers: {}", e);
+    }
+
+    let waypoints = Arc::new(WaypointStore::default());
+    snapshot::restore(&config, &restrictions, &waypoints).await;
+
+    let health = Arc::new(HealthState::new(config.health));
+    let pusher = Arc::new(GisPusher::new(
+        &config.gis_host_grpc,
+        config.gis_port_grpc,
+        config.gis_retry,
+    ));
+    let storage = match storage::open(&config.storage).await {
+        Ok(storage) => storage,
+        Err(e) => {
+            grpc_error!("(grpc_server) Could not open the storage: {}", e);
+            return;
+        }
+    };
+
+    let release_signer = match ReleaseSigner::new(&config.release_tokens) {
+        Ok(signer) => signer.map(Arc::new),
+        Err(e) => {
+            grpc_error!("(grpc_server) Could not load the release token keys: {}", e);
+            return;
+        }
+    };
+
+    let flight_plans = Arc::new(FlightPlanStore::new(storage));
+    if let Err(e) = flight_plans.load().await {
+        grpc_error!("(grpc_server) Could not load flight plans: {}", e);
+    }
+
+    let resolver = Arc::new(DecisionResolver::new(
+        flight_plans.clone(),
+        Some(mq_channel.clone()),
+    ));
+    tokio::spawn(decisions::poll_loop(
+        resolver.clone(),
+        Box::<crate::region::RegionImpl>::default(),
+        config.decisions,
+    ));
+
+    let monitor = Arc::new(ReleaseMonitor::new(
+        flight_plans.clone(),
+        Some(mq_channel.clone()),
+    ));
+    tokio::spawn(releases::expiry_loop(monitor.clone(), config.release));
+
+    if config.webhook.enabled {
+        let address = SocketAddr::new(IpAddr::V6(Ipv6Addr::UNSPECIFIED), config.webhook.port);
+        let receiver = Arc::new(WebhookReceiver::new(config.webhook.clone(), resolver));
+        if let Err(e) = webhook::serve(receiver, address) {
+            grpc_error!("(grpc_server) Could not start the webhook receiver: {}", e);
+        }
+    }
+
     let imp = ServerImpl {
-        mq_channel: Some(mq_channel),
+        mq_channel: Some(mq_channel.clone()),
         region: Box::<crate::region::RegionImpl>::default(),
+        restrictions: restrictions.clone(),
+        waypoints: waypoints.clone(),
+        health: health.clone(),
+        flight_plans: flight_plans.clone(),
+        release: config.release,
+        safety_buffers: config.safety_buffers.clone(),
+        release_signer,
     };
 
-    tokio::spawn(restrictions_loop(
-        config.clone(),
+    let context = RefreshContext {
+        config: config.clone(),
+        region: region.get_region().to_string(),
+        restrictions,
+        waypoints,
+        registry,
+        pusher,
+        health: health.clone(),
+        releases: monitor,
+    };
+    let restriction_sources = Arc::new(sources::restriction_sources(
+        &config,
         Box::<crate::region::RegionImpl>::default(),
     ));
-
-    tokio::spawn(waypoints_loop(
-        config.clone(),
+    let waypoint_sources = Arc::new(sources::waypoint_sources(
+        &config,
         Box::<crate::region::RegionImpl>::default(),
     ));
 
-    let (mut health_reporter, health_service) = tonic_health::server::health_reporter();
-    health_reporter
-        .set_serving::<RpcServiceServer<ServerImpl>>()
-        .await;
+    // The refresh loops start once svc-gis received the initial sync
+    tokio::spawn(async move {
+        startup(&context, &restriction_sources, &waypoint_sources).await;
+        tokio::spawn(restrictions_loop(context.clone(), restriction_sources));
+        tokio::spawn(waypoints_loop(context, waypoint_sources));
+    });
+
+    let (health_reporter, health_service) = tonic_health::server::health_reporter();
+    tokio::spawn(health_loop(health, health_reporter, Some(mq_channel)));
 
     //start server
     grpc_info!(
@@ -354,6 +1726,8 @@ impl RpcService for ServerImpl {
             flight_plan_id: request.flight_plan_id,
             submitted: true,
             result: None,
+            authority_reference: None,
+            pending: false,
         }))
     }
 
@@ -372,6 +1746,207 @@ impl RpcService for ServerImpl {
             flight_plan_id: request.flight_plan_id,
             released: true,
             result: None,
+            release_token: None,
+        }))
+    }
+
+    async fn cancel_flight_plan(
+        &self,
+        request: Request<FlightPlanCancelRequest>,
+    ) -> Result<Response<FlightPlanCancelResponse>, Status> {
+        let region = self.region.get_region();
+        grpc_warn!("(cancel_flight_plan MOCK)[{}] compliance server.", region);
+        grpc_debug!("(cancel_flight_plan MOCK)[{}] [{:?}].", region, request);
+        let request = request.into_inner();
+        Ok(Response::new(FlightPlanCancelResponse {
+            flight_plan_id: request.flight_plan_id,
+            cancelled: true,
+            result: None,
+        }))
+    }
+
+    async fn amend_flight_plan(
+        &self,
+        request: Request<FlightPlanRequest>,
+    ) -> Result<Response<FlightPlanResponse>, Status> {
+        let region = self.region.get_region();
+        grpc_warn!("(amend_flight_plan MOCK)[{}] compliance server.", region);
+        grpc_debug!("(amend_flight_plan MOCK)[{}] [{:?}].", region, request);
+        let request = request.into_inner();
+        Ok(Response::new(FlightPlanResponse {
+            flight_plan_id: request.flight_plan_id,
+            submitted: true,
+            result: None,
+            authority_reference: None,
+            pending: false,
+        }))
+    }
+
+    async fn activate_flight_plan(
+        &self,
+        request: Request<FlightPlanActivateRequest>,
+    ) -> Result<Response<FlightPlanActivateResponse>, Status> {
+        let region = self.region.get_region();
+        grpc_warn!("(activate_flight_plan MOCK)[{}] compliance server.", region);
+        grpc_debug!("(activate_flight_plan MOCK)[{}] [{:?}].", region, request);
+        let request = request.into_inner();
+        Ok(Response::new(FlightPlanActivateResponse {
+            flight_plan_id: request.flight_plan_id,
+            activated: true,
+            result: None,
+        }))
+    }
+
+    async fn close_flight_plan(
+        &self,
+        request: Request<FlightPlanCloseRequest>,
+    ) -> Result<Response<FlightPlanCloseResponse>, Status> {
+        let region = self.region.get_region();
+        grpc_warn!("(close_flight_plan MOCK)[{}] compliance server.", region);
+        grpc_debug!("(close_flight_plan MOCK)[{}] [{:?}].", region, request);
+        let request = request.into_inner();
+        Ok(Response::new(FlightPlanCloseResponse {
+            flight_plan_id: request.flight_plan_id,
+            closed: true,
+            result: None,
+        }))
+    }
+
+    async fn get_release_keys(
+        &self,
+        request: Request<ReleaseKeysRequest>,
+    ) -> Result<Response<ReleaseKeys>, Status> {
+        let region = self.region.get_region();
+        grpc_warn!("(get_release_keys MOCK)[{}] compliance server.", region);
+        grpc_debug!("(get_release_keys MOCK)[{}] [{:?}].", region, request);
+        Ok(Response::new(ReleaseKeys {
+            issuer: "svc-compliance".to_string(),
+            keys: vec![],
+        }))
+    }
+
+    async fn get_flight_plan_status(
+        &self,
+        request: Request<FlightPlanStatusRequest>,
+    ) -> Result<Response<FlightPlanStatus>, Status> {
+        let region = self.region.get_region();
+        grpc_warn!(
+            "(get_flight_plan_status MOCK)[{}] compliance server.",
+            region
+        );
+        grpc_debug!("(get_flight_plan_status MOCK)[{}] [{:?}].", region, request);
+        let request = request.into_inner();
+        Ok(Response::new(FlightPlanStatus {
+            flight_plan_id: request.flight_plan_id,
+            state: FlightPlanState::Accepted.to_string(),
+            authority_reference: None,
+            authority_message: None,
+            release: None,
+            history: vec![],
+        }))
+    }
+
+    async fn create_restriction(
+        &self,
+        request: Request<ManualRestriction>,
+    ) -> Result<Response<ManualRestrictionResponse>, Status> {
+        let region = self.region.get_region();
+        grpc_warn!("(create_restriction MOCK)[{}] compliance server.", region);
+        grpc_debug!("(create_restriction MOCK)[{}] [{:?}].", region, request);
+        let request = request.into_inner();
+        Ok(Response::new(ManualRestrictionResponse {
+            identifier: request.identifier,
+            success: true,
+            result: None,
+        }))
+    }
+
+    async fn update_restriction(
+        &self,
+        request: Request<ManualRestriction>,
+    ) -> Result<Response<ManualRestrictionResponse>, Status> {
+        let region = self.region.get_region();
+        grpc_warn!("(update_restriction MOCK)[{}] compliance server.", region);
+        grpc_debug!("(update_restriction MOCK)[{}] [{:?}].", region, request);
+        let request = request.into_inner();
+        Ok(Response::new(ManualRestrictionResponse {
+            identifier: request.identifier,
+            success: true,
+            result: None,
+        }))
+    }
+
+    async fn delete_restriction(
+        &self,
+        request: Request<DeleteRestrictionRequest>,
+    ) -> Result<Response<ManualRestrictionResponse>, Status> {
+        let region = self.region.get_region();
+        grpc_warn!("(delete_restriction MOCK)[{}] compliance server.", region);
+        grpc_debug!("(delete_restriction MOCK)[{}] [{:?}].", region, request);
+        let request = request.into_inner();
+        Ok(Response::new(ManualRestrictionResponse {
+            identifier: request.identifier,
+            success: true,
+            result: None,
+        }))
+    }
+
+    async fn list_restrictions(
+        &self,
+        request: Request<RestrictionsRequest>,
+    ) -> Result<Response<RestrictionsResponse>, Status> {
+        let region = self.region.get_region();
+        grpc_warn!("(list_restrictions MOCK)[{}] compliance server.", region);
+        grpc_debug!("(list_restrictions MOCK)[{}] [{:?}].", region, request);
+        Ok(Response::new(RestrictionsResponse {
+            restrictions: vec![],
+        }))
+    }
+
+    async fn get_merge_report(
+        &self,
+        request: Request<MergeReportRequest>,
+    ) -> Result<Response<MergeReport>, Status> {
+        let region = self.region.get_region();
+        grpc_warn!("(get_merge_report MOCK)[{}] compliance server.", region);
+        grpc_debug!("(get_merge_report MOCK)[{}] [{:?}].", region, request);
+        Ok(Response::new(MergeReport {
+            timestamp: Some(datetime_to_timestamp(&Utc::now())),
+            feeds: vec![],
+            merged: 0,
+            conflicts: vec![],
+        }))
+    }
+
+    async fn list_waypoints(
+        &self,
+        request: Request<WaypointsRequest>,
+    ) -> Result<Response<WaypointsResponse>, Status> {
+        let region = self.region.get_region();
+        grpc_warn!("(list_waypoints MOCK)[{}] compliance server.", region);
+        grpc_debug!("(list_waypoints MOCK)[{}] [{:?}].", region, request);
+        Ok(Response::new(WaypointsResponse { waypoints: vec![] }))
+    }
+
+    async fn get_waypoint_quality_report(
+        &self,
+        request: Request<WaypointQualityRequest>,
+    ) -> Result<Response<WaypointQualityReport>, Status> {
+        let region = self.region.get_region();
+        grpc_warn!(
+            "(get_waypoint_quality_report MOCK)[{}] compliance server.",
+            region
+        );
+        grpc_debug!(
+            "(get_waypoint_quality_report MOCK)[{}] [{:?}].",
+            region,
+            request
+        );
+        Ok(Response::new(WaypointQualityReport {
+            timestamp: Some(datetime_to_timestamp(&Utc::now())),
+            received: 0,
+            accepted: 0,
+            issues: vec![],
         }))
     }
 }
@@ -380,12 +1955,70 @@ impl RpcService for ServerImpl {
 mod tests {
     use super::grpc_server::*;
     use super::*;
+    use crate::releases::token::TokenConfig;
 
     fn get_server_impl() -> ServerImpl {
         let region = Box::<crate::region::RegionImpl>::default();
         ServerImpl {
             mq_channel: None,
             region,
+            restrictions: Arc::new(RestrictionStore::default()),
+            waypoints: Arc::new(WaypointStore::default()),
+            health: Arc::new(HealthState::new(crate::health::HealthConfig {
+                require_amqp: false,
+                ..Default::default()
+            })),
+            flight_plans: Arc::new(FlightPlanStore::default()),
+            release: ReleaseConfig::default(),
+            safety_buffers: BufferConfig::default(),
+            release_signer: None,
+        }
+    }
+
+    /// Data of a flight plan departing shortly, the same on every call
+    fn flight_plan_data() -> String {
+        static DATA: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+        DATA.get_or_init(|| {
+            serde_json::json!({
+                "departure": Utc::now() + chrono::Duration::try_minutes(10).unwrap(),
+                "route": [
+                    { "latitude": 52.0, "longitude": 4.0 },
+                    { "latitude": 52.1, "longitude": 4.1 }
+                ],
+                "pilot": { "identifier": "P-1" },
+                "aircraft": { "identifier": "A-1" }
+            })
+            .to_string()
+        })
+        .clone()
+    }
+
+    fn get_manual_restriction_request() -> ManualRestriction {
+        let vertices = vec![
+            (4.8822724, 52.3688393),
+            (4.8832170, 52.3781666),
+            (4.9007345, 52.3777998),
+            (4.9001335, 52.3680532),
+            (4.8822724, 52.3688393),
+        ];
+
+        ManualRestriction {
+            identifier: "".to_string(),
+            vertices: vertices
+                .into_iter()
+                .map(|(longitude, latitude)| Coordinates {
+                    latitude,
+                    longitude,
+                })
+                .collect(),
+            altitude_meters_min: 0.0,
+            altitude_meters_max: 500.0,
+            time_start: None,
+            time_end: Some(datetime_to_timestamp(
+                &(Utc::now() + chrono::Duration::try_hours(1).unwrap()),
+            )),
+            reason: "Accident scene".to_string(),
+            author: "operator".to_string(),
         }
     }
 
@@ -412,6 +2045,19 @@ mod tests {
         ut_info!("(test_grpc_server_is_ready) Start.");
 
         let imp = get_server_impl();
+
+        #[cfg(not(feature = "stub_server"))]
+        {
+            // Not ready until the data has been refreshed and pushed
+            let result = imp.is_ready(Request::new(ReadyRequest {})).await;
+            assert!(!result.unwrap().into_inner().ready);
+
+            imp.health.restrictions_refreshed(Utc::now()).await;
+            imp.health.waypoints_refreshed(Utc::now()).await;
+            imp.health.restrictions_pushed(true).await;
+            imp.health.waypoints_pushed(true).await;
+        }
+
         let result = imp.is_ready(Request::new(ReadyRequest {})).await;
         assert!(result.is_ok());
         let result: ReadyResponse = result.unwrap().into_inner();
@@ -428,8 +2074,8 @@ mod tests {
         let imp = get_server_impl();
         let result = imp
             .submit_flight_plan(Request::new(FlightPlanRequest {
-                flight_plan_id: "".to_string(),
-                data: "".to_string(),
+                flight_plan_id: "FP-1".to_string(),
+                data: flight_plan_data(),
             }))
             .await;
 
@@ -438,18 +2084,191 @@ mod tests {
         println!("{:?}", result);
         assert_eq!(result.submitted, true);
 
+        #[cfg(not(feature = "stub_server"))]
+        {
+            let plan = imp.flight_plans.get("FP-1").await.unwrap();
+            assert_eq!(plan.state, FlightPlanState::Accepted);
+            assert!(plan.entered(FlightPlanState::Submitted).is_some());
+
+            // A retried submission returns the stored response
+            let retried = imp
+                .submit_flight_plan(Request::new(FlightPlanRequest {
+                    flight_plan_id: "FP-1".to_string(),
+                    data: flight_plan_data(),
+                }))
+                .await
+                .unwrap()
+                .into_inner();
+            assert_eq!(retried, result);
+            let storage = imp.flight_plans.storage();
+            assert_eq!(storage.authority_responses("FP-1").await.unwrap().len(), 1);
+
+            // Different data under the same identifier is refused
+            let result = imp
+                .submit_flight_plan(Request::new(FlightPlanRequest {
+                    flight_plan_id: "FP-1".to_string(),
+                    data: "{\"changed\":true}".to_string(),
+                }))
+                .await;
+            assert_eq!(result.unwrap_err().code(), tonic::Code::AlreadyExists);
+            let plan = imp.flight_plans.get("FP-1").await.unwrap();
+            assert_eq!(plan.state, FlightPlanState::Accepted);
+            assert_eq!(
+                plan.fingerprint,
+                crate::flight_plans::fingerprint(&flight_plan_data())
+            );
+        }
+
         ut_info!("(test_grpc_submit_flight_plan) Success.");
     }
 
+    #[tokio::test]
+    async fn test_record_decision_pending() {
+        crate::get_log_handle().await;
+        ut_info!("(test_record_decision_pending) Start.");
+
+        let flight_plans = FlightPlanStore::default();
+        flight_plans.submit("FP-1", "{}", Utc::now()).await.unwrap();
+        let pending = FlightPlanResponse {
+            flight_plan_id: "FP-1".to_string(),
+            submitted: true,
+            result: None,
+            authority_reference: Some("REF-1".to_string()),
+            pending: true,
+        };
+
+        let response = record_decision(
+            &flight_plans,
+            "FP-1",
+            AuthorityRequest::Submission,
+            Ok(Response::new(pending.clone())),
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.into_inner(), pending);
+        let plan = flight_plans.get("FP-1").await.unwrap();
+        assert_eq!(plan.state, FlightPlanState::PendingAuthority);
+
+        // A retried submission is told the decision is pending
+        let stored = stored_decision(&flight_plans, "FP-1").await.unwrap();
+        assert_eq!(stored.into_inner(), pending);
+
+        ut_info!("(test_record_decision_pending) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_record_decision_unreachable() {
+        crate::get_log_handle().await;
+        ut_info!("(test_record_decision_unreachable) Start.");
+
+        let flight_plans = FlightPlanStore::default();
+        flight_plans.submit("FP-1", "{}", Utc::now()).await.unwrap();
+
+        // A retry arriving while the authority is asked is not filed again
+        assert!(matches!(
+            flight_plans.submit("FP-1", "{}", Utc::now()).await,
+            Err(FlightPlanError::AlreadySubmitted(_))
+        ));
+        let stored = stored_decision(&flight_plans, "FP-1").await;
+        assert_eq!(stored.unwrap_err().code(), tonic::Code::Aborted);
+
+        let result = record_decision(
+            &flight_plans,
+            "FP-1",
+            AuthorityRequest::Submission,
+            Err(Status::unavailable("authority unreachable")),
+        )
+        .await;
+        assert_eq!(result.unwrap_err().code(), tonic::Code::Unavailable);
+        let plan = flight_plans.get("FP-1").await.unwrap();
+        assert_eq!(plan.state, FlightPlanState::Submitted);
+        let responses = flight_plans
+            .storage()
+            .authority_responses("FP-1")
+            .await
+            .unwrap();
+        assert!(responses.is_empty());
+
+        // Filed again once the authority could not be asked
+        let plan = flight_plans.submit("FP-1", "{}", Utc::now()).await.unwrap();
+        assert_eq!(plan.state, FlightPlanState::Submitted);
+        assert_eq!(plan.history.len(), 2);
+        assert!(matches!(
+            flight_plans
+                .submit("FP-1", "{\"changed\":true}", Utc::now())
+                .await,
+            Err(FlightPlanError::Conflict(_))
+        ));
+
+        ut_info!("(test_record_decision_unreachable) Success.");
+    }
+
     #[tokio::test]
     async fn test_grpc_request_flight_release() {
         crate::get_log_handle().await;
         ut_info!("(test_grpc_request_flight_release) Start.");
 
         let imp = get_server_impl();
+
+        #[cfg(not(feature = "stub_server"))]
+        {
+            // Never submitted
+            let result = imp
+                .request_flight_release(Request::new(FlightReleaseRequest {
+                    flight_plan_id: "FP-1".to_string(),
+                    data: "".to_string(),
+                }))
+                .await;
+            assert_eq!(result.unwrap_err().code(), tonic::Code::FailedPrecondition);
+
+            // Too early for the planned departure, or through a restriction
+            imp.create_restriction(Request::new(get_manual_restriction_request()))
+                .await
+                .unwrap();
+            let departure = Utc::now() + chrono::Duration::try_minutes(10).unwrap();
+            for (flight_plan_id, departure, latitude) in [
+                (
+                    "FP-2",
+                    departure + chrono::Duration::try_hours(2).unwrap(),
+                    52.0,
+                ),
+                ("FP-3", departure, 52.3735),
+            ] {
+                let data = serde_json::json!({
+                    "departure": departure,
+                    "route": [
+                        { "latitude": latitude, "longitude": 4.85 },
+                        { "latitude": latitude, "longitude": 4.95 }
+                    ],
+                    "pilot": { "identifier": "P-1" },
+                    "aircraft": { "identifier": "A-1" }
+                });
+                imp.submit_flight_plan(Request::new(FlightPlanRequest {
+                    flight_plan_id: flight_plan_id.to_string(),
+                    data: data.to_string(),
+                }))
+                .await
+                .unwrap();
+                let result = imp
+                    .request_flight_release(Request::new(FlightReleaseRequest {
+                        flight_plan_id: flight_plan_id.to_string(),
+                        data: "".to_string(),
+                    }))
+                    .await;
+                assert_eq!(result.unwrap_err().code(), tonic::Code::FailedPrecondition);
+            }
+
+            imp.submit_flight_plan(Request::new(FlightPlanRequest {
+                flight_plan_id: "FP-1".to_string(),
+                data: flight_plan_data(),
+            }))
+            .await
+            .unwrap();
+        }
+
         let result = imp
             .request_flight_release(Request::new(FlightReleaseRequest {
-                flight_plan_id: "".to_string(),
+                flight_plan_id: "FP-1".to_string(),
                 data: "".to_string(),
             }))
             .await;
@@ -459,19 +2278,667 @@ mod tests {
         println!("{:?}", result);
         assert_eq!(result.released, true);
 
+        #[cfg(not(feature = "stub_server"))]
+        {
+            let plan = imp.flight_plans.get("FP-1").await.unwrap();
+            assert_eq!(plan.state, FlightPlanState::Released);
+            let storage = imp.flight_plans.storage();
+            let release = storage.release("FP-1").await.unwrap().unwrap();
+            let departure = FlightPlanData::parse(&flight_plan_data())
+                .unwrap()
+                .departure;
+            assert_eq!(
+                release.valid_until,
+                Some(departure + chrono::Duration::try_minutes(15).unwrap())
+            );
+            assert_eq!(storage.authority_responses("FP-1").await.unwrap().len(), 2);
+
+            // Released at the start of the window, valid until after departure
+            let departure = Utc::now() + chrono::Duration::try_minutes(29).unwrap();
+            let data = serde_json::json!({
+                "departure": departure,
+                "route": [
+                    { "latitude": 52.0, "longitude": 4.0 },
+                    { "latitude": 52.1, "longitude": 4.1 }
+                ],
+                "pilot": { "identifier": "P-1" },
+                "aircraft": { "identifier": "A-1" }
+            });
+            imp.submit_flight_plan(Request::new(FlightPlanRequest {
+                flight_plan_id: "FP-4".to_string(),
+                data: data.to_string(),
+            }))
+            .await
+            .unwrap();
+            imp.request_flight_release(Request::new(FlightReleaseRequest {
+                flight_plan_id: "FP-4".to_string(),
+                data: "".to_string(),
+            }))
+            .await
+            .unwrap();
+            let release = storage.release("FP-4").await.unwrap().unwrap();
+            assert_eq!(
+                release.valid_until,
+                Some(departure + chrono::Duration::try_minutes(15).unwrap())
+            );
+
+            // Released once
+            let result = imp
+                .request_flight_release(Request::new(FlightReleaseRequest {
+                    flight_plan_id: "FP-1".to_string(),
+                    data: "".to_string(),
+                }))
+                .await;
+            assert_eq!(result.unwrap_err().code(), tonic::Code::FailedPrecondition);
+        }
+
         ut_info!("(test_grpc_request_flight_release) Success.");
     }
 
+    #[tokio::test]
+    async fn test_grpc_get_flight_plan_status() {
+        crate::get_log_handle().await;
+        ut_info!("(test_grpc_get_flight_plan_status) Start.");
+
+        let imp = get_server_impl();
+
+        #[cfg(not(feature = "stub_server"))]
+        {
+            let result = imp
+                .get_flight_plan_status(Request::new(FlightPlanStatusRequest {
+                    flight_plan_id: "FP-1".to_string(),
+                }))
+                .await;
+            assert_eq!(result.unwrap_err().code(), tonic::Code::NotFound);
+
+            imp.submit_flight_plan(Request::new(FlightPlanRequest {
+                flight_plan_id: "FP-1".to_string(),
+                data: flight_plan_data(),
+            }))
+            .await
+            .unwrap();
+            imp.request_flight_release(Request::new(FlightReleaseRequest {
+                flight_plan_id: "FP-1".to_string(),
+                data: "".to_string(),
+            }))
+            .await
+            .unwrap();
+        }
+
+        let result = imp
+            .get_flight_plan_status(Request::new(FlightPlanStatusRequest {
+                flight_plan_id: "FP-1".to_string(),
+            }))
+            .await;
+
+        assert!(result.is_ok());
+        let result: FlightPlanStatus = result.unwrap().into_inner();
+        println!("{:?}", result);
+        assert_eq!(result.flight_plan_id, "FP-1");
+
+        #[cfg(not(feature = "stub_server"))]
+        {
+            assert_eq!(result.state, "released");
+            assert!(result.release.unwrap().revoked_at.is_none());
+            let states: Vec<&str> = result.history.iter().map(|t| t.state.as_str()).collect();
+            assert_eq!(states, vec!["draft", "submitted", "accepted", "released"]);
+        }
+
+        ut_info!("(test_grpc_get_flight_plan_status) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_grpc_cancel_flight_plan() {
+        crate::get_log_handle().await;
+        ut_info!("(test_grpc_cancel_flight_plan) Start.");
+
+        let imp = get_server_impl();
+
+        #[cfg(not(feature = "stub_server"))]
+        {
+            let result = imp
+                .cancel_flight_plan(Request::new(FlightPlanCancelRequest {
+                    flight_plan_id: "FP-1".to_string(),
+                    reason: None,
+                }))
+                .await;
+            assert_eq!(result.unwrap_err().code(), tonic::Code::NotFound);
+
+            imp.submit_flight_plan(Request::new(FlightPlanRequest {
+                flight_plan_id: "FP-1".to_string(),
+                data: flight_plan_data(),
+            }))
+            .await
+            .unwrap();
+            imp.request_flight_release(Request::new(FlightReleaseRequest {
+                flight_plan_id: "FP-1".to_string(),
+                data: "".to_string(),
+            }))
+            .await
+            .unwrap();
+        }
+
+        let result = imp
+            .cancel_flight_plan(Request::new(FlightPlanCancelRequest {
+                flight_plan_id: "FP-1".to_string(),
+                reason: Some("weather".to_string()),
+            }))
+            .await;
+
+        assert!(result.is_ok());
+        let result: FlightPlanCancelResponse = result.unwrap().into_inner();
+        println!("{:?}", result);
+        assert_eq!(result.cancelled, true);
+
+        #[cfg(not(feature = "stub_server"))]
+        {
+            let plan = imp.flight_plans.get("FP-1").await.unwrap();
+            assert_eq!(plan.state, FlightPlanState::Cancelled);
+            assert_eq!(
+                plan.history.last().unwrap().reason,
+                Some("weather".to_string())
+            );
+
+            // The release is revoked
+            let release = imp.flight_plans.storage().release("FP-1").await.unwrap();
+            assert!(release.unwrap().revoked_at.is_some());
+
+            // Cancelled once
+            let result = imp
+                .cancel_flight_plan(Request::new(FlightPlanCancelRequest {
+                    flight_plan_id: "FP-1".to_string(),
+                    reason: None,
+                }))
+                .await;
+            assert_eq!(result.unwrap_err().code(), tonic::Code::FailedPrecondition);
+        }
+
+        ut_info!("(test_grpc_cancel_flight_plan) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_grpc_amend_flight_plan() {
+        crate::get_log_handle().await;
+        ut_info!("(test_grpc_amend_flight_plan) Start.");
+
+        let imp = get_server_impl();
+        let amended = flight_plan_data().replace("P-1", "P-2");
+
+        #[cfg(not(feature = "stub_server"))]
+        {
+            let result = imp
+                .amend_flight_plan(Request::new(FlightPlanRequest {
+                    flight_plan_id: "FP-1".to_string(),
+                    data: flight_plan_data(),
+                }))
+                .await;
+            assert_eq!(result.unwrap_err().code(), tonic::Code::NotFound);
+
+            imp.submit_flight_plan(Request::new(FlightPlanRequest {
+                flight_plan_id: "FP-1".to_string(),
+                data: flight_plan_data(),
+            }))
+            .await
+            .unwrap();
+        }
+
+        let result = imp
+            .amend_flight_plan(Request::new(FlightPlanRequest {
+                flight_plan_id: "FP-1".to_string(),
+                data: amended.clone(),
+            }))
+            .await;
+
+        assert!(result.is_ok());
+        let result: FlightPlanResponse = result.unwrap().into_inner();
+        println!("{:?}", result);
+        assert_eq!(result.submitted, true);
+
+        #[cfg(not(feature = "stub_server"))]
+        {
+            let plan = imp.flight_plans.get("FP-1").await.unwrap();
+            assert_eq!(plan.state, FlightPlanState::Accepted);
+            assert_eq!(plan.data, amended);
+            let states: Vec<FlightPlanState> = plan.history.iter().map(|t| t.state).collect();
+            assert_eq!(
+                states,
+                vec![
+                    FlightPlanState::Draft,
+                    FlightPlanState::Submitted,
+                    FlightPlanState::Accepted,
+                    FlightPlanState::Submitted,
+                    FlightPlanState::Accepted
+                ]
+            );
+
+            let responses = imp
+                .flight_plans
+                .storage()
+                .authority_responses("FP-1")
+                .await
+                .unwrap();
+            assert_eq!(responses[1].request, AuthorityRequest::Amendment);
+
+            // Released flight plans are cancelled instead
+            imp.request_flight_release(Request::new(FlightReleaseRequest {
+                flight_plan_id: "FP-1".to_string(),
+                data: "".to_string(),
+            }))
+            .await
+            .unwrap();
+            let result = imp
+                .amend_flight_plan(Request::new(FlightPlanRequest {
+                    flight_plan_id: "FP-1".to_string(),
+                    data: flight_plan_data(),
+                }))
+                .await;
+            assert_eq!(result.unwrap_err().code(), tonic::Code::FailedPrecondition);
+        }
+
+        ut_info!("(test_grpc_amend_flight_plan) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_grpc_activate_flight_plan() {
+        crate::get_log_handle().await;
+        ut_info!("(test_grpc_activate_flight_plan) Start.");
+
+        let imp = get_server_impl();
+        let departed_at = Utc::now() - chrono::Duration::try_minutes(1).unwrap();
+
+        #[cfg(not(feature = "stub_server"))]
+        {
+            // A takeoff time is required, and can not be in the future
+            for departed_at in [
+                None,
+                Some(Utc::now() + chrono::Duration::try_hours(1).unwrap()),
+            ] {
+                let result = imp
+                    .activate_flight_plan(Request::new(FlightPlanActivateRequest {
+                        flight_plan_id: "FP-1".to_string(),
+                        departed_at: departed_at.as_ref().map(datetime_to_timestamp),
+                    }))
+                    .await;
+                assert_eq!(result.unwrap_err().code(), tonic::Code::InvalidArgument);
+            }
+
+            // Only released flight plans take off
+            imp.submit_flight_plan(Request::new(FlightPlanRequest {
+                flight_plan_id: "FP-1".to_string(),
+                data: flight_plan_data(),
+            }))
+            .await
+            .unwrap();
+            let result = imp
+                .activate_flight_plan(Request::new(FlightPlanActivateRequest {
+                    flight_plan_id: "FP-1".to_string(),
+                    departed_at: Some(datetime_to_timestamp(&departed_at)),
+                }))
+                .await;
+            assert_eq!(result.unwrap_err().code(), tonic::Code::FailedPrecondition);
+
+            imp.request_flight_release(Request::new(FlightReleaseRequest {
+                flight_plan_id: "FP-1".to_string(),
+                data: "".to_string(),
+            }))
+            .await
+            .unwrap();
+
+            // Not after the release ran out
+            let storage = imp.flight_plans.storage();
+            let mut release = storage.release("FP-1").await.unwrap().unwrap();
+            let valid_until = release.valid_until;
+            release.valid_until = Some(departed_at - chrono::Duration::try_minutes(1).unwrap());
+            storage.save_release(&release).await.unwrap();
+            let result = imp
+                .activate_flight_plan(Request::new(FlightPlanActivateRequest {
+                    flight_plan_id: "FP-1".to_string(),
+                    departed_at: Some(datetime_to_timestamp(&departed_at)),
+                }))
+                .await;
+            assert_eq!(result.unwrap_err().code(), tonic::Code::FailedPrecondition);
+
+            release.valid_until = valid_until;
+            storage.save_release(&release).await.unwrap();
+        }
+
+        let result = imp
+            .activate_flight_plan(Request::new(FlightPlanActivateRequest {
+                flight_plan_id: "FP-1".to_string(),
+                departed_at: Some(datetime_to_timestamp(&departed_at)),
+            }))
+            .await;
+
+        assert!(result.is_ok());
+        let result: FlightPlanActivateResponse = result.unwrap().into_inner();
+        println!("{:?}", result);
+        assert_eq!(result.activated, true);
+
+        #[cfg(not(feature = "stub_server"))]
+        {
+            let plan = imp.flight_plans.get("FP-1").await.unwrap();
+            assert_eq!(plan.state, FlightPlanState::Active);
+
+            // Active flight plans are closed after landing
+            let result = imp
+                .close_flight_plan(Request::new(FlightPlanCloseRequest {
+                    flight_plan_id: "FP-1".to_string(),
+                    landed_at: Some(datetime_to_timestamp(&Utc::now())),
+                }))
+                .await;
+            assert!(result.unwrap().into_inner().closed);
+        }
+
+        ut_info!("(test_grpc_activate_flight_plan) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_grpc_release_tokens() {
+        use base64::engine::general_purpose::STANDARD;
+        use base64::Engine;
+
+        crate::get_log_handle().await;
+        ut_info!("(test_grpc_release_tokens) Start.");
+
+        let config = TokenConfig {
+            signing_key_id: Some("key-2".to_string()),
+            keys: [("key-1", [1; 32]), ("key-2", [2; 32])]
+                .into_iter()
+                .map(|(key_id, seed): (&str, [u8; 32])| (key_id.to_string(), STANDARD.encode(seed)))
+                .collect(),
+            ..Default::default()
+        };
+        let imp = ServerImpl {
+            release_signer: ReleaseSigner::new(&config).unwrap().map(Arc::new),
+            ..get_server_impl()
+        };
+
+        #[cfg(not(feature = "stub_server"))]
+        {
+            // No keys are served while tokens are not issued
+            let result = get_server_impl()
+                .get_release_keys(Request::new(ReleaseKeysRequest {}))
+                .await;
+            assert_eq!(result.unwrap_err().code(), tonic::Code::FailedPrecondition);
+
+            imp.submit_flight_plan(Request::new(FlightPlanRequest {
+                flight_plan_id: "FP-1".to_string(),
+                data: flight_plan_data(),
+            }))
+            .await
+            .unwrap();
+            let response = imp
+                .request_flight_release(Request::new(FlightReleaseRequest {
+                    flight_plan_id: "FP-1".to_string(),
+                    data: "".to_string(),
+                }))
+                .await
+                .unwrap()
+                .into_inner();
+            let token = response.release_token.unwrap();
+            assert_eq!(token.split('.').count(), 3);
+
+            // The token runs out with the release, after the departure
+            let payload = base64::engine::general_purpose::URL_SAFE_NO_PAD
+                .decode(token.split('.').nth(1).unwrap())
+                .unwrap();
+            let claims: serde_json::Value = serde_json::from_slice(&payload).unwrap();
+            let departure = FlightPlanData::parse(&flight_plan_data())
+                .unwrap()
+                .departure;
+            assert_eq!(
+                claims["exp"],
+                (departure + chrono::Duration::try_minutes(15).unwrap()).timestamp()
+            );
+
+            // The token is kept with the release
+            let status = imp
+                .get_flight_plan_status(Request::new(FlightPlanStatusRequest {
+                    flight_plan_id: "FP-1".to_string(),
+                }))
+                .await
+                .unwrap()
+                .into_inner();
+            assert_eq!(status.release.unwrap().token, Some(token));
+        }
+
+        let result = imp
+            .get_release_keys(Request::new(ReleaseKeysRequest {}))
+            .await;
+        assert!(result.is_ok());
+        let result: ReleaseKeys = result.unwrap().into_inner();
+        println!("{:?}", result);
+        assert_eq!(result.issuer, "svc-compliance");
+
+        #[cfg(not(feature = "stub_server"))]
+        {
+            // Both keys are published, the newest one signs
+            assert_eq!(result.keys.len(), 2);
+            assert_eq!(result.keys[0].key_id, "key-1");
+            assert!(!result.keys[0].signing);
+            assert!(result.keys[1].signing);
+            assert_eq!(result.keys[1].algorithm, "EdDSA");
+        }
+
+        ut_info!("(test_grpc_release_tokens) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_grpc_close_flight_plan() {
+        crate::get_log_handle().await;
+        ut_info!("(test_grpc_close_flight_plan) Start.");
+
+        let imp = get_server_impl();
+
+        #[cfg(not(feature = "stub_server"))]
+        {
+            // A landing time is required, and can not be in the future
+            for landed_at in [
+                None,
+                Some(Utc::now() + chrono::Duration::try_hours(1).unwrap()),
+            ] {
+                let result = imp
+                    .close_flight_plan(Request::new(FlightPlanCloseRequest {
+                        flight_plan_id: "FP-1".to_string(),
+                        landed_at: landed_at.as_ref().map(datetime_to_timestamp),
+                    }))
+                    .await;
+                assert_eq!(result.unwrap_err().code(), tonic::Code::InvalidArgument);
+            }
+
+            // Only released or active flight plans are closed
+            imp.submit_flight_plan(Request::new(FlightPlanRequest {
+                flight_plan_id: "FP-1".to_string(),
+                data: flight_plan_data(),
+            }))
+            .await
+            .unwrap();
+            let result = imp
+                .close_flight_plan(Request::new(FlightPlanCloseRequest {
+                    flight_plan_id: "FP-1".to_string(),
+                    landed_at: Some(datetime_to_timestamp(&Utc::now())),
+                }))
+                .await;
+            assert_eq!(result.unwrap_err().code(), tonic::Code::FailedPrecondition);
+
+            imp.request_flight_release(Request::new(FlightReleaseRequest {
+                flight_plan_id: "FP-1".to_string(),
+                data: "".to_string(),
+            }))
+            .await
+            .unwrap();
+
+            // The landing can not precede the release
+            let early = Utc::now() - chrono::Duration::try_minutes(5).unwrap();
+            let result = imp
+                .close_flight_plan(Request::new(FlightPlanCloseRequest {
+                    flight_plan_id: "FP-1".to_string(),
+                    landed_at: Some(datetime_to_timestamp(&early)),
+                }))
+                .await;
+            assert_eq!(result.unwrap_err().code(), tonic::Code::InvalidArgument);
+            assert_eq!(
+                imp.flight_plans.get("FP-1").await.unwrap().state,
+                FlightPlanState::Released
+            );
+        }
+
+        let landed_at = Utc::now();
+        let result = imp
+            .close_flight_plan(Request::new(FlightPlanCloseRequest {
+                flight_plan_id: "FP-1".to_string(),
+                landed_at: Some(datetime_to_timestamp(&landed_at)),
+            }))
+            .await;
+
+        assert!(result.is_ok());
+        let result: FlightPlanCloseResponse = result.unwrap().into_inner();
+        println!("{:?}", result);
+        assert_eq!(result.closed, true);
+
+        #[cfg(not(feature = "stub_server"))]
+        {
+            let plan = imp.flight_plans.get("FP-1").await.unwrap();
+            assert_eq!(plan.state, FlightPlanState::Closed);
+            assert_eq!(
+                plan.history.last().unwrap().reason,
+                Some(format!("landed at {}", landed_at.to_rfc3339()))
+            );
+        }
+
+        ut_info!("(test_grpc_close_flight_plan) Success.");
+    }
+
+    #[tokio::test]
+    #[cfg(not(feature = "stub_server"))]
+    async fn test_grpc_manual_restrictions() {
+        crate::get_log_handle().await;
+        ut_info!("(test_grpc_manual_restrictions) Start.");
+
+        let imp = get_server_impl();
+
+        let mut invalid = get_manual_restriction_request();
+        invalid.time_end = None;
+        let result = imp.create_restriction(Request::new(invalid)).await;
+        assert_eq!(result.unwrap_err().code(), tonic::Code::InvalidArgument);
+
+        let result = imp
+            .create_restriction(Request::new(get_manual_restriction_request()))
+            .await;
+        assert!(result.is_ok());
+        let identifier = result.unwrap().into_inner().identifier;
+        assert!(identifier.contains("-MANUAL-"));
+
+        let mut request = get_manual_restriction_request();
+        request.identifier = identifier.clone();
+        request.altitude_meters_max = 1000.0;
+        let result = imp.update_restriction(Request::new(request)).await;
+        assert!(result.is_ok());
+
+        let result = imp
+            .list_restrictions(Request::new(RestrictionsRequest {}))
+            .await;
+        let restrictions = result.unwrap().into_inner().restrictions;
+        assert_eq!(restrictions.len(), 1);
+        assert_eq!(restrictions[0].identifier, identifier);
+        assert_eq!(restrictions[0].source, "manual");
+        assert_eq!(restrictions[0].feed, "manual");
+        assert_eq!(restrictions[0].altitude_meters_max, 1000.0);
+        assert_eq!(restrictions[0].author, Some("operator".to_string()));
+
+        let result = imp
+            .delete_restriction(Request::new(DeleteRestrictionRequest {
+                identifier: identifier.clone(),
+                author: "operator".to_string(),
+            }))
+            .await;
+        assert!(result.is_ok());
+
+        let result = imp
+            .delete_restriction(Request::new(DeleteRestrictionRequest {
+                identifier,
+                author: "operator".to_string(),
+            }))
+            .await;
+        assert_eq!(result.unwrap_err().code(), tonic::Code::NotFound);
+
+        ut_info!("(test_grpc_manual_restrictions) Success.");
+    }
+
+    #[tokio::test]
+    #[cfg(not(feature = "stub_server"))]
+    async fn test_grpc_list_waypoints() {
+        crate::get_log_handle().await;
+        ut_info!("(test_grpc_list_waypoints) Start.");
+
+        let imp = get_server_impl();
+        let mut waypoint = WaypointDetails::fix(gis::Coordinates {
+            latitude: 52.3745,
+            longitude: 4.9160,
+        });
+        waypoint.name = Some("DAMRAK".to_string());
+        waypoint.waypoint_type = crate::region::WaypointType::HoldingPoint;
+        waypoint.altitude_meters_min = Some(60.0);
+
+        let mut waypoints = HashMap::new();
+        waypoints.insert("ARROW-WEG-0".to_string(), waypoint);
+        imp.waypoints.set(waypoints).await;
+
+        let result = imp.list_waypoints(Request::new(WaypointsRequest {})).await;
+        let waypoints = result.unwrap().into_inner().waypoints;
+        assert_eq!(waypoints.len(), 1);
+        assert_eq!(waypoints[0].identifier, "ARROW-WEG-0");
+        assert_eq!(waypoints[0].name, Some("DAMRAK".to_string()));
+        assert_eq!(waypoints[0].waypoint_type, "holding_point");
+        assert_eq!(waypoints[0].altitude_meters_min, Some(60.0));
+        assert_eq!(waypoints[0].altitude_meters_max, None);
+        assert_eq!(waypoints[0].withheld_by, None);
+
+        ut_info!("(test_grpc_list_waypoints) Success.");
+    }
+
+    #[tokio::test]
+    #[cfg(not(feature = "stub_server"))]
+    async fn test_grpc_get_waypoint_quality_report() {
+        crate::get_log_handle().await;
+        ut_info!("(test_grpc_get_waypoint_quality_report) Start.");
+
+        let imp = get_server_impl();
+        let location = gis::Coordinates {
+            latitude: 52.3745,
+            longitude: 4.9160,
+        };
+        let waypoints = vec![
+            ("ARROW-WEG-0".to_string(), WaypointDetails::fix(location)),
+            ("wp-1".to_string(), WaypointDetails::fix(location)),
+        ];
+        imp.waypoints
+            .import(waypoints, Utc::now(), &quality::QualityConfig::default())
+            .await;
+
+        let result = imp
+            .get_waypoint_quality_report(Request::new(WaypointQualityRequest {}))
+            .await;
+        let report = result.unwrap().into_inner();
+        assert_eq!(report.received, 2);
+        assert_eq!(report.accepted, 1);
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].identifier, "wp-1");
+        assert_eq!(report.issues[0].kind, "invalid_identifier");
+        assert_eq!(report.issues[0].action, "rejected");
+
+        ut_info!("(test_grpc_get_waypoint_quality_report) Success.");
+    }
+
     #[tokio::test]
     async fn test_update_restrictions() {
         crate::get_log_handle().await;
         ut_info!("(test_update_restrictions) Start.");
 
-        let host = "localhost".to_string();
-        let port = 50008;
+        let pusher = GisPusher::new("localhost", 50008, Default::default());
 
         let mut cache: HashMap<String, RestrictionDetails> = HashMap::new();
-        let result = update_restrictions(host.clone(), port, &cache).await;
+        let result = update_restrictions(&pusher, &cache).await;
         assert_eq!(result, UpdateRestrictionsStatus::NoRestrictions);
 
         cache.insert(
@@ -483,10 +2950,11 @@ mod tests {
                 altitude_meters_max: 0.,
                 altitude_meters_min: 200.,
                 zone_type: gis::ZoneType::Restriction,
+                notam_id: None,
             },
         );
 
-        let result = update_restrictions(host.clone(), port, &cache).await;
+        let result = update_restrictions(&pusher, &cache).await;
         assert_eq!(result, UpdateRestrictionsStatus::Success);
 
         ut_info!("(test_update_restrictions) Success.");
@@ -497,24 +2965,61 @@ mod tests {
         crate::get_log_handle().await;
         ut_info!("(test_update_waypoints) Start.");
 
-        let host = "localhost".to_string();
-        let port = 50008;
+        let pusher = GisPusher::new("localhost", 50008, Default::default());
 
-        let mut cache: HashMap<String, gis::Coordinates> = HashMap::new();
-        let result = update_waypoints(host.clone(), port, &cache).await;
+        let mut cache: HashMap<String, WaypointDetails> = HashMap::new();
+        let result = update_waypoints(&pusher, &cache).await;
         assert_eq!(result, UpdateWaypointsStatus::NoWaypoints);
 
         cache.insert(
             "ARROW-WAY-1".to_string(),
-            gis::Coordinates {
+            WaypointDetails::fix(gis::Coordinates {
                 latitude: 0.0,
                 longitude: 0.0,
-            },
+            }),
         );
 
-        let result = update_waypoints(host.clone(), port, &cache).await;
+        let result = update_waypoints(&pusher, &cache).await;
         assert_eq!(result, UpdateWaypointsStatus::Success);
 
         ut_info!("(test_update_waypoints) Success.");
     }
+
+    #[tokio::test]
+    async fn test_startup() {
+        crate::get_log_handle().await;
+        ut_info!("(test_startup) Start.");
+
+        let mut config = Config::default();
+        config.gis_startup.timeout_seconds = 1;
+        config.snapshot_dir = std::env::temp_dir()
+            .join(format!("svc-compliance-startup-{}", uuid::Uuid::new_v4()))
+            .to_string_lossy()
+            .to_string();
+
+        let imp = get_server_impl();
+        let context = RefreshContext {
+            config: config.clone(),
+            region: imp.region.get_region().to_string(),
+            restrictions: imp.restrictions.clone(),
+            waypoints: imp.waypoints.clone(),
+            registry: Arc::new(IdentifierRegistry::default()),
+            pusher: Arc::new(GisPusher::new("localhost", 50008, Default::default())),
+            health: imp.health.clone(),
+            releases: Arc::new(ReleaseMonitor::new(imp.flight_plans.clone(), None)),
+        };
+        let restriction_sources =
+            sources::restriction_sources(&config, Box::<crate::region::RegionImpl>::default());
+        let waypoint_sources =
+            sources::waypoint_sources(&config, Box::<crate::region::RegionImpl>::default());
+        startup(&context, &restriction_sources, &waypoint_sources).await;
+
+        // Data was acquired and pushed before the loops start
+        assert!(!imp.restrictions.get_all().await.is_empty());
+        assert!(!imp.waypoints.get_all().await.is_empty());
+        assert!(imp.health.check(Utc::now(), false).await.is_empty());
+
+        let _ = tokio::fs::remove_dir_all(&config.snapshot_dir).await;
+        ut_info!("(test_startup) Success.");
+    }
 }
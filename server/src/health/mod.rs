@@ -0,0 +1,346 @@
+//! Readiness of the service
+//!
+//! The service is ready when its airspace data is fresh and reached svc-gis,
+//!  and when it can publish to AMQP. The refresh loops record their outcome
+//!  here; `is_ready` and the gRPC health service report the result.
+
+#[macro_use]
+pub mod macros;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use std::fmt::{self, Display, Formatter};
+use tokio::sync::RwLock;
+
+/// Readiness thresholds
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct HealthConfig {
+    /// Maximum age in seconds of the last successful restriction refresh
+    pub max_restrictions_age_seconds: u32,
+
+    /// Maximum age in seconds of the last successful waypoint refresh
+    pub max_waypoints_age_seconds: u32,
+
+    /// Whether an open AMQP channel is required
+    pub require_amqp: bool,
+
+    /// Interval in seconds at which the gRPC health service is updated
+    pub interval_seconds: u16,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        HealthConfig {
+            max_restrictions_age_seconds: 300,
+            max_waypoints_age_seconds: 300,
+            require_amqp: true,
+            interval_seconds: 5,
+        }
+    }
+}
+
+/// Phase of the startup sequence
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StartupPhase {
+    /// Waiting for svc-gis to be ready
+    WaitingForGis,
+
+    /// Performing the initial sync with svc-gis
+    Syncing,
+
+    /// The refresh loops are running, but svc-gis was not ready in time and
+    ///  has not accepted a push of restrictions and waypoints yet
+    Degraded,
+
+    /// The refresh loops are running
+    Complete,
+}
+
+impl Display for StartupPhase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            StartupPhase::WaitingForGis => write!(f, "waiting for svc-gis"),
+            StartupPhase::Syncing => write!(f, "performing the initial sync"),
+            StartupPhase::Degraded => write!(f, "started without svc-gis"),
+            StartupPhase::Complete => write!(f, "started"),
+        }
+    }
+}
+
+/// Reason the service is not ready
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HealthIssue {
+    /// The startup sequence has not completed
+    Starting(StartupPhase),
+
+    /// Restrictions were never refreshed, or too long ago
+    RestrictionsStale(Option<DateTime<Utc>>),
+
+    /// Waypoints were never refreshed, or too long ago
+    WaypointsStale(Option<DateTime<Utc>>),
+
+    /// Restrictions were not pushed to svc-gis, or the last push failed
+    RestrictionsNotPushed,
+
+    /// Waypoints were not pushed to svc-gis, or the last push failed
+    WaypointsNotPushed,
+
+    /// The AMQP channel is not open
+    AmqpDisconnected,
+}
+
+impl Display for HealthIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            HealthIssue::Starting(phase) => write!(f, "{}", phase),
+            HealthIssue::RestrictionsStale(None) => write!(f, "restrictions never refreshed"),
+            HealthIssue::RestrictionsStale(Some(at)) => {
+                write!(f, "restrictions last refreshed at {}", at)
+            }
+            HealthIssue::WaypointsStale(None) => write!(f, "waypoints never refreshed"),
+            HealthIssue::WaypointsStale(Some(at)) => {
+                write!(f, "waypoints last refreshed at {}", at)
+            }
+            HealthIssue::RestrictionsNotPushed => {
+                write!(f, "restrictions not accepted by svc-gis")
+            }
+            HealthIssue::WaypointsNotPushed => write!(f, "waypoints not accepted by svc-gis"),
+            HealthIssue::AmqpDisconnected => write!(f, "AMQP channel not open"),
+        }
+    }
+}
+
+/// Outcome of the refresh loops
+#[derive(Debug, Default)]
+struct DataState {
+    /// Phase of the startup sequence, if one was started
+    startup: Option<StartupPhase>,
+
+    /// Time of the last successful restriction acquisition
+    restrictions_refreshed: Option<DateTime<Utc>>,
+
+    /// Time of the last successful waypoint acquisition
+    waypoints_refreshed: Option<DateTime<Utc>>,
+
+    /// Whether svc-gis accepted the last restriction push
+    restrictions_pushed: Option<bool>,
+
+    /// Whether svc-gis accepted the last waypoint push
+    waypoints_pushed: Option<bool>,
+}
+
+impl DataState {
+    /// Completes a degraded startup once svc-gis accepted both pushes
+    fn complete_degraded(&mut self) {
+        if self.startup == Some(StartupPhase::Degraded)
+            && self.restrictions_pushed == Some(true)
+            && self.waypoints_pushed == Some(true)
+        {
+            health_info!("(complete_degraded) svc-gis accepted the pushes, startup complete.");
+            self.startup = Some(StartupPhase::Complete);
+        }
+    }
+}
+
+/// Health state shared between the refresh loops and the gRPC server
+#[derive(Debug, Default)]
+pub struct HealthState {
+    /// Readiness thresholds
+    config: HealthConfig,
+
+    /// Outcome of the refresh loops
+    state: RwLock<DataState>,
+}
+
+/// Returns true if the time is within the maximum age
+fn is_fresh(refreshed: Option<DateTime<Utc>>, max_age_seconds: u32, at: DateTime<Utc>) -> bool {
+    match (refreshed, Duration::try_seconds(i64::from(max_age_seconds))) {
+        (Some(refreshed), Some(max_age)) => at - refreshed <= max_age,
+        _ => false,
+    }
+}
+
+impl HealthState {
+    /// Create a new health state with the provided thresholds
+    pub fn new(config: HealthConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the readiness thresholds
+    pub fn config(&self) -> &HealthConfig {
+        &self.config
+    }
+
+    /// Record the phase of the startup sequence
+    ///
+    /// A degraded startup completes once svc-gis accepted both a
+    ///  restriction and a waypoint push.
+    pub async fn startup(&self, phase: StartupPhase) {
+        let mut state = self.state.write().await;
+        state.startup = Some(phase);
+        state.complete_degraded();
+        health_info!("(startup) {}.", state.startup.unwrap_or(phase));
+    }
+
+    /// Record a successful restriction acquisition
+    pub async fn restrictions_refreshed(&self, at: DateTime<Utc>) {
+        self.state.write().await.restrictions_refreshed = Some(at);
+    }
+
+    /// Record a successful waypoint acquisition
+    pub async fn waypoints_refreshed(&self, at: DateTime<Utc>) {
+        self.state.write().await.waypoints_refreshed = Some(at);
+    }
+
+    /// Record whether svc-gis accepted the last restriction push
+    pub async fn restrictions_pushed(&self, accepted: bool) {
+        let mut state = self.state.write().await;
+        state.restrictions_pushed = Some(accepted);
+        state.complete_degraded();
+    }
+
+    /// Record whether svc-gis accepted the last waypoint push
+    pub async fn waypoints_pushed(&self, accepted: bool) {
+        let mut state = self.state.write().await;
+        state.waypoints_pushed = Some(accepted);
+        state.complete_degraded();
+    }
+
+    /// Returns the reasons the service is not ready at the provided time, if
+    ///  any
+    pub async fn check(&self, at: DateTime<Utc>, amqp_connected: bool) -> Vec<HealthIssue> {
+        let state = self.state.read().await;
+        let mut issues = vec![];
+
+        if let Some(phase) = state
+            .startup
+            .filter(|phase| *phase != StartupPhase::Complete)
+        {
+            issues.push(HealthIssue::Starting(phase));
+        }
+
+        if !is_fresh(
+            state.restrictions_refreshed,
+            self.config.max_restrictions_age_seconds,
+            at,
+        ) {
+            issues.push(HealthIssue::RestrictionsStale(state.restrictions_refreshed));
+        }
+
+        if !is_fresh(
+            state.waypoints_refreshed,
+            self.config.max_waypoints_age_seconds,
+            at,
+        ) {
+            issues.push(HealthIssue::WaypointsStale(state.waypoints_refreshed));
+        }
+
+        if state.restrictions_pushed != Some(true) {
+            issues.push(HealthIssue::RestrictionsNotPushed);
+        }
+
+        if state.waypoints_pushed != Some(true) {
+            issues.push(HealthIssue::WaypointsNotPushed);
+        }
+
+        if self.config.require_amqp && !amqp_connected {
+            issues.push(HealthIssue::AmqpDisconnected);
+        }
+
+        for issue in issues.iter() {
+            health_debug!("(check) Not ready: {}.", issue);
+        }
+
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_health_check() {
+        crate::get_log_handle().await;
+        ut_info!("(test_health_check) Start.");
+
+        let health = HealthState::default();
+        let now = Utc::now();
+        let issues = health.check(now, false).await;
+        assert_eq!(
+            issues,
+            vec![
+                HealthIssue::RestrictionsStale(None),
+                HealthIssue::WaypointsStale(None),
+                HealthIssue::RestrictionsNotPushed,
+                HealthIssue::WaypointsNotPushed,
+                HealthIssue::AmqpDisconnected,
+            ]
+        );
+
+        health.restrictions_refreshed(now).await;
+        health.waypoints_refreshed(now).await;
+        health.restrictions_pushed(true).await;
+        health.waypoints_pushed(true).await;
+        assert!(health.check(now, true).await.is_empty());
+
+        // Stale data and a rejected push
+        let later = now + Duration::try_seconds(301).unwrap();
+        health.waypoints_refreshed(later).await;
+        health.restrictions_pushed(false).await;
+        assert_eq!(
+            health.check(later, true).await,
+            vec![
+                HealthIssue::RestrictionsStale(Some(now)),
+                HealthIssue::RestrictionsNotPushed,
+            ]
+        );
+
+        // Fresh data does not make the service ready during startup
+        let health = HealthState::default();
+        health.startup(StartupPhase::Syncing).await;
+        health.restrictions_refreshed(now).await;
+        health.waypoints_refreshed(now).await;
+        health.restrictions_pushed(true).await;
+        health.waypoints_pushed(true).await;
+        assert_eq!(
+            health.check(now, true).await,
+            vec![HealthIssue::Starting(StartupPhase::Syncing)]
+        );
+        health.startup(StartupPhase::Complete).await;
+        assert!(health.check(now, true).await.is_empty());
+
+        // Not ready after a startup without svc-gis until it accepts a push
+        let health = HealthState::default();
+        health.restrictions_refreshed(now).await;
+        health.waypoints_refreshed(now).await;
+        health.restrictions_pushed(false).await;
+        health.waypoints_pushed(true).await;
+        health.startup(StartupPhase::Degraded).await;
+        assert_eq!(
+            health.check(now, true).await,
+            vec![
+                HealthIssue::Starting(StartupPhase::Degraded),
+                HealthIssue::RestrictionsNotPushed,
+            ]
+        );
+        health.restrictions_pushed(true).await;
+        assert!(health.check(now, true).await.is_empty());
+
+        // AMQP not required
+        let health = HealthState::new(HealthConfig {
+            require_amqp: false,
+            ..Default::default()
+        });
+        assert!(!health
+            .check(now, false)
+            .await
+            .contains(&HealthIssue::AmqpDisconnected));
+
+        ut_info!("(test_health_check) Success.");
+    }
+}
@@ -0,0 +1,309 @@
+//! Stable identifiers for zones and waypoints
+//!
+//! Identifiers are derived from the key an item has in its source, or from
+//!  its content when the source has no keys, and are namespaced by region and
+//!  source kind: `ARROW-{REGION}-{SOURCE}-{KEY}`. Reordering a source no
+//!  longer renames its items.
+//!
+//! Items that were pushed to svc-gis under an older identifier keep being
+//!  published under it, so they are updated in place rather than deleted and
+//!  added again. The [`IdentifierRegistry`] holds this mapping.
+
+#[macro_use]
+pub mod macros;
+
+use crate::restrictions::merge::SourceKind;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use svc_gis_client_grpc::prelude::gis::Coordinates;
+use tokio::sync::RwLock;
+
+/// Number of hexadecimal digits kept from content hashes
+const HASH_DIGITS: usize = 12;
+
+/// Number of hexadecimal digits kept from the hashes of normalized keys
+const KEY_HASH_DIGITS: usize = 8;
+
+/// Errors returned by the identifier registry
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum IdentifierError {
+    /// The registry could not be persisted or loaded
+    #[error("error: Could not persist the identifier registry: {0}.")]
+    Persistence(String),
+}
+
+/// Returns the identifier namespace of a region and source
+fn namespace(region: &str, source: SourceKind) -> String {
+    format!(
+        "ARROW-{}-{}",
+        region.to_ascii_uppercase(),
+        source.as_str().to_ascii_uppercase()
+    )
+}
+
+/// Returns the first digits of the upper case hexadecimal SHA-256 digest of
+///  the content
+fn hash(content: &str, digits: usize) -> String {
+    Sha256::digest(content.as_bytes())
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect::<String>()
+        .chars()
+        .take(digits)
+        .collect()
+}
+
+/// Returns the identifier of an item with a key in its source
+///
+/// The key is upper cased and characters other than letters, digits and
+///  hyphens are replaced with hyphens. A key changed by this gets a hash of
+///  the original key appended, so keys such as `A/1`, `a 1` and `A-1` keep
+///  distinct identifiers.
+pub fn from_key(region: &str, source: SourceKind, key: &str) -> String {
+    let key = key.trim();
+    let normalized: String = key
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' => c.to_ascii_uppercase(),
+            _ => '-',
+        })
+        .collect();
+
+    match normalized == key {
+        true => format!("{}-{}", namespace(region, source), normalized),
+        false => format!(
+            "{}-{}-{}",
+            namespace(region, source),
+            normalized,
+            hash(key, KEY_HASH_DIGITS)
+        ),
+    }
+}
+
+/// Returns the identifier of an item without a key in its source, derived
+///  from a hash of its content
+pub fn from_content(region: &str, source: SourceKind, content: &str) -> String {
+    format!(
+        "{}-{}",
+        namespace(region, source),
+        hash(content, HASH_DIGITS)
+    )
+}
+
+/// Returns the identifier of a waypoint without a key in its source, derived
+///  from its location
+///
+/// Coordinates are rounded to 7 decimals, about a centimeter, so the
+///  identifier does not depend on how the source formats them.
+pub fn from_location(region: &str, source: SourceKind, location: &Coordinates) -> String {
+    from_content(
+        region,
+        source,
+        &format!("WP:{:.7},{:.7}", location.latitude, location.longitude),
+    )
+}
+
+/// Mapping of stable identifiers to the identifiers items were first
+///  published under
+#[derive(Debug, Default)]
+pub struct IdentifierRegistry {
+    /// Published identifiers by stable identifier
+    aliases: RwLock<HashMap<String, String>>,
+
+    /// File used to persist the registry across restarts
+    file: Option<PathBuf>,
+}
+
+impl IdentifierRegistry {
+    /// Create a new registry, persisted to the provided file if any
+    pub fn new(file: Option<PathBuf>) -> Self {
+        Self {
+            file,
+            ..Default::default()
+        }
+    }
+
+    /// Load a previously persisted registry
+    pub async fn load(&self) -> Result<usize, IdentifierError> {
+        let Some(path) = &self.file else {
+            return Ok(0);
+        };
+
+        let contents = match tokio::fs::read(path).await {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                identifiers_info!("(load) No identifier registry found at {:?}.", path);
+                return Ok(0);
+            }
+            Err(e) => {
+                identifiers_error!("(load) Could not read {:?}: {}", path, e);
+                return Err(IdentifierError::Persistence(e.to_string()));
+            }
+        };
+
+        let aliases: HashMap<String, String> = serde_json::from_slice(&contents).map_err(|e| {
+            identifiers_error!("(load) Could not parse {:?}: {}", path, e);
+            IdentifierError::Persistence(e.to_string())
+        })?;
+
+        let count = aliases.len();
+        *self.aliases.write().await = aliases;
+        identifiers_info!("(load) Loaded {} identifier alias(es).", count);
+        Ok(count)
+    }
+
+    /// Record the identifiers items were published under before stable
+    ///  identifiers were introduced
+    ///
+    /// Takes pairs of legacy and stable identifiers. Stable identifiers
+    ///  already mapped and legacy identifiers already in use are left alone,
+    ///  so adopting the same pairs again has no effect. Returns the number of
+    ///  aliases added.
+    pub async fn adopt(&self, legacy: Vec<(String, String)>) -> Result<usize, IdentifierError> {
+        let mut aliases = self.aliases.write().await;
+        let mut updated = aliases.clone();
+        let mut added = 0;
+        for (published, stable) in legacy {
+            if updated.contains_key(&stable) || updated.values().any(|v| *v == published) {
+                continue;
+            }
+
+            identifiers_debug!("(adopt) Publishing {} as {}.", stable, published);
+            updated.insert(stable, published);
+            added += 1;
+        }
+
+        if added > 0 {
+            self.persist(&updated).await?;
+            *aliases = updated;
+            identifiers_info!("(adopt) Added {} identifier alias(es).", added);
+        }
+
+        Ok(added)
+    }
+
+    /// Returns the identifier an item is published under
+    pub async fn resolve(&self, stable: &str) -> String {
+        self.aliases
+            .read()
+            .await
+            .get(stable)
+            .cloned()
+            .unwrap_or_else(|| stable.to_string())
+    }
+
+    /// Returns the items keyed by the identifiers they are published under
+    pub async fn publish<T>(&self, items: HashMap<String, T>) -> HashMap<String, T> {
+        let aliases = self.aliases.read().await;
+        items
+            .into_iter()
+            .map(|(identifier, item)| match aliases.get(&identifier) {
+                Some(published) => (published.clone(), item),
+                None => (identifier, item),
+            })
+            .collect()
+    }
+
+    /// Writes the registry to its file, if any
+    async fn persist(&self, aliases: &HashMap<String, String>) -> Result<(), IdentifierError> {
+        let Some(path) = &self.file else {
+            return Ok(());
+        };
+
+        let contents = serde_json::to_vec_pretty(aliases).map_err(|e| {
+            identifiers_error!("(persist) Could not serialize the registry: {}", e);
+            IdentifierError::Persistence(e.to_string())
+        })?;
+
+        crate::files::write_atomic(path, &contents)
+            .await
+            .map_err(|e| {
+                identifiers_error!("(persist) Could not write {:?}: {}", path, e);
+                IdentifierError::Persistence(e.to_string())
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_identifiers() {
+        crate::get_log_handle().await;
+        ut_info!("(test_identifiers) Start.");
+
+        assert_eq!(
+            from_key("nl", SourceKind::Static, " NOFLY-SCHIPHOL "),
+            "ARROW-NL-STATIC-NOFLY-SCHIPHOL"
+        );
+
+        // Keys that only differ in the characters replaced stay distinct
+        let keys = ["A-1", "A/1", "A 1", "a-1"].map(|key| from_key("nl", SourceKind::File, key));
+        assert_eq!(keys[0], "ARROW-NL-FILE-A-1");
+        assert!(keys[1].starts_with("ARROW-NL-FILE-A-1-"));
+        assert_eq!(keys[1].len(), "ARROW-NL-FILE-A-1-".len() + KEY_HASH_DIGITS);
+        for (i, key) in keys.iter().enumerate() {
+            assert!(keys[i + 1..].iter().all(|other| other != key));
+        }
+
+        let location = Coordinates {
+            latitude: 52.3745,
+            longitude: 4.9160,
+        };
+        let identifier = from_location("nl", SourceKind::Static, &location);
+        assert!(identifier.starts_with("ARROW-NL-STATIC-"));
+        assert_eq!(identifier.len(), "ARROW-NL-STATIC-".len() + HASH_DIGITS);
+
+        // Formatting noise below the rounding does not change the identifier
+        let noisy = Coordinates {
+            latitude: 52.374500000001,
+            longitude: 4.9160,
+        };
+        assert_eq!(identifier, from_location("nl", SourceKind::Static, &noisy));
+        assert_ne!(
+            identifier,
+            from_location("us", SourceKind::Static, &location)
+        );
+
+        ut_info!("(test_identifiers) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_registry() {
+        crate::get_log_handle().await;
+        ut_info!("(test_registry) Start.");
+
+        let path = std::env::temp_dir().join(format!(
+            "svc-compliance-identifiers-{}.json",
+            uuid::Uuid::new_v4()
+        ));
+
+        let registry = IdentifierRegistry::new(Some(path.clone()));
+        let legacy = vec![
+            ("ARROW-WEG-0".to_string(), "ARROW-NL-STATIC-A".to_string()),
+            ("ARROW-WEG-1".to_string(), "ARROW-NL-STATIC-B".to_string()),
+        ];
+        assert_eq!(registry.adopt(legacy.clone()).await.unwrap(), 2);
+        assert_eq!(registry.adopt(legacy).await.unwrap(), 0);
+
+        // A reordered source does not reassign legacy identifiers
+        let reordered = vec![("ARROW-WEG-0".to_string(), "ARROW-NL-STATIC-B".to_string())];
+        assert_eq!(registry.adopt(reordered).await.unwrap(), 0);
+
+        let mut items = HashMap::new();
+        items.insert("ARROW-NL-STATIC-A".to_string(), 1);
+        items.insert("ARROW-NL-STATIC-C".to_string(), 2);
+        let published = registry.publish(items).await;
+        assert_eq!(published["ARROW-WEG-0"], 1);
+        assert_eq!(published["ARROW-NL-STATIC-C"], 2);
+
+        let reloaded = IdentifierRegistry::new(Some(path.clone()));
+        assert_eq!(reloaded.load().await.unwrap(), 2);
+        assert_eq!(reloaded.resolve("ARROW-NL-STATIC-B").await, "ARROW-WEG-1");
+
+        let _ = tokio::fs::remove_file(&path).await;
+        ut_info!("(test_registry) Success.");
+    }
+}
@@ -8,8 +8,21 @@ pub mod test_util;
 
 pub mod amqp;
 pub mod config;
+pub mod decisions;
+pub mod files;
+pub mod flight_plans;
+pub mod gis;
 pub mod grpc;
+pub mod health;
+pub mod identifiers;
 pub mod region;
+pub mod releases;
+pub mod restrictions;
+pub mod snapshot;
+pub mod sources;
+pub mod storage;
+pub mod waypoints;
+pub mod webhook;
 
 pub use crate::config::Config;
 
@@ -15,9 +15,13 @@ pub mod utils;
 
 use crate::grpc::server;
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use server::{FlightPlanCancelRequest, FlightPlanCancelResponse};
+use server::{FlightPlanCloseRequest, FlightPlanCloseResponse};
 use server::{FlightPlanRequest, FlightPlanResponse};
 use server::{FlightReleaseRequest, FlightReleaseResponse};
 use std::collections::HashMap;
+use std::sync::Arc;
 use svc_gis_client_grpc::prelude::gis;
 use tonic::{Request, Response, Status};
 
@@ -29,9 +33,10 @@ pub struct RegionImpl {
 }
 
 /// Details of a flight restriction
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RestrictionDetails {
     /// The boundary vertices of the restriction
+    #[serde(with = "utils::coordinates_serde")]
     pub vertices: Vec<gis::Coordinates>,
 
     /// The start time of the restriction
@@ -41,6 +46,7 @@ pub struct RestrictionDetails {
     pub timestamp_end: Option<DateTime<Utc>>,
 
     /// The restriction type
+    #[serde(with = "utils::zone_type_serde")]
     pub zone_type: gis::ZoneType,
 
     /// The maximum altitude
@@ -48,6 +54,183 @@ pub struct RestrictionDetails {
 
     /// The minimum altitude
     pub altitude_meters_min: f32,
+
+    /// The NOTAM this restriction was published in, if any
+    ///
+    /// Used to recognize the same NOTAM provided by several feeds.
+    #[serde(default)]
+    pub notam_id: Option<String>,
+}
+
+impl RestrictionDetails {
+    /// Returns true if the restriction is in effect at the provided time
+    pub fn is_active_at(&self, at: DateTime<Utc>) -> bool {
+        !matches!(self.timestamp_start, Some(start) if start > at)
+            && !matches!(self.timestamp_end, Some(end) if end <= at)
+    }
+
+    /// Returns true if the location lies inside or on the boundary of the
+    ///  restriction
+    pub fn contains(&self, location: &gis::Coordinates) -> bool {
+        let ring = utils::open_ring(&self.vertices);
+        if ring.len() < 3 {
+            return false;
+        }
+
+        let projection = utils::LocalProjection::new(&ring[0]);
+        let points: Vec<utils::LocalPoint> = ring.iter().map(|v| projection.to_local(v)).collect();
+        utils::point_in_polygon(&projection.to_local(location), &points, 0.0)
+    }
+
+    /// Returns true if the restriction is in effect at any time between the
+    ///  provided times
+    pub fn is_active_between(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> bool {
+        !matches!(self.timestamp_start, Some(restriction_start) if restriction_start > end)
+            && !matches!(self.timestamp_end, Some(restriction_end) if restriction_end <= start)
+    }
+
+    /// Returns true if a point of the route lies inside the restriction, or a
+    ///  leg of the route crosses its boundary
+    pub fn intersects_route(&self, route: &[gis::Coordinates]) -> bool {
+        let ring = utils::open_ring(&self.vertices);
+        if ring.len() < 3 || route.is_empty() {
+            return false;
+        }
+
+        let projection = utils::LocalProjection::new(&ring[0]);
+        let points: Vec<utils::LocalPoint> = ring.iter().map(|v| projection.to_local(v)).collect();
+        let route: Vec<utils::LocalPoint> = route.iter().map(|c| projection.to_local(c)).collect();
+        if route
+            .iter()
+            .any(|p| utils::point_in_polygon(p, &points, 0.0))
+        {
+            return true;
+        }
+
+        route.windows(2).any(|leg| {
+            (0..points.len()).any(|i| {
+                utils::segments_intersect(
+                    &leg[0],
+                    &leg[1],
+                    &points[i],
+                    &points[(i + 1) % points.len()],
+                )
+            })
+        })
+    }
+}
+
+/// Kind of waypoint
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WaypointType {
+    /// Navigation fix
+    #[default]
+    Fix,
+
+    /// Entry point of a vertiport approach
+    VertiportEntry,
+
+    /// Point where aircraft may hold
+    HoldingPoint,
+}
+
+impl WaypointType {
+    /// Returns the name used in the gRPC interface
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WaypointType::Fix => "fix",
+            WaypointType::VertiportEntry => "vertiport_entry",
+            WaypointType::HoldingPoint => "holding_point",
+        }
+    }
+}
+
+/// Details of a waypoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WaypointDetails {
+    /// The location of the waypoint
+    #[serde(with = "utils::point_serde")]
+    pub location: gis::Coordinates,
+
+    /// The published name of the waypoint, if any
+    pub name: Option<String>,
+
+    /// The waypoint type
+    pub waypoint_type: WaypointType,
+
+    /// The minimum altitude at this waypoint
+    pub altitude_meters_min: Option<f32>,
+
+    /// The maximum altitude at this waypoint
+    pub altitude_meters_max: Option<f32>,
+
+    /// The start of the validity of the waypoint
+    pub timestamp_start: Option<DateTime<Utc>>,
+
+    /// The end of the validity of the waypoint
+    pub timestamp_end: Option<DateTime<Utc>>,
+}
+
+impl WaypointDetails {
+    /// Create a fix at the provided location, valid at all times
+    pub fn fix(location: gis::Coordinates) -> Self {
+        Self {
+            location,
+            name: None,
+            waypoint_type: WaypointType::Fix,
+            altitude_meters_min: None,
+            altitude_meters_max: None,
+            timestamp_start: None,
+            timestamp_end: None,
+        }
+    }
+
+    /// Returns true if the waypoint is valid at the provided time
+    pub fn is_valid_at(&self, at: DateTime<Utc>) -> bool {
+        !matches!(self.timestamp_start, Some(start) if start > at)
+            && !matches!(self.timestamp_end, Some(end) if end <= at)
+    }
+}
+
+/// Errors returned by the region implementations
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum RegionError {
+    /// Data could not be acquired from the regional authority
+    #[error("error: Could not acquire data from the regional authority: {0}.")]
+    Acquisition(String),
+
+    /// Coordinates could not be converted to WGS84
+    #[error("error: Could not convert coordinates: {0}.")]
+    Conversion(String),
+}
+
+/// Decision of the authority on a flight plan it did not decide on right
+///  away
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthorityDecision {
+    /// Whether the flight plan was accepted
+    pub granted: bool,
+
+    /// Message of the authority, if any
+    pub message: Option<String>,
+
+    /// Reference number assigned by the authority, if any
+    pub reference: Option<String>,
+}
+
+/// Converts a point of a source coordinate system to WGS84
+///
+/// Points are given in the axis order of the source system.
+pub type CoordinateConverter =
+    Arc<dyn Fn(f64, f64) -> Result<gis::Coordinates, RegionError> + Send + Sync>;
+
+/// Returns true if the name designates WGS84 latitude and longitude
+pub fn is_wgs84(system: &str) -> bool {
+    let name = system.trim().to_uppercase();
+    matches!(
+        name.strip_prefix("EPSG:").unwrap_or(&name),
+        "4326" | "WGS84"
+    )
 }
 
 /// Interface to regional authorities
@@ -56,6 +239,31 @@ pub trait RegionInterface {
     /// Return the region short code of the implementation
     fn get_region(&self) -> &str;
 
+    /// Return the identifiers the region data was published under before
+    ///  stable identifiers were introduced, paired with the stable ones
+    fn legacy_identifiers(&self) -> Vec<(String, String)> {
+        vec![]
+    }
+
+    /// Return the converter of points in the named coordinate system to
+    ///  WGS84, used by sources publishing in regional coordinates
+    ///
+    /// Only WGS84 latitude and longitude are supported by default.
+    fn coordinate_converter(&self, system: &str) -> Result<CoordinateConverter, RegionError> {
+        if !is_wgs84(system) {
+            return Err(RegionError::Conversion(format!(
+                "unsupported coordinate system: {system}"
+            )));
+        }
+
+        Ok(Arc::new(|latitude, longitude| {
+            Ok(gis::Coordinates {
+                latitude,
+                longitude,
+            })
+        }))
+    }
+
     /// Submit a new flight plan for the region
     fn submit_flight_plan(
         &self,
@@ -68,11 +276,48 @@ pub trait RegionInterface {
         request: Request<FlightReleaseRequest>,
     ) -> Result<Response<FlightReleaseResponse>, Status>;
 
-    /// Refresh the in memory stored restrictions
-    async fn acquire_restrictions(&self, restrictions: &mut HashMap<String, RestrictionDetails>);
+    /// Cancel a submitted flight plan for the region
+    fn cancel_flight_plan(
+        &self,
+        request: FlightPlanCancelRequest,
+    ) -> Result<Response<FlightPlanCancelResponse>, Status>;
+
+    /// Submit a changed route or time of a submitted flight plan for the
+    ///  region, which is validated again
+    fn amend_flight_plan(
+        &self,
+        request: FlightPlanRequest,
+    ) -> Result<Response<FlightPlanResponse>, Status>;
+
+    /// Close a flown flight plan for the region
+    fn close_flight_plan(
+        &self,
+        request: FlightPlanCloseRequest,
+    ) -> Result<Response<FlightPlanCloseResponse>, Status>;
+
+    /// Ask the authority for its decision on a flight plan that was
+    ///  submitted with a pending result, returning `None` while it is still
+    ///  undecided
+    async fn poll_decision(
+        &self,
+        flight_plan_id: &str,
+        reference: Option<&str>,
+    ) -> Result<Option<AuthorityDecision>, RegionError>;
+
+    /// Acquire the current restrictions of the regional authority
+    ///
+    /// The returned restrictions replace the previously acquired ones as a
+    ///  whole; they are merged with the other restriction feeds by the
+    ///  [`RestrictionStore`](crate::restrictions::RestrictionStore).
+    async fn acquire_restrictions(
+        &self,
+    ) -> Result<HashMap<String, RestrictionDetails>, RegionError>;
 
-    /// Refresh the in memory stored waypoints
-    async fn acquire_waypoints(&self, waypoints: &mut HashMap<String, gis::Coordinates>);
+    /// Acquire the current waypoints of the regional authority
+    ///
+    /// Waypoints are returned in the order of the source, so that quality
+    ///  checks can detect identifiers used more than once.
+    async fn acquire_waypoints(&self) -> Result<Vec<(String, WaypointDetails)>, RegionError>;
 }
 
 #[cfg(test)]
@@ -95,4 +340,65 @@ mod tests {
 
         ut_info!("(test_region_code) Success.");
     }
+
+    #[tokio::test]
+    async fn test_coordinate_converter() {
+        crate::get_log_handle().await;
+        ut_info!("(test_coordinate_converter) Start.");
+
+        let region_impl = RegionImpl::default();
+        let convert = region_impl.coordinate_converter("EPSG:4326").unwrap();
+        let coordinates = convert(52.3745, 4.9160).unwrap();
+        assert_eq!(coordinates.latitude, 52.3745);
+        assert_eq!(coordinates.longitude, 4.9160);
+        assert!(region_impl.coordinate_converter("EPSG:3857").is_err());
+
+        ut_info!("(test_coordinate_converter) Success.");
+    }
+
+    #[test]
+    fn test_intersects_route() {
+        let at = |latitude, longitude| gis::Coordinates {
+            latitude,
+            longitude,
+        };
+        let restriction = RestrictionDetails {
+            vertices: vec![
+                at(52.0, 4.0),
+                at(52.0, 4.1),
+                at(52.1, 4.1),
+                at(52.1, 4.0),
+                at(52.0, 4.0),
+            ],
+            timestamp_start: None,
+            timestamp_end: None,
+            zone_type: gis::ZoneType::Restriction,
+            altitude_meters_max: 500.0,
+            altitude_meters_min: 0.0,
+            notam_id: None,
+        };
+
+        // A point inside
+        assert!(restriction.intersects_route(&[at(51.9, 3.9), at(52.05, 4.05)]));
+
+        // Crossing without a point inside
+        assert!(restriction.intersects_route(&[at(52.05, 3.9), at(52.05, 4.2)]));
+
+        // Passing by
+        assert!(!restriction.intersects_route(&[at(51.9, 3.9), at(51.9, 4.2)]));
+        assert!(!restriction.intersects_route(&[]));
+
+        let now = Utc::now();
+        let restriction = RestrictionDetails {
+            timestamp_start: Some(now + chrono::Duration::hours(1)),
+            timestamp_end: Some(now + chrono::Duration::hours(2)),
+            ..restriction
+        };
+        assert!(!restriction.is_active_between(now, now + chrono::Duration::minutes(30)));
+        assert!(restriction.is_active_between(now, now + chrono::Duration::minutes(90)));
+        assert!(!restriction.is_active_between(
+            now + chrono::Duration::hours(2),
+            now + chrono::Duration::hours(3)
+        ));
+    }
 }
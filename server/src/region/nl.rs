@@ -1,13 +1,20 @@
 //! Region implementation for The Netherlands (NL)
 
+pub mod rd;
+
 use crate::grpc::server::{
-    FlightPlanRequest, FlightPlanResponse, FlightReleaseRequest, FlightReleaseResponse,
+    FlightPlanCancelRequest, FlightPlanCancelResponse, FlightPlanCloseRequest,
+    FlightPlanCloseResponse, FlightPlanRequest, FlightPlanResponse, FlightReleaseRequest,
+    FlightReleaseResponse,
 };
 
-use crate::region::RegionInterface;
-use crate::region::RestrictionDetails;
+use crate::identifiers;
+use crate::region::{AuthorityDecision, RegionError, RestrictionDetails, WaypointDetails};
+use crate::region::{CoordinateConverter, RegionInterface};
+use crate::restrictions::merge::SourceKind;
 use chrono::{Duration, Utc};
 use std::collections::HashMap;
+use std::sync::Arc;
 use svc_gis_client_grpc::prelude::gis::{Coordinates, ZoneType};
 use tonic::{Request, Response, Status};
 
@@ -18,6 +25,110 @@ use tonic::{Request, Response, Status};
 // const NL_RESTRICTION_REFRESH_INTERVAL_MS: u64 = 30000; // 30s
 // const NL_WAYPOINT_REFRESH_INTERVAL_MS: u64 = 60000; // 60s
 
+/// Amsterdam Drone Lab waypoints as (longitude, latitude), in the order they
+///  were published under index based identifiers
+const WAYPOINTS: &[(f64, f64)] = &[
+    // Valid waypoints
+    (4.9160, 52.3745),
+    (4.9156, 52.3749),
+    (4.9153, 52.3752),
+    (4.9156, 52.3753),
+    // Waypoint within the TFR
+    (4.9161, 52.3750),
+    // Others
+    (5.2021015, 52.9635294),
+    (5.2570586, 52.8691297),
+    (5.3724685, 52.7878146),
+    (5.4164342, 52.6780473),
+    (5.3559814, 52.6047156),
+    (5.2735457, 52.5513062),
+    (5.2021015, 52.4944873),
+    (5.1361530, 52.4174978),
+    (5.0784480, 52.3638604),
+    (5.0234941, 52.2858024),
+    (4.9699542, 52.2542885),
+    (4.9095214, 52.2742499),
+    (4.9075041, 52.3103678),
+    (5.2844417, 52.6672220),
+    (5.2133705, 52.6122211),
+    (5.1309348, 52.5930378),
+    (5.0732299, 52.5796880),
+    (5.0057699, 52.5550636),
+    (4.9314804, 52.5341847),
+    (4.8697511, 52.5015937),
+    (4.8566987, 52.4677236),
+    (4.8148570, 52.5329316),
+    (4.7921165, 52.5742635),
+    (4.8375440, 52.6038816),
+    (4.8918141, 52.6330629),
+    (4.9550148, 52.6701368),
+    (5.0051632, 52.6959449),
+    (5.0553115, 52.7092593),
+    (5.1189528, 52.7421118),
+    (5.1892695, 52.8060822),
+    (5.5227080, 52.7703701),
+    (5.5213340, 52.7080113),
+    (5.5309515, 52.6380634),
+    (5.5007251, 52.5888665),
+    (5.3784456, 52.5270836),
+    (5.3399756, 52.4727431),
+    (5.3866891, 52.4258727),
+    (5.4457680, 52.3630218),
+    (5.6862054, 52.4091213),
+    (5.7865021, 52.4568462),
+    (5.8538245, 52.5496361),
+    (5.0791343, 53.0230443),
+    (4.9870812, 53.0519455),
+    (4.9280023, 53.1030944),
+    (4.9664723, 53.1739423),
+    (5.0502818, 53.2380987),
+    (5.2357621, 53.3103651),
+    (5.3621634, 53.3513711),
+    (5.5023041, 53.3857856),
+    (5.6026008, 53.4005262),
+    (5.7606025, 53.4136247),
+    (4.8892276, 52.3661141),
+    (4.8931777, 52.3793717),
+    (4.8784938, 52.3742892),
+    (4.9035680, 52.3718788),
+    (4.7266651, 52.2700483),
+    (4.8111616, 52.3481340),
+    (4.6383902, 52.3676339),
+    (4.8558143, 52.2631146),
+    (4.8805450, 52.3166644),
+    (4.7946745, 52.2286406),
+    (4.6164074, 52.3109975),
+    (4.7022778, 52.4068174),
+    (5.0968408, 52.6132634),
+    (5.1521414, 52.6491043),
+    (5.1342804, 52.6751331),
+    (5.0985582, 52.6969853),
+    (5.0456620, 52.6903267),
+    (5.0034138, 52.6645152),
+    (4.9955137, 52.6330629),
+    (5.0463490, 52.6134719),
+];
+
+/// Source keys of the hardcoded zones, which were published as
+///  `ARROW-NL-{key}`
+const ZONE_KEYS: &[&str] = &["NOFLY-SCHIPHOL", "TFR-PALEIS", "NOFLY-HOORN"];
+
+/// Location of a hardcoded waypoint
+fn waypoint_location((longitude, latitude): &(f64, f64)) -> Coordinates {
+    Coordinates {
+        latitude: *latitude,
+        longitude: *longitude,
+    }
+}
+
+/// Vertices of a hardcoded zone, given in the provided coordinate system
+fn static_vertices(
+    system: rd::CoordinateSystem,
+    points: &[(f64, f64)],
+) -> Result<Vec<Coordinates>, RegionError> {
+    rd::to_vertices(system, points).map_err(|e| RegionError::Conversion(e.to_string()))
+}
+
 impl Default for super::RegionImpl {
     fn default() -> Self {
         Self {
@@ -33,6 +144,40 @@ impl RegionInterface for super::RegionImpl {
         &self.region
     }
 
+    fn legacy_identifiers(&self) -> Vec<(String, String)> {
+        let zones = ZONE_KEYS.iter().map(|key| {
+            (
+                format!("ARROW-NL-{}", key),
+                identifiers::from_key(&self.region, SourceKind::Static, key),
+            )
+        });
+
+        let waypoints = WAYPOINTS.iter().enumerate().map(|(i, waypoint)| {
+            (
+                format!("ARROW-WEG-{}", i),
+                identifiers::from_location(
+                    &self.region,
+                    SourceKind::Static,
+                    &waypoint_location(waypoint),
+                ),
+            )
+        });
+
+        zones.chain(waypoints).collect()
+    }
+
+    fn coordinate_converter(&self, system: &str) -> Result<CoordinateConverter, RegionError> {
+        let system: rd::CoordinateSystem = system
+            .parse()
+            .map_err(|e: rd::ConversionError| RegionError::Conversion(e.to_string()))?;
+
+        Ok(Arc::new(move |first, second| {
+            system
+                .to_wgs84(first, second)
+                .map_err(|e| RegionError::Conversion(e.to_string()))
+        }))
+    }
+
     fn submit_flight_plan(
         &self,
         request: FlightPlanRequest,
@@ -48,6 +193,8 @@ impl RegionInterface for super::RegionImpl {
             flight_plan_id,
             submitted: true,
             result: None,
+            authority_reference: None,
+            pending: false,
         }))
     }
 
@@ -66,212 +213,191 @@ impl RegionInterface for super::RegionImpl {
             flight_plan_id,
             released: true,
             result: None,
+            release_token: None,
         }))
     }
 
-    async fn acquire_restrictions(&self, restrictions: &mut HashMap<String, RestrictionDetails>) {
+    fn cancel_flight_plan(
+        &self,
+        request: FlightPlanCancelRequest,
+    ) -> Result<Response<FlightPlanCancelResponse>, Status> {
+        region_info!("(cancel_flight_plan)[nl] entry.");
+
+        //
+        // TODO(R4) implement
+        //
+
+        let flight_plan_id = request.flight_plan_id;
+        Ok(Response::new(FlightPlanCancelResponse {
+            flight_plan_id,
+            cancelled: true,
+            result: None,
+        }))
+    }
+
+    fn amend_flight_plan(
+        &self,
+        request: FlightPlanRequest,
+    ) -> Result<Response<FlightPlanResponse>, Status> {
+        region_info!("(amend_flight_plan)[nl] entry.");
+
+        //
+        // TODO(R4) implement
+        //
+
+        let flight_plan_id = request.flight_plan_id;
+        Ok(Response::new(FlightPlanResponse {
+            flight_plan_id,
+            submitted: true,
+            result: None,
+            authority_reference: None,
+            pending: false,
+        }))
+    }
+
+    fn close_flight_plan(
+        &self,
+        request: FlightPlanCloseRequest,
+    ) -> Result<Response<FlightPlanCloseResponse>, Status> {
+        region_info!("(close_flight_plan)[nl] entry.");
+
+        //
+        // TODO(R4) implement
+        //
+
+        let flight_plan_id = request.flight_plan_id;
+        Ok(Response::new(FlightPlanCloseResponse {
+            flight_plan_id,
+            closed: true,
+            result: None,
+        }))
+    }
+
+    async fn poll_decision(
+        &self,
+        flight_plan_id: &str,
+        reference: Option<&str>,
+    ) -> Result<Option<AuthorityDecision>, RegionError> {
+        region_info!(
+            "(poll_decision)[nl] entry, flight plan {} ({:?}).",
+            flight_plan_id,
+            reference
+        );
+
+        //
+        // TODO(R4) implement
+        //
+
+        Ok(Some(AuthorityDecision {
+            granted: true,
+            message: None,
+            reference: reference.map(str::to_string),
+        }))
+    }
+
+    async fn acquire_restrictions(
+        &self,
+    ) -> Result<HashMap<String, RestrictionDetails>, RegionError> {
         //
         // TODO(R4): This is currently hardcoded. This should be replaced with a call to
         //  an API.
         //
         let Some(delta) = Duration::try_hours(1) else {
             region_error!("Failed to create duration");
-            return;
+            return Err(RegionError::Acquisition(
+                "failed to create duration".to_string(),
+            ));
         };
 
         let mut from_remote: HashMap<String, RestrictionDetails> = HashMap::new();
 
         let vertices = vec![
-            (4.7091866, 52.3827247),
-            (4.6507947, 52.3294647),
-            (4.7560834, 52.2572307),
-            (4.8234058, 52.3214912),
-            (4.7091866, 52.3827247),
+            (52.3827247, 4.7091866),
+            (52.3294647, 4.6507947),
+            (52.2572307, 4.7560834),
+            (52.3214912, 4.8234058),
+            (52.3827247, 4.7091866),
         ];
 
         from_remote.insert(
-            "ARROW-NL-NOFLY-SCHIPHOL".to_string(),
+            identifiers::from_key(&self.region, SourceKind::Static, "NOFLY-SCHIPHOL"),
             RestrictionDetails {
-                vertices: vertices
-                    .into_iter()
-                    .map(|(x, y)| Coordinates {
-                        latitude: y,
-                        longitude: x,
-                    })
-                    .collect(),
+                vertices: static_vertices(rd::CoordinateSystem::Wgs84, &vertices)?,
                 timestamp_end: None,
                 timestamp_start: None,
                 altitude_meters_min: 0.0,
                 altitude_meters_max: 2000.0,
                 zone_type: ZoneType::Restriction,
+                notam_id: None,
             },
         );
 
+        // Municipal event zones are published in RD New
         let vertices = vec![
-            (4.8822724, 52.3688393),
-            (4.8832170, 52.3781666),
-            (4.9007345, 52.3777998),
-            (4.9001335, 52.3680532),
-            (4.8822724, 52.3688393),
+            (120_610.97, 486_892.65),
+            (120_682.52, 487_929.99),
+            (121_875.03, 487_881.02),
+            (121_826.82, 486_796.86),
+            (120_610.97, 486_892.65),
         ];
 
         from_remote.insert(
-            "ARROW-NL-TFR-PALEIS".to_string(),
+            identifiers::from_key(&self.region, SourceKind::Static, "TFR-PALEIS"),
             RestrictionDetails {
-                vertices: vertices
-                    .into_iter()
-                    .map(|(longitude, latitude)| Coordinates {
-                        latitude,
-                        longitude,
-                    })
-                    .collect(),
+                vertices: static_vertices(rd::CoordinateSystem::RdNew, &vertices)?,
                 timestamp_end: Some(Utc::now() + delta),
                 timestamp_start: Some(Utc::now()),
                 altitude_meters_min: 0.0,
                 altitude_meters_max: 2000.0,
                 zone_type: ZoneType::Restriction,
+                notam_id: None,
             },
         );
 
         // HOORN
         let vertices = vec![
-            (5.0232724, 52.6317085),
-            (5.1069102, 52.6347298),
-            (5.1036471, 52.6459798),
-            (5.1227104, 52.6501458),
-            (5.0948883, 52.6829387),
-            (5.0306572, 52.6710736),
-            (5.0358094, 52.6534782),
-            (5.0102200, 52.6393135),
-            (5.0229289, 52.6317085),
-            (5.0232724, 52.6317085),
+            (52.6317085, 5.0232724),
+            (52.6347298, 5.1069102),
+            (52.6459798, 5.1036471),
+            (52.6501458, 5.1227104),
+            (52.6829387, 5.0948883),
+            (52.6710736, 5.0306572),
+            (52.6534782, 5.0358094),
+            (52.6393135, 5.0102200),
+            (52.6317085, 5.0229289),
+            (52.6317085, 5.0232724),
         ];
 
         from_remote.insert(
-            "ARROW-NL-NOFLY-HOORN".to_string(),
+            identifiers::from_key(&self.region, SourceKind::Static, "NOFLY-HOORN"),
             RestrictionDetails {
-                vertices: vertices
-                    .into_iter()
-                    .map(|(longitude, latitude)| Coordinates {
-                        latitude,
-                        longitude,
-                    })
-                    .collect(),
+                vertices: static_vertices(rd::CoordinateSystem::Wgs84, &vertices)?,
                 timestamp_end: None,
                 timestamp_start: None,
                 altitude_meters_min: 0.0,
                 altitude_meters_max: 2000.0,
                 zone_type: ZoneType::Restriction,
+                notam_id: None,
             },
         );
 
         //
         // END HARDCODE
         //
-        restrictions.retain(|k, _| from_remote.contains_key(k));
-        for (label, details) in from_remote.into_iter() {
-            restrictions.insert(label, details);
-        }
+        Ok(from_remote)
     }
 
-    async fn acquire_waypoints(&self, waypoints: &mut HashMap<String, Coordinates>) {
+    async fn acquire_waypoints(&self) -> Result<Vec<(String, WaypointDetails)>, RegionError> {
         //
         // TODO(R4): This is currently hardcoded. This should be replaced with a call to an API
         //
-
-        // Amsterdam Drone Lab Waypoints
-        let from_remote: Vec<(f64, f64)> = vec![
-            // Valid waypoints
-            (4.9160, 52.3745),
-            (4.9156, 52.3749),
-            (4.9153, 52.3752),
-            (4.9156, 52.3753),
-            // Waypoint within the TFR
-            (4.9161, 52.3750),
-            // Others
-            (5.2021015, 52.9635294),
-            (5.2570586, 52.8691297),
-            (5.3724685, 52.7878146),
-            (5.4164342, 52.6780473),
-            (5.3559814, 52.6047156),
-            (5.2735457, 52.5513062),
-            (5.2021015, 52.4944873),
-            (5.1361530, 52.4174978),
-            (5.0784480, 52.3638604),
-            (5.0234941, 52.2858024),
-            (4.9699542, 52.2542885),
-            (4.9095214, 52.2742499),
-            (4.9075041, 52.3103678),
-            (5.2844417, 52.6672220),
-            (5.2133705, 52.6122211),
-            (5.1309348, 52.5930378),
-            (5.0732299, 52.5796880),
-            (5.0057699, 52.5550636),
-            (4.9314804, 52.5341847),
-            (4.8697511, 52.5015937),
-            (4.8566987, 52.4677236),
-            (4.8148570, 52.5329316),
-            (4.7921165, 52.5742635),
-            (4.8375440, 52.6038816),
-            (4.8918141, 52.6330629),
-            (4.9550148, 52.6701368),
-            (5.0051632, 52.6959449),
-            (5.0553115, 52.7092593),
-            (5.1189528, 52.7421118),
-            (5.1892695, 52.8060822),
-            (5.5227080, 52.7703701),
-            (5.5213340, 52.7080113),
-            (5.5309515, 52.6380634),
-            (5.5007251, 52.5888665),
-            (5.3784456, 52.5270836),
-            (5.3399756, 52.4727431),
-            (5.3866891, 52.4258727),
-            (5.4457680, 52.3630218),
-            (5.6862054, 52.4091213),
-            (5.7865021, 52.4568462),
-            (5.8538245, 52.5496361),
-            (5.0791343, 53.0230443),
-            (4.9870812, 53.0519455),
-            (4.9280023, 53.1030944),
-            (4.9664723, 53.1739423),
-            (5.0502818, 53.2380987),
-            (5.2357621, 53.3103651),
-            (5.3621634, 53.3513711),
-            (5.5023041, 53.3857856),
-            (5.6026008, 53.4005262),
-            (5.7606025, 53.4136247),
-            (4.8892276, 52.3661141),
-            (4.8931777, 52.3793717),
-            (4.8784938, 52.3742892),
-            (4.9035680, 52.3718788),
-            (4.7266651, 52.2700483),
-            (4.8111616, 52.3481340),
-            (4.6383902, 52.3676339),
-            (4.8558143, 52.2631146),
-            (4.8805450, 52.3166644),
-            (4.7946745, 52.2286406),
-            (4.6164074, 52.3109975),
-            (4.7022778, 52.4068174),
-            (5.0968408, 52.6132634),
-            (5.1521414, 52.6491043),
-            (5.1342804, 52.6751331),
-            (5.0985582, 52.6969853),
-            (5.0456620, 52.6903267),
-            (5.0034138, 52.6645152),
-            (4.9955137, 52.6330629),
-            (5.0463490, 52.6134719),
-        ];
-
-        let from_remote: HashMap<String, Coordinates> = from_remote
+        let from_remote: Vec<(String, WaypointDetails)> = WAYPOINTS
             .iter()
-            .enumerate()
-            .map(|(i, (longitude, latitude))| {
+            .map(|waypoint| {
+                let location = waypoint_location(waypoint);
                 (
-                    format!("ARROW-WEG-{}", i),
-                    Coordinates {
-                        latitude: *latitude,
-                        longitude: *longitude,
-                    },
+                    identifiers::from_location(&self.region, SourceKind::Static, &location),
+                    WaypointDetails::fix(location),
                 )
             })
             .collect();
@@ -279,11 +405,7 @@ impl RegionInterface for super::RegionImpl {
         //
         // END HARDCODE
         //
-
-        waypoints.retain(|k, _| from_remote.contains_key(k));
-        for (label, details) in from_remote.into_iter() {
-            waypoints.insert(label, details);
-        }
+        Ok(from_remote)
     }
 }
 
@@ -341,17 +463,72 @@ mod tests {
         ut_info!("(test_request_flight_release)[nl] Success.");
     }
 
+    #[tokio::test]
+    async fn test_cancel_amend_close_flight_plan() {
+        crate::get_log_handle().await;
+        ut_info!("(test_cancel_amend_close_flight_plan)[nl] Start.");
+
+        let region = RegionImpl::default();
+        let result = region
+            .amend_flight_plan(FlightPlanRequest {
+                flight_plan_id: "FP-1".to_string(),
+                data: "".to_string(),
+            })
+            .unwrap()
+            .into_inner();
+        assert!(result.submitted);
+
+        let result = region
+            .cancel_flight_plan(FlightPlanCancelRequest {
+                flight_plan_id: "FP-1".to_string(),
+                reason: None,
+            })
+            .unwrap()
+            .into_inner();
+        assert!(result.cancelled);
+
+        let result = region
+            .close_flight_plan(FlightPlanCloseRequest {
+                flight_plan_id: "FP-1".to_string(),
+                landed_at: None,
+            })
+            .unwrap()
+            .into_inner();
+        assert!(result.closed);
+
+        ut_info!("(test_cancel_amend_close_flight_plan)[nl] Success.");
+    }
+
+    #[tokio::test]
+    async fn test_poll_decision() {
+        crate::get_log_handle().await;
+        ut_info!("(test_poll_decision)[nl] Start.");
+
+        let region = RegionImpl::default();
+        let decision = region.poll_decision("FP-1", Some("REF-1")).await.unwrap();
+        let decision = decision.unwrap();
+        assert!(decision.granted);
+        assert_eq!(decision.reference, Some("REF-1".to_string()));
+
+        ut_info!("(test_poll_decision)[nl] Success.");
+    }
+
     #[tokio::test]
     async fn test_acquire_restrictions() {
         crate::get_log_handle().await;
         ut_info!("(test_acquire_restrictions)[nl] Start.");
 
         let region = RegionImpl::default();
-        let mut cache = HashMap::<String, RestrictionDetails>::new();
-        region.acquire_restrictions(&mut cache).await;
+        let cache = region.acquire_restrictions().await.unwrap();
         ut_debug!("(test_acquire_restrictions)[nl] Cache content: {:?}", cache);
         assert!(cache.keys().len() > 0);
 
+        // The event zone is given in RD New
+        let paleis =
+            &cache[&identifiers::from_key(&region.region, SourceKind::Static, "TFR-PALEIS")];
+        assert!((paleis.vertices[0].latitude - 52.3688393).abs() < 1e-6);
+        assert!((paleis.vertices[0].longitude - 4.8822724).abs() < 1e-6);
+
         ut_info!("(test_acquire_restrictions)[nl] Success.");
     }
 
@@ -361,10 +538,60 @@ mod tests {
         ut_info!("(test_refresh_waypoints)[nl] Start.");
 
         let region = RegionImpl::default();
-        let mut cache = HashMap::<String, Coordinates>::new();
-        region.acquire_waypoints(&mut cache).await;
-        assert!(cache.keys().len() > 0);
+        let cache = region.acquire_waypoints().await.unwrap();
+        assert!(!cache.is_empty());
 
         ut_info!("(test_refresh_waypoints)[nl] Success.");
     }
+
+    #[tokio::test]
+    async fn test_legacy_identifiers() {
+        crate::get_log_handle().await;
+        ut_info!("(test_legacy_identifiers)[nl] Start.");
+
+        let region = RegionImpl::default();
+        let legacy = region.legacy_identifiers();
+        assert!(legacy.contains(&(
+            "ARROW-NL-NOFLY-SCHIPHOL".to_string(),
+            "ARROW-NL-STATIC-NOFLY-SCHIPHOL".to_string()
+        )));
+
+        // Every acquired item has a legacy identifier
+        let mut stable: Vec<String> = region
+            .acquire_restrictions()
+            .await
+            .unwrap()
+            .into_keys()
+            .collect();
+        stable.extend(
+            region
+                .acquire_waypoints()
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|(identifier, _)| identifier),
+        );
+
+        assert_eq!(stable.len(), legacy.len());
+        for identifier in stable {
+            assert!(legacy.iter().any(|(_, s)| *s == identifier));
+        }
+
+        ut_info!("(test_legacy_identifiers)[nl] Success.");
+    }
+
+    #[tokio::test]
+    async fn test_coordinate_converter() {
+        crate::get_log_handle().await;
+        ut_info!("(test_coordinate_converter)[nl] Start.");
+
+        let region = RegionImpl::default();
+        let convert = region.coordinate_converter("EPSG:28992").unwrap();
+        let amersfoort = convert(155_000.0, 463_000.0).unwrap();
+        assert!((amersfoort.latitude - 52.155_174_40).abs() < 1e-6);
+        assert!((amersfoort.longitude - 5.387_206_21).abs() < 1e-6);
+        assert!(convert(0.0, 0.0).is_err());
+
+        ut_info!("(test_coordinate_converter)[nl] Success.");
+    }
 }
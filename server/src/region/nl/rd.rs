@@ -0,0 +1,290 @@
+//! Conversion of Dutch Rijksdriehoekstelsel (RD New, EPSG:28992) coordinates
+//!
+//! Many Dutch government data sets (PDOK, municipal event zones) are
+//!  published in RD coordinates. These are converted to WGS84 with the
+//!  polynomial approximation by Schreutelkamp and Strang van Hees, accurate
+//!  to about a meter within the Netherlands. RDNAPTRANS grid corrections are
+//!  not applied.
+//!
+//! ETRS89 (EPSG:4258) coordinates are accepted as is: ETRS89 and WGS84
+//!  diverge by less than a meter in the Netherlands, within the accuracy of
+//!  the RD approximation.
+
+use std::fmt;
+use std::str::FromStr;
+use svc_gis_client_grpc::prelude::gis::Coordinates;
+
+/// RD coordinates of the reference point, the Onze Lieve Vrouwetoren in
+///  Amersfoort
+const X0: f64 = 155_000.0;
+const Y0: f64 = 463_000.0;
+
+/// WGS84 coordinates of the reference point
+const PHI0: f64 = 52.155_174_40;
+const LAM0: f64 = 5.387_206_21;
+
+/// Coefficients (p, q, K) for the latitude in arc seconds from RD
+const K_PQ: [(i32, i32, f64); 11] = [
+    (0, 1, 3235.65389),
+    (2, 0, -32.58297),
+    (0, 2, -0.24750),
+    (2, 1, -0.84978),
+    (0, 3, -0.06550),
+    (2, 2, -0.01709),
+    (1, 0, -0.00738),
+    (4, 0, 0.00530),
+    (2, 3, -0.00039),
+    (4, 1, 0.00033),
+    (1, 1, -0.00012),
+];
+
+/// Coefficients (p, q, L) for the longitude in arc seconds from RD
+const L_PQ: [(i32, i32, f64); 12] = [
+    (1, 0, 5260.52916),
+    (1, 1, 105.94684),
+    (1, 2, 2.45656),
+    (3, 0, -0.81885),
+    (1, 3, 0.05594),
+    (3, 1, -0.05607),
+    (0, 1, 0.01199),
+    (3, 2, -0.00256),
+    (1, 4, 0.00128),
+    (0, 2, 0.00022),
+    (2, 0, -0.00022),
+    (5, 0, 0.00026),
+];
+
+/// Coefficients (p, q, R) for the RD x coordinate from WGS84
+const R_PQ: [(i32, i32, f64); 9] = [
+    (0, 1, 190_094.945),
+    (1, 1, -11_832.228),
+    (2, 1, -114.221),
+    (0, 3, -32.391),
+    (1, 0, -0.705),
+    (3, 1, -2.340),
+    (1, 3, -0.608),
+    (0, 2, -0.008),
+    (2, 3, 0.148),
+];
+
+/// Coefficients (p, q, S) for the RD y coordinate from WGS84
+const S_PQ: [(i32, i32, f64); 10] = [
+    (1, 0, 309_056.544),
+    (0, 2, 3638.893),
+    (2, 0, 73.077),
+    (1, 2, -157.984),
+    (3, 0, 59.788),
+    (0, 1, 0.433),
+    (2, 2, -6.439),
+    (1, 1, -0.032),
+    (0, 4, 0.092),
+    (1, 4, -0.054),
+];
+
+/// Bounds of the RD grid in which the approximation is valid
+const X_MIN: f64 = -7_000.0;
+const X_MAX: f64 = 300_000.0;
+const Y_MIN: f64 = 289_000.0;
+const Y_MAX: f64 = 629_000.0;
+
+/// Errors converting coordinates
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum ConversionError {
+    /// The coordinates are outside of the area covered by RD
+    #[error("error: Coordinates ({0}, {1}) are outside of the RD grid.")]
+    OutOfBounds(f64, f64),
+
+    /// The coordinate system is not supported
+    #[error("error: Unsupported coordinate system: {0}.")]
+    UnsupportedSystem(String),
+}
+
+/// Coordinate systems accepted by the NL importers
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CoordinateSystem {
+    /// WGS84 latitude and longitude in degrees (EPSG:4326)
+    Wgs84,
+
+    /// ETRS89 latitude and longitude in degrees (EPSG:4258)
+    Etrs89,
+
+    /// Rijksdriehoekstelsel x and y in meters (EPSG:28992)
+    RdNew,
+}
+
+impl fmt::Display for CoordinateSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CoordinateSystem::Wgs84 => "EPSG:4326",
+            CoordinateSystem::Etrs89 => "EPSG:4258",
+            CoordinateSystem::RdNew => "EPSG:28992",
+        })
+    }
+}
+
+impl FromStr for CoordinateSystem {
+    type Err = ConversionError;
+
+    /// Parses an EPSG code such as `EPSG:28992`, or a common name
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_uppercase();
+        match name.strip_prefix("EPSG:").unwrap_or(&name) {
+            "4326" | "WGS84" => Ok(CoordinateSystem::Wgs84),
+            "4258" | "ETRS89" => Ok(CoordinateSystem::Etrs89),
+            "28992" | "RD" | "RDNEW" | "RD_NEW" => Ok(CoordinateSystem::RdNew),
+            _ => Err(ConversionError::UnsupportedSystem(s.to_string())),
+        }
+    }
+}
+
+impl CoordinateSystem {
+    /// Converts a point of this system to WGS84
+    ///
+    /// Points are given in the axis order of the system: latitude and
+    ///  longitude for the geographic systems, x (east) and y (north) for RD.
+    pub fn to_wgs84(&self, first: f64, second: f64) -> Result<Coordinates, ConversionError> {
+        match self {
+            CoordinateSystem::Wgs84 | CoordinateSystem::Etrs89 => Ok(Coordinates {
+                latitude: first,
+                longitude: second,
+            }),
+            CoordinateSystem::RdNew => rd_to_wgs84(first, second),
+        }
+    }
+}
+
+fn polynomial(coefficients: &[(i32, i32, f64)], a: f64, b: f64) -> f64 {
+    coefficients
+        .iter()
+        .map(|(p, q, k)| k * a.powi(*p) * b.powi(*q))
+        .sum()
+}
+
+/// Converts RD New coordinates in meters to WGS84
+pub fn rd_to_wgs84(x: f64, y: f64) -> Result<Coordinates, ConversionError> {
+    if !(X_MIN..=X_MAX).contains(&x) || !(Y_MIN..=Y_MAX).contains(&y) {
+        return Err(ConversionError::OutOfBounds(x, y));
+    }
+
+    let dx = (x - X0) * 1e-5;
+    let dy = (y - Y0) * 1e-5;
+
+    Ok(Coordinates {
+        latitude: PHI0 + polynomial(&K_PQ, dx, dy) / 3600.0,
+        longitude: LAM0 + polynomial(&L_PQ, dx, dy) / 3600.0,
+    })
+}
+
+/// Converts WGS84 coordinates to RD New, returning x and y in meters
+pub fn wgs84_to_rd(coordinates: &Coordinates) -> Result<(f64, f64), ConversionError> {
+    let d_phi = 0.36 * (coordinates.latitude - PHI0);
+    let d_lam = 0.36 * (coordinates.longitude - LAM0);
+
+    let x = X0 + polynomial(&R_PQ, d_phi, d_lam);
+    let y = Y0 + polynomial(&S_PQ, d_phi, d_lam);
+    if !(X_MIN..=X_MAX).contains(&x) || !(Y_MIN..=Y_MAX).contains(&y) {
+        return Err(ConversionError::OutOfBounds(
+            coordinates.latitude,
+            coordinates.longitude,
+        ));
+    }
+
+    Ok((x, y))
+}
+
+/// Converts a list of points in the provided system to WGS84 vertices
+pub fn to_vertices(
+    system: CoordinateSystem,
+    points: &[(f64, f64)],
+) -> Result<Vec<Coordinates>, ConversionError> {
+    points
+        .iter()
+        .map(|(first, second)| system.to_wgs84(*first, *second))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Reference points: name, RD x, RD y, WGS84 latitude and longitude
+    const REFERENCE_POINTS: [(&str, f64, f64, f64, f64); 2] = [
+        ("Amersfoort", 155_000.0, 463_000.0, 52.15517440, 5.38720621),
+        (
+            "Westertoren",
+            120_700.723,
+            487_525.501,
+            52.37453253,
+            4.88352559,
+        ),
+    ];
+
+    #[tokio::test]
+    async fn test_rd_to_wgs84() {
+        crate::get_log_handle().await;
+        ut_info!("(test_rd_to_wgs84) Start.");
+
+        for (name, x, y, latitude, longitude) in REFERENCE_POINTS {
+            let result = rd_to_wgs84(x, y).unwrap();
+            ut_debug!("(test_rd_to_wgs84) {}: {:?}", name, result);
+            // 1e-6 degrees is about 0.1 meter
+            assert!((result.latitude - latitude).abs() < 1e-6, "{name}");
+            assert!((result.longitude - longitude).abs() < 1e-6, "{name}");
+        }
+
+        assert_eq!(
+            rd_to_wgs84(0.0, 0.0),
+            Err(ConversionError::OutOfBounds(0.0, 0.0))
+        );
+
+        ut_info!("(test_rd_to_wgs84) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_wgs84_to_rd() {
+        crate::get_log_handle().await;
+        ut_info!("(test_wgs84_to_rd) Start.");
+
+        for (name, x, y, latitude, longitude) in REFERENCE_POINTS {
+            let (rd_x, rd_y) = wgs84_to_rd(&Coordinates {
+                latitude,
+                longitude,
+            })
+            .unwrap();
+            ut_debug!("(test_wgs84_to_rd) {}: ({}, {})", name, rd_x, rd_y);
+            assert!((rd_x - x).abs() < 0.01, "{name}");
+            assert!((rd_y - y).abs() < 0.01, "{name}");
+        }
+
+        ut_info!("(test_wgs84_to_rd) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_coordinate_system() {
+        crate::get_log_handle().await;
+        ut_info!("(test_coordinate_system) Start.");
+
+        assert_eq!(
+            "EPSG:28992".parse::<CoordinateSystem>(),
+            Ok(CoordinateSystem::RdNew)
+        );
+        assert_eq!(
+            "etrs89".parse::<CoordinateSystem>(),
+            Ok(CoordinateSystem::Etrs89)
+        );
+        assert!("EPSG:3857".parse::<CoordinateSystem>().is_err());
+
+        let vertices = to_vertices(
+            CoordinateSystem::RdNew,
+            &[(120_700.723, 487_525.501), (155_000.0, 463_000.0)],
+        )
+        .unwrap();
+        assert_eq!(vertices.len(), 2);
+        assert!((vertices[1].latitude - 52.15517440).abs() < 1e-9);
+
+        let vertices = to_vertices(CoordinateSystem::Etrs89, &[(52.3745, 4.9160)]).unwrap();
+        assert_eq!(vertices[0].longitude, 4.9160);
+
+        ut_info!("(test_coordinate_system) Success.");
+    }
+}
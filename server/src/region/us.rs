@@ -1,16 +1,41 @@
 //! Region implementation for the United States (US)
 
 use crate::grpc::server::{
-    FlightPlanRequest, FlightPlanResponse, FlightReleaseRequest, FlightReleaseResponse,
+    FlightPlanCancelRequest, FlightPlanCancelResponse, FlightPlanCloseRequest,
+    FlightPlanCloseResponse, FlightPlanRequest, FlightPlanResponse, FlightReleaseRequest,
+    FlightReleaseResponse,
 };
 
+use crate::identifiers;
 use crate::region::RegionInterface;
-use crate::region::RestrictionDetails;
+use crate::region::{AuthorityDecision, RegionError, RestrictionDetails, WaypointDetails};
+use crate::restrictions::merge::SourceKind;
 use chrono::{Duration, Utc};
 use std::collections::HashMap;
 use svc_gis_client_grpc::prelude::gis::{Coordinates, ZoneType};
 use tonic::{Request, Response, Status};
 
+/// West TX waypoints, in the order they were published under index based
+///  identifiers
+const WAYPOINTS: &[(f64, f64)] = &[
+    // Ideal waypoint around hardcoded flight restriction
+    (30.9311, -104.0428),
+    // waypoint within the hardcoded flight restriction
+    (30.9308, -104.0412),
+];
+
+/// Source keys of the hardcoded zones, which were published as
+///  `ARROW-USA-{key}`
+const ZONE_KEYS: &[&str] = &["TFR-ZONE", "NOFLY-ZONE"];
+
+/// Location of a hardcoded waypoint
+fn waypoint_location((longitude, latitude): &(f64, f64)) -> Coordinates {
+    Coordinates {
+        latitude: *latitude,
+        longitude: *longitude,
+    }
+}
+
 impl Default for super::RegionImpl {
     fn default() -> Self {
         Self {
@@ -26,6 +51,28 @@ impl RegionInterface for super::RegionImpl {
         &self.region
     }
 
+    fn legacy_identifiers(&self) -> Vec<(String, String)> {
+        let zones = ZONE_KEYS.iter().map(|key| {
+            (
+                format!("ARROW-USA-{}", key),
+                identifiers::from_key(&self.region, SourceKind::Static, key),
+            )
+        });
+
+        let waypoints = WAYPOINTS.iter().enumerate().map(|(i, waypoint)| {
+            (
+                format!("ARROW-WEG-{}", i),
+                identifiers::from_location(
+                    &self.region,
+                    SourceKind::Static,
+                    &waypoint_location(waypoint),
+                ),
+            )
+        });
+
+        zones.chain(waypoints).collect()
+    }
+
     fn submit_flight_plan(
         &self,
         request: FlightPlanRequest,
@@ -37,6 +84,8 @@ impl RegionInterface for super::RegionImpl {
             flight_plan_id,
             submitted: true,
             result: None,
+            authority_reference: None,
+            pending: false,
         }))
     }
 
@@ -51,10 +100,75 @@ impl RegionInterface for super::RegionImpl {
             flight_plan_id,
             released: true,
             result: None,
+            release_token: None,
+        }))
+    }
+
+    fn cancel_flight_plan(
+        &self,
+        request: FlightPlanCancelRequest,
+    ) -> Result<Response<FlightPlanCancelResponse>, Status> {
+        region_info!("(cancel_flight_plan)[us] entry.");
+        // TODO(R4) implement
+        let flight_plan_id = request.flight_plan_id;
+        Ok(Response::new(FlightPlanCancelResponse {
+            flight_plan_id,
+            cancelled: true,
+            result: None,
+        }))
+    }
+
+    fn amend_flight_plan(
+        &self,
+        request: FlightPlanRequest,
+    ) -> Result<Response<FlightPlanResponse>, Status> {
+        region_info!("(amend_flight_plan)[us] entry.");
+        // TODO(R4) implement
+        let flight_plan_id = request.flight_plan_id;
+        Ok(Response::new(FlightPlanResponse {
+            flight_plan_id,
+            submitted: true,
+            result: None,
+            authority_reference: None,
+            pending: false,
         }))
     }
 
-    async fn acquire_restrictions(&self, restrictions: &mut HashMap<String, RestrictionDetails>) {
+    fn close_flight_plan(
+        &self,
+        request: FlightPlanCloseRequest,
+    ) -> Result<Response<FlightPlanCloseResponse>, Status> {
+        region_info!("(close_flight_plan)[us] entry.");
+        // TODO(R4) implement
+        let flight_plan_id = request.flight_plan_id;
+        Ok(Response::new(FlightPlanCloseResponse {
+            flight_plan_id,
+            closed: true,
+            result: None,
+        }))
+    }
+
+    async fn poll_decision(
+        &self,
+        flight_plan_id: &str,
+        reference: Option<&str>,
+    ) -> Result<Option<AuthorityDecision>, RegionError> {
+        region_info!(
+            "(poll_decision)[us] entry, flight plan {} ({:?}).",
+            flight_plan_id,
+            reference
+        );
+        // TODO(R4) implement
+        Ok(Some(AuthorityDecision {
+            granted: true,
+            message: None,
+            reference: reference.map(str::to_string),
+        }))
+    }
+
+    async fn acquire_restrictions(
+        &self,
+    ) -> Result<HashMap<String, RestrictionDetails>, RegionError> {
         //
         // TODO(R4): This is currently hardcoded. This should be replaced with a call to
         //  an API.
@@ -71,11 +185,13 @@ impl RegionInterface for super::RegionImpl {
 
         let Some(delta) = Duration::try_hours(1) else {
             region_error!("Failed to create duration");
-            return;
+            return Err(RegionError::Acquisition(
+                "failed to create duration".to_string(),
+            ));
         };
 
         from_remote.insert(
-            "ARROW-USA-TFR-ZONE".to_string(),
+            identifiers::from_key(&self.region, SourceKind::Static, "TFR-ZONE"),
             RestrictionDetails {
                 vertices: vertices
                     .into_iter()
@@ -89,6 +205,7 @@ impl RegionInterface for super::RegionImpl {
                 altitude_meters_min: 0.0,
                 altitude_meters_max: 2000.0,
                 zone_type: ZoneType::Restriction,
+                notam_id: None,
             },
         );
 
@@ -105,7 +222,7 @@ impl RegionInterface for super::RegionImpl {
         ];
 
         from_remote.insert(
-            "ARROW-USA-NOFLY-ZONE".to_string(),
+            identifiers::from_key(&self.region, SourceKind::Static, "NOFLY-ZONE"),
             RestrictionDetails {
                 vertices: vertices
                     .into_iter()
@@ -121,42 +238,28 @@ impl RegionInterface for super::RegionImpl {
                 altitude_meters_min: 0.0,
                 altitude_meters_max: 200.0,
                 zone_type: ZoneType::Restriction,
+                notam_id: None,
             },
         );
 
         //
         // END HARDCODE
         //
-
-        restrictions.retain(|k, _| from_remote.contains_key(k));
-        for (label, details) in from_remote.into_iter() {
-            restrictions.insert(label, details);
-        }
+        Ok(from_remote)
     }
 
-    async fn acquire_waypoints(&self, waypoints: &mut HashMap<String, Coordinates>) {
+    async fn acquire_waypoints(&self) -> Result<Vec<(String, WaypointDetails)>, RegionError> {
         //
         // TODO(R4): This is currently hardcoded. This should be replaced with a call to an API
         //
 
-        // West TX
-        let from_remote: Vec<(f64, f64)> = vec![
-            // Ideal waypoint around hardcoded flight restriction
-            (30.9311, -104.0428),
-            // waypoint within the hardcoded flight restriction
-            (30.9308, -104.0412),
-        ];
-
-        let from_remote: HashMap<String, Coordinates> = from_remote
-            .into_iter()
-            .enumerate()
-            .map(|(i, (longitude, latitude))| {
+        let from_remote: Vec<(String, WaypointDetails)> = WAYPOINTS
+            .iter()
+            .map(|waypoint| {
+                let location = waypoint_location(waypoint);
                 (
-                    format!("ARROW-WEG-{}", i),
-                    Coordinates {
-                        latitude,
-                        longitude,
-                    },
+                    identifiers::from_location(&self.region, SourceKind::Static, &location),
+                    WaypointDetails::fix(location),
                 )
             })
             .collect();
@@ -164,11 +267,7 @@ impl RegionInterface for super::RegionImpl {
         //
         // END HARDCODE
         //
-
-        waypoints.retain(|k, _| from_remote.contains_key(k));
-        for (label, details) in from_remote.into_iter() {
-            waypoints.insert(label, details);
-        }
+        Ok(from_remote)
     }
 }
 
@@ -226,14 +325,63 @@ mod tests {
         ut_info!("(test_request_flight_release)[us] Success.");
     }
 
+    #[tokio::test]
+    async fn test_cancel_amend_close_flight_plan() {
+        crate::get_log_handle().await;
+        ut_info!("(test_cancel_amend_close_flight_plan)[us] Start.");
+
+        let region = RegionImpl::default();
+        let result = region
+            .amend_flight_plan(FlightPlanRequest {
+                flight_plan_id: "FP-1".to_string(),
+                data: "".to_string(),
+            })
+            .unwrap()
+            .into_inner();
+        assert!(result.submitted);
+
+        let result = region
+            .cancel_flight_plan(FlightPlanCancelRequest {
+                flight_plan_id: "FP-1".to_string(),
+                reason: None,
+            })
+            .unwrap()
+            .into_inner();
+        assert!(result.cancelled);
+
+        let result = region
+            .close_flight_plan(FlightPlanCloseRequest {
+                flight_plan_id: "FP-1".to_string(),
+                landed_at: None,
+            })
+            .unwrap()
+            .into_inner();
+        assert!(result.closed);
+
+        ut_info!("(test_cancel_amend_close_flight_plan)[us] Success.");
+    }
+
+    #[tokio::test]
+    async fn test_poll_decision() {
+        crate::get_log_handle().await;
+        ut_info!("(test_poll_decision)[us] Start.");
+
+        let region = RegionImpl::default();
+        let decision = region.poll_decision("FP-1", Some("REF-1")).await.unwrap();
+        let decision = decision.unwrap();
+        assert!(decision.granted);
+        assert_eq!(decision.reference, Some("REF-1".to_string()));
+
+        ut_info!("(test_poll_decision)[us] Success.");
+    }
+
     #[tokio::test]
     async fn test_acquire_restrictions() {
         crate::get_log_handle().await;
         ut_info!("(test_acquire_restrictions)[us] Start.");
 
         let region = RegionImpl::default();
-        let mut cache = HashMap::<String, RestrictionDetails>::new();
-        region.acquire_restrictions(&mut cache).await;
+        let cache = region.acquire_restrictions().await.unwrap();
         ut_debug!("(test_acquire_restrictions)[us] Cache content: {:?}", cache);
         assert!(cache.keys().len() > 0);
 
@@ -246,9 +394,8 @@ mod tests {
         ut_info!("(test_refresh_waypoints)[us] Start.");
 
         let region = RegionImpl::default();
-        let mut cache = HashMap::<String, Coordinates>::new();
-        region.acquire_waypoints(&mut cache).await;
-        assert!(cache.keys().len() > 0);
+        let cache = region.acquire_waypoints().await.unwrap();
+        assert!(!cache.is_empty());
 
         ut_info!("(test_refresh_waypoints)[us] Success.");
     }
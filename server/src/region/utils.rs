@@ -1,6 +1,430 @@
 //! Region utility functions
 
+use svc_gis_client_grpc::prelude::gis::Coordinates;
+
+/// Serde helpers for vectors of [`gis::Coordinates`](svc_gis_client_grpc::prelude::gis::Coordinates)
+///
+/// The GIS client types do not implement serde, so the vertices are
+///  (de)serialized through a local mirror struct.
+pub mod coordinates_serde {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use svc_gis_client_grpc::prelude::gis::Coordinates;
+
+    #[derive(Serialize, Deserialize)]
+    struct Point {
+        latitude: f64,
+        longitude: f64,
+    }
+
+    /// Serialize a list of coordinates
+    pub fn serialize<S>(vertices: &[Coordinates], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        vertices
+            .iter()
+            .map(|c| Point {
+                latitude: c.latitude,
+                longitude: c.longitude,
+            })
+            .collect::<Vec<Point>>()
+            .serialize(serializer)
+    }
+
+    /// Deserialize a list of coordinates
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<Coordinates>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Vec::<Point>::deserialize(deserializer)?
+            .into_iter()
+            .map(|p| Coordinates {
+                latitude: p.latitude,
+                longitude: p.longitude,
+            })
+            .collect())
+    }
+}
+
+/// Serde helpers for a single [`gis::Coordinates`](svc_gis_client_grpc::prelude::gis::Coordinates)
+pub mod point_serde {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use svc_gis_client_grpc::prelude::gis::Coordinates;
+
+    #[derive(Serialize, Deserialize)]
+    struct Point {
+        latitude: f64,
+        longitude: f64,
+    }
+
+    /// Serialize coordinates
+    pub fn serialize<S>(location: &Coordinates, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        Point {
+            latitude: location.latitude,
+            longitude: location.longitude,
+        }
+        .serialize(serializer)
+    }
+
+    /// Deserialize coordinates
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Coordinates, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let point = Point::deserialize(deserializer)?;
+        Ok(Coordinates {
+            latitude: point.latitude,
+            longitude: point.longitude,
+        })
+    }
+}
+
+/// Serde helpers for [`gis::ZoneType`](svc_gis_client_grpc::prelude::gis::ZoneType)
+///
+/// Zone types are stored by their protobuf name so stored data survives
+///  renumbering of the enum.
+pub mod zone_type_serde {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use svc_gis_client_grpc::prelude::gis::ZoneType;
+
+    /// Serialize a zone type
+    pub fn serialize<S>(zone_type: &ZoneType, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(zone_type.as_str_name())
+    }
+
+    /// Deserialize a zone type
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<ZoneType, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        ZoneType::from_str_name(&name)
+            .ok_or_else(|| D::Error::custom(format!("unknown zone type: {name}")))
+    }
+}
+
+/// Mean earth radius in meters
+pub const EARTH_RADIUS_METERS: f64 = 6_371_000.0;
+
+/// A point in a local planar frame, in meters east (x) and north (y)
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LocalPoint {
+    /// Meters east of the origin
+    pub x: f64,
+
+    /// Meters north of the origin
+    pub y: f64,
+}
+
+/// Equirectangular projection around an origin
+///
+/// Accurate enough for geometries spanning a few tens of kilometers, which
+///  covers the restriction zones handled by this service.
+#[derive(Debug, Copy, Clone)]
+pub struct LocalProjection {
+    latitude: f64,
+    longitude: f64,
+    cos_latitude: f64,
+}
+
+impl LocalProjection {
+    /// Create a projection centered on the provided origin
+    pub fn new(origin: &Coordinates) -> Self {
+        Self {
+            latitude: origin.latitude,
+            longitude: origin.longitude,
+            cos_latitude: origin.latitude.to_radians().cos(),
+        }
+    }
+
+    /// Project coordinates into the local frame
+    pub fn to_local(&self, coordinates: &Coordinates) -> LocalPoint {
+        LocalPoint {
+            x: (coordinates.longitude - self.longitude).to_radians()
+                * self.cos_latitude
+                * EARTH_RADIUS_METERS,
+            y: (coordinates.latitude - self.latitude).to_radians() * EARTH_RADIUS_METERS,
+        }
+    }
+
+    /// Convert a point of the local frame back into coordinates
+    pub fn to_coordinates(&self, point: &LocalPoint) -> Coordinates {
+        Coordinates {
+            latitude: self.latitude + (point.y / EARTH_RADIUS_METERS).to_degrees(),
+            longitude: self.longitude
+                + (point.x / (EARTH_RADIUS_METERS * self.cos_latitude)).to_degrees(),
+        }
+    }
+}
+
+/// Returns the vertices of a polygon without the closing vertex, if any
+pub fn open_ring(vertices: &[Coordinates]) -> &[Coordinates] {
+    match vertices {
+        [first, .., last] if vertices.len() > 1 && first == last => &vertices[..vertices.len() - 1],
+        _ => vertices,
+    }
+}
+
+/// Twice the signed area of a polygon, positive for counter-clockwise rings
+pub fn signed_area_2x(points: &[LocalPoint]) -> f64 {
+    (0..points.len())
+        .map(|i| {
+            let a = points[i];
+            let b = points[(i + 1) % points.len()];
+            a.x * b.y - b.x * a.y
+        })
+        .sum()
+}
+
+/// Tolerance in square meters below which points are considered collinear
+const COLLINEAR_EPSILON: f64 = 1e-6;
+
+/// Cross product of `b - a` and `c - a`, positive if `c` lies left of `a -> b`
+pub fn cross(a: &LocalPoint, b: &LocalPoint, c: &LocalPoint) -> f64 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+/// Distance in meters from `p` to the segment `a -> b`
+pub fn segment_distance(p: &LocalPoint, a: &LocalPoint, b: &LocalPoint) -> f64 {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let length_2 = dx * dx + dy * dy;
+    let t = match length_2 > 0.0 {
+        true => (((p.x - a.x) * dx + (p.y - a.y) * dy) / length_2).clamp(0.0, 1.0),
+        false => 0.0,
+    };
+
+    ((p.x - a.x - t * dx).powi(2) + (p.y - a.y - t * dy).powi(2)).sqrt()
+}
+
+/// Returns true if `p` lies within the bounding box of `a` and `b`
+fn in_bounds(p: &LocalPoint, a: &LocalPoint, b: &LocalPoint) -> bool {
+    p.x >= a.x.min(b.x) - COLLINEAR_EPSILON
+        && p.x <= a.x.max(b.x) + COLLINEAR_EPSILON
+        && p.y >= a.y.min(b.y) - COLLINEAR_EPSILON
+        && p.y <= a.y.max(b.y) + COLLINEAR_EPSILON
+}
+
+/// Returns true if the segments `a -> b` and `c -> d` cross or touch
+pub fn segments_intersect(a: &LocalPoint, b: &LocalPoint, c: &LocalPoint, d: &LocalPoint) -> bool {
+    let d1 = cross(c, d, a);
+    let d2 = cross(c, d, b);
+    let d3 = cross(a, b, c);
+    let d4 = cross(a, b, d);
+
+    if ((d1 > COLLINEAR_EPSILON && d2 < -COLLINEAR_EPSILON)
+        || (d1 < -COLLINEAR_EPSILON && d2 > COLLINEAR_EPSILON))
+        && ((d3 > COLLINEAR_EPSILON && d4 < -COLLINEAR_EPSILON)
+            || (d3 < -COLLINEAR_EPSILON && d4 > COLLINEAR_EPSILON))
+    {
+        return true;
+    }
+
+    (d1.abs() <= COLLINEAR_EPSILON && in_bounds(a, c, d))
+        || (d2.abs() <= COLLINEAR_EPSILON && in_bounds(b, c, d))
+        || (d3.abs() <= COLLINEAR_EPSILON && in_bounds(c, a, b))
+        || (d4.abs() <= COLLINEAR_EPSILON && in_bounds(d, a, b))
+}
+
+/// Returns true if `p` lies inside or on the boundary of the triangle `a, b, c`
+pub fn point_in_triangle(p: &LocalPoint, a: &LocalPoint, b: &LocalPoint, c: &LocalPoint) -> bool {
+    let d = [cross(a, b, p), cross(b, c, p), cross(c, a, p)];
+    let has_negative = d.iter().any(|v| *v < -COLLINEAR_EPSILON);
+    let has_positive = d.iter().any(|v| *v > COLLINEAR_EPSILON);
+    if has_negative && has_positive {
+        return false;
+    }
+
+    // Degenerate triangles only contain points between their vertices
+    p.x >= a.x.min(b.x).min(c.x) - COLLINEAR_EPSILON
+        && p.x <= a.x.max(b.x).max(c.x) + COLLINEAR_EPSILON
+        && p.y >= a.y.min(b.y).min(c.y) - COLLINEAR_EPSILON
+        && p.y <= a.y.max(b.y).max(c.y) + COLLINEAR_EPSILON
+}
+
+/// Returns true if `p` lies inside or within `tolerance` meters of the
+///  boundary of the polygon
+pub fn point_in_polygon(p: &LocalPoint, ring: &[LocalPoint], tolerance: f64) -> bool {
+    let count = ring.len();
+    let mut inside = false;
+    for i in 0..count {
+        let a = &ring[i];
+        let b = &ring[(i + 1) % count];
+        if segment_distance(p, a, b) <= tolerance {
+            return true;
+        }
+
+        if (a.y > p.y) != (b.y > p.y) && p.x < (b.x - a.x) * (p.y - a.y) / (b.y - a.y) + a.x {
+            inside = !inside;
+        }
+    }
+
+    inside
+}
+
+/// Returns the convex hull of the points, counter-clockwise
+pub fn convex_hull(points: &[LocalPoint]) -> Vec<LocalPoint> {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    sorted.dedup();
+    if sorted.len() < 3 {
+        return sorted;
+    }
+
+    let mut hull: Vec<LocalPoint> = vec![];
+    for pass in 0..2 {
+        let start = hull.len();
+        for p in sorted.iter() {
+            while hull.len() >= start + 2
+                && cross(&hull[hull.len() - 2], &hull[hull.len() - 1], p) <= 0.0
+            {
+                hull.pop();
+            }
+            hull.push(*p);
+        }
+
+        // The last point is the first point of the other half
+        hull.pop();
+        if pass == 0 {
+            sorted.reverse();
+        }
+    }
+
+    hull
+}
+
 #[cfg(test)]
 mod tests {
-    // use super::*;
+    use super::*;
+    use crate::region::RestrictionDetails;
+    use svc_gis_client_grpc::prelude::gis::ZoneType;
+
+    #[tokio::test]
+    async fn test_restriction_details_serde() {
+        crate::get_log_handle().await;
+        ut_info!("(test_restriction_details_serde) Start.");
+
+        let details = RestrictionDetails {
+            vertices: vec![
+                Coordinates {
+                    latitude: 52.3745,
+                    longitude: 4.9160,
+                },
+                Coordinates {
+                    latitude: 52.3749,
+                    longitude: 4.9156,
+                },
+            ],
+            timestamp_start: Some(chrono::Utc::now()),
+            timestamp_end: None,
+            zone_type: ZoneType::Restriction,
+            altitude_meters_max: 200.0,
+            altitude_meters_min: 0.0,
+            notam_id: Some("A1234/24".to_string()),
+        };
+
+        let json = serde_json::to_string(&details).unwrap();
+        ut_debug!("(test_restriction_details_serde) {}", json);
+        let result: RestrictionDetails = serde_json::from_str(&json).unwrap();
+        assert_eq!(result.vertices, details.vertices);
+        assert_eq!(result.timestamp_start, details.timestamp_start);
+        assert_eq!(result.zone_type, details.zone_type);
+        assert_eq!(result.notam_id, details.notam_id);
+
+        ut_info!("(test_restriction_details_serde) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_local_projection() {
+        crate::get_log_handle().await;
+        ut_info!("(test_local_projection) Start.");
+
+        let origin = Coordinates {
+            latitude: 52.3745,
+            longitude: 4.9160,
+        };
+        let projection = LocalProjection::new(&origin);
+        let point = Coordinates {
+            latitude: 52.3845,
+            longitude: 4.9360,
+        };
+
+        let local = projection.to_local(&point);
+        // 0.01 degree of latitude is about 1112 meters
+        assert!((local.y - 1111.95).abs() < 0.1);
+        assert!(local.x > 1300.0 && local.x < 1400.0);
+
+        let back = projection.to_coordinates(&local);
+        assert!((back.latitude - point.latitude).abs() < 1e-9);
+        assert!((back.longitude - point.longitude).abs() < 1e-9);
+
+        ut_info!("(test_local_projection) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_polygon_primitives() {
+        crate::get_log_handle().await;
+        ut_info!("(test_polygon_primitives) Start.");
+
+        let p = |x: f64, y: f64| LocalPoint { x, y };
+        let square = vec![p(0.0, 0.0), p(10.0, 0.0), p(10.0, 10.0), p(0.0, 10.0)];
+        assert!(signed_area_2x(&square) > 0.0);
+        assert!(point_in_polygon(&p(5.0, 5.0), &square, 0.0));
+        assert!(point_in_polygon(&p(10.0, 5.0), &square, 1e-6));
+        assert!(!point_in_polygon(&p(11.0, 5.0), &square, 0.5));
+        assert!(point_in_polygon(&p(10.4, 5.0), &square, 0.5));
+
+        assert!(segments_intersect(
+            &p(0.0, 0.0),
+            &p(10.0, 10.0),
+            &p(0.0, 10.0),
+            &p(10.0, 0.0)
+        ));
+        assert!(segments_intersect(
+            &p(0.0, 0.0),
+            &p(5.0, 0.0),
+            &p(5.0, 0.0),
+            &p(5.0, 5.0)
+        ));
+        assert!(!segments_intersect(
+            &p(0.0, 0.0),
+            &p(4.0, 0.0),
+            &p(5.0, 0.0),
+            &p(9.0, 0.0)
+        ));
+
+        assert!(point_in_triangle(
+            &p(1.0, 1.0),
+            &square[0],
+            &square[1],
+            &square[2]
+        ));
+        assert!(!point_in_triangle(
+            &p(1.0, 5.0),
+            &square[0],
+            &square[1],
+            &square[2]
+        ));
+        assert!(!point_in_triangle(
+            &p(20.0, 0.0),
+            &square[0],
+            &p(5.0, 0.0),
+            &square[1]
+        ));
+
+        let mut points = square.clone();
+        points.push(p(5.0, 5.0));
+        points.push(p(2.0, 8.0));
+        let hull = convex_hull(&points);
+        assert_eq!(hull.len(), 4);
+        assert!(signed_area_2x(&hull) > 0.0);
+        assert!((segment_distance(&p(5.0, 5.0), &square[0], &square[1]) - 5.0).abs() < 1e-9);
+
+        ut_info!("(test_polygon_primitives) Success.");
+    }
 }
@@ -0,0 +1,809 @@
+//! Preconditions and validity of flight releases
+//!
+//! A release is only requested from the authority for an accepted flight
+//!  plan that is about to depart: the request must fall within a window
+//!  around the planned departure, the credentials of the pilot and the
+//!  aircraft must be valid until the flight ends, and no restriction in
+//!  effect may intersect the route. These are read from the JSON data of the
+//!  flight plan, see [`FlightPlanData`].
+//!
+//! A granted release is valid for a limited time from the planned departure,
+//!  or from the release when it is granted after the departure, so a release
+//!  requested early in the window does not run out before the departure.
+//!  The [`ReleaseMonitor`]
+//!  expires flight plans that did not take off before their release ran
+//!  out, revoking their release, and publishes each expiry on the
+//!  flightplan exchange.
+//!
+//! Restrictions published after a release was granted are checked by the
+//!  monitor as well. Whenever the restrictions are refreshed, released and
+//!  active flight plans are checked against the restrictions that are new or
+//!  changed since the previous check. A released plan crossed by one goes
+//!  back to accepted and must be released again, while an active flight is
+//!  flagged. Either way the release is revoked and the restrictions are
+//!  published with the revocation.
+
+#[macro_use]
+pub mod macros;
+pub mod token;
+
+use crate::amqp::{self, FlightPlanEvent};
+use crate::flight_plans::{FlightPlanError, FlightPlanState, FlightPlanStore};
+use crate::region::{utils, RestrictionDetails};
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use svc_gis_client_grpc::prelude::gis;
+use tokio::sync::Mutex;
+
+/// Release checks and validity
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ReleaseConfig {
+    /// How many minutes before the planned departure a release can be
+    ///  requested
+    pub earliest_minutes_before_departure: u16,
+
+    /// How many minutes after the planned departure a release can still be
+    ///  requested
+    pub latest_minutes_after_departure: u16,
+
+    /// How many minutes a release stays valid without takeoff, after the
+    ///  planned departure or after the release if it is granted later
+    pub validity_minutes: u16,
+
+    /// Interval in seconds at which releases are checked for expiry
+    pub expiry_interval_seconds: u16,
+}
+
+impl Default for ReleaseConfig {
+    fn default() -> Self {
+        ReleaseConfig {
+            earliest_minutes_before_departure: 30,
+            latest_minutes_after_departure: 10,
+            validity_minutes: 15,
+            expiry_interval_seconds: 30,
+        }
+    }
+}
+
+/// A license or registration and the time it is valid until
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Credential {
+    /// Identifier of the credential
+    pub identifier: String,
+
+    /// When the credential stops being valid, if ever
+    #[serde(default)]
+    pub valid_until: Option<DateTime<Utc>>,
+}
+
+/// The parts of the flight plan data a release depends on
+///
+/// ```json
+/// {
+///     "departure": "2024-01-01T12:00:00Z",
+///     "arrival": "2024-01-01T12:30:00Z",
+///     "route": [{ "latitude": 52.37, "longitude": 4.88 }, ...],
+///     "pilot": { "identifier": "P-1", "valid_until": "2025-01-01T00:00:00Z" },
+///     "aircraft": { "identifier": "A-1", "valid_until": null }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct FlightPlanData {
+    /// Planned departure time
+    pub departure: DateTime<Utc>,
+
+    /// Planned arrival time, if known
+    #[serde(default)]
+    pub arrival: Option<DateTime<Utc>>,
+
+    /// Points of the route, in order
+    #[serde(with = "utils::coordinates_serde")]
+    pub route: Vec<gis::Coordinates>,
+
+    /// License of the pilot
+    pub pilot: Credential,
+
+    /// Registration of the aircraft
+    pub aircraft: Credential,
+}
+
+impl FlightPlanData {
+    /// Reads the JSON data of a flight plan
+    pub fn parse(data: &str) -> Result<Self, ReleaseError> {
+        let data: FlightPlanData =
+            serde_json::from_str(data).map_err(|e| ReleaseError::InvalidData(e.to_string()))?;
+
+        if data.route.is_empty() {
+            return Err(ReleaseError::InvalidData("the route is empty".to_string()));
+        }
+
+        Ok(data)
+    }
+
+    /// Returns when a release granted at the provided time runs out
+    pub fn release_end(&self, config: &ReleaseConfig, released_at: DateTime<Utc>) -> DateTime<Utc> {
+        released_at.max(self.departure) + Duration::minutes(config.validity_minutes as i64)
+    }
+
+    /// Returns the time the flight is expected to end, at the latest when a
+    ///  release requested now would run out
+    pub fn flight_end(&self, config: &ReleaseConfig, now: DateTime<Utc>) -> DateTime<Utc> {
+        self.arrival
+            .unwrap_or(self.departure)
+            .max(self.release_end(config, now))
+    }
+}
+
+/// Reasons a release can not be requested
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum ReleaseError {
+    /// The flight plan data can not be read
+    #[error("error: Invalid flight plan data: {0}.")]
+    InvalidData(String),
+
+    /// The request is too early or too late for the planned departure
+    #[error("error: A release can only be requested between {earliest} and {latest}.")]
+    OutsideWindow {
+        /// Earliest time a release can be requested
+        earliest: DateTime<Utc>,
+
+        /// Latest time a release can be requested
+        latest: DateTime<Utc>,
+    },
+
+    /// A credential expires before the flight ends
+    #[error("error: The {kind} credential {identifier} is not valid until the flight ends.")]
+    CredentialExpired {
+        /// Which credential, `pilot` or `aircraft`
+        kind: &'static str,
+
+        /// Identifier of the credential
+        identifier: String,
+    },
+
+    /// Restrictions in effect intersect the route
+    #[error("error: The route intersects restrictions {}.", .0.join(", "))]
+    Restricted(Vec<String>),
+}
+
+/// Returns the identifiers of the restrictions in effect during the flight
+///  that intersect its route, sorted
+pub fn intersecting_restrictions(
+    data: &FlightPlanData,
+    restrictions: &HashMap<String, RestrictionDetails>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Vec<String> {
+    let mut identifiers: Vec<String> = restrictions
+        .iter()
+        .filter(|(_, restriction)| restriction.zone_type == gis::ZoneType::Restriction)
+        .filter(|(_, restriction)| restriction.is_active_between(start, end))
+        .filter(|(_, restriction)| restriction.intersects_route(&data.route))
+        .map(|(identifier, _)| identifier.clone())
+        .collect();
+
+    identifiers.sort();
+    identifiers
+}
+
+/// Checks that a release can be requested now for the flight plan
+pub fn check_preconditions(
+    config: &ReleaseConfig,
+    data: &FlightPlanData,
+    restrictions: &HashMap<String, RestrictionDetails>,
+    now: DateTime<Utc>,
+) -> Result<(), ReleaseError> {
+    let earliest =
+        data.departure - Duration::minutes(config.earliest_minutes_before_departure as i64);
+    let latest = data.departure + Duration::minutes(config.latest_minutes_after_departure as i64);
+    if now < earliest || now > latest {
+        return Err(ReleaseError::OutsideWindow { earliest, latest });
+    }
+
+    let end = data.flight_end(config, now);
+    for (kind, credential) in [("pilot", &data.pilot), ("aircraft", &data.aircraft)] {
+        if matches!(credential.valid_until, Some(valid_until) if valid_until < end) {
+            return Err(ReleaseError::CredentialExpired {
+                kind,
+                identifier: credential.identifier.clone(),
+            });
+        }
+    }
+
+    let restricted = intersecting_restrictions(data, restrictions, now, end);
+    if !restricted.is_empty() {
+        return Err(ReleaseError::Restricted(restricted));
+    }
+
+    Ok(())
+}
+
+/// Expires releases that were not used in time, and revokes releases
+///  crossed by new restrictions
+#[derive(Debug)]
+pub struct ReleaseMonitor {
+    /// Tracked flight plans
+    flight_plans: Arc<FlightPlanStore>,
+
+    /// AMQP channel the expiries and revocations are published on
+    mq_channel: Option<lapin::Channel>,
+
+    /// Restrictions as of the previous check for new restrictions
+    known_restrictions: Mutex<HashMap<String, RestrictionDetails>>,
+}
+
+impl ReleaseMonitor {
+    /// Create a new monitor
+    pub fn new(flight_plans: Arc<FlightPlanStore>, mq_channel: Option<lapin::Channel>) -> Self {
+        Self {
+            flight_plans,
+            mq_channel,
+            known_restrictions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Expires the released flight plans whose release ran out before the
+    ///  provided time, returning the number of flight plans expired
+    ///
+    /// A flight plan that can not be expired is logged and left for the next
+    ///  call, without holding back the others.
+    pub async fn expire(&self, now: DateTime<Utc>) -> usize {
+        let mut expired = 0;
+        for plan in self.flight_plans.in_state(FlightPlanState::Released).await {
+            match self.expire_plan(&plan.flight_plan_id, now).await {
+                Ok(true) => expired += 1,
+                Ok(false) => (),
+                Err(e) => releases_error!(
+                    "(expire) Could not expire the release of {}: {}",
+                    plan.flight_plan_id,
+                    e
+                ),
+            }
+        }
+
+        expired
+    }
+
+    /// Expires a released flight plan if its release ran out before the
+    ///  provided time, returning whether it expired
+    async fn expire_plan(
+        &self,
+        flight_plan_id: &str,
+        now: DateTime<Utc>,
+    ) -> Result<bool, FlightPlanError> {
+        let storage = self.flight_plans.storage();
+        let Some(mut release) = storage.release(flight_plan_id).await? else {
+            return Ok(false);
+        };
+
+        let Some(valid_until) = release.valid_until else {
+            return Ok(false);
+        };
+
+        if valid_until > now || release.revoked_at.is_some() {
+            return Ok(false);
+        }
+
+        let reason = format!("no takeoff before {}", valid_until.to_rfc3339());
+        release.revoked_at = Some(now);
+        release.revoked_reason = Some("release expired".to_string());
+        let plan = self
+            .flight_plans
+            .transition_release(
+                flight_plan_id,
+                FlightPlanState::Expired,
+                now,
+                Some(reason.clone()),
+                &release,
+            )
+            .await?;
+
+        releases_info!("(expire) Release of {} expired.", flight_plan_id);
+        amqp::publish_flight_plan_event(
+            &self.mq_channel,
+            amqp::ROUTING_KEY_EXPIRED,
+            &FlightPlanEvent {
+                flight_plan_id: flight_plan_id.to_string(),
+                state: plan.state,
+                timestamp: plan.updated_at(),
+                reason: Some(reason),
+                data: None,
+                landed_at: None,
+                departed_at: None,
+                zones: vec![],
+            },
+        )
+        .await;
+
+        Ok(true)
+    }
+
+    /// Revokes the releases of the released and active flight plans whose
+    ///  route is crossed by a restriction that is new or changed since the
+    ///  previous call, returning the number of releases revoked
+    ///
+    /// All restrictions are new on the first call, so after a restart every
+    ///  released and active flight plan is checked against the restored
+    ///  restrictions, in case the service stopped before it could. Releases
+    ///  revoked before are left as they are. A flight plan whose release can
+    ///  not be revoked is logged, without holding back the others, and the
+    ///  restrictions crossing it stay new for the next call.
+    pub async fn revoke_restricted(
+        &self,
+        config: &ReleaseConfig,
+        restrictions: &HashMap<String, RestrictionDetails>,
+        now: DateTime<Utc>,
+    ) -> usize {
+        let mut known = self.known_restrictions.lock().await;
+        let changed: HashMap<String, RestrictionDetails> = restrictions
+            .iter()
+            .filter(|(identifier, restriction)| known.get(*identifier) != Some(*restriction))
+            .map(|(identifier, restriction)| (identifier.clone(), restriction.clone()))
+            .collect();
+
+        if changed.is_empty() {
+            *known = restrictions.clone();
+            return 0;
+        }
+
+        let mut revoked = 0;
+        let mut unrevoked: HashSet<String> = HashSet::new();
+        for state in [FlightPlanState::Released, FlightPlanState::Active] {
+            for plan in self.flight_plans.in_state(state).await {
+                let flight_plan_id = &plan.flight_plan_id;
+                let data = match FlightPlanData::parse(&plan.data) {
+                    Ok(data) => data,
+                    Err(e) => {
+                        releases_warn!("(revoke_restricted) Flight plan {}: {}", flight_plan_id, e);
+                        continue;
+                    }
+                };
+
+                let zones =
+                    intersecting_restrictions(&data, &changed, now, data.flight_end(config, now));
+                if zones.is_empty() {
+                    continue;
+                }
+
+                match self.revoke(flight_plan_id, state, zones.clone(), now).await {
+                    Ok(true) => revoked += 1,
+                    Ok(false) => (),
+                    Err(e) => {
+                        releases_error!(
+                            "(revoke_restricted) Could not revoke the release of {}: {}",
+                            flight_plan_id,
+                            e
+                        );
+                        unrevoked.extend(zones);
+                    }
+                }
+            }
+        }
+
+        *known = restrictions
+            .iter()
+            .filter(|(identifier, _)| !unrevoked.contains(*identifier))
+            .map(|(identifier, restriction)| (identifier.clone(), restriction.clone()))
+            .collect();
+        revoked
+    }
+
+    /// Revokes the release of a released or active flight plan crossed by
+    ///  the provided restrictions, returning whether it was revoked
+    async fn revoke(
+        &self,
+        flight_plan_id: &str,
+        state: FlightPlanState,
+        zones: Vec<String>,
+        now: DateTime<Utc>,
+    ) -> Result<bool, FlightPlanError> {
+        let storage = self.flight_plans.storage();
+        let Some(mut release) = storage.release(flight_plan_id).await? else {
+            return Ok(false);
+        };
+
+        if release.revoked_at.is_some() {
+            return Ok(false);
+        }
+
+        // An active flight can not be released again, it is only flagged
+        let reason = format!("restricted by {}", zones.join(", "));
+        release.revoked_at = Some(now);
+        release.revoked_reason = Some(reason.clone());
+        let state = match state {
+            FlightPlanState::Released => {
+                self.flight_plans
+                    .transition_release(
+                        flight_plan_id,
+                        FlightPlanState::Accepted,
+                        now,
+                        Some(reason.clone()),
+                        &release,
+                    )
+                    .await?
+                    .state
+            }
+            _ => {
+                storage.save_release(&release).await?;
+                state
+            }
+        };
+
+        releases_warn!(
+            "(revoke_restricted) Release of {} revoked, {}.",
+            flight_plan_id,
+            reason
+        );
+        amqp::publish_flight_plan_event(
+            &self.mq_channel,
+            amqp::ROUTING_KEY_REVOKED,
+            &FlightPlanEvent {
+                flight_plan_id: flight_plan_id.to_string(),
+                state,
+                timestamp: now,
+                reason: Some(reason),
+                data: None,
+                landed_at: None,
+                departed_at: None,
+                zones,
+            },
+        )
+        .await;
+
+        Ok(true)
+    }
+}
+
+/// Periodically expires releases that were not used in time
+pub async fn expiry_loop(monitor: Arc<ReleaseMonitor>, config: ReleaseConfig) {
+    releases_info!(
+        "(expiry_loop) Starting loop with interval: {} seconds.",
+        config.expiry_interval_seconds
+    );
+
+    let interval = std::time::Duration::from_secs(config.expiry_interval_seconds as u64);
+    loop {
+        let expired = monitor.expire(Utc::now()).await;
+        if expired > 0 {
+            releases_info!("(expiry_loop) Expired {} release(s).", expired);
+        }
+
+        tokio::time::sleep(interval).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::memory::MemoryStorage;
+    use crate::storage::ReleaseRecord;
+
+    fn at(latitude: f64, longitude: f64) -> gis::Coordinates {
+        gis::Coordinates {
+            latitude,
+            longitude,
+        }
+    }
+
+    fn data(departure: DateTime<Utc>) -> FlightPlanData {
+        FlightPlanData {
+            departure,
+            arrival: Some(departure + Duration::minutes(30)),
+            route: vec![at(52.0, 4.0), at(52.2, 4.2)],
+            pilot: Credential {
+                identifier: "P-1".to_string(),
+                valid_until: None,
+            },
+            aircraft: Credential {
+                identifier: "A-1".to_string(),
+                valid_until: Some(departure + Duration::days(30)),
+            },
+        }
+    }
+
+    fn restriction(zone_type: gis::ZoneType) -> RestrictionDetails {
+        RestrictionDetails {
+            vertices: vec![
+                at(52.05, 4.05),
+                at(52.05, 4.15),
+                at(52.15, 4.15),
+                at(52.15, 4.05),
+            ],
+            timestamp_start: None,
+            timestamp_end: None,
+            zone_type,
+            altitude_meters_max: 500.0,
+            altitude_meters_min: 0.0,
+            notam_id: None,
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let parsed = FlightPlanData::parse(
+            r#"{
+                "departure": "2024-01-01T12:00:00Z",
+                "route": [{"latitude": 52.0, "longitude": 4.0}],
+                "pilot": {"identifier": "P-1", "valid_until": "2025-01-01T00:00:00Z"},
+                "aircraft": {"identifier": "A-1"}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.route.len(), 1);
+        assert_eq!(parsed.arrival, None);
+        assert_eq!(parsed.aircraft.valid_until, None);
+
+        assert!(matches!(
+            FlightPlanData::parse(""),
+            Err(ReleaseError::InvalidData(_))
+        ));
+        assert!(matches!(
+            FlightPlanData::parse(
+                r#"{"departure": "2024-01-01T12:00:00Z", "route": [],
+                    "pilot": {"identifier": "P-1"}, "aircraft": {"identifier": "A-1"}}"#
+            ),
+            Err(ReleaseError::InvalidData(_))
+        ));
+    }
+
+    #[test]
+    fn test_check_preconditions() {
+        let config = ReleaseConfig::default();
+        let now = Utc::now();
+        let restrictions = HashMap::new();
+        let departure = now + Duration::minutes(10);
+        assert!(check_preconditions(&config, &data(departure), &restrictions, now).is_ok());
+
+        // Too early and too late
+        for departure in [now + Duration::hours(1), now - Duration::minutes(11)] {
+            assert!(matches!(
+                check_preconditions(&config, &data(departure), &restrictions, now),
+                Err(ReleaseError::OutsideWindow { .. })
+            ));
+        }
+
+        // The pilot license runs out during the flight
+        let mut expiring = data(departure);
+        expiring.pilot.valid_until = Some(departure + Duration::minutes(20));
+        assert_eq!(
+            check_preconditions(&config, &expiring, &restrictions, now),
+            Err(ReleaseError::CredentialExpired {
+                kind: "pilot",
+                identifier: "P-1".to_string(),
+            })
+        );
+
+        // Ports are part of the route, restrictions are not
+        let mut restrictions = HashMap::from([
+            ("PORT-1".to_string(), restriction(gis::ZoneType::Port)),
+            ("LATER-1".to_string(), {
+                let mut later = restriction(gis::ZoneType::Restriction);
+                later.timestamp_start = Some(now + Duration::hours(2));
+                later
+            }),
+        ]);
+        assert!(check_preconditions(&config, &data(departure), &restrictions, now).is_ok());
+
+        restrictions.insert(
+            "NOTAM-1".to_string(),
+            restriction(gis::ZoneType::Restriction),
+        );
+        assert_eq!(
+            check_preconditions(&config, &data(departure), &restrictions, now),
+            Err(ReleaseError::Restricted(vec!["NOTAM-1".to_string()]))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_expire() {
+        crate::get_log_handle().await;
+        ut_info!("(test_expire) Start.");
+
+        let flight_plans = Arc::new(FlightPlanStore::default());
+        let monitor = ReleaseMonitor::new(flight_plans.clone(), None);
+        let now = Utc::now();
+        for flight_plan_id in ["FP-1", "FP-2"] {
+            flight_plans
+                .submit(flight_plan_id, "{}", now)
+                .await
+                .unwrap();
+            for state in [FlightPlanState::Accepted, FlightPlanState::Released] {
+                flight_plans
+                    .transition(flight_plan_id, state, now, None)
+                    .await
+                    .unwrap();
+            }
+        }
+
+        let storage = flight_plans.storage();
+        for (flight_plan_id, minutes) in [("FP-1", 5), ("FP-2", 20)] {
+            storage
+                .save_release(&ReleaseRecord {
+                    flight_plan_id: flight_plan_id.to_string(),
+                    released_at: now,
+                    valid_until: Some(now + Duration::minutes(minutes)),
+                    revoked_at: None,
+                    revoked_reason: None,
+                    token: None,
+                })
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(monitor.expire(now).await, 0);
+        let later = now + Duration::minutes(10);
+        assert_eq!(monitor.expire(later).await, 1);
+        assert_eq!(
+            flight_plans.get("FP-1").await.unwrap().state,
+            FlightPlanState::Expired
+        );
+        assert_eq!(
+            flight_plans.get("FP-2").await.unwrap().state,
+            FlightPlanState::Released
+        );
+        let release = storage.release("FP-1").await.unwrap().unwrap();
+        assert_eq!(release.revoked_at, Some(later));
+        assert_eq!(monitor.expire(later).await, 0);
+
+        ut_info!("(test_expire) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_revoke_restricted() {
+        crate::get_log_handle().await;
+        ut_info!("(test_revoke_restricted) Start.");
+
+        let memory = Arc::new(MemoryStorage::default());
+        let flight_plans = Arc::new(FlightPlanStore::new(memory.clone()));
+        let monitor = ReleaseMonitor::new(flight_plans.clone(), None);
+        let config = ReleaseConfig::default();
+        let now = Utc::now();
+        let storage = flight_plans.storage();
+        for (flight_plan_id, route, state) in [
+            (
+                "FP-1",
+                [(52.0, 4.0), (52.2, 4.2)],
+                FlightPlanState::Released,
+            ),
+            (
+                "FP-2",
+                [(53.0, 5.0), (53.1, 5.1)],
+                FlightPlanState::Released,
+            ),
+            ("FP-3", [(52.0, 4.0), (52.2, 4.2)], FlightPlanState::Active),
+        ] {
+            let data = serde_json::json!({
+                "departure": now + Duration::minutes(10),
+                "route": route.map(|(latitude, longitude)| {
+                    serde_json::json!({ "latitude": latitude, "longitude": longitude })
+                }),
+                "pilot": { "identifier": "P-1" },
+                "aircraft": { "identifier": "A-1" }
+            });
+            submit_in_state(&flight_plans, flight_plan_id, &data.to_string(), state, now).await;
+            storage
+                .save_release(&ReleaseRecord {
+                    flight_plan_id: flight_plan_id.to_string(),
+                    released_at: now,
+                    valid_until: Some(now + Duration::minutes(15)),
+                    revoked_at: None,
+                    revoked_reason: None,
+                    token: None,
+                })
+                .await
+                .unwrap();
+        }
+
+        let mut restrictions =
+            HashMap::from([("PORT-1".to_string(), restriction(gis::ZoneType::Port))]);
+        assert_eq!(
+            monitor.revoke_restricted(&config, &restrictions, now).await,
+            0
+        );
+
+        // A new restriction crosses the first and the last flight, and is
+        //  checked again until their releases could be revoked
+        restrictions.insert(
+            "NOTAM-1".to_string(),
+            restriction(gis::ZoneType::Restriction),
+        );
+        memory.set_failing(true);
+        assert_eq!(
+            monitor.revoke_restricted(&config, &restrictions, now).await,
+            0
+        );
+        memory.set_failing(false);
+        assert_eq!(
+            monitor.revoke_restricted(&config, &restrictions, now).await,
+            2
+        );
+        assert_eq!(
+            flight_plans.get("FP-1").await.unwrap().state,
+            FlightPlanState::Accepted
+        );
+        assert_eq!(
+            flight_plans.get("FP-3").await.unwrap().state,
+            FlightPlanState::Active
+        );
+        for flight_plan_id in ["FP-1", "FP-3"] {
+            let release = storage.release(flight_plan_id).await.unwrap().unwrap();
+            assert_eq!(release.revoked_at, Some(now));
+            assert_eq!(
+                release.revoked_reason,
+                Some("restricted by NOTAM-1".to_string())
+            );
+        }
+        let release = storage.release("FP-2").await.unwrap().unwrap();
+        assert_eq!(release.revoked_at, None);
+
+        // Known restrictions are not checked again
+        assert_eq!(
+            monitor.revoke_restricted(&config, &restrictions, now).await,
+            0
+        );
+
+        // After a restart, every flight is checked against the restored
+        //  restrictions, without revoking a release twice
+        let restarted = ReleaseMonitor::new(flight_plans.clone(), None);
+        assert_eq!(
+            restarted
+                .revoke_restricted(&config, &restrictions, now)
+                .await,
+            0
+        );
+        let mut moved = restriction(gis::ZoneType::Restriction);
+        moved.vertices = moved
+            .vertices
+            .iter()
+            .map(|vertex| at(vertex.latitude + 0.97, vertex.longitude + 0.97))
+            .collect();
+        restrictions.insert("NOTAM-1".to_string(), moved);
+        assert_eq!(
+            restarted
+                .revoke_restricted(&config, &restrictions, now)
+                .await,
+            1
+        );
+        assert_eq!(
+            storage
+                .release("FP-2")
+                .await
+                .unwrap()
+                .unwrap()
+                .revoked_reason,
+            Some("restricted by NOTAM-1".to_string())
+        );
+
+        ut_info!("(test_revoke_restricted) Success.");
+    }
+
+    /// Submits a flight plan and moves it to the provided state
+    async fn submit_in_state(
+        flight_plans: &FlightPlanStore,
+        flight_plan_id: &str,
+        data: &str,
+        state: FlightPlanState,
+        now: DateTime<Utc>,
+    ) {
+        flight_plans
+            .submit(flight_plan_id, data, now)
+            .await
+            .unwrap();
+        for next in [
+            FlightPlanState::Accepted,
+            FlightPlanState::Released,
+            FlightPlanState::Active,
+        ] {
+            flight_plans
+                .transition(flight_plan_id, next, now, None)
+                .await
+                .unwrap();
+            if next == state {
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,405 @@
+//! Signed tokens proving a release
+//!
+//! A granted release comes with a compact JWS signed with Ed25519
+//!  (`"alg": "EdDSA"`). Its claims bind the flight plan, the aircraft, a
+//!  hash of the route and the validity of the release, so aircraft and
+//!  vertiport systems can check a release without asking the service.
+//!
+//! Signing keys are configured by key identifier, and the identifier of the
+//!  signing key is put in the `kid` header of every token. To rotate, a new
+//!  key is added and made the signing key while the old one stays
+//!  configured, so tokens it signed can be verified until they run out. A
+//!  retired key can instead be configured by its public key alone, which
+//!  keeps it published without keeping its seed. The public keys are served
+//!  by `getReleaseKeys`.
+
+use super::FlightPlanData;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signer, SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use svc_gis_client_grpc::prelude::gis;
+
+/// Signing of release tokens
+#[derive(Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct TokenConfig {
+    /// Issuer put in the tokens
+    pub issuer: String,
+
+    /// Identifier of the key tokens are signed with, optional when a single
+    ///  key is configured
+    pub signing_key_id: Option<String>,
+
+    /// Base64 encoded 32 byte Ed25519 seeds by key identifier
+    pub keys: HashMap<String, String>,
+
+    /// Base64url encoded 32 byte Ed25519 public keys of retired keys by key
+    ///  identifier, published to verify the tokens they signed
+    pub public_keys: HashMap<String, String>,
+}
+
+impl Default for TokenConfig {
+    fn default() -> Self {
+        TokenConfig {
+            issuer: String::from("svc-compliance"),
+            signing_key_id: None,
+            keys: HashMap::new(),
+            public_keys: HashMap::new(),
+        }
+    }
+}
+
+impl std::fmt::Debug for TokenConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut keys: Vec<&String> = self.keys.keys().collect();
+        keys.sort();
+
+        f.debug_struct("TokenConfig")
+            .field("issuer", &self.issuer)
+            .field("signing_key_id", &self.signing_key_id)
+            .field("keys", &keys)
+            .field("public_keys", &self.public_keys)
+            .finish()
+    }
+}
+
+/// Errors from configuring the signer
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum TokenError {
+    /// A configured key is not a base64 encoded 32 byte seed, or a valid
+    ///  public key
+    #[error("error: Invalid release token key {0}.")]
+    InvalidKey(String),
+
+    /// The signing key is not configured, or can not be chosen
+    #[error("error: Unknown release token signing key {0}.")]
+    UnknownSigningKey(String),
+}
+
+/// Header of a release token
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct TokenHeader<'a> {
+    /// Signature algorithm
+    alg: &'a str,
+
+    /// Token type
+    typ: &'a str,
+
+    /// Identifier of the signing key
+    kid: &'a str,
+}
+
+/// Claims of a release token
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReleaseClaims {
+    /// Issuer of the token
+    pub iss: String,
+
+    /// Identifier of the released flight plan
+    pub sub: String,
+
+    /// Identifier of the released aircraft
+    pub aircraft: String,
+
+    /// Hash of the route, see [`route_hash`]
+    pub route_hash: String,
+
+    /// When the release was granted, in unix seconds
+    pub iat: i64,
+
+    /// When the release starts being valid, in unix seconds
+    pub nbf: i64,
+
+    /// When the release stops being valid, in unix seconds
+    pub exp: i64,
+}
+
+impl ReleaseClaims {
+    /// Claims of the release of a flight plan, valid between the provided
+    ///  times
+    pub fn new(
+        issuer: &str,
+        flight_plan_id: &str,
+        data: &FlightPlanData,
+        released_at: DateTime<Utc>,
+        valid_until: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            iss: issuer.to_string(),
+            sub: flight_plan_id.to_string(),
+            aircraft: data.aircraft.identifier.clone(),
+            route_hash: route_hash(&data.route),
+            iat: released_at.timestamp(),
+            nbf: released_at.timestamp(),
+            exp: valid_until.timestamp(),
+        }
+    }
+}
+
+/// Returns the hash of a route: the hexadecimal SHA-256 digest of its points
+///  written as `{latitude},{longitude}` with 7 decimals, joined by `;`
+pub fn route_hash(route: &[gis::Coordinates]) -> String {
+    let route = route
+        .iter()
+        .map(|point| format!("{:.7},{:.7}", point.latitude, point.longitude))
+        .collect::<Vec<String>>()
+        .join(";");
+
+    hex::encode(Sha256::digest(route.as_bytes()))
+}
+
+/// Signs release tokens
+pub struct ReleaseSigner {
+    /// Issuer put in the tokens
+    issuer: String,
+
+    /// Identifier of the key tokens are signed with
+    signing_key_id: String,
+
+    /// Configured keys by identifier
+    keys: HashMap<String, SigningKey>,
+
+    /// Public keys of retired keys by identifier, only published
+    retired_keys: HashMap<String, VerifyingKey>,
+}
+
+impl std::fmt::Debug for ReleaseSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReleaseSigner")
+            .field("issuer", &self.issuer)
+            .field("signing_key_id", &self.signing_key_id)
+            .finish()
+    }
+}
+
+impl ReleaseSigner {
+    /// Create a signer from the configured keys, or nothing if no keys are
+    ///  configured
+    pub fn new(config: &TokenConfig) -> Result<Option<Self>, TokenError> {
+        if config.keys.is_empty() {
+            return Ok(None);
+        }
+
+        let mut keys = HashMap::new();
+        for (key_id, seed) in &config.keys {
+            let seed: [u8; 32] = STANDARD
+                .decode(seed.trim())
+                .ok()
+                .and_then(|seed| seed.try_into().ok())
+                .ok_or_else(|| TokenError::InvalidKey(key_id.clone()))?;
+            keys.insert(key_id.to_lowercase(), SigningKey::from_bytes(&seed));
+        }
+
+        let signing_key_id = match &config.signing_key_id {
+            Some(key_id) => key_id.to_lowercase(),
+            None if keys.len() == 1 => keys.keys().next().cloned().unwrap_or_default(),
+            None => return Err(TokenError::UnknownSigningKey("(none)".to_string())),
+        };
+
+        if !keys.contains_key(&signing_key_id) {
+            return Err(TokenError::UnknownSigningKey(signing_key_id));
+        }
+
+        let mut retired_keys = HashMap::new();
+        for (key_id, public_key) in &config.public_keys {
+            let public_key = URL_SAFE_NO_PAD
+                .decode(public_key.trim())
+                .ok()
+                .and_then(|public_key| public_key.try_into().ok())
+                .and_then(|public_key: [u8; 32]| VerifyingKey::from_bytes(&public_key).ok())
+                .ok_or_else(|| TokenError::InvalidKey(key_id.clone()))?;
+            retired_keys.insert(key_id.to_lowercase(), public_key);
+        }
+
+        Ok(Some(Self {
+            issuer: config.issuer.clone(),
+            signing_key_id,
+            keys,
+            retired_keys,
+        }))
+    }
+
+    /// Returns the issuer put in the tokens
+    pub fn issuer(&self) -> &str {
+        &self.issuer
+    }
+
+    /// Returns the identifier of the key tokens are signed with
+    pub fn signing_key_id(&self) -> &str {
+        &self.signing_key_id
+    }
+
+    /// Returns the base64url encoded public keys by key identifier, sorted,
+    ///  including the retired keys
+    ///
+    /// A retired key sharing its identifier with a configured seed is left
+    ///  out.
+    pub fn public_keys(&self) -> Vec<(String, String)> {
+        let mut keys: HashMap<&String, VerifyingKey> = self
+            .retired_keys
+            .iter()
+            .map(|(key_id, key)| (key_id, *key))
+            .collect();
+        keys.extend(
+            self.keys
+                .iter()
+                .map(|(key_id, key)| (key_id, key.verifying_key())),
+        );
+
+        let mut keys: Vec<(String, String)> = keys
+            .into_iter()
+            .map(|(key_id, key)| (key_id.clone(), URL_SAFE_NO_PAD.encode(key.as_bytes())))
+            .collect();
+
+        keys.sort();
+        keys
+    }
+
+    /// Returns the compact JWS of the claims
+    pub fn sign(&self, claims: &ReleaseClaims) -> Result<String, serde_json::Error> {
+        let header = TokenHeader {
+            alg: "EdDSA",
+            typ: "JWT",
+            kid: &self.signing_key_id,
+        };
+
+        let signing_input = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(serde_json::to_vec(&header)?),
+            URL_SAFE_NO_PAD.encode(serde_json::to_vec(claims)?)
+        );
+
+        let signature = self.keys[&self.signing_key_id].sign(signing_input.as_bytes());
+        Ok(format!(
+            "{signing_input}.{}",
+            URL_SAFE_NO_PAD.encode(signature.to_bytes())
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+    fn config(signing_key_id: Option<&str>, key_ids: &[&str]) -> TokenConfig {
+        TokenConfig {
+            signing_key_id: signing_key_id.map(str::to_string),
+            keys: key_ids
+                .iter()
+                .enumerate()
+                .map(|(index, key_id)| (key_id.to_string(), STANDARD.encode([index as u8; 32])))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_route_hash() {
+        let route = [
+            gis::Coordinates {
+                latitude: 52.0,
+                longitude: 4.0,
+            },
+            gis::Coordinates {
+                latitude: 52.1,
+                longitude: 4.1,
+            },
+        ];
+
+        // sha256("52.0000000,4.0000000;52.1000000,4.1000000"), also checked
+        //  by the client library
+        assert_eq!(
+            route_hash(&route),
+            "aa44255c85140a372b4f9059c58c76b809f1d657e14100527309df5bdb5e541e"
+        );
+        assert_ne!(route_hash(&route), route_hash(&route[..1]));
+    }
+
+    #[test]
+    fn test_release_signer() {
+        assert!(ReleaseSigner::new(&TokenConfig::default())
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            ReleaseSigner::new(&config(None, &["k1", "k2"])).unwrap_err(),
+            TokenError::UnknownSigningKey("(none)".to_string())
+        );
+        assert_eq!(
+            ReleaseSigner::new(&config(Some("k3"), &["k1", "k2"])).unwrap_err(),
+            TokenError::UnknownSigningKey("k3".to_string())
+        );
+        let mut invalid = config(None, &["k1"]);
+        invalid
+            .keys
+            .insert("k1".to_string(), STANDARD.encode([0; 16]));
+        assert_eq!(
+            ReleaseSigner::new(&invalid).unwrap_err(),
+            TokenError::InvalidKey("k1".to_string())
+        );
+        let mut invalid = config(None, &["k1"]);
+        invalid
+            .public_keys
+            .insert("k0".to_string(), URL_SAFE_NO_PAD.encode([0; 16]));
+        assert_eq!(
+            ReleaseSigner::new(&invalid).unwrap_err(),
+            TokenError::InvalidKey("k0".to_string())
+        );
+
+        // Rotated to k2, k1 stays published, and so does the retired k0
+        let retired = SigningKey::from_bytes(&[9; 32]).verifying_key();
+        let mut rotated = config(Some("k2"), &["k1", "k2"]);
+        rotated
+            .public_keys
+            .insert("K0".to_string(), URL_SAFE_NO_PAD.encode(retired.as_bytes()));
+        let signer = ReleaseSigner::new(&rotated).unwrap().unwrap();
+        let keys = signer.public_keys();
+        assert_eq!(keys.len(), 3);
+        assert_eq!(
+            keys[0],
+            ("k0".to_string(), URL_SAFE_NO_PAD.encode(retired.as_bytes()))
+        );
+        assert_eq!(keys[2].0, "k2");
+
+        let claims = ReleaseClaims {
+            iss: "svc-compliance".to_string(),
+            sub: "FP-1".to_string(),
+            aircraft: "A-1".to_string(),
+            route_hash: "0".repeat(64),
+            iat: 1700000000,
+            nbf: 1700000000,
+            exp: 1700000900,
+        };
+        let token = signer.sign(&claims).unwrap();
+        let parts: Vec<&str> = token.split('.').collect();
+        assert_eq!(parts.len(), 3);
+
+        let header: serde_json::Value =
+            serde_json::from_slice(&URL_SAFE_NO_PAD.decode(parts[0]).unwrap()).unwrap();
+        assert_eq!(header["alg"], "EdDSA");
+        assert_eq!(header["kid"], "k2");
+        let decoded: ReleaseClaims =
+            serde_json::from_slice(&URL_SAFE_NO_PAD.decode(parts[1]).unwrap()).unwrap();
+        assert_eq!(decoded, claims);
+
+        let public_key: [u8; 32] = URL_SAFE_NO_PAD
+            .decode(&keys[2].1)
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let signature: [u8; 64] = URL_SAFE_NO_PAD
+            .decode(parts[2])
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let signing_input = format!("{}.{}", parts[0], parts[1]);
+        assert!(VerifyingKey::from_bytes(&public_key)
+            .unwrap()
+            .verify(signing_input.as_bytes(), &Signature::from_bytes(&signature))
+            .is_ok());
+    }
+}
@@ -0,0 +1,437 @@
+//! Safety buffers around restriction zones
+//!
+//! Regulations require keeping clear of restricted airspace by a margin. The
+//!  buffered geometry is derived from the authoritative one when restrictions
+//!  are pushed or checked; the stored restrictions are never modified.
+
+use crate::region::utils::{
+    convex_hull, open_ring, segments_intersect, signed_area_2x, LocalPoint, LocalProjection,
+};
+use crate::region::RestrictionDetails;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+/// Suffix appended to the identifier of buffered zones pushed alongside the
+///  original
+pub const BUFFER_SUFFIX: &str = "-BUFFER";
+
+/// Largest angle covered by a single segment when rounding convex corners
+const MAX_ARC_STEP_RADIANS: f64 = PI / 12.0;
+
+/// How buffered geometries are pushed to svc-gis
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BufferMode {
+    /// Push the buffered zone as a separate zone next to the original
+    #[default]
+    Alongside,
+
+    /// Push the buffered zone instead of the original
+    Replace,
+}
+
+/// Margins to keep clear of a zone
+#[derive(Debug, Copy, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SafetyMargin {
+    /// Horizontal distance in meters
+    pub lateral_meters: f64,
+
+    /// Distance in meters below the floor and above the ceiling of the zone
+    pub vertical_meters: f32,
+}
+
+impl SafetyMargin {
+    /// Returns true if the margin leaves the zone unchanged
+    pub fn is_zero(&self) -> bool {
+        self.lateral_meters <= 0.0 && self.vertical_meters <= 0.0
+    }
+}
+
+/// Safety buffer settings
+///
+/// Margins are looked up by region code, then by zone type name, for
+///  example `SAFETY_BUFFERS__MARGINS__NL__RESTRICTION__LATERAL_METERS=100`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct BufferConfig {
+    /// How buffered geometries are pushed
+    pub mode: BufferMode,
+
+    /// Margins by region code and zone type name
+    pub margins: HashMap<String, HashMap<String, SafetyMargin>>,
+}
+
+impl BufferConfig {
+    /// Returns the margin to apply to a restriction in the provided region
+    pub fn margin(&self, region: &str, details: &RestrictionDetails) -> SafetyMargin {
+        let zone_type = details.zone_type.as_str_name();
+        self.margins
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(region))
+            .and_then(|(_, margins)| {
+                margins
+                    .iter()
+                    .find(|(key, _)| key.eq_ignore_ascii_case(zone_type))
+            })
+            .map(|(_, margin)| *margin)
+            .unwrap_or_default()
+    }
+}
+
+/// Returns the restriction grown by the provided margin
+///
+/// The boundary is offset outward in a local projection. Convex corners are
+///  rounded with segments tangent to the arc, so the result always contains
+///  the exact buffer. Offsetting a concave ring by more than the width of a
+///  notch folds the ring over itself; the convex hull of the zone is offset
+///  instead in that case, which still contains the exact buffer.
+pub fn buffer_restriction(
+    details: &RestrictionDetails,
+    margin: &SafetyMargin,
+) -> RestrictionDetails {
+    let mut buffered = details.clone();
+    if margin.vertical_meters > 0.0 {
+        buffered.altitude_meters_max += margin.vertical_meters;
+        if buffered.altitude_meters_min > 0.0 {
+            buffered.altitude_meters_min =
+                (buffered.altitude_meters_min - margin.vertical_meters).max(0.0);
+        }
+    }
+
+    let ring = open_ring(&details.vertices);
+    if margin.lateral_meters <= 0.0 || ring.len() < 3 {
+        return buffered;
+    }
+
+    let projection = LocalProjection::new(&ring[0]);
+    let mut points: Vec<LocalPoint> = ring.iter().map(|v| projection.to_local(v)).collect();
+    if signed_area_2x(&points) < 0.0 {
+        points.reverse();
+    }
+
+    let mut offset = offset_ring(&points, margin.lateral_meters);
+    if !is_simple(&offset) {
+        restrictions_warn!(
+            "(buffer_restriction) Buffered ring intersects itself, buffering the convex hull."
+        );
+        offset = offset_ring(&convex_hull(&points), margin.lateral_meters);
+    }
+
+    let mut vertices: Vec<_> = offset
+        .iter()
+        .map(|p| projection.to_coordinates(p))
+        .collect();
+    if let Some(first) = vertices.first().cloned() {
+        vertices.push(first);
+    }
+
+    buffered.vertices = vertices;
+    buffered
+}
+
+/// Offsets a counter-clockwise ring outward by the provided distance
+fn offset_ring(points: &[LocalPoint], distance: f64) -> Vec<LocalPoint> {
+    let count = points.len();
+    let mut result: Vec<LocalPoint> = vec![];
+
+    for i in 0..count {
+        let previous = points[(i + count - 1) % count];
+        let current = points[i];
+        let next = points[(i + 1) % count];
+
+        let (Some(n1), Some(n2)) = (
+            outward_normal(&previous, &current),
+            outward_normal(&current, &next),
+        ) else {
+            // Repeated vertex, the neighbours provide the offset
+            continue;
+        };
+
+        let turn = (current.x - previous.x) * (next.y - current.y)
+            - (current.y - previous.y) * (next.x - current.x);
+
+        if turn > 0.0 {
+            // Convex corner: circumscribe the arc between both edge normals
+            let start = n1.1.atan2(n1.0);
+            let mut sweep = n2.1.atan2(n2.0) - start;
+            if sweep < 0.0 {
+                sweep += 2.0 * PI;
+            }
+
+            let steps = (sweep / MAX_ARC_STEP_RADIANS).ceil().max(1.0);
+            let step = sweep / steps;
+            let radius = distance / (step / 2.0).cos();
+
+            result.push(translate(&current, n1, distance));
+            for k in 0..steps as usize {
+                let angle = start + step * (k as f64 + 0.5);
+                result.push(translate(&current, (angle.cos(), angle.sin()), radius));
+            }
+            result.push(translate(&current, n2, distance));
+        } else {
+            // Concave or straight corner: intersect both offset edges
+            let denominator = 1.0 + n1.0 * n2.0 + n1.1 * n2.1;
+            if denominator < 1e-6 {
+                result.push(translate(&current, n1, distance));
+                result.push(translate(&current, n2, distance));
+            } else {
+                let miter = ((n1.0 + n2.0) / denominator, (n1.1 + n2.1) / denominator);
+                result.push(translate(&current, miter, distance));
+            }
+        }
+    }
+
+    result
+}
+
+/// Returns true if no two edges of the ring cross, other than neighbouring
+///  edges at their shared vertex
+fn is_simple(points: &[LocalPoint]) -> bool {
+    let count = points.len();
+    for i in 0..count {
+        for j in (i + 2)..count {
+            // The last edge shares a vertex with the first
+            if i == 0 && j == count - 1 {
+                continue;
+            }
+
+            if segments_intersect(
+                &points[i],
+                &points[(i + 1) % count],
+                &points[j],
+                &points[(j + 1) % count],
+            ) {
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+/// Unit normal pointing to the right of the edge, outward for a
+///  counter-clockwise ring
+fn outward_normal(a: &LocalPoint, b: &LocalPoint) -> Option<(f64, f64)> {
+    let dx = b.x - a.x;
+    let dy = b.y - a.y;
+    let length = (dx * dx + dy * dy).sqrt();
+    if length < 1e-9 {
+        return None;
+    }
+
+    Some((dy / length, -dx / length))
+}
+
+fn translate(point: &LocalPoint, direction: (f64, f64), distance: f64) -> LocalPoint {
+    LocalPoint {
+        x: point.x + direction.0 * distance,
+        y: point.y + direction.1 * distance,
+    }
+}
+
+/// Applies the configured safety buffers to the restrictions to be pushed
+///
+/// Depending on the [`BufferMode`] the buffered zones are added with the
+///  [`BUFFER_SUFFIX`] or replace the original zones. Restrictions without a
+///  margin are passed through unchanged.
+pub fn apply_buffers(
+    restrictions: &HashMap<String, RestrictionDetails>,
+    config: &BufferConfig,
+    region: &str,
+) -> HashMap<String, RestrictionDetails> {
+    let mut result: HashMap<String, RestrictionDetails> = HashMap::new();
+    for (identifier, details) in restrictions.iter() {
+        let margin = config.margin(region, details);
+        if margin.is_zero() {
+            result.insert(identifier.clone(), details.clone());
+            continue;
+        }
+
+        let buffered = buffer_restriction(details, &margin);
+        match config.mode {
+            BufferMode::Alongside => {
+                result.insert(identifier.clone(), details.clone());
+                result.insert(format!("{identifier}{BUFFER_SUFFIX}"), buffered);
+            }
+            BufferMode::Replace => {
+                result.insert(identifier.clone(), buffered);
+            }
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svc_gis_client_grpc::prelude::gis::{Coordinates, ZoneType};
+
+    /// Square of about 1km around Amsterdam Centraal, clockwise
+    fn get_details() -> RestrictionDetails {
+        let vertices = vec![
+            (52.3745, 4.8950),
+            (52.3835, 4.8950),
+            (52.3835, 4.9097),
+            (52.3745, 4.9097),
+            (52.3745, 4.8950),
+        ];
+
+        RestrictionDetails {
+            vertices: vertices
+                .into_iter()
+                .map(|(latitude, longitude)| Coordinates {
+                    latitude,
+                    longitude,
+                })
+                .collect(),
+            timestamp_start: None,
+            timestamp_end: None,
+            zone_type: ZoneType::Restriction,
+            altitude_meters_min: 100.0,
+            altitude_meters_max: 500.0,
+            notam_id: None,
+        }
+    }
+
+    fn get_config(mode: BufferMode) -> BufferConfig {
+        let mut zones = HashMap::new();
+        zones.insert(
+            "restriction".to_string(),
+            SafetyMargin {
+                lateral_meters: 100.0,
+                vertical_meters: 150.0,
+            },
+        );
+
+        let mut margins = HashMap::new();
+        margins.insert("nl".to_string(), zones);
+        BufferConfig { mode, margins }
+    }
+
+    #[tokio::test]
+    async fn test_buffer_restriction() {
+        crate::get_log_handle().await;
+        ut_info!("(test_buffer_restriction) Start.");
+
+        let details = get_details();
+        let margin = SafetyMargin {
+            lateral_meters: 100.0,
+            vertical_meters: 150.0,
+        };
+
+        let buffered = buffer_restriction(&details, &margin);
+        assert_eq!(buffered.altitude_meters_min, 0.0);
+        assert_eq!(buffered.altitude_meters_max, 650.0);
+        assert_eq!(buffered.vertices.first(), buffered.vertices.last());
+
+        // Every original vertex keeps at least the margin to the new boundary
+        let projection = LocalProjection::new(&details.vertices[0]);
+        let ring: Vec<LocalPoint> = open_ring(&buffered.vertices)
+            .iter()
+            .map(|v| projection.to_local(v))
+            .collect();
+
+        for vertex in open_ring(&details.vertices) {
+            let p = projection.to_local(vertex);
+            let distance = (0..ring.len())
+                .map(|i| segment_distance(&p, &ring[i], &ring[(i + 1) % ring.len()]))
+                .fold(f64::MAX, f64::min);
+            assert!(distance >= 99.9, "distance {distance} below margin");
+            assert!(distance <= 110.0, "distance {distance} far above margin");
+        }
+
+        // The original geometry is untouched
+        assert_eq!(details.vertices.len(), 5);
+
+        ut_info!("(test_buffer_restriction) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_buffer_concave_restriction() {
+        crate::get_log_handle().await;
+        ut_info!("(test_buffer_concave_restriction) Start.");
+
+        // L-shaped zone whose inner corner is closer than the margin to the
+        //  ends of both arms
+        let origin = Coordinates {
+            latitude: 52.3745,
+            longitude: 4.8950,
+        };
+        let projection = LocalProjection::new(&origin);
+        let shape = [
+            (0.0, 0.0),
+            (300.0, 0.0),
+            (300.0, 100.0),
+            (100.0, 100.0),
+            (100.0, 300.0),
+            (0.0, 300.0),
+            (0.0, 0.0),
+        ];
+        let mut details = get_details();
+        details.vertices = shape
+            .iter()
+            .map(|(x, y)| projection.to_coordinates(&LocalPoint { x: *x, y: *y }))
+            .collect();
+
+        let points: Vec<LocalPoint> = open_ring(&details.vertices)
+            .iter()
+            .map(|v| projection.to_local(v))
+            .collect();
+        assert!(!is_simple(&offset_ring(&points, 300.0)));
+
+        let margin = SafetyMargin {
+            lateral_meters: 300.0,
+            vertical_meters: 0.0,
+        };
+        let buffered = buffer_restriction(&details, &margin);
+        let ring: Vec<LocalPoint> = open_ring(&buffered.vertices)
+            .iter()
+            .map(|v| projection.to_local(v))
+            .collect();
+        assert!(is_simple(&ring));
+
+        // Still keeps the margin around every vertex of the zone
+        for p in points.iter() {
+            let distance = (0..ring.len())
+                .map(|i| segment_distance(p, &ring[i], &ring[(i + 1) % ring.len()]))
+                .fold(f64::MAX, f64::min);
+            assert!(distance >= 299.9, "distance {distance} below margin");
+        }
+
+        ut_info!("(test_buffer_concave_restriction) Success.");
+    }
+
+    fn segment_distance(p: &LocalPoint, a: &LocalPoint, b: &LocalPoint) -> f64 {
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+        let t = (((p.x - a.x) * dx + (p.y - a.y) * dy) / (dx * dx + dy * dy)).clamp(0.0, 1.0);
+        ((p.x - a.x - t * dx).powi(2) + (p.y - a.y - t * dy).powi(2)).sqrt()
+    }
+
+    #[tokio::test]
+    async fn test_apply_buffers() {
+        crate::get_log_handle().await;
+        ut_info!("(test_apply_buffers) Start.");
+
+        let mut restrictions = HashMap::new();
+        restrictions.insert("ZONE".to_string(), get_details());
+
+        let result = apply_buffers(&restrictions, &get_config(BufferMode::Alongside), "NL");
+        assert_eq!(result.len(), 2);
+        assert_eq!(result["ZONE"].altitude_meters_max, 500.0);
+        assert_eq!(result["ZONE-BUFFER"].altitude_meters_max, 650.0);
+
+        let result = apply_buffers(&restrictions, &get_config(BufferMode::Replace), "nl");
+        assert_eq!(result.len(), 1);
+        assert_eq!(result["ZONE"].altitude_meters_max, 650.0);
+
+        // No margins configured for this region
+        let result = apply_buffers(&restrictions, &get_config(BufferMode::Replace), "us");
+        assert_eq!(result.len(), 1);
+        assert_eq!(result["ZONE"].altitude_meters_max, 500.0);
+
+        ut_info!("(test_apply_buffers) Success.");
+    }
+}
@@ -0,0 +1,250 @@
+//! Restrictions entered manually by operators
+
+use crate::region::RestrictionDetails;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::RestrictionError;
+
+/// Minimum number of distinct vertices for a valid restriction area
+const MIN_DISTINCT_VERTICES: usize = 3;
+
+/// A restriction entered by an operator, for example to close the airspace
+///  around an accident scene before any authority publishes it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManualRestriction {
+    /// The restriction itself, `timestamp_end` holds the expiry
+    pub details: RestrictionDetails,
+
+    /// Why the restriction was entered
+    pub reason: String,
+
+    /// The operator who last created or changed the restriction
+    pub author: String,
+
+    /// When the restriction was created
+    pub created_at: DateTime<Utc>,
+
+    /// When the restriction was last changed
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ManualRestriction {
+    /// Returns true if the restriction expired before the provided time
+    pub fn is_expired(&self, at: DateTime<Utc>) -> bool {
+        match self.details.timestamp_end {
+            Some(end) => end <= at,
+            None => false,
+        }
+    }
+
+    /// Checks if the restriction can be accepted
+    pub fn validate(&self) -> Result<(), RestrictionError> {
+        let details = &self.details;
+
+        if self.reason.trim().is_empty() {
+            return Err(RestrictionError::InvalidArgument(
+                "a reason must be provided".to_string(),
+            ));
+        }
+
+        if self.author.trim().is_empty() {
+            return Err(RestrictionError::InvalidArgument(
+                "an author must be provided".to_string(),
+            ));
+        }
+
+        let Some(end) = details.timestamp_end else {
+            return Err(RestrictionError::InvalidArgument(
+                "an expiry must be provided".to_string(),
+            ));
+        };
+
+        if end <= Utc::now() {
+            return Err(RestrictionError::InvalidArgument(
+                "expiry must be in the future".to_string(),
+            ));
+        }
+
+        if let Some(start) = details.timestamp_start {
+            if start >= end {
+                return Err(RestrictionError::InvalidArgument(
+                    "start must be before the expiry".to_string(),
+                ));
+            }
+        }
+
+        if details.altitude_meters_min >= details.altitude_meters_max {
+            return Err(RestrictionError::InvalidArgument(
+                "minimum altitude must be below the maximum altitude".to_string(),
+            ));
+        }
+
+        let invalid = details.vertices.iter().any(|v| {
+            !(-90.0..=90.0).contains(&v.latitude) || !(-180.0..=180.0).contains(&v.longitude)
+        });
+
+        if invalid {
+            return Err(RestrictionError::InvalidArgument(
+                "vertices must be valid coordinates".to_string(),
+            ));
+        }
+
+        let mut distinct = details.vertices.clone();
+        distinct.dedup();
+        if distinct.len() > 1 && distinct.first() == distinct.last() {
+            distinct.pop();
+        }
+
+        if distinct.len() < MIN_DISTINCT_VERTICES {
+            return Err(RestrictionError::InvalidArgument(format!(
+                "at least {MIN_DISTINCT_VERTICES} distinct vertices are required"
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Loads manual restrictions from the provided file
+///
+/// A missing file is not an error, it will be created on the first change.
+pub async fn load_from_file(
+    path: &Path,
+) -> Result<HashMap<String, ManualRestriction>, RestrictionError> {
+    let contents = match tokio::fs::read(path).await {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            restrictions_info!(
+                "(load_from_file) No manual restrictions file found at {:?}.",
+                path
+            );
+            return Ok(HashMap::new());
+        }
+        Err(e) => {
+            restrictions_error!("(load_from_file) Could not read {:?}: {}", path, e);
+            return Err(RestrictionError::Persistence(e.to_string()));
+        }
+    };
+
+    serde_json::from_slice(&contents).map_err(|e| {
+        restrictions_error!("(load_from_file) Could not parse {:?}: {}", path, e);
+        RestrictionError::Persistence(e.to_string())
+    })
+}
+
+/// Writes manual restrictions to the provided file, see
+///  [`write_atomic`](crate::files::write_atomic)
+pub async fn save_to_file(
+    path: &Path,
+    restrictions: &HashMap<String, ManualRestriction>,
+) -> Result<(), RestrictionError> {
+    let contents = serde_json::to_vec_pretty(restrictions).map_err(|e| {
+        restrictions_error!("(save_to_file) Could not serialize restrictions: {}", e);
+        RestrictionError::Persistence(e.to_string())
+    })?;
+
+    crate::files::write_atomic(path, &contents)
+        .await
+        .map_err(|e| {
+            restrictions_error!("(save_to_file) Could not write {:?}: {}", path, e);
+            RestrictionError::Persistence(e.to_string())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use svc_gis_client_grpc::prelude::gis::{Coordinates, ZoneType};
+
+    fn get_manual_restriction() -> ManualRestriction {
+        let vertices = vec![
+            (4.8822724, 52.3688393),
+            (4.8832170, 52.3781666),
+            (4.9007345, 52.3777998),
+            (4.9001335, 52.3680532),
+            (4.8822724, 52.3688393),
+        ];
+
+        ManualRestriction {
+            details: RestrictionDetails {
+                vertices: vertices
+                    .into_iter()
+                    .map(|(longitude, latitude)| Coordinates {
+                        latitude,
+                        longitude,
+                    })
+                    .collect(),
+                timestamp_start: None,
+                timestamp_end: Some(Utc::now() + Duration::try_hours(1).unwrap()),
+                zone_type: ZoneType::Restriction,
+                altitude_meters_min: 0.0,
+                altitude_meters_max: 500.0,
+                notam_id: None,
+            },
+            reason: "Accident scene".to_string(),
+            author: "operator".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_validate() {
+        crate::get_log_handle().await;
+        ut_info!("(test_validate) Start.");
+
+        let restriction = get_manual_restriction();
+        assert!(restriction.validate().is_ok());
+
+        let mut invalid = restriction.clone();
+        invalid.reason = " ".to_string();
+        assert!(invalid.validate().is_err());
+
+        let mut invalid = restriction.clone();
+        invalid.details.timestamp_end = None;
+        assert!(invalid.validate().is_err());
+
+        let mut invalid = restriction.clone();
+        invalid.details.timestamp_end = Some(Utc::now() - Duration::try_minutes(1).unwrap());
+        assert!(invalid.validate().is_err());
+
+        let mut invalid = restriction.clone();
+        invalid.details.altitude_meters_min = 600.0;
+        assert!(invalid.validate().is_err());
+
+        let mut invalid = restriction.clone();
+        invalid.details.vertices.truncate(2);
+        assert!(invalid.validate().is_err());
+
+        ut_info!("(test_validate) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_save_and_load() {
+        crate::get_log_handle().await;
+        ut_info!("(test_save_and_load) Start.");
+
+        let path = std::env::temp_dir().join(format!(
+            "svc-compliance-manual-{}.json",
+            uuid::Uuid::new_v4()
+        ));
+
+        let result = load_from_file(&path).await.unwrap();
+        assert!(result.is_empty());
+
+        let mut restrictions = HashMap::new();
+        restrictions.insert("ARROW-MANUAL-TEST".to_string(), get_manual_restriction());
+        save_to_file(&path, &restrictions).await.unwrap();
+
+        let result = load_from_file(&path).await.unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result["ARROW-MANUAL-TEST"].reason, "Accident scene");
+
+        let _ = tokio::fs::remove_file(&path).await;
+        ut_info!("(test_save_and_load) Success.");
+    }
+}
@@ -0,0 +1,530 @@
+//! Merging of restriction feeds from several sources
+//!
+//! Feeds are reconciled deterministically: candidates are ordered by the
+//!  configured source priority, then by feed name. The first candidate for an
+//!  identifier or NOTAM wins, every other candidate is dropped and recorded in
+//!  the [`MergeReport`].
+
+use crate::region::RestrictionDetails;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Kind of source a restriction feed is acquired from
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceKind {
+    /// Data provided by the region implementation itself
+    Static,
+
+    /// Data read from a file
+    File,
+
+    /// Notices to airmen
+    Notam,
+
+    /// Restrictions entered by operators
+    Manual,
+}
+
+impl SourceKind {
+    /// Returns the name used in configuration and reports
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SourceKind::Static => "static",
+            SourceKind::File => "file",
+            SourceKind::Notam => "notam",
+            SourceKind::Manual => "manual",
+        }
+    }
+}
+
+impl fmt::Display for SourceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SourceKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "static" => Ok(SourceKind::Static),
+            "file" => Ok(SourceKind::File),
+            "notam" => Ok(SourceKind::Notam),
+            "manual" => Ok(SourceKind::Manual),
+            other => Err(format!("unknown restriction source: {other}")),
+        }
+    }
+}
+
+/// Order in which sources take precedence, highest priority first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourcePriorities(Vec<SourceKind>);
+
+impl Default for SourcePriorities {
+    fn default() -> Self {
+        Self(vec![
+            SourceKind::Manual,
+            SourceKind::Notam,
+            SourceKind::File,
+            SourceKind::Static,
+        ])
+    }
+}
+
+impl FromStr for SourcePriorities {
+    type Err = String;
+
+    /// Parses a comma separated list such as `manual,notam,file,static`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut kinds: Vec<SourceKind> = vec![];
+        for kind in s.split(',').filter(|k| !k.trim().is_empty()) {
+            let kind = kind.parse()?;
+            if !kinds.contains(&kind) {
+                kinds.push(kind);
+            }
+        }
+
+        Ok(Self(kinds))
+    }
+}
+
+impl SourcePriorities {
+    /// Returns the rank of a source kind, lower ranks take precedence
+    ///
+    /// Kinds missing from the configuration rank below all listed kinds.
+    pub fn rank(&self, kind: SourceKind) -> usize {
+        self.0
+            .iter()
+            .position(|k| *k == kind)
+            .unwrap_or(self.0.len())
+    }
+}
+
+/// Restrictions provided by a single feed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestrictionFeed {
+    /// The kind of source providing the feed
+    pub kind: SourceKind,
+
+    /// Unique name of the feed
+    pub name: String,
+
+    /// The restrictions of this feed, by identifier
+    pub restrictions: HashMap<String, RestrictionDetails>,
+
+    /// When the feed was acquired from its source
+    ///
+    /// Feeds saved without it count as acquired at the Unix epoch, so they
+    ///  are not restored.
+    #[serde(default)]
+    pub acquired_at: DateTime<Utc>,
+}
+
+/// A restriction accepted by the merge
+#[derive(Debug, Clone)]
+pub struct MergedRestriction {
+    /// The restriction
+    pub details: RestrictionDetails,
+
+    /// The kind of source the restriction was taken from
+    pub kind: SourceKind,
+
+    /// The feed the restriction was taken from
+    pub feed: String,
+}
+
+/// Reference to a restriction within a feed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeedReference {
+    /// The kind of source providing the feed
+    pub kind: SourceKind,
+
+    /// Name of the feed
+    pub feed: String,
+
+    /// Identifier of the restriction within the feed
+    pub identifier: String,
+}
+
+/// Why a restriction was dropped during the merge
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConflictReason {
+    /// The same NOTAM was provided by another feed
+    DuplicateNotam,
+
+    /// Another feed provided an identical definition under the same identifier
+    IdenticalDefinition,
+
+    /// Another feed provided a different definition under the same identifier
+    ConflictingDefinition,
+}
+
+impl fmt::Display for ConflictReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ConflictReason::DuplicateNotam => "duplicate_notam",
+            ConflictReason::IdenticalDefinition => "identical_definition",
+            ConflictReason::ConflictingDefinition => "conflicting_definition",
+        })
+    }
+}
+
+/// A restriction dropped in favor of another one
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MergeConflict {
+    /// The restriction that was kept
+    pub kept: FeedReference,
+
+    /// The restriction that was dropped
+    pub dropped: FeedReference,
+
+    /// Why the restriction was dropped
+    pub reason: ConflictReason,
+}
+
+/// Number of restrictions provided by a feed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeedSummary {
+    /// The kind of source providing the feed
+    pub kind: SourceKind,
+
+    /// Name of the feed
+    pub feed: String,
+
+    /// Number of restrictions provided by the feed
+    pub restrictions: usize,
+}
+
+/// Outcome of a merge
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MergeReport {
+    /// When the merge was performed
+    pub timestamp: DateTime<Utc>,
+
+    /// The merged feeds in order of precedence
+    pub feeds: Vec<FeedSummary>,
+
+    /// Number of restrictions accepted
+    pub merged: usize,
+
+    /// Restrictions dropped in favor of others
+    pub conflicts: Vec<MergeConflict>,
+}
+
+impl Default for MergeReport {
+    fn default() -> Self {
+        Self {
+            timestamp: Utc::now(),
+            feeds: vec![],
+            merged: 0,
+            conflicts: vec![],
+        }
+    }
+}
+
+/// Returns true if two restrictions describe the same area, altitudes and times
+fn is_identical(a: &RestrictionDetails, b: &RestrictionDetails) -> bool {
+    a.vertices == b.vertices
+        && a.timestamp_start == b.timestamp_start
+        && a.timestamp_end == b.timestamp_end
+        && a.zone_type == b.zone_type
+        && a.altitude_meters_min == b.altitude_meters_min
+        && a.altitude_meters_max == b.altitude_meters_max
+}
+
+/// Normalizes a NOTAM number so the same NOTAM matches across feeds
+fn normalize_notam(notam: &str) -> String {
+    notam
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_uppercase()
+}
+
+/// Merges restriction feeds into a single set of restrictions
+pub fn merge(
+    feeds: &[RestrictionFeed],
+    priorities: &SourcePriorities,
+) -> (HashMap<String, MergedRestriction>, MergeReport) {
+    let mut ordered: Vec<&RestrictionFeed> = feeds.iter().collect();
+    ordered.sort_by(|a, b| {
+        priorities
+            .rank(a.kind)
+            .cmp(&priorities.rank(b.kind))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    let mut merged: HashMap<String, MergedRestriction> = HashMap::new();
+    let mut notams: HashMap<String, FeedReference> = HashMap::new();
+    let mut conflicts: Vec<MergeConflict> = vec![];
+
+    for feed in ordered.iter() {
+        // Sort identifiers so the report does not depend on hash order
+        let mut identifiers: Vec<&String> = feed.restrictions.keys().collect();
+        identifiers.sort();
+
+        for identifier in identifiers {
+            let details = &feed.restrictions[identifier];
+            let reference = FeedReference {
+                kind: feed.kind,
+                feed: feed.name.clone(),
+                identifier: identifier.clone(),
+            };
+
+            let notam = details.notam_id.as_deref().map(normalize_notam);
+            if let Some(kept) = notam.as_ref().and_then(|notam| notams.get(notam)) {
+                // The same NOTAM may appear under several identifiers
+                //  within a single feed as well
+                conflicts.push(MergeConflict {
+                    kept: kept.clone(),
+                    dropped: reference,
+                    reason: ConflictReason::DuplicateNotam,
+                });
+                continue;
+            }
+
+            if let Some(kept) = merged.get(identifier) {
+                let reason = match is_identical(&kept.details, details) {
+                    true => ConflictReason::IdenticalDefinition,
+                    false => ConflictReason::ConflictingDefinition,
+                };
+
+                conflicts.push(MergeConflict {
+                    kept: FeedReference {
+                        kind: kept.kind,
+                        feed: kept.feed.clone(),
+                        identifier: identifier.clone(),
+                    },
+                    dropped: reference,
+                    reason,
+                });
+                continue;
+            }
+
+            // Only a merged restriction keeps its NOTAM from being merged again
+            if let Some(notam) = notam {
+                notams.insert(notam, reference);
+            }
+
+            merged.insert(
+                identifier.clone(),
+                MergedRestriction {
+                    details: details.clone(),
+                    kind: feed.kind,
+                    feed: feed.name.clone(),
+                },
+            );
+        }
+    }
+
+    let report = MergeReport {
+        timestamp: Utc::now(),
+        feeds: ordered
+            .iter()
+            .map(|feed| FeedSummary {
+                kind: feed.kind,
+                feed: feed.name.clone(),
+                restrictions: feed.restrictions.len(),
+            })
+            .collect(),
+        merged: merged.len(),
+        conflicts,
+    };
+
+    (merged, report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svc_gis_client_grpc::prelude::gis::{Coordinates, ZoneType};
+
+    fn get_details(altitude_meters_max: f32, notam_id: Option<&str>) -> RestrictionDetails {
+        RestrictionDetails {
+            vertices: vec![
+                Coordinates {
+                    latitude: 30.9310,
+                    longitude: -104.0424,
+                },
+                Coordinates {
+                    latitude: 30.9316,
+                    longitude: -104.0399,
+                },
+                Coordinates {
+                    latitude: 30.9301,
+                    longitude: -104.039,
+                },
+            ],
+            timestamp_start: None,
+            timestamp_end: None,
+            zone_type: ZoneType::Restriction,
+            altitude_meters_min: 0.0,
+            altitude_meters_max,
+            notam_id: notam_id.map(|n| n.to_string()),
+        }
+    }
+
+    fn get_feed(
+        kind: SourceKind,
+        name: &str,
+        entries: Vec<(&str, RestrictionDetails)>,
+    ) -> RestrictionFeed {
+        RestrictionFeed {
+            kind,
+            name: name.to_string(),
+            restrictions: entries
+                .into_iter()
+                .map(|(id, details)| (id.to_string(), details))
+                .collect(),
+            acquired_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_priorities_from_str() {
+        crate::get_log_handle().await;
+        ut_info!("(test_priorities_from_str) Start.");
+
+        let priorities: SourcePriorities = "notam, manual,notam".parse().unwrap();
+        assert_eq!(priorities.rank(SourceKind::Notam), 0);
+        assert_eq!(priorities.rank(SourceKind::Manual), 1);
+        assert_eq!(priorities.rank(SourceKind::Static), 2);
+        assert_eq!(priorities.rank(SourceKind::File), 2);
+        assert!("notam,unknown".parse::<SourcePriorities>().is_err());
+
+        ut_info!("(test_priorities_from_str) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_merge_identifier_conflicts() {
+        crate::get_log_handle().await;
+        ut_info!("(test_merge_identifier_conflicts) Start.");
+
+        let feeds = vec![
+            get_feed(
+                SourceKind::Static,
+                "us",
+                vec![
+                    ("ZONE-A", get_details(200.0, None)),
+                    ("ZONE-B", get_details(200.0, None)),
+                ],
+            ),
+            get_feed(
+                SourceKind::File,
+                "file-b",
+                vec![("ZONE-A", get_details(400.0, None))],
+            ),
+            get_feed(
+                SourceKind::File,
+                "file-a",
+                vec![("ZONE-B", get_details(200.0, None))],
+            ),
+        ];
+
+        let (merged, report) = merge(&feeds, &SourcePriorities::default());
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged["ZONE-A"].feed, "file-b");
+        assert_eq!(merged["ZONE-A"].details.altitude_meters_max, 400.0);
+        assert_eq!(merged["ZONE-B"].feed, "file-a");
+
+        assert_eq!(report.merged, 2);
+        assert_eq!(report.feeds[0].feed, "file-a");
+        assert_eq!(report.feeds[2].feed, "us");
+        assert_eq!(report.conflicts.len(), 2);
+        assert_eq!(
+            report.conflicts[0].reason,
+            ConflictReason::ConflictingDefinition
+        );
+        assert_eq!(report.conflicts[0].dropped.feed, "us");
+        assert_eq!(
+            report.conflicts[1].reason,
+            ConflictReason::IdenticalDefinition
+        );
+
+        // Reversing the priorities changes the outcome
+        let priorities: SourcePriorities = "static,file".parse().unwrap();
+        let (merged, _) = merge(&feeds, &priorities);
+        assert_eq!(merged["ZONE-A"].feed, "us");
+
+        ut_info!("(test_merge_identifier_conflicts) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_merge_duplicate_notams() {
+        crate::get_log_handle().await;
+        ut_info!("(test_merge_duplicate_notams) Start.");
+
+        let feeds = vec![
+            get_feed(
+                SourceKind::Notam,
+                "notam-b",
+                vec![("B-1234", get_details(200.0, Some("A1234/24")))],
+            ),
+            get_feed(
+                SourceKind::Notam,
+                "notam-a",
+                vec![
+                    ("A-1234", get_details(200.0, Some("a1234/24 "))),
+                    ("A-5678", get_details(200.0, Some("A5678/24"))),
+                ],
+            ),
+        ];
+
+        // Merging in any input order gives the same result
+        for feeds in [feeds.clone(), feeds.into_iter().rev().collect()] {
+            let (merged, report) = merge(&feeds, &SourcePriorities::default());
+            assert_eq!(merged.len(), 2);
+            assert!(merged.contains_key("A-1234"));
+            assert!(merged.contains_key("A-5678"));
+            assert_eq!(report.conflicts.len(), 1);
+            assert_eq!(report.conflicts[0].reason, ConflictReason::DuplicateNotam);
+            assert_eq!(report.conflicts[0].dropped.identifier, "B-1234");
+        }
+
+        ut_info!("(test_merge_duplicate_notams) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_merge_dropped_notam() {
+        crate::get_log_handle().await;
+        ut_info!("(test_merge_dropped_notam) Start.");
+
+        // The file restriction loses its identifier, so its NOTAM is still
+        //  taken from the lower priority feed
+        let feeds = vec![
+            get_feed(
+                SourceKind::Notam,
+                "notam-a",
+                vec![("ZONE-A", get_details(200.0, None))],
+            ),
+            get_feed(
+                SourceKind::File,
+                "file-a",
+                vec![("ZONE-A", get_details(400.0, Some("A1234/24")))],
+            ),
+            get_feed(
+                SourceKind::Static,
+                "us",
+                vec![("US-1234", get_details(400.0, Some("A1234/24")))],
+            ),
+        ];
+
+        let (merged, report) = merge(&feeds, &SourcePriorities::default());
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged["ZONE-A"].feed, "notam-a");
+        assert_eq!(merged["US-1234"].feed, "us");
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(
+            report.conflicts[0].reason,
+            ConflictReason::ConflictingDefinition
+        );
+        assert_eq!(report.conflicts[0].dropped.feed, "file-a");
+
+        ut_info!("(test_merge_dropped_notam) Success.");
+    }
+}
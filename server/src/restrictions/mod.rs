@@ -0,0 +1,521 @@
+//! Shared store of the flight restrictions known to this service
+//!
+//! Restrictions are kept per feed (region data, files, NOTAMs, operator
+//!  entries) and merged by source priority before they are pushed to svc-gis.
+
+#[macro_use]
+pub mod macros;
+pub mod buffer;
+pub mod manual;
+pub mod merge;
+pub mod simplify;
+
+use crate::region::RestrictionDetails;
+use chrono::{DateTime, Utc};
+use manual::ManualRestriction;
+use merge::{MergeReport, MergedRestriction, RestrictionFeed, SourceKind, SourcePriorities};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tokio::sync::{Notify, RwLock};
+
+/// Name of the feed holding the manual restrictions
+pub const MANUAL_FEED_NAME: &str = "manual";
+
+/// Errors returned by the restriction store
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum RestrictionError {
+    /// A restriction with this identifier already exists
+    #[error("error: Restriction {0} already exists.")]
+    AlreadyExists(String),
+
+    /// No manual restriction with this identifier exists
+    #[error("error: Restriction {0} not found.")]
+    NotFound(String),
+
+    /// The provided restriction is invalid
+    #[error("error: Invalid restriction: {0}.")]
+    InvalidArgument(String),
+
+    /// The restrictions could not be persisted or loaded
+    #[error("error: Could not persist restrictions: {0}.")]
+    Persistence(String),
+}
+
+/// A merged restriction as listed to operators
+#[derive(Debug, Clone)]
+pub struct ListedRestriction {
+    /// The restriction identifier
+    pub identifier: String,
+
+    /// The restriction and the feed it was taken from
+    pub merged: MergedRestriction,
+
+    /// The operator provided details, for manual restrictions
+    pub manual: Option<ManualRestriction>,
+}
+
+/// In memory store of restrictions, shared between the gRPC server and the
+///  refresh loop
+#[derive(Debug, Default)]
+pub struct RestrictionStore {
+    /// Restrictions acquired from automated sources, by feed name
+    feeds: RwLock<HashMap<String, RestrictionFeed>>,
+
+    /// Restrictions entered by operators
+    manual: RwLock<HashMap<String, ManualRestriction>>,
+
+    /// Precedence of the sources when merging the feeds
+    priorities: SourcePriorities,
+
+    /// File used to persist the manual restrictions across restarts
+    manual_file: Option<PathBuf>,
+
+    /// Notified when the manual restrictions change
+    changed: Notify,
+}
+
+impl RestrictionStore {
+    /// Create a new store, persisting manual restrictions to the provided
+    ///  file if any
+    pub fn new(manual_file: Option<PathBuf>, priorities: SourcePriorities) -> Self {
+        Self {
+            manual_file,
+            priorities,
+            ..Default::default()
+        }
+    }
+
+    /// Load previously persisted manual restrictions
+    pub async fn load(&self) -> Result<usize, RestrictionError> {
+        let Some(path) = &self.manual_file else {
+            return Ok(0);
+        };
+
+        let loaded = manual::load_from_file(path).await?;
+        let count = loaded.len();
+        *self.manual.write().await = loaded;
+
+        restrictions_info!("(load) Loaded {} manual restriction(s).", count);
+        Ok(count)
+    }
+
+    /// Replace the restrictions of a feed
+    ///
+    /// Manual restrictions are managed through
+    ///  [`create_manual`](Self::create_manual) and friends instead.
+    pub async fn set_feed(&self, feed: RestrictionFeed) {
+        restrictions_debug!(
+            "(set_feed) Feed {} ({}) provided {} restriction(s).",
+            feed.name,
+            feed.kind,
+            feed.restrictions.len()
+        );
+
+        self.feeds.write().await.insert(feed.name.clone(), feed);
+    }
+
+    /// Returns the feeds acquired from automated sources, sorted by name
+    pub async fn feeds(&self) -> Vec<RestrictionFeed> {
+        let mut feeds: Vec<RestrictionFeed> = self.feeds.read().await.values().cloned().collect();
+        feeds.sort_by(|a, b| a.name.cmp(&b.name));
+        feeds
+    }
+
+    /// Restore feeds from a snapshot
+    ///
+    /// Feeds acquired since the service started are more recent and are left
+    ///  alone. Returns the number of feeds restored.
+    pub async fn restore_feeds(&self, restored: Vec<RestrictionFeed>) -> usize {
+        let mut feeds = self.feeds.write().await;
+        let mut count = 0;
+        for feed in restored {
+            if feeds.contains_key(&feed.name) {
+                continue;
+            }
+
+            restrictions_info!(
+                "(restore_feeds) Restoring feed {} ({}) with {} restriction(s).",
+                feed.name,
+                feed.kind,
+                feed.restrictions.len()
+            );
+            feeds.insert(feed.name.clone(), feed);
+            count += 1;
+        }
+
+        count
+    }
+
+    /// Merges all feeds according to the source priorities
+    pub async fn merge(&self) -> (HashMap<String, MergedRestriction>, MergeReport) {
+        let mut feeds: Vec<RestrictionFeed> = self.feeds.read().await.values().cloned().collect();
+        feeds.push(RestrictionFeed {
+            kind: SourceKind::Manual,
+            name: MANUAL_FEED_NAME.to_string(),
+            restrictions: self
+                .manual
+                .read()
+                .await
+                .iter()
+                .map(|(identifier, restriction)| (identifier.clone(), restriction.details.clone()))
+                .collect(),
+            acquired_at: Utc::now(),
+        });
+
+        let (merged, report) = merge::merge(&feeds, &self.priorities);
+        for conflict in report.conflicts.iter() {
+            restrictions_debug!(
+                "(merge) Dropped {} from {} in favor of {} from {}: {}.",
+                conflict.dropped.identifier,
+                conflict.dropped.feed,
+                conflict.kept.identifier,
+                conflict.kept.feed,
+                conflict.reason
+            );
+        }
+
+        (merged, report)
+    }
+
+    /// Returns all restrictions to be pushed to svc-gis
+    pub async fn get_all(&self) -> HashMap<String, RestrictionDetails> {
+        let (merged, _) = self.merge().await;
+        merged
+            .into_iter()
+            .map(|(identifier, restriction)| (identifier, restriction.details))
+            .collect()
+    }
+
+    /// Returns the merged restrictions with the feed they were taken from,
+    ///  sorted by identifier
+    pub async fn list(&self) -> Vec<ListedRestriction> {
+        let (merged, _) = self.merge().await;
+        let manual = self.manual.read().await;
+        let mut restrictions: Vec<ListedRestriction> = merged
+            .into_iter()
+            .map(|(identifier, merged)| ListedRestriction {
+                manual: match merged.kind {
+                    SourceKind::Manual => manual.get(&identifier).cloned(),
+                    _ => None,
+                },
+                identifier,
+                merged,
+            })
+            .collect();
+
+        restrictions.sort_by(|a, b| a.identifier.cmp(&b.identifier));
+        restrictions
+    }
+
+    /// Add a new manual restriction
+    pub async fn create_manual(
+        &self,
+        identifier: String,
+        restriction: ManualRestriction,
+    ) -> Result<(), RestrictionError> {
+        restriction.validate()?;
+
+        if self
+            .feeds
+            .read()
+            .await
+            .values()
+            .any(|feed| feed.restrictions.contains_key(&identifier))
+        {
+            return Err(RestrictionError::AlreadyExists(identifier));
+        }
+
+        let mut manual = self.manual.write().await;
+        if let Some(existing) = manual.get(&identifier) {
+            // Expired restrictions may be replaced until they are purged
+            if !existing.is_expired(Utc::now()) {
+                return Err(RestrictionError::AlreadyExists(identifier));
+            }
+        }
+
+        let mut updated = manual.clone();
+        updated.insert(identifier.clone(), restriction);
+        self.persist(&updated).await?;
+        *manual = updated;
+        restrictions_info!(
+            "(create_manual) Restriction {} created by {}: {}",
+            identifier,
+            manual[&identifier].author,
+            manual[&identifier].reason
+        );
+        drop(manual);
+
+        self.changed.notify_one();
+        Ok(())
+    }
+
+    /// Replace an existing manual restriction, keeping its creation time
+    pub async fn update_manual(
+        &self,
+        identifier: String,
+        mut restriction: ManualRestriction,
+    ) -> Result<(), RestrictionError> {
+        restriction.validate()?;
+
+        let mut manual = self.manual.write().await;
+        let Some(existing) = manual.get(&identifier) else {
+            return Err(RestrictionError::NotFound(identifier));
+        };
+
+        if existing.is_expired(Utc::now()) {
+            return Err(RestrictionError::NotFound(identifier));
+        }
+
+        restriction.created_at = existing.created_at;
+        let mut updated = manual.clone();
+        updated.insert(identifier.clone(), restriction);
+        self.persist(&updated).await?;
+        *manual = updated;
+        restrictions_info!(
+            "(update_manual) Restriction {} updated by {}: {}",
+            identifier,
+            manual[&identifier].author,
+            manual[&identifier].reason
+        );
+        drop(manual);
+
+        self.changed.notify_one();
+        Ok(())
+    }
+
+    /// Ends a manual restriction
+    ///
+    /// The restriction is expired rather than removed, so the next push to
+    ///  svc-gis carries the new end time. It is removed from the store once
+    ///  that push succeeded.
+    pub async fn delete_manual(
+        &self,
+        identifier: String,
+        author: String,
+    ) -> Result<(), RestrictionError> {
+        let now = Utc::now();
+        let mut manual = self.manual.write().await;
+        let mut updated = manual.clone();
+        let Some(existing) = updated.get_mut(&identifier) else {
+            return Err(RestrictionError::NotFound(identifier));
+        };
+
+        if existing.is_expired(now) {
+            return Err(RestrictionError::NotFound(identifier));
+        }
+
+        existing.details.timestamp_end = Some(now);
+        existing.updated_at = now;
+        existing.author = author;
+
+        self.persist(&updated).await?;
+        *manual = updated;
+        restrictions_info!(
+            "(delete_manual) Restriction {} deleted by {}.",
+            identifier,
+            manual[&identifier].author
+        );
+        drop(manual);
+
+        self.changed.notify_one();
+        Ok(())
+    }
+
+    /// Remove manual restrictions that expired before the provided time
+    ///
+    /// Call this after a successful push to svc-gis with the time the pushed
+    ///  restrictions were read from the store.
+    pub async fn purge_expired(&self, before: DateTime<Utc>) {
+        let mut manual = self.manual.write().await;
+        let count = manual.len();
+        manual.retain(|_, restriction| !restriction.is_expired(before));
+
+        if manual.len() == count {
+            return;
+        }
+
+        restrictions_info!(
+            "(purge_expired) Removed {} expired manual restriction(s).",
+            count - manual.len()
+        );
+
+        if let Err(e) = self.persist(&manual).await {
+            restrictions_error!("(purge_expired) {}", e);
+        }
+    }
+
+    /// Waits until the manual restrictions change
+    pub async fn changed(&self) {
+        self.changed.notified().await
+    }
+
+    async fn persist(
+        &self,
+        manual: &HashMap<String, ManualRestriction>,
+    ) -> Result<(), RestrictionError> {
+        match &self.manual_file {
+            Some(path) => manual::save_to_file(path, manual).await,
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use svc_gis_client_grpc::prelude::gis::{Coordinates, ZoneType};
+
+    fn get_details(hours: i64) -> RestrictionDetails {
+        RestrictionDetails {
+            vertices: vec![
+                Coordinates {
+                    latitude: 52.3688393,
+                    longitude: 4.8822724,
+                },
+                Coordinates {
+                    latitude: 52.3781666,
+                    longitude: 4.8832170,
+                },
+                Coordinates {
+                    latitude: 52.3777998,
+                    longitude: 4.9007345,
+                },
+                Coordinates {
+                    latitude: 52.3688393,
+                    longitude: 4.8822724,
+                },
+            ],
+            timestamp_start: None,
+            timestamp_end: Some(Utc::now() + Duration::try_hours(hours).unwrap()),
+            zone_type: ZoneType::Restriction,
+            altitude_meters_min: 0.0,
+            altitude_meters_max: 500.0,
+            notam_id: None,
+        }
+    }
+
+    fn get_manual_restriction() -> ManualRestriction {
+        ManualRestriction {
+            details: get_details(1),
+            reason: "Event".to_string(),
+            author: "operator".to_string(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_manual_lifecycle() {
+        crate::get_log_handle().await;
+        ut_info!("(test_manual_lifecycle) Start.");
+
+        let store = RestrictionStore::default();
+        let mut authority = HashMap::new();
+        authority.insert("ARROW-AUTHORITY".to_string(), get_details(2));
+        store
+            .set_feed(RestrictionFeed {
+                kind: SourceKind::Static,
+                name: "us".to_string(),
+                restrictions: authority,
+                acquired_at: Utc::now(),
+            })
+            .await;
+
+        // Can't shadow an authority restriction
+        let result = store
+            .create_manual("ARROW-AUTHORITY".to_string(), get_manual_restriction())
+            .await;
+        assert_eq!(
+            result,
+            Err(RestrictionError::AlreadyExists(
+                "ARROW-AUTHORITY".to_string()
+            ))
+        );
+
+        let identifier = "ARROW-MANUAL".to_string();
+        store
+            .create_manual(identifier.clone(), get_manual_restriction())
+            .await
+            .unwrap();
+        assert!(store
+            .create_manual(identifier.clone(), get_manual_restriction())
+            .await
+            .is_err());
+        assert_eq!(store.get_all().await.len(), 2);
+
+        let mut updated = get_manual_restriction();
+        updated.details.altitude_meters_max = 1000.0;
+        store
+            .update_manual(identifier.clone(), updated)
+            .await
+            .unwrap();
+        assert_eq!(
+            store.get_all().await[&identifier].altitude_meters_max,
+            1000.0
+        );
+
+        let list = store.list().await;
+        assert_eq!(list.len(), 2);
+        assert!(list.iter().any(|r| r.identifier == identifier
+            && r.merged.kind == SourceKind::Manual
+            && r.manual.is_some()));
+
+        store
+            .delete_manual(identifier.clone(), "supervisor".to_string())
+            .await
+            .unwrap();
+
+        // Still pushed once with the new end time
+        let all = store.get_all().await;
+        assert!(all[&identifier].timestamp_end.unwrap() <= Utc::now());
+
+        store.purge_expired(Utc::now()).await;
+        assert_eq!(store.get_all().await.len(), 1);
+        assert_eq!(
+            store
+                .update_manual(identifier.clone(), get_manual_restriction())
+                .await,
+            Err(RestrictionError::NotFound(identifier))
+        );
+
+        ut_info!("(test_manual_lifecycle) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_persistence() {
+        crate::get_log_handle().await;
+        ut_info!("(test_persistence) Start.");
+
+        let path = std::env::temp_dir().join(format!(
+            "svc-compliance-store-{}.json",
+            uuid::Uuid::new_v4()
+        ));
+
+        let store = RestrictionStore::new(Some(path.clone()), SourcePriorities::default());
+        assert_eq!(store.load().await, Ok(0));
+        store
+            .create_manual("ARROW-MANUAL".to_string(), get_manual_restriction())
+            .await
+            .unwrap();
+
+        let store = RestrictionStore::new(Some(path.clone()), SourcePriorities::default());
+        assert_eq!(store.load().await, Ok(1));
+        assert!(store.get_all().await.contains_key("ARROW-MANUAL"));
+
+        let _ = tokio::fs::remove_file(&path).await;
+
+        // Changes that could not be written are not kept
+        let path = std::env::temp_dir()
+            .join(uuid::Uuid::new_v4().to_string())
+            .join("manual.json");
+        let store = RestrictionStore::new(Some(path), SourcePriorities::default());
+        let result = store
+            .create_manual("ARROW-MANUAL".to_string(), get_manual_restriction())
+            .await;
+        assert!(matches!(result, Err(RestrictionError::Persistence(_))));
+        assert!(store.get_all().await.is_empty());
+
+        ut_info!("(test_persistence) Success.");
+    }
+}
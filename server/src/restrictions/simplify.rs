@@ -0,0 +1,527 @@
+//! Conservative simplification of restriction polygons
+//!
+//! Imported airspace can carry thousands of vertices, which slows down
+//!  routing in svc-gis. Polygons are simplified before they are pushed, but
+//!  only ever grown: every step adds area outside the zone, never removes it.
+//!
+//! Two kinds of steps are applied, smallest added area first:
+//! - a concave vertex is removed, adding the triangle it formed with its
+//!   neighbours
+//! - an edge between two convex vertices is removed by extending the
+//!   neighbouring edges until they meet
+//!
+//! Steps are applied while the accumulated outward deviation stays within
+//!  the tolerance, and beyond it while the polygon exceeds the vertex budget.
+//!  If the budget can't be met, the convex hull is reduced instead.
+
+use crate::region::utils::{
+    convex_hull, cross, open_ring, point_in_polygon, point_in_triangle, segment_distance,
+    segments_intersect, signed_area_2x, LocalPoint, LocalProjection,
+};
+use crate::region::RestrictionDetails;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Smallest vertex budget, any convex polygon can be reduced to four vertices
+///  and the closing vertex
+const MIN_VERTICES: usize = 5;
+
+/// Polygons with more vertices than this are simplified on a blocking thread,
+///  so the refresh does not hold up other tasks
+const BLOCKING_VERTICES: usize = 1000;
+
+/// Distance in meters within which original vertices are considered on the
+///  simplified boundary
+const CONTAINMENT_TOLERANCE_METERS: f64 = 1e-3;
+
+/// Polygon simplification settings
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct SimplifyConfig {
+    /// Largest distance in meters the boundary may move outward
+    pub tolerance_meters: f64,
+
+    /// Largest number of vertices pushed for a single zone, including the
+    ///  closing vertex
+    pub max_vertices: usize,
+}
+
+impl Default for SimplifyConfig {
+    fn default() -> Self {
+        Self {
+            tolerance_meters: 10.0,
+            max_vertices: 500,
+        }
+    }
+}
+
+/// A simplification step
+#[derive(Debug, Copy, Clone)]
+enum Step {
+    /// Remove the concave vertex
+    RemoveVertex(usize),
+
+    /// Move the vertex to the provided point and remove the vertex after it
+    ExtendEdge(usize, LocalPoint),
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Candidate {
+    step: Step,
+    area: f64,
+    deviation: f64,
+}
+
+/// Polygon as a doubly linked list, so vertices can be removed in place
+#[derive(Debug)]
+struct Ring {
+    points: Vec<LocalPoint>,
+    prev: Vec<usize>,
+    next: Vec<usize>,
+    alive: Vec<bool>,
+
+    /// Accumulated outward deviation of the edge starting at each vertex
+    deviation: Vec<f64>,
+
+    count: usize,
+}
+
+impl Ring {
+    fn new(points: Vec<LocalPoint>) -> Self {
+        let count = points.len();
+        Self {
+            prev: (0..count).map(|i| (i + count - 1) % count).collect(),
+            next: (0..count).map(|i| (i + 1) % count).collect(),
+            alive: vec![true; count],
+            deviation: vec![0.0; count],
+            points,
+            count,
+        }
+    }
+
+    fn indices(&self) -> Vec<usize> {
+        (0..self.points.len()).filter(|i| self.alive[*i]).collect()
+    }
+
+    fn to_points(&self) -> Vec<LocalPoint> {
+        let Some(start) = (0..self.points.len()).find(|i| self.alive[*i]) else {
+            return vec![];
+        };
+
+        let mut result = vec![self.points[start]];
+        let mut i = self.next[start];
+        while i != start {
+            result.push(self.points[i]);
+            i = self.next[i];
+        }
+
+        result
+    }
+
+    fn candidates(&self) -> Vec<Candidate> {
+        let mut candidates = vec![];
+        for b in self.indices() {
+            let a = self.prev[b];
+            let c = self.next[b];
+            let d = self.next[c];
+            let (pa, pb, pc, pd) = (
+                &self.points[a],
+                &self.points[b],
+                &self.points[c],
+                &self.points[d],
+            );
+
+            let turn_b = cross(pa, pb, pc);
+            if turn_b <= 0.0 {
+                candidates.push(Candidate {
+                    step: Step::RemoveVertex(b),
+                    area: -turn_b / 2.0,
+                    deviation: self.deviation[a].max(self.deviation[b])
+                        + segment_distance(pb, pa, pc),
+                });
+                continue;
+            }
+
+            if self.count < 5 || cross(pb, pc, pd) <= 0.0 {
+                continue;
+            }
+
+            // Intersect the lines a -> b and d -> c beyond b and c
+            let r = (pb.x - pa.x, pb.y - pa.y);
+            let s = (pc.x - pd.x, pc.y - pd.y);
+            let denominator = r.0 * s.1 - r.1 * s.0;
+            if denominator.abs() < 1e-12 {
+                continue;
+            }
+
+            let w = (pd.x - pa.x, pd.y - pa.y);
+            let t = (w.0 * s.1 - w.1 * s.0) / denominator;
+            let u = (w.0 * r.1 - w.1 * r.0) / denominator;
+            if t <= 1.0 || u <= 1.0 {
+                continue;
+            }
+
+            let x = LocalPoint {
+                x: pa.x + t * r.0,
+                y: pa.y + t * r.1,
+            };
+
+            candidates.push(Candidate {
+                step: Step::ExtendEdge(b, x),
+                area: cross(pb, &x, pc).abs() / 2.0,
+                deviation: self.deviation[a]
+                    .max(self.deviation[b])
+                    .max(self.deviation[c])
+                    + segment_distance(&x, pb, pc),
+            });
+        }
+
+        candidates
+    }
+
+    /// Returns true if no other part of the polygon lies within the added
+    ///  area, so the step keeps the polygon simple
+    fn is_valid(&self, step: &Step) -> bool {
+        let (triangle, edges, skip) = match *step {
+            Step::RemoveVertex(b) => {
+                let (a, c) = (self.prev[b], self.next[b]);
+                (
+                    [self.points[a], self.points[b], self.points[c]],
+                    vec![],
+                    [a, b, c, c],
+                )
+            }
+            Step::ExtendEdge(b, x) => {
+                let (a, c, d) = (self.prev[b], self.next[b], self.next[self.next[b]]);
+                (
+                    [self.points[b], x, self.points[c]],
+                    vec![(self.points[b], x), (x, self.points[c])],
+                    [a, b, c, d],
+                )
+            }
+        };
+
+        for i in self.indices() {
+            if !skip.contains(&i)
+                && point_in_triangle(&self.points[i], &triangle[0], &triangle[1], &triangle[2])
+            {
+                return false;
+            }
+
+            let j = self.next[i];
+            if skip.contains(&i) || skip.contains(&j) {
+                continue;
+            }
+
+            if edges
+                .iter()
+                .any(|(p, q)| segments_intersect(p, q, &self.points[i], &self.points[j]))
+            {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn apply(&mut self, candidate: &Candidate) {
+        match candidate.step {
+            Step::RemoveVertex(b) => {
+                let (a, c) = (self.prev[b], self.next[b]);
+                self.next[a] = c;
+                self.prev[c] = a;
+                self.alive[b] = false;
+                self.deviation[a] = candidate.deviation;
+            }
+            Step::ExtendEdge(b, x) => {
+                let (a, c) = (self.prev[b], self.next[b]);
+                let d = self.next[c];
+                self.points[b] = x;
+                self.next[b] = d;
+                self.prev[d] = b;
+                self.alive[c] = false;
+                self.deviation[a] = self.deviation[a].max(candidate.deviation);
+                self.deviation[b] = candidate.deviation;
+            }
+        }
+
+        self.count -= 1;
+    }
+
+    /// Vertices touched by a step
+    fn window(&self, step: &Step) -> [usize; 4] {
+        match *step {
+            Step::RemoveVertex(b) => [self.prev[b], b, self.next[b], self.next[b]],
+            Step::ExtendEdge(b, _) => [self.prev[b], b, self.next[b], self.next[self.next[b]]],
+        }
+    }
+}
+
+/// Simplifies a counter-clockwise ring without shrinking it
+fn reduce(points: Vec<LocalPoint>, tolerance: f64, max_vertices: usize) -> Vec<LocalPoint> {
+    let mut ring = Ring::new(points);
+    loop {
+        let mut candidates = ring.candidates();
+        candidates.sort_by(|a, b| a.area.total_cmp(&b.area));
+
+        // Apply non overlapping steps in a single pass
+        let mut touched = vec![false; ring.points.len()];
+        let mut applied = 0;
+        for candidate in candidates.iter() {
+            if ring.count <= 3 {
+                break;
+            }
+
+            if ring.count <= max_vertices && candidate.deviation > tolerance {
+                continue;
+            }
+
+            let window = ring.window(&candidate.step);
+            if window.iter().any(|i| touched[*i]) || !ring.is_valid(&candidate.step) {
+                continue;
+            }
+
+            ring.apply(candidate);
+            window.iter().for_each(|i| touched[*i] = true);
+            applied += 1;
+        }
+
+        if applied == 0 {
+            break;
+        }
+    }
+
+    ring.to_points()
+}
+
+/// Returns the restriction with a simplified boundary containing the
+///  original one
+pub fn simplify_restriction(
+    details: &RestrictionDetails,
+    config: &SimplifyConfig,
+) -> RestrictionDetails {
+    let ring = open_ring(&details.vertices);
+
+    // The pushed polygon is closed by repeating its first vertex
+    let max_vertices = config.max_vertices.max(MIN_VERTICES) - 1;
+    if ring.len() <= 3 || (ring.len() <= max_vertices && config.tolerance_meters <= 0.0) {
+        return details.clone();
+    }
+
+    let projection = LocalProjection::new(&ring[0]);
+    let mut points: Vec<LocalPoint> = ring.iter().map(|v| projection.to_local(v)).collect();
+    if signed_area_2x(&points) < 0.0 {
+        points.reverse();
+    }
+
+    let mut simplified = reduce(points.clone(), config.tolerance_meters, max_vertices);
+    if simplified.len() > max_vertices {
+        restrictions_warn!(
+            "(simplify_restriction) Could not reduce {} vertices to {}, reducing the convex hull.",
+            points.len(),
+            max_vertices
+        );
+
+        simplified = reduce(convex_hull(&points), config.tolerance_meters, max_vertices);
+    }
+
+    // Never push a polygon that does not contain the original
+    if simplified.len() < 3
+        || !points
+            .iter()
+            .all(|p| point_in_polygon(p, &simplified, CONTAINMENT_TOLERANCE_METERS))
+    {
+        restrictions_error!("(simplify_restriction) Simplified polygon does not contain the original, keeping the original.");
+        return details.clone();
+    }
+
+    if simplified.len() == points.len() {
+        return details.clone();
+    }
+
+    let mut vertices: Vec<_> = simplified
+        .iter()
+        .map(|p| projection.to_coordinates(p))
+        .collect();
+    if let Some(first) = vertices.first().cloned() {
+        vertices.push(first);
+    }
+
+    let mut result = details.clone();
+    result.vertices = vertices;
+    result
+}
+
+/// Simplifies all restrictions to be pushed to svc-gis
+///
+/// Large polygons are simplified on a blocking thread.
+pub async fn simplify_restrictions(
+    restrictions: HashMap<String, RestrictionDetails>,
+    config: &SimplifyConfig,
+) -> HashMap<String, RestrictionDetails> {
+    let large = restrictions
+        .values()
+        .any(|details| details.vertices.len() > BLOCKING_VERTICES);
+    if !large {
+        return simplify_all(restrictions, config);
+    }
+
+    let config = *config;
+    let original = restrictions.clone();
+    let handle = tokio::task::spawn_blocking(move || simplify_all(original, &config));
+    match handle.await {
+        Ok(simplified) => simplified,
+        Err(e) => {
+            restrictions_error!(
+                "(simplify_restrictions) Simplification failed, keeping the originals: {}",
+                e
+            );
+            restrictions
+        }
+    }
+}
+
+fn simplify_all(
+    restrictions: HashMap<String, RestrictionDetails>,
+    config: &SimplifyConfig,
+) -> HashMap<String, RestrictionDetails> {
+    restrictions
+        .into_iter()
+        .map(|(identifier, details)| {
+            let simplified = simplify_restriction(&details, config);
+            if simplified.vertices.len() != details.vertices.len() {
+                restrictions_debug!(
+                    "(simplify_restrictions) {}: {} -> {} vertices.",
+                    identifier,
+                    details.vertices.len(),
+                    simplified.vertices.len()
+                );
+            }
+
+            (identifier, simplified)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+    use svc_gis_client_grpc::prelude::gis::{Coordinates, ZoneType};
+
+    /// Polygon around Rotterdam with a wavy boundary of many vertices
+    fn get_details(count: usize, waves: f64) -> RestrictionDetails {
+        let center = Coordinates {
+            latitude: 51.9225,
+            longitude: 4.4792,
+        };
+        let projection = LocalProjection::new(&center);
+        let mut vertices: Vec<Coordinates> = (0..count)
+            .map(|i| {
+                let angle = 2.0 * PI * i as f64 / count as f64;
+                let radius = 1000.0 + 50.0 * (angle * waves).sin();
+                projection.to_coordinates(&LocalPoint {
+                    x: radius * angle.cos(),
+                    y: radius * angle.sin(),
+                })
+            })
+            .collect();
+        vertices.extend(vertices.first().cloned());
+
+        RestrictionDetails {
+            vertices,
+            timestamp_start: None,
+            timestamp_end: None,
+            zone_type: ZoneType::Restriction,
+            altitude_meters_min: 0.0,
+            altitude_meters_max: 500.0,
+            notam_id: None,
+        }
+    }
+
+    fn assert_contains(simplified: &RestrictionDetails, original: &RestrictionDetails) {
+        let projection = LocalProjection::new(&original.vertices[0]);
+        let ring: Vec<LocalPoint> = open_ring(&simplified.vertices)
+            .iter()
+            .map(|v| projection.to_local(v))
+            .collect();
+
+        for vertex in original.vertices.iter() {
+            let p = projection.to_local(vertex);
+            assert!(point_in_polygon(&p, &ring, 1e-3), "{p:?} outside");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_simplify_tolerance() {
+        crate::get_log_handle().await;
+        ut_info!("(test_simplify_tolerance) Start.");
+
+        let details = get_details(720, 0.0);
+        let config = SimplifyConfig {
+            tolerance_meters: 5.0,
+            max_vertices: 1000,
+        };
+
+        let simplified = simplify_restriction(&details, &config);
+        ut_debug!(
+            "(test_simplify_tolerance) {} -> {} vertices.",
+            details.vertices.len(),
+            simplified.vertices.len()
+        );
+        assert!(simplified.vertices.len() < details.vertices.len() / 2);
+        assert_eq!(simplified.vertices.first(), simplified.vertices.last());
+        assert_contains(&simplified, &details);
+
+        // Within tolerance of the original circle
+        let projection = LocalProjection::new(&Coordinates {
+            latitude: 51.9225,
+            longitude: 4.4792,
+        });
+        for vertex in simplified.vertices.iter() {
+            let p = projection.to_local(vertex);
+            assert!((p.x * p.x + p.y * p.y).sqrt() < 1005.0 + 1e-3);
+        }
+
+        ut_info!("(test_simplify_tolerance) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_simplify_budget() {
+        crate::get_log_handle().await;
+        ut_info!("(test_simplify_budget) Start.");
+
+        // Star like shape with concave and convex stretches
+        let details = get_details(600, 7.0);
+        let config = SimplifyConfig {
+            tolerance_meters: 1.0,
+            max_vertices: 24,
+        };
+
+        let simplified = simplify_restriction(&details, &config);
+        assert!(simplified.vertices.len() <= 24);
+        assert_contains(&simplified, &details);
+
+        // Nothing to do for small polygons without tolerance
+        let details = get_details(12, 0.0);
+        let config = SimplifyConfig {
+            tolerance_meters: 0.0,
+            max_vertices: 500,
+        };
+        let simplified = simplify_restriction(&details, &config);
+        assert_eq!(simplified.vertices, details.vertices);
+
+        // Large polygons are simplified on a blocking thread
+        let details = get_details(BLOCKING_VERTICES + 200, 7.0);
+        let config = SimplifyConfig {
+            tolerance_meters: 1.0,
+            max_vertices: 64,
+        };
+        let restrictions = HashMap::from([("ZONE".to_string(), details.clone())]);
+        let simplified = simplify_restrictions(restrictions, &config).await;
+        assert!(simplified["ZONE"].vertices.len() <= 64);
+        assert_contains(&simplified["ZONE"], &details);
+
+        ut_info!("(test_simplify_budget) Success.");
+    }
+}
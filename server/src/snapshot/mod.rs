@@ -0,0 +1,350 @@
+//! Snapshots of the acquired restrictions and waypoints
+//!
+//! The stores are written to local files after every successful acquisition
+//!  and reloaded on boot, so a restart during an outage of the regional
+//!  authority does not forget active restrictions. Feeds and waypoints
+//!  acquired longer ago than the configured maximum age are not restored.
+
+#[macro_use]
+pub mod macros;
+
+use crate::config::Config;
+use crate::region::WaypointDetails;
+use crate::restrictions::merge::RestrictionFeed;
+use crate::restrictions::RestrictionStore;
+use crate::waypoints::WaypointStore;
+use chrono::{DateTime, Duration, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Name of the restrictions snapshot file
+pub const RESTRICTIONS_FILE: &str = "restrictions.json";
+
+/// Name of the waypoints snapshot file
+pub const WAYPOINTS_FILE: &str = "waypoints.json";
+
+/// Errors returned when saving or loading snapshots
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum SnapshotError {
+    /// The snapshot could not be written or read
+    #[error("error: Could not persist snapshot: {0}.")]
+    Persistence(String),
+}
+
+/// Data saved with the time it was taken
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot<T> {
+    /// When the snapshot was taken
+    pub timestamp: DateTime<Utc>,
+
+    /// The saved data
+    pub data: T,
+}
+
+/// Waypoints saved with the time they were acquired
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AcquiredWaypoints {
+    /// When the waypoints were acquired, by the source acquired the longest
+    ///  ago
+    pub acquired_at: DateTime<Utc>,
+
+    /// The waypoints, by identifier
+    pub waypoints: HashMap<String, WaypointDetails>,
+}
+
+/// Writes a snapshot of the data to the provided file, see
+///  [`write_atomic`](crate::files::write_atomic)
+pub async fn save<T: Serialize>(path: &Path, data: &T) -> Result<(), SnapshotError> {
+    let contents = serde_json::to_vec(&Snapshot {
+        timestamp: Utc::now(),
+        data,
+    })
+    .map_err(|e| {
+        snapshot_error!("(save) Could not serialize snapshot: {}", e);
+        SnapshotError::Persistence(e.to_string())
+    })?;
+
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await.map_err(|e| {
+            snapshot_error!("(save) Could not create {:?}: {}", parent, e);
+            SnapshotError::Persistence(e.to_string())
+        })?;
+    }
+
+    crate::files::write_atomic(path, &contents)
+        .await
+        .map_err(|e| {
+            snapshot_error!("(save) Could not write {:?}: {}", path, e);
+            SnapshotError::Persistence(e.to_string())
+        })
+}
+
+/// Reads a snapshot from the provided file
+///
+/// Returns `None` if there is no snapshot or if it is older than the maximum
+///  age.
+pub async fn load<T: DeserializeOwned>(
+    path: &Path,
+    max_age: Duration,
+) -> Result<Option<Snapshot<T>>, SnapshotError> {
+    let contents = match tokio::fs::read(path).await {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            snapshot_info!("(load) No snapshot found at {:?}.", path);
+            return Ok(None);
+        }
+        Err(e) => {
+            snapshot_error!("(load) Could not read {:?}: {}", path, e);
+            return Err(SnapshotError::Persistence(e.to_string()));
+        }
+    };
+
+    let snapshot: Snapshot<T> = serde_json::from_slice(&contents).map_err(|e| {
+        snapshot_error!("(load) Could not parse {:?}: {}", path, e);
+        SnapshotError::Persistence(e.to_string())
+    })?;
+
+    let age = Utc::now() - snapshot.timestamp;
+    if age > max_age {
+        snapshot_warn!(
+            "(load) Ignoring snapshot {:?} taken at {}, older than {} seconds.",
+            path,
+            snapshot.timestamp,
+            max_age.num_seconds()
+        );
+        return Ok(None);
+    }
+
+    Ok(Some(snapshot))
+}
+
+/// Returns the path of a snapshot file in the configured directory
+fn snapshot_path(config: &Config, file: &str) -> PathBuf {
+    Path::new(&config.snapshot_dir).join(file)
+}
+
+/// Saves the restriction feeds acquired from automated sources
+pub async fn save_restrictions(config: &Config, store: &RestrictionStore) {
+    let path = snapshot_path(config, RESTRICTIONS_FILE);
+    if let Err(e) = save(&path, &store.feeds().await).await {
+        snapshot_error!("(save_restrictions) {}", e);
+    }
+}
+
+/// Saves the waypoints accepted from the regional authority
+pub async fn save_waypoints(config: &Config, store: &WaypointStore) {
+    let Some(acquired_at) = store.acquired_at().await else {
+        snapshot_debug!("(save_waypoints) No waypoints acquired yet.");
+        return;
+    };
+
+    let path = snapshot_path(config, WAYPOINTS_FILE);
+    let waypoints = AcquiredWaypoints {
+        acquired_at,
+        waypoints: store.get_all().await,
+    };
+    if let Err(e) = save(&path, &waypoints).await {
+        snapshot_error!("(save_waypoints) {}", e);
+    }
+}
+
+/// Restores the restriction feeds and waypoints acquired no longer ago than
+///  the configured maximum age
+///
+/// The age is checked per feed, so a feed whose source kept failing before
+///  the snapshot was taken is not restored along with fresher feeds.
+///  Restrictions that ended since the snapshot was taken are left out, so
+///  they are not pushed again.
+pub async fn restore(config: &Config, restrictions: &RestrictionStore, waypoints: &WaypointStore) {
+    let Some(max_age) = Duration::try_seconds(i64::from(config.snapshot_max_age_seconds)) else {
+        snapshot_error!("(restore) Invalid maximum snapshot age.");
+        return;
+    };
+
+    let path = snapshot_path(config, RESTRICTIONS_FILE);
+    match load::<Vec<RestrictionFeed>>(&path, max_age).await {
+        Ok(Some(snapshot)) => {
+            let now = Utc::now();
+            let mut feeds = snapshot.data;
+            feeds.retain(|feed| {
+                let fresh = now - feed.acquired_at <= max_age;
+                if !fresh {
+                    snapshot_warn!(
+                        "(restore) Ignoring feed {} acquired at {}, older than {} seconds.",
+                        feed.name,
+                        feed.acquired_at,
+                        max_age.num_seconds()
+                    );
+                }
+
+                fresh
+            });
+            for feed in feeds.iter_mut() {
+                feed.restrictions.retain(
+                    |_, restriction| !matches!(restriction.timestamp_end, Some(end) if end <= now),
+                );
+            }
+
+            let count = restrictions.restore_feeds(feeds).await;
+            snapshot_info!(
+                "(restore) Restored {} restriction feed(s) from {}.",
+                count,
+                snapshot.timestamp
+            );
+        }
+        Ok(None) => (),
+        Err(e) => snapshot_error!("(restore) {}", e),
+    }
+
+    let path = snapshot_path(config, WAYPOINTS_FILE);
+    match load::<AcquiredWaypoints>(&path, max_age).await {
+        Ok(Some(snapshot)) if Utc::now() - snapshot.data.acquired_at > max_age => {
+            snapshot_warn!(
+                "(restore) Ignoring waypoints acquired at {}, older than {} seconds.",
+                snapshot.data.acquired_at,
+                max_age.num_seconds()
+            );
+        }
+        Ok(Some(snapshot)) => {
+            let acquired_at = snapshot.data.acquired_at;
+            if waypoints
+                .restore(snapshot.data.waypoints, acquired_at)
+                .await
+            {
+                snapshot_info!("(restore) Restored waypoints acquired at {}.", acquired_at);
+            }
+        }
+        Ok(None) => (),
+        Err(e) => snapshot_error!("(restore) {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::region::RestrictionDetails;
+    use crate::restrictions::merge::SourceKind;
+    use crate::waypoints::quality::QualityConfig;
+    use svc_gis_client_grpc::prelude::gis::{Coordinates, ZoneType};
+
+    fn get_config() -> Config {
+        let mut config = Config::default();
+        config.snapshot_dir = std::env::temp_dir()
+            .join(format!("svc-compliance-snapshot-{}", uuid::Uuid::new_v4()))
+            .to_string_lossy()
+            .to_string();
+        config
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_round_trip() {
+        crate::get_log_handle().await;
+        ut_info!("(test_snapshot_round_trip) Start.");
+
+        let config = get_config();
+        let restrictions = RestrictionStore::default();
+        let mut feed = HashMap::new();
+        feed.insert(
+            "ARROW-NL-STATIC-TFR-PALEIS".to_string(),
+            RestrictionDetails {
+                vertices: vec![],
+                timestamp_start: None,
+                timestamp_end: Some(Utc::now() + Duration::try_hours(1).unwrap()),
+                zone_type: ZoneType::Restriction,
+                altitude_meters_min: 0.0,
+                altitude_meters_max: 2000.0,
+                notam_id: None,
+            },
+        );
+        feed.insert(
+            "ARROW-NL-STATIC-TFR-ENDED".to_string(),
+            RestrictionDetails {
+                vertices: vec![],
+                timestamp_start: None,
+                timestamp_end: Some(Utc::now() - Duration::try_minutes(1).unwrap()),
+                zone_type: ZoneType::Restriction,
+                altitude_meters_min: 0.0,
+                altitude_meters_max: 2000.0,
+                notam_id: None,
+            },
+        );
+        restrictions
+            .set_feed(RestrictionFeed {
+                kind: SourceKind::Static,
+                name: "nl".to_string(),
+                restrictions: feed.clone(),
+                acquired_at: Utc::now(),
+            })
+            .await;
+
+        // A feed whose source kept failing is as old as its last acquisition
+        let max_age = Duration::try_seconds(i64::from(config.snapshot_max_age_seconds)).unwrap();
+        restrictions
+            .set_feed(RestrictionFeed {
+                kind: SourceKind::Notam,
+                name: "notams".to_string(),
+                restrictions: HashMap::from([(
+                    "ARROW-NL-NOTAM-STALE".to_string(),
+                    feed["ARROW-NL-STATIC-TFR-PALEIS"].clone(),
+                )]),
+                acquired_at: Utc::now() - max_age - Duration::try_minutes(1).unwrap(),
+            })
+            .await;
+
+        let waypoints = WaypointStore::default();
+        let accepted = vec![(
+            "ARROW-WEG-0".to_string(),
+            WaypointDetails::fix(Coordinates {
+                latitude: 52.3745,
+                longitude: 4.9160,
+            }),
+        )];
+        waypoints
+            .import(accepted.clone(), Utc::now(), &QualityConfig::default())
+            .await;
+
+        save_restrictions(&config, &restrictions).await;
+        save_waypoints(&config, &waypoints).await;
+
+        // A restarted service picks up where it left off
+        let restored_restrictions = RestrictionStore::default();
+        let restored_waypoints = WaypointStore::default();
+        restore(&config, &restored_restrictions, &restored_waypoints).await;
+        let restored = restored_restrictions.get_all().await;
+        assert!(restored.contains_key("ARROW-NL-STATIC-TFR-PALEIS"));
+        assert!(!restored.contains_key("ARROW-NL-NOTAM-STALE"));
+
+        // Restrictions that ended are not pushed again
+        assert!(!restored.contains_key("ARROW-NL-STATIC-TFR-ENDED"));
+        assert_eq!(restored_waypoints.get_all().await.len(), 1);
+
+        // Waypoints are as old as their acquisition, not as the snapshot
+        let stale_waypoints = WaypointStore::default();
+        stale_waypoints
+            .import(
+                accepted,
+                Utc::now() - max_age - Duration::try_minutes(1).unwrap(),
+                &QualityConfig::default(),
+            )
+            .await;
+        save_waypoints(&config, &stale_waypoints).await;
+        let restored_waypoints = WaypointStore::default();
+        restore(&config, &RestrictionStore::default(), &restored_waypoints).await;
+        assert!(restored_waypoints.get_all().await.is_empty());
+
+        // Snapshots beyond the maximum age are ignored
+        let mut config = config;
+        config.snapshot_max_age_seconds = 0;
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        let stale_restrictions = RestrictionStore::default();
+        let stale_waypoints = WaypointStore::default();
+        restore(&config, &stale_restrictions, &stale_waypoints).await;
+        assert!(stale_restrictions.get_all().await.is_empty());
+        assert!(stale_waypoints.get_all().await.is_empty());
+
+        let _ = tokio::fs::remove_dir_all(&config.snapshot_dir).await;
+        ut_info!("(test_snapshot_round_trip) Success.");
+    }
+}
@@ -0,0 +1,309 @@
+//! Format of restriction and waypoint feeds
+//!
+//! Feeds are JSON arrays of records. Points are pairs in the axis order of
+//!  the coordinate system of the feed: latitude and longitude for WGS84.
+//!
+//! ```json
+//! [
+//!     {
+//!         "key": "DAM-EVENT",
+//!         "vertices": [[52.3731, 4.8922], [52.3725, 4.8941], [52.3716, 4.8925]],
+//!         "zone_type": "RESTRICTION",
+//!         "altitude_meters_min": 0.0,
+//!         "altitude_meters_max": 120.0,
+//!         "timestamp_end": "2024-04-27T18:00:00Z"
+//!     }
+//! ]
+//! ```
+//!
+//! Records are identified by their key, or by their content when they have
+//!  none. A feed with an invalid record is rejected as a whole, so the
+//!  previously acquired version of the feed stays in effect.
+
+use super::SourceError;
+use crate::identifiers;
+use crate::region::{
+    utils, CoordinateConverter, RestrictionDetails, WaypointDetails, WaypointType,
+};
+use crate::restrictions::merge::SourceKind;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+use svc_gis_client_grpc::prelude::gis::{Coordinates, ZoneType};
+
+/// Where the records of a feed come from
+#[derive(Clone)]
+pub struct FeedOrigin {
+    /// Region short code, used to namespace identifiers
+    pub region: String,
+
+    /// Kind of source providing the feed
+    pub kind: SourceKind,
+
+    /// Converter of the feed coordinates to WGS84
+    pub converter: CoordinateConverter,
+}
+
+impl std::fmt::Debug for FeedOrigin {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FeedOrigin")
+            .field("region", &self.region)
+            .field("kind", &self.kind)
+            .finish()
+    }
+}
+
+fn default_zone_type() -> ZoneType {
+    ZoneType::Restriction
+}
+
+/// A restriction record
+#[derive(Debug, Clone, Deserialize)]
+struct FeedRestriction {
+    /// Key of the restriction in its source
+    #[serde(default)]
+    key: Option<String>,
+
+    /// Boundary vertices
+    vertices: Vec<(f64, f64)>,
+
+    /// Protobuf name of the zone type
+    #[serde(with = "utils::zone_type_serde", default = "default_zone_type")]
+    zone_type: ZoneType,
+
+    /// The minimum altitude
+    #[serde(default)]
+    altitude_meters_min: f32,
+
+    /// The maximum altitude
+    altitude_meters_max: f32,
+
+    /// The start time of the restriction
+    #[serde(default)]
+    timestamp_start: Option<DateTime<Utc>>,
+
+    /// The end time of the restriction
+    #[serde(default)]
+    timestamp_end: Option<DateTime<Utc>>,
+
+    /// The NOTAM the restriction was published in, if any
+    #[serde(default)]
+    notam_id: Option<String>,
+}
+
+/// A waypoint record
+#[derive(Debug, Clone, Deserialize)]
+struct FeedWaypoint {
+    /// Key of the waypoint in its source
+    #[serde(default)]
+    key: Option<String>,
+
+    /// Location of the waypoint
+    location: (f64, f64),
+
+    /// The published name of the waypoint
+    #[serde(default)]
+    name: Option<String>,
+
+    /// The waypoint type
+    #[serde(default)]
+    waypoint_type: WaypointType,
+
+    /// The minimum altitude at this waypoint
+    #[serde(default)]
+    altitude_meters_min: Option<f32>,
+
+    /// The maximum altitude at this waypoint
+    #[serde(default)]
+    altitude_meters_max: Option<f32>,
+
+    /// The start of the validity of the waypoint
+    #[serde(default)]
+    timestamp_start: Option<DateTime<Utc>>,
+
+    /// The end of the validity of the waypoint
+    #[serde(default)]
+    timestamp_end: Option<DateTime<Utc>>,
+}
+
+/// Converts a point of the feed to WGS84
+fn convert(origin: &FeedOrigin, (first, second): (f64, f64)) -> Result<Coordinates, SourceError> {
+    (origin.converter)(first, second).map_err(|e| SourceError::InvalidData(e.to_string()))
+}
+
+/// Parses a restriction feed
+pub fn parse_restrictions(
+    contents: &[u8],
+    origin: &FeedOrigin,
+) -> Result<HashMap<String, RestrictionDetails>, SourceError> {
+    let records: Vec<FeedRestriction> =
+        serde_json::from_slice(contents).map_err(|e| SourceError::InvalidData(e.to_string()))?;
+
+    let mut restrictions = HashMap::new();
+    for record in records {
+        let vertices = record
+            .vertices
+            .iter()
+            .map(|point| convert(origin, *point))
+            .collect::<Result<Vec<Coordinates>, SourceError>>()?;
+
+        // A NOTAM feed is keyed by NOTAM
+        let notam_id = match (origin.kind, record.notam_id) {
+            (SourceKind::Notam, None) => record.key.clone(),
+            (_, notam_id) => notam_id,
+        };
+
+        let identifier = match &record.key {
+            Some(key) => identifiers::from_key(&origin.region, origin.kind, key),
+            None => identifiers::from_content(
+                &origin.region,
+                origin.kind,
+                &vertices
+                    .iter()
+                    .map(|v| format!("{:.7},{:.7}", v.latitude, v.longitude))
+                    .collect::<Vec<String>>()
+                    .join(";"),
+            ),
+        };
+
+        if restrictions.contains_key(&identifier) {
+            return Err(SourceError::InvalidData(format!(
+                "restriction {identifier} listed more than once"
+            )));
+        }
+
+        restrictions.insert(
+            identifier,
+            RestrictionDetails {
+                vertices,
+                timestamp_start: record.timestamp_start,
+                timestamp_end: record.timestamp_end,
+                zone_type: record.zone_type,
+                altitude_meters_min: record.altitude_meters_min,
+                altitude_meters_max: record.altitude_meters_max,
+                notam_id,
+            },
+        );
+    }
+
+    Ok(restrictions)
+}
+
+/// Parses a waypoint feed, keeping the order of the records
+pub fn parse_waypoints(
+    contents: &[u8],
+    origin: &FeedOrigin,
+) -> Result<Vec<(String, WaypointDetails)>, SourceError> {
+    let records: Vec<FeedWaypoint> =
+        serde_json::from_slice(contents).map_err(|e| SourceError::InvalidData(e.to_string()))?;
+
+    records
+        .into_iter()
+        .map(|record| {
+            let location = convert(origin, record.location)?;
+            let identifier = match &record.key {
+                Some(key) => identifiers::from_key(&origin.region, origin.kind, key),
+                None => identifiers::from_location(&origin.region, origin.kind, &location),
+            };
+
+            Ok((
+                identifier,
+                WaypointDetails {
+                    location,
+                    name: record.name,
+                    waypoint_type: record.waypoint_type,
+                    altitude_meters_min: record.altitude_meters_min,
+                    altitude_meters_max: record.altitude_meters_max,
+                    timestamp_start: record.timestamp_start,
+                    timestamp_end: record.timestamp_end,
+                },
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::region::{RegionImpl, RegionInterface};
+
+    fn origin(kind: SourceKind) -> FeedOrigin {
+        FeedOrigin {
+            region: "nl".to_string(),
+            kind,
+            converter: RegionImpl::default().coordinate_converter("WGS84").unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parse_restrictions() {
+        crate::get_log_handle().await;
+        ut_info!("(test_parse_restrictions) Start.");
+
+        let contents = br#"[
+            {
+                "key": "A1234/24",
+                "vertices": [[52.3731, 4.8922], [52.3725, 4.8941], [52.3716, 4.8925]],
+                "altitude_meters_max": 120.0
+            },
+            {
+                "vertices": [[52.0, 4.0], [52.1, 4.0], [52.1, 4.1]],
+                "zone_type": "RESTRICTION",
+                "altitude_meters_max": 500.0,
+                "timestamp_end": "2024-04-27T18:00:00Z"
+            }
+        ]"#;
+
+        let restrictions = parse_restrictions(contents, &origin(SourceKind::Notam)).unwrap();
+        assert_eq!(restrictions.len(), 2);
+        let notam = &restrictions[&identifiers::from_key("nl", SourceKind::Notam, "A1234/24")];
+        assert_eq!(notam.notam_id, Some("A1234/24".to_string()));
+        assert_eq!(notam.vertices[0].latitude, 52.3731);
+        assert_eq!(notam.zone_type, ZoneType::Restriction);
+
+        // Identifiers of records without a key do not depend on their order
+        let reordered = br#"[
+            {
+                "vertices": [[52.0, 4.0], [52.1, 4.0], [52.1, 4.1]],
+                "altitude_meters_max": 500.0
+            }
+        ]"#;
+        let keyless = parse_restrictions(reordered, &origin(SourceKind::Notam)).unwrap();
+        assert!(restrictions.contains_key(keyless.keys().next().unwrap()));
+
+        // Keys written differently do not collide
+        let similar = br#"[
+            { "key": "A1/24", "vertices": [[52.0, 4.0], [52.1, 4.0], [52.1, 4.1]], "altitude_meters_max": 1.0 },
+            { "key": "A1-24", "vertices": [[52.0, 4.0], [52.1, 4.0], [52.1, 4.1]], "altitude_meters_max": 1.0 }
+        ]"#;
+        let similar = parse_restrictions(similar, &origin(SourceKind::Notam)).unwrap();
+        assert_eq!(similar.len(), 2);
+        assert!(similar.contains_key("ARROW-NL-NOTAM-A1-24"));
+
+        // A single invalid record rejects the feed
+        let invalid = br#"[{ "key": "X", "vertices": [[52.0]], "altitude_meters_max": 1.0 }]"#;
+        assert!(parse_restrictions(invalid, &origin(SourceKind::File)).is_err());
+
+        ut_info!("(test_parse_restrictions) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_parse_waypoints() {
+        crate::get_log_handle().await;
+        ut_info!("(test_parse_waypoints) Start.");
+
+        let contents = br#"[
+            { "key": "DAMRK", "location": [52.3745, 4.9160], "name": "Damrak" },
+            { "location": [52.3749, 4.9156], "waypoint_type": "HoldingPoint" }
+        ]"#;
+
+        let waypoints = parse_waypoints(contents, &origin(SourceKind::File)).unwrap();
+        assert_eq!(waypoints.len(), 2);
+        assert_eq!(waypoints[0].0, "ARROW-NL-FILE-DAMRK");
+        assert_eq!(waypoints[0].1.name, Some("Damrak".to_string()));
+        assert_eq!(waypoints[1].1.waypoint_type, WaypointType::HoldingPoint);
+        assert!(waypoints[1].0.starts_with("ARROW-NL-FILE-"));
+
+        ut_info!("(test_parse_waypoints) Success.");
+    }
+}
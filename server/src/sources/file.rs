@@ -0,0 +1,72 @@
+//! Feeds read from local files
+
+use super::feed::{self, FeedOrigin};
+use super::{RestrictionSource, SourceError, WaypointSource};
+use crate::region::{RestrictionDetails, WaypointDetails};
+use crate::restrictions::merge::SourceKind;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// A feed read from a file, see [`feed`] for the format
+///
+/// The file is read again on every refresh, so it can be replaced while the
+///  service is running.
+#[derive(Debug)]
+pub struct FileSource {
+    /// Unique name of the source
+    name: String,
+
+    /// Where the records come from
+    origin: FeedOrigin,
+
+    /// The feed file
+    path: PathBuf,
+}
+
+impl FileSource {
+    /// Create a new source reading the provided file
+    pub fn new(name: &str, origin: FeedOrigin, path: PathBuf) -> Self {
+        Self {
+            name: name.to_string(),
+            origin,
+            path,
+        }
+    }
+
+    /// Reads the feed file
+    async fn read(&self) -> Result<Vec<u8>, SourceError> {
+        tokio::fs::read(&self.path)
+            .await
+            .map_err(|e| SourceError::Acquisition(format!("could not read {:?}: {}", self.path, e)))
+    }
+}
+
+#[tonic::async_trait]
+impl RestrictionSource for FileSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn kind(&self) -> SourceKind {
+        self.origin.kind
+    }
+
+    async fn acquire(&self) -> Result<HashMap<String, RestrictionDetails>, SourceError> {
+        feed::parse_restrictions(&self.read().await?, &self.origin)
+    }
+}
+
+#[tonic::async_trait]
+impl WaypointSource for FileSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn kind(&self) -> SourceKind {
+        self.origin.kind
+    }
+
+    async fn acquire(&self) -> Result<Vec<(String, WaypointDetails)>, SourceError> {
+        feed::parse_waypoints(&self.read().await?, &self.origin)
+    }
+}
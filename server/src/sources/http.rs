@@ -0,0 +1,526 @@
+//! Feeds fetched over HTTP
+//!
+//! Feeds are fetched with conditional requests: the `ETag` and
+//!  `Last-Modified` validators of the last response are sent back, and a
+//!  `304 Not Modified` answer reuses the body received before. Responses may
+//!  be gzip compressed. Requests time out, and bodies larger than the
+//!  configured limit are refused, compressed or not.
+//!
+//! In replay mode nothing is fetched: the feed is read from `{name}.json` in
+//!  the replay directory, as written in record mode. This serves recorded or
+//!  hand written fixtures without network access.
+
+use super::feed::{self, FeedOrigin};
+use super::{RestrictionSource, SourceError, WaypointSource};
+use crate::region::{RestrictionDetails, WaypointDetails};
+use crate::restrictions::merge::SourceKind;
+use flate2::read::GzDecoder;
+use hyper::body::HttpBody;
+use hyper::client::HttpConnector;
+use hyper::header::{self, HeaderName, HeaderValue};
+use hyper::{Body, Client, Request, StatusCode};
+use hyper_tls::HttpsConnector;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+/// Value of the `User-Agent` header
+const USER_AGENT: &str = concat!("svc-compliance/", env!("CARGO_PKG_VERSION"));
+
+/// Credentials sent with every request
+#[derive(Clone, PartialEq, Eq)]
+pub enum FetchAuth {
+    /// `Authorization: Bearer {token}`
+    Bearer(String),
+
+    /// An API key in the named header
+    ApiKey {
+        /// Name of the header
+        header: String,
+
+        /// The key
+        key: String,
+    },
+}
+
+impl std::fmt::Debug for FetchAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FetchAuth::Bearer(_) => write!(f, "Bearer(..)"),
+            FetchAuth::ApiKey { header, .. } => write!(f, "ApiKey({header})"),
+        }
+    }
+}
+
+/// Where feeds are read from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FetchMode {
+    /// Fetch over the network
+    Network,
+
+    /// Fetch over the network and write every new body to the directory
+    Record(PathBuf),
+
+    /// Read the bodies from the directory instead of fetching them
+    Replay(PathBuf),
+}
+
+/// Settings of a fetcher
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FetchConfig {
+    /// The feed URL
+    pub url: String,
+
+    /// Maximum duration of a request, including reading the body
+    pub timeout: Duration,
+
+    /// Maximum size of a body in bytes, after decompression
+    pub max_bytes: usize,
+
+    /// Credentials, if any
+    pub auth: Option<FetchAuth>,
+
+    /// Where feeds are read from
+    pub mode: FetchMode,
+}
+
+/// Validators and body of the last successful response
+#[derive(Debug, Default)]
+struct Cached {
+    /// `ETag` header of the response
+    etag: Option<HeaderValue>,
+
+    /// `Last-Modified` header of the response
+    last_modified: Option<HeaderValue>,
+
+    /// The decoded body
+    body: Option<Vec<u8>>,
+}
+
+/// Result of a fetch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fetched {
+    /// The decoded body
+    pub body: Vec<u8>,
+
+    /// False if the server answered that the body did not change
+    pub modified: bool,
+}
+
+/// Fetches a feed over HTTP
+#[derive(Debug)]
+pub struct HttpFetcher {
+    /// Name of the feed, used for recorded files and logs
+    name: String,
+
+    /// Settings
+    config: FetchConfig,
+
+    /// Client reused for every request
+    client: Client<HttpsConnector<HttpConnector>>,
+
+    /// Last successful response
+    cached: Mutex<Cached>,
+}
+
+/// Reads a body, refusing bodies larger than the limit
+async fn read_body(mut body: Body, max_bytes: usize) -> Result<Vec<u8>, SourceError> {
+    let mut contents = vec![];
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|e| SourceError::Acquisition(e.to_string()))?;
+        if contents.len() + chunk.len() > max_bytes {
+            return Err(SourceError::InvalidData(format!(
+                "response larger than {max_bytes} bytes"
+            )));
+        }
+
+        contents.extend_from_slice(&chunk);
+    }
+
+    Ok(contents)
+}
+
+/// Decompresses a gzip body, refusing bodies larger than the limit
+fn gunzip(contents: &[u8], max_bytes: usize) -> Result<Vec<u8>, SourceError> {
+    let mut decoded = vec![];
+    GzDecoder::new(contents)
+        .take(max_bytes as u64 + 1)
+        .read_to_end(&mut decoded)
+        .map_err(|e| SourceError::InvalidData(format!("invalid gzip body: {e}")))?;
+
+    if decoded.len() > max_bytes {
+        return Err(SourceError::InvalidData(format!(
+            "decompressed response larger than {max_bytes} bytes"
+        )));
+    }
+
+    Ok(decoded)
+}
+
+impl HttpFetcher {
+    /// Create a new fetcher
+    pub fn new(name: &str, config: FetchConfig) -> Self {
+        Self {
+            name: name.to_string(),
+            config,
+            client: Client::builder().build(HttpsConnector::new()),
+            cached: Mutex::new(Cached::default()),
+        }
+    }
+
+    /// Returns the path of the recorded body in the provided directory
+    fn recording(&self, dir: &std::path::Path) -> PathBuf {
+        dir.join(format!("{}.json", self.name))
+    }
+
+    /// Fetches the feed
+    pub async fn fetch(&self) -> Result<Fetched, SourceError> {
+        match &self.config.mode {
+            FetchMode::Replay(dir) => self.replay(dir).await,
+            FetchMode::Network => self.request().await,
+            FetchMode::Record(dir) => {
+                let fetched = self.request().await?;
+                if fetched.modified {
+                    self.record(dir, &fetched.body).await;
+                }
+
+                Ok(fetched)
+            }
+        }
+    }
+
+    /// Reads a recorded body
+    async fn replay(&self, dir: &std::path::Path) -> Result<Fetched, SourceError> {
+        let path = self.recording(dir);
+        let body = tokio::fs::read(&path)
+            .await
+            .map_err(|e| SourceError::Acquisition(format!("could not read {:?}: {}", path, e)))?;
+
+        if body.len() > self.config.max_bytes {
+            return Err(SourceError::InvalidData(format!(
+                "recording larger than {} bytes",
+                self.config.max_bytes
+            )));
+        }
+
+        Ok(Fetched {
+            body,
+            modified: true,
+        })
+    }
+
+    /// Writes a body to the recording directory
+    async fn record(&self, dir: &std::path::Path, body: &[u8]) {
+        let path = self.recording(dir);
+        let result = match tokio::fs::create_dir_all(dir).await {
+            Ok(()) => tokio::fs::write(&path, body).await,
+            Err(e) => Err(e),
+        };
+
+        if let Err(e) = result {
+            sources_warn!("(record) Could not record {:?}: {}", path, e);
+        }
+    }
+
+    /// Sends a conditional request and reads the response
+    async fn request(&self) -> Result<Fetched, SourceError> {
+        let mut cached = self.cached.lock().await;
+
+        let mut request = Request::get(&self.config.url)
+            .header(header::USER_AGENT, USER_AGENT)
+            .header(header::ACCEPT_ENCODING, "gzip");
+
+        if cached.body.is_some() {
+            if let Some(etag) = &cached.etag {
+                request = request.header(header::IF_NONE_MATCH, etag.clone());
+            }
+
+            if let Some(last_modified) = &cached.last_modified {
+                request = request.header(header::IF_MODIFIED_SINCE, last_modified.clone());
+            }
+        }
+
+        request = match &self.config.auth {
+            Some(FetchAuth::Bearer(token)) => {
+                request.header(header::AUTHORIZATION, format!("Bearer {token}"))
+            }
+            Some(FetchAuth::ApiKey { header, key }) => {
+                let name = HeaderName::from_bytes(header.as_bytes())
+                    .map_err(|e| SourceError::Configuration(e.to_string()))?;
+                request.header(name, key.as_str())
+            }
+            None => request,
+        };
+
+        let request = request
+            .body(Body::empty())
+            .map_err(|e| SourceError::Configuration(e.to_string()))?;
+
+        let exchange = async {
+            let response = self
+                .client
+                .request(request)
+                .await
+                .map_err(|e| SourceError::Acquisition(e.to_string()))?;
+
+            let (parts, body) = response.into_parts();
+            if parts.status == StatusCode::NOT_MODIFIED {
+                return Ok((parts, None));
+            }
+
+            if !parts.status.is_success() {
+                return Err(SourceError::Acquisition(format!(
+                    "{} answered {}",
+                    self.config.url, parts.status
+                )));
+            }
+
+            let body = read_body(body, self.config.max_bytes).await?;
+            Ok((parts, Some(body)))
+        };
+
+        let (parts, body) = tokio::time::timeout(self.config.timeout, exchange)
+            .await
+            .map_err(|_| {
+                SourceError::Acquisition(format!(
+                    "{} did not answer within {} seconds",
+                    self.config.url,
+                    self.config.timeout.as_secs()
+                ))
+            })??;
+
+        let Some(body) = body else {
+            sources_debug!("(request) {} not modified.", self.name);
+            return match &cached.body {
+                Some(body) => Ok(Fetched {
+                    body: body.clone(),
+                    modified: false,
+                }),
+                None => Err(SourceError::Acquisition(
+                    "not modified answered to an unconditional request".to_string(),
+                )),
+            };
+        };
+
+        let gzipped = parts
+            .headers
+            .get(header::CONTENT_ENCODING)
+            .is_some_and(|encoding| encoding.as_bytes().eq_ignore_ascii_case(b"gzip"));
+        let body = match gzipped {
+            true => {
+                // Decompressing a large body would hold up the async runtime
+                let max_bytes = self.config.max_bytes;
+                tokio::task::spawn_blocking(move || gunzip(&body, max_bytes))
+                    .await
+                    .map_err(|e| SourceError::Acquisition(format!("gzip task failed: {e}")))??
+            }
+            false => body,
+        };
+
+        sources_debug!("(request) {} fetched, {} bytes.", self.name, body.len());
+        *cached = Cached {
+            etag: parts.headers.get(header::ETAG).cloned(),
+            last_modified: parts.headers.get(header::LAST_MODIFIED).cloned(),
+            body: Some(body.clone()),
+        };
+
+        Ok(Fetched {
+            body,
+            modified: true,
+        })
+    }
+}
+
+/// A feed fetched over HTTP, see [`feed`] for the format
+#[derive(Debug)]
+pub struct HttpSource {
+    /// Where the records come from
+    origin: FeedOrigin,
+
+    /// The fetcher
+    fetcher: HttpFetcher,
+}
+
+impl HttpSource {
+    /// Create a new source fetching the provided feed
+    pub fn new(name: &str, origin: FeedOrigin, config: FetchConfig) -> Self {
+        Self {
+            origin,
+            fetcher: HttpFetcher::new(name, config),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl RestrictionSource for HttpSource {
+    fn name(&self) -> &str {
+        &self.fetcher.name
+    }
+
+    fn kind(&self) -> SourceKind {
+        self.origin.kind
+    }
+
+    async fn acquire(&self) -> Result<HashMap<String, RestrictionDetails>, SourceError> {
+        feed::parse_restrictions(&self.fetcher.fetch().await?.body, &self.origin)
+    }
+}
+
+#[tonic::async_trait]
+impl WaypointSource for HttpSource {
+    fn name(&self) -> &str {
+        &self.fetcher.name
+    }
+
+    fn kind(&self) -> SourceKind {
+        self.origin.kind
+    }
+
+    async fn acquire(&self) -> Result<Vec<(String, WaypointDetails)>, SourceError> {
+        feed::parse_waypoints(&self.fetcher.fetch().await?.body, &self.origin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Response, Server};
+    use std::convert::Infallible;
+    use std::io::Write;
+    use std::net::SocketAddr;
+
+    const FEED: &[u8] = br#"[{ "key": "A1/24", "vertices": [[52.0, 4.0], [52.1, 4.0], [52.1, 4.1]], "altitude_meters_max": 120.0 }]"#;
+
+    /// Stand-in for an authority: serves the feed gzipped with an ETag to
+    ///  authorized clients
+    async fn serve(request: Request<Body>) -> Result<Response<Body>, Infallible> {
+        let authorized = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .is_some_and(|value| value == "Bearer secret");
+        if !authorized {
+            return Ok(Response::builder()
+                .status(StatusCode::UNAUTHORIZED)
+                .body(Body::empty())
+                .unwrap());
+        }
+
+        let matches = request
+            .headers()
+            .get(header::IF_NONE_MATCH)
+            .is_some_and(|value| value == "\"v1\"");
+        if matches {
+            return Ok(Response::builder()
+                .status(StatusCode::NOT_MODIFIED)
+                .body(Body::empty())
+                .unwrap());
+        }
+
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(FEED).unwrap();
+        Ok(Response::builder()
+            .header(header::ETAG, "\"v1\"")
+            .header(header::CONTENT_ENCODING, "gzip")
+            .body(Body::from(encoder.finish().unwrap()))
+            .unwrap())
+    }
+
+    fn start_server() -> SocketAddr {
+        let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service_fn(
+            |_| async { Ok::<_, Infallible>(service_fn(serve)) },
+        ));
+        let address = server.local_addr();
+        tokio::spawn(server);
+        address
+    }
+
+    fn config(address: SocketAddr) -> FetchConfig {
+        FetchConfig {
+            url: format!("http://{address}/feed"),
+            timeout: Duration::from_secs(5),
+            max_bytes: 1024,
+            auth: Some(FetchAuth::Bearer("secret".to_string())),
+            mode: FetchMode::Network,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch() {
+        crate::get_log_handle().await;
+        ut_info!("(test_fetch) Start.");
+
+        let address = start_server();
+        let fetcher = HttpFetcher::new("notams", config(address));
+
+        let fetched = fetcher.fetch().await.unwrap();
+        assert!(fetched.modified);
+        assert_eq!(fetched.body, FEED);
+
+        // The ETag is sent back and the cached body reused
+        let fetched = fetcher.fetch().await.unwrap();
+        assert!(!fetched.modified);
+        assert_eq!(fetched.body, FEED);
+
+        // Unauthorized
+        let fetcher = HttpFetcher::new(
+            "notams",
+            FetchConfig {
+                auth: None,
+                ..config(address)
+            },
+        );
+        assert!(fetcher.fetch().await.is_err());
+
+        // The limit applies to the decompressed body
+        let fetcher = HttpFetcher::new(
+            "notams",
+            FetchConfig {
+                max_bytes: 64,
+                ..config(address)
+            },
+        );
+        assert!(matches!(
+            fetcher.fetch().await,
+            Err(SourceError::InvalidData(_))
+        ));
+
+        ut_info!("(test_fetch) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_record_and_replay() {
+        crate::get_log_handle().await;
+        ut_info!("(test_record_and_replay) Start.");
+
+        let dir =
+            std::env::temp_dir().join(format!("svc-compliance-replay-{}", uuid::Uuid::new_v4()));
+        let address = start_server();
+        let recorder = HttpFetcher::new(
+            "notams",
+            FetchConfig {
+                mode: FetchMode::Record(dir.clone()),
+                ..config(address)
+            },
+        );
+        recorder.fetch().await.unwrap();
+
+        // No server is needed to replay
+        let player = HttpFetcher::new(
+            "notams",
+            FetchConfig {
+                url: "http://127.0.0.1:9/unreachable".to_string(),
+                mode: FetchMode::Replay(dir.clone()),
+                ..config(address)
+            },
+        );
+        assert_eq!(player.fetch().await.unwrap().body, FEED);
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+        ut_info!("(test_record_and_replay) Success.");
+    }
+}
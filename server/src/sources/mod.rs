@@ -0,0 +1,807 @@
+//! Sources of restrictions and waypoints
+//!
+//! The region implementation provides its own data as a static source, and
+//!  further feeds are added from configuration without changes to the
+//!  region: `SOURCES__{NAME}__KIND`, `SOURCES__{NAME}__DATA`,
+//!  `SOURCES__{NAME}__PATH` or `SOURCES__{NAME}__URL` and so on, see
+//!  [`SourceConfig`].
+//!
+//! Every source is refreshed on its own interval. A failing source is tried
+//!  again after its retry delay, while the data it provided last stays in
+//!  effect. Manual restrictions are not a configured source, they are entered
+//!  through the gRPC interface.
+
+#[macro_use]
+pub mod macros;
+pub mod feed;
+pub mod file;
+pub mod http;
+pub mod region;
+
+use crate::config::Config;
+use crate::region::{RegionInterface, RestrictionDetails, WaypointDetails};
+use crate::restrictions::merge::{RestrictionFeed, SourceKind};
+use crate::restrictions::RestrictionStore;
+use crate::waypoints::quality::QualityConfig;
+use crate::waypoints::WaypointStore;
+use chrono::{DateTime, Utc};
+use feed::FeedOrigin;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// Errors returned by the sources
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum SourceError {
+    /// The data could not be acquired
+    #[error("error: Could not acquire data: {0}.")]
+    Acquisition(String),
+
+    /// The acquired data is invalid
+    #[error("error: Invalid data: {0}.")]
+    InvalidData(String),
+
+    /// The source is not configured correctly
+    #[error("error: Invalid source configuration: {0}.")]
+    Configuration(String),
+}
+
+/// A source of restrictions
+#[tonic::async_trait]
+pub trait RestrictionSource: std::fmt::Debug + Send + Sync {
+    /// Return the unique name of the source, used as feed name
+    fn name(&self) -> &str;
+
+    /// Return the kind of the source, which sets its merge priority
+    fn kind(&self) -> SourceKind;
+
+    /// Acquire the current restrictions of the source
+    ///
+    /// The returned restrictions replace the previously acquired ones as a
+    ///  whole.
+    async fn acquire(&self) -> Result<HashMap<String, RestrictionDetails>, SourceError>;
+}
+
+/// A source of waypoints
+#[tonic::async_trait]
+pub trait WaypointSource: std::fmt::Debug + Send + Sync {
+    /// Return the unique name of the source
+    fn name(&self) -> &str;
+
+    /// Return the kind of the source
+    fn kind(&self) -> SourceKind;
+
+    /// Acquire the current waypoints of the source, in source order
+    async fn acquire(&self) -> Result<Vec<(String, WaypointDetails)>, SourceError>;
+}
+
+/// Kind of data provided by a configured source
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceData {
+    /// Restriction zones
+    Restrictions,
+
+    /// Waypoints
+    Waypoints,
+}
+
+fn default_coordinates() -> String {
+    String::from("EPSG:4326")
+}
+
+/// Settings of a configured source
+#[derive(Clone, PartialEq, Eq, Deserialize)]
+pub struct SourceConfig {
+    /// Kind of the source: `file`, `notam` or `manual`
+    pub kind: SourceKind,
+
+    /// Kind of data provided
+    pub data: SourceData,
+
+    /// Path of the feed file
+    #[serde(default)]
+    pub path: Option<String>,
+
+    /// URL of the feed, fetched instead of a file
+    #[serde(default)]
+    pub url: Option<String>,
+
+    /// Bearer token sent with requests
+    #[serde(default)]
+    pub bearer_token: Option<String>,
+
+    /// Header carrying the API key sent with requests
+    #[serde(default)]
+    pub api_key_header: Option<String>,
+
+    /// API key sent with requests
+    #[serde(default)]
+    pub api_key: Option<String>,
+
+    /// Maximum duration of a request in seconds, 30 by default
+    #[serde(default)]
+    pub timeout_seconds: Option<u32>,
+
+    /// Maximum size of a fetched feed in bytes, 16 MiB by default
+    #[serde(default)]
+    pub max_bytes: Option<usize>,
+
+    /// Directory to record fetched feeds to
+    #[serde(default)]
+    pub record_dir: Option<String>,
+
+    /// Directory to replay recorded feeds from instead of fetching them
+    #[serde(default)]
+    pub replay_dir: Option<String>,
+
+    /// Coordinate system of the feed, WGS84 by default
+    #[serde(default = "default_coordinates")]
+    pub coordinates: String,
+
+    /// Interval in seconds between refreshes, the refresh interval of the
+    ///  kind of data by default
+    #[serde(default)]
+    pub interval_seconds: Option<u32>,
+
+    /// Delay in seconds before a failed refresh is tried again, the refresh
+    ///  interval by default
+    #[serde(default)]
+    pub retry_seconds: Option<u32>,
+}
+
+impl std::fmt::Debug for SourceConfig {
+    /// Leaves out the credentials, so the configuration can be logged
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let redacted = |secret: &Option<String>| secret.as_ref().map(|_| "..");
+        f.debug_struct("SourceConfig")
+            .field("kind", &self.kind)
+            .field("data", &self.data)
+            .field("path", &self.path)
+            .field("url", &self.url)
+            .field("bearer_token", &redacted(&self.bearer_token))
+            .field("api_key_header", &self.api_key_header)
+            .field("api_key", &redacted(&self.api_key))
+            .field("timeout_seconds", &self.timeout_seconds)
+            .field("max_bytes", &self.max_bytes)
+            .field("record_dir", &self.record_dir)
+            .field("replay_dir", &self.replay_dir)
+            .field("coordinates", &self.coordinates)
+            .field("interval_seconds", &self.interval_seconds)
+            .field("retry_seconds", &self.retry_seconds)
+            .finish()
+    }
+}
+
+/// When a source is due
+#[derive(Debug)]
+struct Schedule {
+    /// Interval between refreshes
+    interval: Duration,
+
+    /// Delay before a failed refresh is tried again
+    retry: Duration,
+
+    /// When the source is due next
+    next: Instant,
+
+    /// Number of consecutive failures
+    failures: u32,
+}
+
+impl Schedule {
+    /// Create a schedule due immediately
+    fn new(interval: Duration, retry: Duration) -> Self {
+        Self {
+            interval,
+            retry,
+            next: Instant::now(),
+            failures: 0,
+        }
+    }
+
+    /// Record a successful refresh
+    fn succeeded(&mut self, now: Instant) {
+        self.failures = 0;
+        self.next = now + self.interval;
+    }
+
+    /// Record a failed refresh
+    fn failed(&mut self, now: Instant) {
+        self.failures += 1;
+        self.next = now + self.retry;
+    }
+}
+
+/// Outcome of refreshing a set of sources
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceStatus {
+    /// Number of sources acquired successfully in this refresh
+    pub acquired: usize,
+
+    /// Names of the sources whose last refresh failed
+    pub failing: Vec<String>,
+}
+
+impl SourceStatus {
+    /// Returns true if the last refresh of every source succeeded
+    pub fn is_healthy(&self) -> bool {
+        self.failing.is_empty()
+    }
+}
+
+/// Restriction sources with their schedules
+#[derive(Debug, Default)]
+pub struct RestrictionSources {
+    /// The sources, in the order they are refreshed
+    sources: Mutex<Vec<(Box<dyn RestrictionSource>, Schedule)>>,
+}
+
+impl RestrictionSources {
+    /// Add a source refreshed on the provided interval
+    pub fn add(&mut self, source: Box<dyn RestrictionSource>, interval: Duration, retry: Duration) {
+        sources_info!(
+            "(add) Restriction source {} ({}) every {} seconds.",
+            source.name(),
+            source.kind(),
+            interval.as_secs()
+        );
+        self.sources
+            .get_mut()
+            .push((source, Schedule::new(interval, retry)));
+    }
+
+    /// Returns when the next source is due, if any
+    pub async fn next_due(&self) -> Option<Instant> {
+        self.sources
+            .lock()
+            .await
+            .iter()
+            .map(|(_, schedule)| schedule.next)
+            .min()
+    }
+
+    /// Acquires the sources that are due, replacing their feeds in the store
+    pub async fn refresh(&self, store: &RestrictionStore) -> SourceStatus {
+        let mut sources = self.sources.lock().await;
+        let mut status = SourceStatus::default();
+        for (source, schedule) in sources.iter_mut() {
+            let now = Instant::now();
+            if schedule.next <= now {
+                match source.acquire().await {
+                    Ok(restrictions) => {
+                        sources_debug!(
+                            "(refresh) Acquired {} restriction(s) from {}.",
+                            restrictions.len(),
+                            source.name()
+                        );
+                        store
+                            .set_feed(RestrictionFeed {
+                                kind: source.kind(),
+                                name: source.name().to_string(),
+                                restrictions,
+                                acquired_at: Utc::now(),
+                            })
+                            .await;
+                        schedule.succeeded(now);
+                        status.acquired += 1;
+                    }
+                    Err(e) => {
+                        schedule.failed(now);
+                        sources_error!(
+                            "(refresh) Restriction source {} failed {} time(s) in a row: {}",
+                            source.name(),
+                            schedule.failures,
+                            e
+                        );
+                    }
+                }
+            }
+
+            if schedule.failures > 0 {
+                status.failing.push(source.name().to_string());
+            }
+        }
+
+        status
+    }
+}
+
+/// Waypoint sources with their schedules and the waypoints they provided
+///  last
+#[derive(Debug, Default)]
+pub struct WaypointSources {
+    /// The sources, in the order their waypoints are imported
+    #[allow(clippy::type_complexity)]
+    sources: Mutex<
+        Vec<(
+            Box<dyn WaypointSource>,
+            Schedule,
+            Option<(DateTime<Utc>, Vec<(String, WaypointDetails)>)>,
+        )>,
+    >,
+}
+
+impl WaypointSources {
+    /// Add a source refreshed on the provided interval
+    pub fn add(&mut self, source: Box<dyn WaypointSource>, interval: Duration, retry: Duration) {
+        sources_info!(
+            "(add) Waypoint source {} ({}) every {} seconds.",
+            source.name(),
+            source.kind(),
+            interval.as_secs()
+        );
+        self.sources
+            .get_mut()
+            .push((source, Schedule::new(interval, retry), None));
+    }
+
+    /// Returns when the next source is due, if any
+    pub async fn next_due(&self) -> Option<Instant> {
+        self.sources
+            .lock()
+            .await
+            .iter()
+            .map(|(_, schedule, _)| schedule.next)
+            .min()
+    }
+
+    /// Acquires the sources that are due and imports the waypoints of all
+    ///  sources into the store
+    ///
+    /// Waypoints are imported once every source provided them at least
+    ///  once, so waypoints restored from a snapshot are not replaced by a
+    ///  partial set.
+    pub async fn refresh(&self, store: &WaypointStore, quality: &QualityConfig) -> SourceStatus {
+        let mut sources = self.sources.lock().await;
+        let mut status = SourceStatus::default();
+        for (source, schedule, last) in sources.iter_mut() {
+            let now = Instant::now();
+            if schedule.next <= now {
+                match source.acquire().await {
+                    Ok(waypoints) => {
+                        sources_debug!(
+                            "(refresh) Acquired {} waypoint(s) from {}.",
+                            waypoints.len(),
+                            source.name()
+                        );
+                        *last = Some((Utc::now(), waypoints));
+                        schedule.succeeded(now);
+                        status.acquired += 1;
+                    }
+                    Err(e) => {
+                        schedule.failed(now);
+                        sources_error!(
+                            "(refresh) Waypoint source {} failed {} time(s) in a row: {}",
+                            source.name(),
+                            schedule.failures,
+                            e
+                        );
+                    }
+                }
+            }
+
+            if schedule.failures > 0 {
+                status.failing.push(source.name().to_string());
+            }
+        }
+
+        if status.acquired == 0 {
+            return status;
+        }
+
+        let missing: Vec<&str> = sources
+            .iter()
+            .filter(|(_, _, last)| last.is_none())
+            .map(|(source, _, _)| source.name())
+            .collect();
+        if !missing.is_empty() {
+            sources_warn!(
+                "(refresh) Not importing waypoints before {} provided them.",
+                missing.join(", ")
+            );
+            return status;
+        }
+
+        // The waypoints are as old as the source acquired the longest ago
+        let acquired_at = sources
+            .iter()
+            .filter_map(|(_, _, last)| last.as_ref().map(|(at, _)| *at))
+            .min()
+            .unwrap_or_else(Utc::now);
+        let waypoints = sources
+            .iter()
+            .filter_map(|(_, _, last)| last.as_ref().map(|(_, waypoints)| waypoints.clone()))
+            .flatten()
+            .collect();
+        store.import(waypoints, acquired_at, quality).await;
+        status
+    }
+}
+
+/// Returns the configured sources providing the kind of data, by name
+fn configured(config: &Config, data: SourceData) -> Vec<(&String, &SourceConfig)> {
+    let mut sources: Vec<(&String, &SourceConfig)> = config
+        .sources
+        .iter()
+        .filter(|(_, source)| source.data == data)
+        .collect();
+    sources.sort_by(|a, b| a.0.cmp(b.0));
+    sources
+}
+
+/// Default maximum duration of a request in seconds
+const DEFAULT_TIMEOUT_SECONDS: u32 = 30;
+
+/// Default maximum size of a fetched feed in bytes
+const DEFAULT_MAX_BYTES: usize = 16 * 1024 * 1024;
+
+/// A configured feed, read from a file or fetched over HTTP
+#[derive(Debug)]
+enum Feed {
+    /// Read from a file
+    File(file::FileSource),
+
+    /// Fetched over HTTP
+    Http(Box<http::HttpSource>),
+}
+
+/// Returns the fetcher settings of a configuration entry
+fn fetch_config(url: &str, source: &SourceConfig) -> Result<http::FetchConfig, SourceError> {
+    let auth = match (
+        &source.bearer_token,
+        &source.api_key_header,
+        &source.api_key,
+    ) {
+        (Some(token), None, None) => Some(http::FetchAuth::Bearer(token.clone())),
+        (None, Some(header), Some(key)) => Some(http::FetchAuth::ApiKey {
+            header: header.clone(),
+            key: key.clone(),
+        }),
+        (None, None, None) => None,
+        _ => {
+            return Err(SourceError::Configuration(
+                "either a bearer token or an API key header and key are expected".to_string(),
+            ))
+        }
+    };
+
+    let mode = match (&source.record_dir, &source.replay_dir) {
+        (None, None) => http::FetchMode::Network,
+        (Some(dir), None) => http::FetchMode::Record(dir.into()),
+        (None, Some(dir)) => http::FetchMode::Replay(dir.into()),
+        (Some(_), Some(_)) => {
+            return Err(SourceError::Configuration(
+                "a source can not both record and replay".to_string(),
+            ))
+        }
+    };
+
+    Ok(http::FetchConfig {
+        url: url.to_string(),
+        timeout: Duration::from_secs(
+            source.timeout_seconds.unwrap_or(DEFAULT_TIMEOUT_SECONDS) as u64
+        ),
+        max_bytes: source.max_bytes.unwrap_or(DEFAULT_MAX_BYTES),
+        auth,
+        mode,
+    })
+}
+
+/// Creates the feed source of a configuration entry
+fn feed_source(
+    name: &str,
+    source: &SourceConfig,
+    region: &(dyn RegionInterface + Send + Sync),
+) -> Result<Feed, SourceError> {
+    if source.kind == SourceKind::Static {
+        return Err(SourceError::Configuration(
+            "static data is provided by the region".to_string(),
+        ));
+    }
+
+    let converter = region
+        .coordinate_converter(&source.coordinates)
+        .map_err(|e| SourceError::Configuration(e.to_string()))?;
+
+    let origin = FeedOrigin {
+        region: region.get_region().to_string(),
+        kind: source.kind,
+        converter,
+    };
+
+    match (&source.path, &source.url) {
+        (Some(path), None) => Ok(Feed::File(file::FileSource::new(name, origin, path.into()))),
+        (None, Some(url)) => Ok(Feed::Http(Box::new(http::HttpSource::new(
+            name,
+            origin,
+            fetch_config(url, source)?,
+        )))),
+        _ => Err(SourceError::Configuration(
+            "either a path or a URL is expected".to_string(),
+        )),
+    }
+}
+
+/// Returns the refresh interval and retry delay of a configured source
+fn schedule(source: &SourceConfig, default_interval_seconds: u16) -> (Duration, Duration) {
+    let interval = source
+        .interval_seconds
+        .unwrap_or(default_interval_seconds as u32);
+    let retry = source.retry_seconds.unwrap_or(interval);
+    (
+        Duration::from_secs(interval as u64),
+        Duration::from_secs(retry as u64),
+    )
+}
+
+/// Returns the configured file and HTTP restriction feeds, followed by the
+///  region as the `static` source
+///
+/// The kind of a configured feed (`file`, `notam` or `manual`) only sets its
+///  merge priority: NOTAMs have no source of their own but are read from
+///  feeds of kind `notam`, and operator entered restrictions are managed
+///  through the gRPC interface, not as a source. Invalid configuration
+///  entries are logged and skipped.
+pub fn restriction_sources(
+    config: &Config,
+    region: Box<dyn RegionInterface + Send + Sync>,
+) -> RestrictionSources {
+    let mut sources = RestrictionSources::default();
+    for (name, source) in configured(config, SourceData::Restrictions) {
+        match feed_source(name, source, &*region) {
+            Ok(feed) => {
+                let (interval, retry) = schedule(source, config.interval_seconds_refresh_zones);
+                let feed: Box<dyn RestrictionSource> = match feed {
+                    Feed::File(file) => Box::new(file),
+                    Feed::Http(http) => http,
+                };
+                sources.add(feed, interval, retry);
+            }
+            Err(e) => sources_error!("(restriction_sources) Skipping source {}: {}", name, e),
+        }
+    }
+
+    let interval = Duration::from_secs(config.interval_seconds_refresh_zones as u64);
+    sources.add(
+        Box::new(region::RegionSource::new(region)),
+        interval,
+        interval,
+    );
+    sources
+}
+
+/// Returns the region as the `static` source, followed by the configured
+///  file and HTTP waypoint feeds
+///
+/// Invalid configuration entries are logged and skipped.
+pub fn waypoint_sources(
+    config: &Config,
+    region: Box<dyn RegionInterface + Send + Sync>,
+) -> WaypointSources {
+    let mut sources = WaypointSources::default();
+    let mut feeds: Vec<(Box<dyn WaypointSource>, Duration, Duration)> = vec![];
+    for (name, source) in configured(config, SourceData::Waypoints) {
+        match feed_source(name, source, &*region) {
+            Ok(feed) => {
+                let (interval, retry) = schedule(source, config.interval_seconds_refresh_waypoints);
+                let feed: Box<dyn WaypointSource> = match feed {
+                    Feed::File(file) => Box::new(file),
+                    Feed::Http(http) => http,
+                };
+                feeds.push((feed, interval, retry));
+            }
+            Err(e) => sources_error!("(waypoint_sources) Skipping source {}: {}", name, e),
+        }
+    }
+
+    // The region waypoints come first, so they win quality checks
+    let interval = Duration::from_secs(config.interval_seconds_refresh_waypoints as u64);
+    sources.add(
+        Box::new(region::RegionSource::new(region)),
+        interval,
+        interval,
+    );
+    for (feed, interval, retry) in feeds {
+        sources.add(feed, interval, retry);
+    }
+
+    sources
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::region::RegionImpl;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// Source failing on the calls listed
+    #[derive(Debug)]
+    struct FlakySource {
+        calls: AtomicU32,
+        failing: Vec<u32>,
+    }
+
+    #[tonic::async_trait]
+    impl WaypointSource for FlakySource {
+        fn name(&self) -> &str {
+            "flaky"
+        }
+
+        fn kind(&self) -> SourceKind {
+            SourceKind::File
+        }
+
+        async fn acquire(&self) -> Result<Vec<(String, WaypointDetails)>, SourceError> {
+            let call = self.calls.fetch_add(1, Ordering::Relaxed);
+            if self.failing.contains(&call) {
+                return Err(SourceError::Acquisition("unavailable".to_string()));
+            }
+
+            Ok(vec![(
+                "ARROW-NL-FILE-DAMRK".to_string(),
+                WaypointDetails::fix(svc_gis_client_grpc::prelude::gis::Coordinates {
+                    latitude: 52.3745,
+                    longitude: 4.9160,
+                }),
+            )])
+        }
+    }
+
+    fn source(kind: SourceKind) -> SourceConfig {
+        SourceConfig {
+            kind,
+            data: SourceData::Restrictions,
+            path: None,
+            url: None,
+            bearer_token: None,
+            api_key_header: None,
+            api_key: None,
+            timeout_seconds: None,
+            max_bytes: None,
+            record_dir: None,
+            replay_dir: None,
+            coordinates: default_coordinates(),
+            interval_seconds: None,
+            retry_seconds: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_restriction_sources() {
+        crate::get_log_handle().await;
+        ut_info!("(test_restriction_sources) Start.");
+
+        let path = std::env::temp_dir().join(format!(
+            "svc-compliance-source-{}.json",
+            uuid::Uuid::new_v4()
+        ));
+        tokio::fs::write(
+            &path,
+            br#"[{ "key": "A1/24", "vertices": [[52.0, 4.0], [52.1, 4.0], [52.1, 4.1]], "altitude_meters_max": 120.0 }]"#,
+        )
+        .await
+        .unwrap();
+
+        // A recorded feed replayed from a directory
+        let dir =
+            std::env::temp_dir().join(format!("svc-compliance-replay-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        tokio::fs::write(
+            dir.join("events.json"),
+            br#"[{ "key": "PARADE", "vertices": [[52.0, 4.0], [52.1, 4.0], [52.1, 4.1]], "altitude_meters_max": 120.0 }]"#,
+        )
+        .await
+        .unwrap();
+
+        let mut config = Config::default();
+        config.sources.insert(
+            "notams".to_string(),
+            SourceConfig {
+                path: Some(path.to_string_lossy().to_string()),
+                interval_seconds: Some(300),
+                ..source(SourceKind::Notam)
+            },
+        );
+        config.sources.insert(
+            "events".to_string(),
+            SourceConfig {
+                url: Some("http://127.0.0.1:9/events".to_string()),
+                replay_dir: Some(dir.to_string_lossy().to_string()),
+                ..source(SourceKind::File)
+            },
+        );
+        config.sources.insert(
+            "invalid".to_string(),
+            SourceConfig {
+                path: Some(String::new()),
+                ..source(SourceKind::Static)
+            },
+        );
+        config.sources.insert(
+            "ambiguous".to_string(),
+            SourceConfig {
+                path: Some(path.to_string_lossy().to_string()),
+                url: Some("http://127.0.0.1:9/events".to_string()),
+                ..source(SourceKind::File)
+            },
+        );
+
+        let sources = restriction_sources(&config, Box::<RegionImpl>::default());
+        let store = RestrictionStore::default();
+        let status = sources.refresh(&store).await;
+        assert_eq!(status.acquired, 3);
+        assert!(status.is_healthy());
+
+        let feeds: Vec<(SourceKind, String)> = store
+            .feeds()
+            .await
+            .into_iter()
+            .map(|feed| (feed.kind, feed.name))
+            .collect();
+        assert!(feeds.contains(&(SourceKind::Notam, "notams".to_string())));
+        assert!(feeds.contains(&(SourceKind::File, "events".to_string())));
+        let identifier =
+            crate::identifiers::from_key(&RegionImpl::default().region, SourceKind::Notam, "A1/24");
+        assert!(store.get_all().await.contains_key(&identifier));
+
+        // Nothing is due right after a refresh
+        assert_eq!(sources.refresh(&store).await.acquired, 0);
+        assert!(sources.next_due().await.unwrap() > Instant::now());
+
+        // Credentials are left out of the logged configuration
+        let secret = SourceConfig {
+            bearer_token: Some("secret-token".to_string()),
+            ..source(SourceKind::File)
+        };
+        assert!(!format!("{:?}", secret).contains("secret-token"));
+
+        let _ = tokio::fs::remove_file(&path).await;
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+        ut_info!("(test_restriction_sources) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_waypoint_sources() {
+        crate::get_log_handle().await;
+        ut_info!("(test_waypoint_sources) Start.");
+
+        let mut sources = WaypointSources::default();
+        sources.add(
+            Box::new(FlakySource {
+                calls: AtomicU32::new(0),
+                failing: vec![0],
+            }),
+            Duration::from_secs(300),
+            Duration::ZERO,
+        );
+
+        // The failure keeps the restored waypoints
+        let store = WaypointStore::default();
+        let mut restored = HashMap::new();
+        restored.insert(
+            "ARROW-WEG-0".to_string(),
+            WaypointDetails::fix(svc_gis_client_grpc::prelude::gis::Coordinates {
+                latitude: 52.0,
+                longitude: 4.0,
+            }),
+        );
+        store.restore(restored, Utc::now()).await;
+
+        let status = sources.refresh(&store, &QualityConfig::default()).await;
+        assert_eq!(status.failing, vec!["flaky".to_string()]);
+        assert!(store.get_all().await.contains_key("ARROW-WEG-0"));
+
+        // Retried right away, then imported
+        let status = sources.refresh(&store, &QualityConfig::default()).await;
+        assert!(status.is_healthy());
+        assert_eq!(status.acquired, 1);
+        let waypoints = store.get_all().await;
+        assert_eq!(waypoints.len(), 1);
+        assert!(waypoints.contains_key("ARROW-NL-FILE-DAMRK"));
+
+        ut_info!("(test_waypoint_sources) Success.");
+    }
+}
@@ -0,0 +1,63 @@
+//! Data provided by the region implementation itself
+
+use super::{RestrictionSource, SourceError, WaypointSource};
+use crate::region::{RegionInterface, RestrictionDetails, WaypointDetails};
+use crate::restrictions::merge::SourceKind;
+use std::collections::HashMap;
+
+/// Restrictions and waypoints acquired by the region implementation
+pub struct RegionSource {
+    /// The region implementation
+    region: Box<dyn RegionInterface + Send + Sync>,
+}
+
+impl std::fmt::Debug for RegionSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RegionSource")
+            .field("region", &self.region.get_region())
+            .finish()
+    }
+}
+
+impl RegionSource {
+    /// Create a new source acquiring data from the provided region
+    pub fn new(region: Box<dyn RegionInterface + Send + Sync>) -> Self {
+        Self { region }
+    }
+}
+
+#[tonic::async_trait]
+impl RestrictionSource for RegionSource {
+    fn name(&self) -> &str {
+        self.region.get_region()
+    }
+
+    fn kind(&self) -> SourceKind {
+        SourceKind::Static
+    }
+
+    async fn acquire(&self) -> Result<HashMap<String, RestrictionDetails>, SourceError> {
+        self.region
+            .acquire_restrictions()
+            .await
+            .map_err(|e| SourceError::Acquisition(e.to_string()))
+    }
+}
+
+#[tonic::async_trait]
+impl WaypointSource for RegionSource {
+    fn name(&self) -> &str {
+        self.region.get_region()
+    }
+
+    fn kind(&self) -> SourceKind {
+        SourceKind::Static
+    }
+
+    async fn acquire(&self) -> Result<Vec<(String, WaypointDetails)>, SourceError> {
+        self.region
+            .acquire_waypoints()
+            .await
+            .map_err(|e| SourceError::Acquisition(e.to_string()))
+    }
+}
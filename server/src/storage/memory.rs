@@ -0,0 +1,178 @@
+//! Storage kept in memory, for tests
+
+use super::{AuditRecord, AuthorityResponse, ReleaseRecord, Storage, StorageError};
+use crate::flight_plans::FlightPlanRecord;
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+/// Schema version reported by the in-memory storage
+const VERSION: u32 = 1;
+
+/// Contents of the storage
+#[derive(Debug, Default)]
+struct Tables {
+    /// Flight plans by identifier
+    flight_plans: HashMap<String, FlightPlanRecord>,
+
+    /// Answers of the authority, oldest first
+    authority_responses: Vec<AuthorityResponse>,
+
+    /// Releases by flight plan identifier
+    releases: HashMap<String, ReleaseRecord>,
+
+    /// The audit trail, oldest first
+    audit: Vec<AuditRecord>,
+}
+
+/// Storage kept in memory, lost on restart
+#[derive(Debug, Default)]
+pub struct MemoryStorage {
+    /// Contents of the storage
+    tables: RwLock<Tables>,
+
+    /// Whether writes fail, to exercise the handling of storage failures
+    #[cfg(test)]
+    failing: std::sync::atomic::AtomicBool,
+}
+
+impl MemoryStorage {
+    /// Makes writes fail until called again with false
+    #[cfg(test)]
+    pub fn set_failing(&self, failing: bool) {
+        self.failing
+            .store(failing, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    /// Returns an error if writes fail
+    fn writable(&self) -> Result<(), StorageError> {
+        #[cfg(test)]
+        if self.failing.load(std::sync::atomic::Ordering::SeqCst) {
+            return Err(StorageError::Backend("writes are failing".to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+#[tonic::async_trait]
+impl Storage for MemoryStorage {
+    async fn migrate(&self) -> Result<u32, StorageError> {
+        Ok(VERSION)
+    }
+
+    async fn flight_plans(&self) -> Result<Vec<FlightPlanRecord>, StorageError> {
+        Ok(self
+            .tables
+            .read()
+            .await
+            .flight_plans
+            .values()
+            .cloned()
+            .collect())
+    }
+
+    async fn save_flight_plan(&self, record: &FlightPlanRecord) -> Result<(), StorageError> {
+        self.writable()?;
+        self.tables
+            .write()
+            .await
+            .flight_plans
+            .insert(record.flight_plan_id.clone(), record.clone());
+        Ok(())
+    }
+
+    async fn save_flight_plan_and_release(
+        &self,
+        record: &FlightPlanRecord,
+        release: &ReleaseRecord,
+    ) -> Result<(), StorageError> {
+        self.writable()?;
+        let mut tables = self.tables.write().await;
+        tables
+            .flight_plans
+            .insert(record.flight_plan_id.clone(), record.clone());
+        tables
+            .releases
+            .insert(release.flight_plan_id.clone(), release.clone());
+        Ok(())
+    }
+
+    async fn add_authority_response(
+        &self,
+        response: &AuthorityResponse,
+    ) -> Result<(), StorageError> {
+        self.writable()?;
+        self.tables
+            .write()
+            .await
+            .authority_responses
+            .push(response.clone());
+        Ok(())
+    }
+
+    async fn authority_responses(
+        &self,
+        flight_plan_id: &str,
+    ) -> Result<Vec<AuthorityResponse>, StorageError> {
+        Ok(self
+            .tables
+            .read()
+            .await
+            .authority_responses
+            .iter()
+            .filter(|response| response.flight_plan_id == flight_plan_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn save_release(&self, release: &ReleaseRecord) -> Result<(), StorageError> {
+        self.writable()?;
+        self.tables
+            .write()
+            .await
+            .releases
+            .insert(release.flight_plan_id.clone(), release.clone());
+        Ok(())
+    }
+
+    async fn release(&self, flight_plan_id: &str) -> Result<Option<ReleaseRecord>, StorageError> {
+        Ok(self
+            .tables
+            .read()
+            .await
+            .releases
+            .get(flight_plan_id)
+            .cloned())
+    }
+
+    async fn releases(&self) -> Result<Vec<ReleaseRecord>, StorageError> {
+        Ok(self
+            .tables
+            .read()
+            .await
+            .releases
+            .values()
+            .cloned()
+            .collect())
+    }
+
+    async fn add_audit(&self, record: &AuditRecord) -> Result<(), StorageError> {
+        self.writable()?;
+        self.tables.write().await.audit.push(record.clone());
+        Ok(())
+    }
+
+    async fn audit(&self, flight_plan_id: Option<&str>) -> Result<Vec<AuditRecord>, StorageError> {
+        Ok(self
+            .tables
+            .read()
+            .await
+            .audit
+            .iter()
+            .filter(|record| {
+                flight_plan_id.is_none() || record.flight_plan_id.as_deref() == flight_plan_id
+            })
+            .cloned()
+            .collect())
+    }
+}
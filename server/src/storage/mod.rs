@@ -0,0 +1,341 @@
+//! Storage of flight plans, authority responses, releases and audit records
+//!
+//! The [`Storage`] trait is implemented in memory for tests and on SQLite
+//!  for production, selected by `STORAGE__BACKEND`. The SQLite schema is
+//!  migrated when the database is opened at startup, so every pending
+//!  interaction with the authority survives a restart.
+
+#[macro_use]
+pub mod macros;
+pub mod memory;
+pub mod sqlite;
+
+use crate::flight_plans::FlightPlanRecord;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Errors returned by storage backends
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum StorageError {
+    /// The storage could not be opened or migrated
+    #[error("error: Could not open the storage: {0}.")]
+    Open(String),
+
+    /// A record could not be read or written
+    #[error("error: Storage failure: {0}.")]
+    Backend(String),
+
+    /// A stored record could not be decoded
+    #[error("error: Invalid stored record: {0}.")]
+    Corrupt(String),
+}
+
+/// Storage backend
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    /// Kept in memory, lost on restart
+    Memory,
+
+    /// Kept in a SQLite database file
+    #[default]
+    Sqlite,
+}
+
+/// Storage settings
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    /// The backend
+    pub backend: StorageBackend,
+
+    /// Path of the SQLite database
+    pub path: String,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        StorageConfig {
+            backend: StorageBackend::default(),
+            path: String::from("compliance.db"),
+        }
+    }
+}
+
+/// Request made to the authority
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthorityRequest {
+    /// Submission of a flight plan
+    Submission,
+
+    /// Request to release a flight plan
+    Release,
+
+    /// Cancellation of a flight plan
+    Cancellation,
+
+    /// Amendment of a submitted flight plan
+    Amendment,
+
+    /// Closure of a flown flight plan
+    Closure,
+}
+
+impl Display for AuthorityRequest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthorityRequest::Submission => write!(f, "submission"),
+            AuthorityRequest::Release => write!(f, "release"),
+            AuthorityRequest::Cancellation => write!(f, "cancellation"),
+            AuthorityRequest::Amendment => write!(f, "amendment"),
+            AuthorityRequest::Closure => write!(f, "closure"),
+        }
+    }
+}
+
+impl FromStr for AuthorityRequest {
+    type Err = StorageError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "submission" => Ok(AuthorityRequest::Submission),
+            "release" => Ok(AuthorityRequest::Release),
+            "cancellation" => Ok(AuthorityRequest::Cancellation),
+            "amendment" => Ok(AuthorityRequest::Amendment),
+            "closure" => Ok(AuthorityRequest::Closure),
+            _ => Err(StorageError::Corrupt(format!(
+                "unknown authority request {s}"
+            ))),
+        }
+    }
+}
+
+/// Answer of the authority to a request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuthorityResponse {
+    /// Identifier of the flight plan
+    pub flight_plan_id: String,
+
+    /// The request answered
+    pub request: AuthorityRequest,
+
+    /// When the answer was received
+    pub received_at: DateTime<Utc>,
+
+    /// Whether the request was granted
+    pub granted: bool,
+
+    /// Message of the authority, if any
+    pub message: Option<String>,
+
+    /// Reference number assigned by the authority, if any
+    #[serde(default)]
+    pub reference: Option<String>,
+
+    /// Whether the authority decides later
+    #[serde(default)]
+    pub pending: bool,
+}
+
+/// A release granted for a flight plan
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReleaseRecord {
+    /// Identifier of the flight plan
+    pub flight_plan_id: String,
+
+    /// When the release was granted
+    pub released_at: DateTime<Utc>,
+
+    /// When the release stops being valid, if ever
+    pub valid_until: Option<DateTime<Utc>>,
+
+    /// When the release was revoked, if it was
+    pub revoked_at: Option<DateTime<Utc>>,
+
+    /// Why the release was revoked
+    pub revoked_reason: Option<String>,
+
+    /// Signed token proving the release, if one was issued
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+/// An entry of the audit trail
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// When the action happened
+    pub at: DateTime<Utc>,
+
+    /// The flight plan concerned, if any
+    pub flight_plan_id: Option<String>,
+
+    /// What happened, for example `transition`
+    pub action: String,
+
+    /// Details of the action
+    pub detail: String,
+}
+
+/// Persistent state of the service
+#[tonic::async_trait]
+pub trait Storage: fmt::Debug + Send + Sync {
+    /// Brings the schema up to date, returning its version
+    async fn migrate(&self) -> Result<u32, StorageError>;
+
+    /// Returns every flight plan
+    async fn flight_plans(&self) -> Result<Vec<FlightPlanRecord>, StorageError>;
+
+    /// Inserts or replaces a flight plan
+    async fn save_flight_plan(&self, record: &FlightPlanRecord) -> Result<(), StorageError>;
+
+    /// Inserts or replaces a flight plan and its release together, so a
+    ///  transition is never persisted without the release it goes with
+    async fn save_flight_plan_and_release(
+        &self,
+        record: &FlightPlanRecord,
+        release: &ReleaseRecord,
+    ) -> Result<(), StorageError>;
+
+    /// Records an answer of the authority
+    async fn add_authority_response(
+        &self,
+        response: &AuthorityResponse,
+    ) -> Result<(), StorageError>;
+
+    /// Returns the answers of the authority about a flight plan, oldest first
+    async fn authority_responses(
+        &self,
+        flight_plan_id: &str,
+    ) -> Result<Vec<AuthorityResponse>, StorageError>;
+
+    /// Inserts or replaces the release of a flight plan
+    async fn save_release(&self, release: &ReleaseRecord) -> Result<(), StorageError>;
+
+    /// Returns the release of a flight plan, if any
+    async fn release(&self, flight_plan_id: &str) -> Result<Option<ReleaseRecord>, StorageError>;
+
+    /// Returns every release
+    async fn releases(&self) -> Result<Vec<ReleaseRecord>, StorageError>;
+
+    /// Appends an entry to the audit trail
+    async fn add_audit(&self, record: &AuditRecord) -> Result<(), StorageError>;
+
+    /// Returns the audit trail of a flight plan, or the whole trail, oldest
+    ///  first
+    async fn audit(&self, flight_plan_id: Option<&str>) -> Result<Vec<AuditRecord>, StorageError>;
+}
+
+/// Opens the configured storage and migrates its schema
+pub async fn open(config: &StorageConfig) -> Result<Arc<dyn Storage>, StorageError> {
+    let storage: Arc<dyn Storage> = match config.backend {
+        StorageBackend::Memory => {
+            storage_warn!("(open) Using in-memory storage, state is lost on restart.");
+            Arc::new(memory::MemoryStorage::default())
+        }
+        StorageBackend::Sqlite => Arc::new(sqlite::SqliteStorage::open(&config.path).await?),
+    };
+
+    let version = storage.migrate().await?;
+    storage_info!("(open) Storage ready at schema version {}.", version);
+    Ok(storage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flight_plans::FlightPlanState;
+
+    /// Exercises a backend through the trait
+    pub(crate) async fn exercise(storage: &dyn Storage) {
+        let at = Utc::now();
+        let mut plan = FlightPlanRecord::new("FP-1", "{}", at);
+        storage.save_flight_plan(&plan).await.unwrap();
+        plan.transition(FlightPlanState::Submitted, at, None)
+            .unwrap();
+        storage.save_flight_plan(&plan).await.unwrap();
+        assert_eq!(storage.flight_plans().await.unwrap(), vec![plan.clone()]);
+
+        for granted in [false, true] {
+            storage
+                .add_authority_response(&AuthorityResponse {
+                    flight_plan_id: "FP-1".to_string(),
+                    request: AuthorityRequest::Submission,
+                    received_at: at,
+                    granted,
+                    message: None,
+                    reference: granted.then(|| "REF-1".to_string()),
+                    pending: granted,
+                })
+                .await
+                .unwrap();
+        }
+
+        let responses = storage.authority_responses("FP-1").await.unwrap();
+        assert_eq!(responses.len(), 2);
+        assert!(responses[1].granted);
+        assert_eq!(responses[1].reference, Some("REF-1".to_string()));
+        assert!(responses[1].pending);
+        assert!(!responses[0].pending);
+        assert!(storage
+            .authority_responses("FP-2")
+            .await
+            .unwrap()
+            .is_empty());
+
+        let mut release = ReleaseRecord {
+            flight_plan_id: "FP-1".to_string(),
+            released_at: at,
+            valid_until: None,
+            revoked_at: None,
+            revoked_reason: None,
+            token: Some("header.claims.signature".to_string()),
+        };
+        storage.save_release(&release).await.unwrap();
+        release.revoked_at = Some(at);
+        plan.transition(FlightPlanState::Accepted, at, None)
+            .unwrap();
+        storage
+            .save_flight_plan_and_release(&plan, &release)
+            .await
+            .unwrap();
+        assert_eq!(storage.flight_plans().await.unwrap(), vec![plan]);
+        assert_eq!(storage.release("FP-1").await.unwrap(), Some(release));
+        assert_eq!(storage.releases().await.unwrap().len(), 1);
+        assert_eq!(storage.release("FP-2").await.unwrap(), None);
+
+        for flight_plan_id in [Some("FP-1"), None] {
+            storage
+                .add_audit(&AuditRecord {
+                    at,
+                    flight_plan_id: flight_plan_id.map(str::to_string),
+                    action: "test".to_string(),
+                    detail: String::new(),
+                })
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(storage.audit(Some("FP-1")).await.unwrap().len(), 1);
+        assert_eq!(storage.audit(None).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_open() {
+        crate::get_log_handle().await;
+        ut_info!("(test_open) Start.");
+
+        let storage = open(&StorageConfig {
+            backend: StorageBackend::Memory,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        exercise(&*storage).await;
+
+        ut_info!("(test_open) Success.");
+    }
+}
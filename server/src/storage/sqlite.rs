@@ -0,0 +1,416 @@
+//! Storage kept in a SQLite database
+//!
+//! The schema version is kept in `PRAGMA user_version`. Migrations newer
+//!  than the version of the database are applied in a single transaction
+//!  when the service starts. Migrations are only ever appended to
+//!  [`MIGRATIONS`], never changed.
+
+use super::{AuditRecord, AuthorityResponse, ReleaseRecord, Storage, StorageError};
+use crate::flight_plans::FlightPlanRecord;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::sync::{Arc, Mutex};
+
+/// Schema migrations, the version of the schema is the number of migrations
+///  applied
+const MIGRATIONS: &[&str] = &[
+    // 1: flight plans, authority responses, releases and audit trail
+    "CREATE TABLE flight_plans (
+        flight_plan_id TEXT PRIMARY KEY,
+        state TEXT NOT NULL,
+        record TEXT NOT NULL,
+        updated_at TEXT NOT NULL
+    );
+    CREATE TABLE authority_responses (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        flight_plan_id TEXT NOT NULL,
+        request TEXT NOT NULL,
+        received_at TEXT NOT NULL,
+        granted INTEGER NOT NULL,
+        message TEXT
+    );
+    CREATE INDEX authority_responses_flight_plan ON authority_responses (flight_plan_id);
+    CREATE TABLE releases (
+        flight_plan_id TEXT PRIMARY KEY,
+        released_at TEXT NOT NULL,
+        valid_until TEXT,
+        revoked_at TEXT,
+        revoked_reason TEXT
+    );
+    CREATE TABLE audit (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        at TEXT NOT NULL,
+        flight_plan_id TEXT,
+        action TEXT NOT NULL,
+        detail TEXT NOT NULL
+    );
+    CREATE INDEX audit_flight_plan ON audit (flight_plan_id);",
+    // 2: reference numbers assigned by the authority
+    "ALTER TABLE authority_responses ADD COLUMN reference TEXT;",
+    // 3: fingerprints of the flight plan data, to recognize retried submissions
+    "ALTER TABLE flight_plans ADD COLUMN fingerprint TEXT;",
+    // 4: answers of the authority announcing a later decision
+    "ALTER TABLE authority_responses ADD COLUMN pending INTEGER NOT NULL DEFAULT 0;",
+    // 5: signed tokens proving the releases
+    "ALTER TABLE releases ADD COLUMN token TEXT;",
+];
+
+/// Converts a SQLite error
+fn backend(e: rusqlite::Error) -> StorageError {
+    StorageError::Backend(e.to_string())
+}
+
+/// Reads a release from a row of the `releases` table
+fn release_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<ReleaseRecord> {
+    Ok(ReleaseRecord {
+        flight_plan_id: row.get(0)?,
+        released_at: row.get(1)?,
+        valid_until: row.get(2)?,
+        revoked_at: row.get(3)?,
+        revoked_reason: row.get(4)?,
+        token: row.get(5)?,
+    })
+}
+
+/// Inserts or replaces a flight plan
+fn insert_flight_plan(
+    connection: &Connection,
+    record: &FlightPlanRecord,
+) -> Result<(), StorageError> {
+    let contents =
+        serde_json::to_string(record).map_err(|e| StorageError::Backend(e.to_string()))?;
+    connection
+        .execute(
+            "INSERT OR REPLACE INTO flight_plans
+            (flight_plan_id, state, record, updated_at, fingerprint)
+            VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                record.flight_plan_id,
+                record.state.to_string(),
+                contents,
+                record.updated_at(),
+                record.fingerprint
+            ],
+        )
+        .map_err(backend)?;
+    Ok(())
+}
+
+/// Inserts or replaces a release
+fn insert_release(connection: &Connection, release: &ReleaseRecord) -> Result<(), StorageError> {
+    connection
+        .execute(
+            "INSERT OR REPLACE INTO releases
+            (flight_plan_id, released_at, valid_until, revoked_at, revoked_reason, token)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                release.flight_plan_id,
+                release.released_at,
+                release.valid_until,
+                release.revoked_at,
+                release.revoked_reason,
+                release.token
+            ],
+        )
+        .map_err(backend)?;
+    Ok(())
+}
+
+/// Storage kept in a SQLite database
+#[derive(Debug)]
+pub struct SqliteStorage {
+    /// Connection to the database, used from blocking tasks
+    connection: Arc<Mutex<Connection>>,
+}
+
+impl SqliteStorage {
+    /// Opens the database at the provided path, creating it if needed
+    ///
+    /// The schema is not migrated, see [`Storage::migrate`].
+    pub async fn open(path: &str) -> Result<Self, StorageError> {
+        let path = path.to_string();
+        let connection = tokio::task::spawn_blocking(move || Connection::open(path))
+            .await
+            .map_err(|e| StorageError::Open(e.to_string()))?
+            .map_err(|e| StorageError::Open(e.to_string()))?;
+
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    /// Runs the provided function on the connection in a blocking task
+    async fn run<T, F>(&self, f: F) -> Result<T, StorageError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> Result<T, StorageError> + Send + 'static,
+    {
+        let connection = self.connection.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut connection = connection
+                .lock()
+                .map_err(|e| StorageError::Backend(e.to_string()))?;
+            f(&mut connection)
+        })
+        .await
+        .map_err(|e| StorageError::Backend(e.to_string()))?
+    }
+}
+
+#[tonic::async_trait]
+impl Storage for SqliteStorage {
+    async fn migrate(&self) -> Result<u32, StorageError> {
+        self.run(|connection| {
+            let open = |e: rusqlite::Error| StorageError::Open(e.to_string());
+            let version: u32 = connection
+                .query_row("PRAGMA user_version", [], |row| row.get(0))
+                .map_err(open)?;
+            let latest = MIGRATIONS.len() as u32;
+            if version > latest {
+                return Err(StorageError::Open(format!(
+                    "schema version {version} is newer than the supported version {latest}"
+                )));
+            }
+
+            let transaction = connection.transaction().map_err(open)?;
+            for (index, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+                storage_info!("(migrate) Migrating the schema to version {}.", index + 1);
+                transaction.execute_batch(migration).map_err(open)?;
+            }
+
+            transaction
+                .pragma_update(None, "user_version", latest)
+                .map_err(open)?;
+            transaction.commit().map_err(open)?;
+            Ok(latest)
+        })
+        .await
+    }
+
+    async fn flight_plans(&self) -> Result<Vec<FlightPlanRecord>, StorageError> {
+        let records: Vec<String> = self
+            .run(|connection| {
+                let mut statement = connection
+                    .prepare("SELECT record FROM flight_plans")
+                    .map_err(backend)?;
+                let rows = statement.query_map([], |row| row.get(0)).map_err(backend)?;
+                rows.collect::<rusqlite::Result<Vec<String>>>()
+                    .map_err(backend)
+            })
+            .await?;
+
+        records
+            .iter()
+            .map(|record| {
+                serde_json::from_str(record).map_err(|e| StorageError::Corrupt(e.to_string()))
+            })
+            .collect()
+    }
+
+    async fn save_flight_plan(&self, record: &FlightPlanRecord) -> Result<(), StorageError> {
+        let record = record.clone();
+        self.run(move |connection| insert_flight_plan(connection, &record))
+            .await
+    }
+
+    async fn save_flight_plan_and_release(
+        &self,
+        record: &FlightPlanRecord,
+        release: &ReleaseRecord,
+    ) -> Result<(), StorageError> {
+        let record = record.clone();
+        let release = release.clone();
+        self.run(move |connection| {
+            let transaction = connection.transaction().map_err(backend)?;
+            insert_flight_plan(&transaction, &record)?;
+            insert_release(&transaction, &release)?;
+            transaction.commit().map_err(backend)
+        })
+        .await
+    }
+
+    async fn add_authority_response(
+        &self,
+        response: &AuthorityResponse,
+    ) -> Result<(), StorageError> {
+        let response = response.clone();
+        self.run(move |connection| {
+            connection
+                .execute(
+                    "INSERT INTO authority_responses
+                    (flight_plan_id, request, received_at, granted, message, reference, pending)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                    params![
+                        response.flight_plan_id,
+                        response.request.to_string(),
+                        response.received_at,
+                        response.granted,
+                        response.message,
+                        response.reference,
+                        response.pending
+                    ],
+                )
+                .map_err(backend)?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn authority_responses(
+        &self,
+        flight_plan_id: &str,
+    ) -> Result<Vec<AuthorityResponse>, StorageError> {
+        let flight_plan_id = flight_plan_id.to_string();
+        self.run(move |connection| {
+            let mut statement = connection
+                .prepare(
+                    "SELECT flight_plan_id, request, received_at, granted, message, reference,
+                    pending FROM authority_responses WHERE flight_plan_id = ?1 ORDER BY id",
+                )
+                .map_err(backend)?;
+            let rows = statement
+                .query_map([flight_plan_id], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                        row.get(5)?,
+                        row.get(6)?,
+                    ))
+                })
+                .map_err(backend)?;
+
+            rows.map(|row| {
+                let (flight_plan_id, request, received_at, granted, message, reference, pending) =
+                    row.map_err(backend)?;
+                Ok(AuthorityResponse {
+                    flight_plan_id,
+                    request: request.parse()?,
+                    received_at,
+                    granted,
+                    message,
+                    reference,
+                    pending,
+                })
+            })
+            .collect()
+        })
+        .await
+    }
+
+    async fn save_release(&self, release: &ReleaseRecord) -> Result<(), StorageError> {
+        let release = release.clone();
+        self.run(move |connection| insert_release(connection, &release))
+            .await
+    }
+
+    async fn release(&self, flight_plan_id: &str) -> Result<Option<ReleaseRecord>, StorageError> {
+        let flight_plan_id = flight_plan_id.to_string();
+        self.run(move |connection| {
+            connection
+                .query_row(
+                    "SELECT flight_plan_id, released_at, valid_until, revoked_at, revoked_reason, token
+                    FROM releases WHERE flight_plan_id = ?1",
+                    [flight_plan_id],
+                    release_from_row,
+                )
+                .optional()
+                .map_err(backend)
+        })
+        .await
+    }
+
+    async fn releases(&self) -> Result<Vec<ReleaseRecord>, StorageError> {
+        self.run(|connection| {
+            let mut statement = connection
+                .prepare(
+                    "SELECT flight_plan_id, released_at, valid_until, revoked_at, revoked_reason, token
+                    FROM releases",
+                )
+                .map_err(backend)?;
+            let rows = statement.query_map([], release_from_row).map_err(backend)?;
+            rows.collect::<rusqlite::Result<Vec<ReleaseRecord>>>()
+                .map_err(backend)
+        })
+        .await
+    }
+
+    async fn add_audit(&self, record: &AuditRecord) -> Result<(), StorageError> {
+        let record = record.clone();
+        self.run(move |connection| {
+            connection
+                .execute(
+                    "INSERT INTO audit (at, flight_plan_id, action, detail) VALUES (?1, ?2, ?3, ?4)",
+                    params![record.at, record.flight_plan_id, record.action, record.detail],
+                )
+                .map_err(backend)?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn audit(&self, flight_plan_id: Option<&str>) -> Result<Vec<AuditRecord>, StorageError> {
+        let flight_plan_id = flight_plan_id.map(str::to_string);
+        self.run(move |connection| {
+            let mut statement = connection
+                .prepare(
+                    "SELECT at, flight_plan_id, action, detail FROM audit
+                    WHERE ?1 IS NULL OR flight_plan_id = ?1 ORDER BY id",
+                )
+                .map_err(backend)?;
+            let rows = statement
+                .query_map([flight_plan_id], |row| {
+                    Ok(AuditRecord {
+                        at: row.get(0)?,
+                        flight_plan_id: row.get(1)?,
+                        action: row.get(2)?,
+                        detail: row.get(3)?,
+                    })
+                })
+                .map_err(backend)?;
+            rows.collect::<rusqlite::Result<Vec<AuditRecord>>>()
+                .map_err(backend)
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sqlite_storage() {
+        crate::get_log_handle().await;
+        ut_info!("(test_sqlite_storage) Start.");
+
+        let path = std::env::temp_dir().join(format!(
+            "svc-compliance-storage-{}.db",
+            uuid::Uuid::new_v4()
+        ));
+        let path = path.to_string_lossy().to_string();
+
+        let storage = SqliteStorage::open(&path).await.unwrap();
+        assert_eq!(storage.migrate().await.unwrap(), MIGRATIONS.len() as u32);
+        crate::storage::tests::exercise(&storage).await;
+        drop(storage);
+
+        // Reopened after a restart, migrations are not applied twice
+        let storage = SqliteStorage::open(&path).await.unwrap();
+        assert_eq!(storage.migrate().await.unwrap(), MIGRATIONS.len() as u32);
+        assert_eq!(storage.flight_plans().await.unwrap().len(), 1);
+        assert_eq!(storage.authority_responses("FP-1").await.unwrap().len(), 2);
+        assert!(storage
+            .release("FP-1")
+            .await
+            .unwrap()
+            .unwrap()
+            .revoked_at
+            .is_some());
+        assert_eq!(storage.audit(None).await.unwrap().len(), 2);
+
+        let _ = tokio::fs::remove_file(&path).await;
+        ut_info!("(test_sqlite_storage) Success.");
+    }
+}
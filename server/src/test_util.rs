@@ -1,3 +1,38 @@
 use lib_common::log_macros;
 
 log_macros!("ut", "test");
+
+use crate::flight_plans::{FlightPlanState, FlightPlanStore};
+use crate::storage::{AuthorityRequest, AuthorityResponse};
+use chrono::Utc;
+
+/// Records a flight plan awaiting the authority under the provided reference
+pub async fn pending_plan(flight_plans: &FlightPlanStore, flight_plan_id: &str, reference: &str) {
+    flight_plans
+        .submit(flight_plan_id, "{}", Utc::now())
+        .await
+        .unwrap();
+    flight_plans.answered(flight_plan_id).await;
+    flight_plans
+        .storage()
+        .add_authority_response(&AuthorityResponse {
+            flight_plan_id: flight_plan_id.to_string(),
+            request: AuthorityRequest::Submission,
+            received_at: Utc::now(),
+            granted: true,
+            message: None,
+            reference: Some(reference.to_string()),
+            pending: true,
+        })
+        .await
+        .unwrap();
+    flight_plans
+        .transition(
+            flight_plan_id,
+            FlightPlanState::PendingAuthority,
+            Utc::now(),
+            None,
+        )
+        .await
+        .unwrap();
+}
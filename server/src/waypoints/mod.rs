@@ -0,0 +1,459 @@
+//! Shared store of the waypoints known to this service
+
+#[macro_use]
+pub mod macros;
+pub mod quality;
+pub mod zones;
+
+use crate::region::{RestrictionDetails, WaypointDetails};
+use chrono::{DateTime, Utc};
+use quality::{QualityConfig, QualityReport};
+use std::collections::{HashMap, HashSet};
+use tokio::sync::RwLock;
+
+/// A waypoint as listed to clients
+#[derive(Debug, Clone)]
+pub struct ListedWaypoint {
+    /// Waypoint identifier
+    pub identifier: String,
+
+    /// Waypoint details
+    pub details: WaypointDetails,
+
+    /// The active restriction the waypoint is withheld for, if any
+    pub withheld_by: Option<String>,
+}
+
+/// Waypoints to push, see [`WaypointStore::get_available`]
+#[derive(Debug, Clone, Default)]
+pub struct Availability {
+    /// Waypoints valid and outside the active restrictions
+    pub available: HashMap<String, WaypointDetails>,
+
+    /// Withheld waypoints svc-gis may still hold, until
+    ///  [`WaypointStore::removed`] records their removal
+    pub withdrawn: HashMap<String, WaypointDetails>,
+}
+
+/// In memory store of waypoints, shared between the gRPC server and the
+///  refresh loop
+#[derive(Debug, Default)]
+pub struct WaypointStore {
+    /// Waypoints acquired from the regional authority
+    waypoints: RwLock<HashMap<String, WaypointDetails>>,
+
+    /// When the waypoints were acquired, by the source acquired the longest
+    ///  ago
+    acquired_at: RwLock<Option<DateTime<Utc>>>,
+
+    /// Withheld waypoints and the restriction withholding them
+    withheld: RwLock<HashMap<String, String>>,
+
+    /// Withheld waypoints not removed from svc-gis yet
+    unremoved: RwLock<HashSet<String>>,
+
+    /// Quality report of the last import
+    report: RwLock<QualityReport>,
+
+    /// Zones pushed for the altitude limits of the waypoints, see [`zones`]
+    zones: RwLock<HashMap<String, RestrictionDetails>>,
+}
+
+/// Returns the first active restriction, by identifier, covering the waypoint
+///
+/// Waypoints with altitude constraints entirely above or below the
+///  restriction are not affected by it.
+pub fn find_restriction<'a>(
+    waypoint: &WaypointDetails,
+    restrictions: &'a HashMap<String, RestrictionDetails>,
+    at: DateTime<Utc>,
+) -> Option<&'a str> {
+    let mut identifiers: Vec<&String> = restrictions.keys().collect();
+    identifiers.sort();
+    identifiers
+        .into_iter()
+        .find(|identifier| {
+            let restriction = &restrictions[identifier.as_str()];
+            restriction.is_active_at(at)
+                && !matches!(waypoint.altitude_meters_max, Some(max) if max < restriction.altitude_meters_min)
+                && !matches!(waypoint.altitude_meters_min, Some(min) if min > restriction.altitude_meters_max)
+                && restriction.contains(&waypoint.location)
+        })
+        .map(|identifier| identifier.as_str())
+}
+
+impl WaypointStore {
+    /// Replace the waypoints acquired from the regional authority
+    pub async fn set(&self, waypoints: HashMap<String, WaypointDetails>) {
+        waypoints_debug!("(set) Storing {} waypoint(s).", waypoints.len());
+        *self.waypoints.write().await = waypoints;
+    }
+
+    /// Restore waypoints acquired at the provided time from a snapshot,
+    ///  unless waypoints were acquired since the service started
+    ///
+    /// Returns true if the waypoints were restored.
+    pub async fn restore(
+        &self,
+        waypoints: HashMap<String, WaypointDetails>,
+        acquired_at: DateTime<Utc>,
+    ) -> bool {
+        let mut current = self.waypoints.write().await;
+        if !current.is_empty() {
+            return false;
+        }
+
+        waypoints_info!("(restore) Restoring {} waypoint(s).", waypoints.len());
+        *current = waypoints;
+        *self.acquired_at.write().await = Some(acquired_at);
+        true
+    }
+
+    /// Returns when the stored waypoints were acquired, if any were
+    pub async fn acquired_at(&self) -> Option<DateTime<Utc>> {
+        *self.acquired_at.read().await
+    }
+
+    /// Returns all stored waypoints
+    pub async fn get_all(&self) -> HashMap<String, WaypointDetails> {
+        self.waypoints.read().await.clone()
+    }
+
+    /// Check the waypoints acquired from the regional authority at the
+    ///  provided time and store the accepted ones
+    pub async fn import(
+        &self,
+        waypoints: Vec<(String, WaypointDetails)>,
+        acquired_at: DateTime<Utc>,
+        config: &QualityConfig,
+    ) {
+        let (accepted, report) = quality::check(waypoints, config);
+        if !report.issues.is_empty() {
+            waypoints_warn!(
+                "(import) {} of {} waypoint(s) accepted, {} quality issue(s).",
+                report.accepted,
+                report.received,
+                report.issues.len()
+            );
+        }
+
+        self.set(accepted).await;
+        *self.acquired_at.write().await = Some(acquired_at);
+        *self.report.write().await = report;
+    }
+
+    /// Returns the quality report of the last import
+    pub async fn quality_report(&self) -> QualityReport {
+        self.report.read().await.clone()
+    }
+
+    /// Returns the waypoints valid at the provided time
+    pub async fn get_valid(&self, at: DateTime<Utc>) -> HashMap<String, WaypointDetails> {
+        self.waypoints
+            .read()
+            .await
+            .iter()
+            .filter(|(_, waypoint)| waypoint.is_valid_at(at))
+            .map(|(identifier, waypoint)| (identifier.clone(), waypoint.clone()))
+            .collect()
+    }
+
+    /// Returns the waypoints valid at the provided time that are not inside
+    ///  any of the active restrictions
+    ///
+    /// The withheld waypoints are recorded until the next call, so that a
+    ///  waypoint is restored once the restriction covering it expires. The
+    ///  withheld waypoints are returned to be removed from svc-gis on every
+    ///  call, until their removal is recorded.
+    pub async fn get_available(
+        &self,
+        at: DateTime<Utc>,
+        restrictions: &HashMap<String, RestrictionDetails>,
+    ) -> Availability {
+        let mut available = HashMap::new();
+        let mut withheld = HashMap::new();
+        let mut withheld_details = HashMap::new();
+        for (identifier, waypoint) in self.get_valid(at).await {
+            match find_restriction(&waypoint, restrictions, at) {
+                Some(restriction) => {
+                    withheld.insert(identifier.clone(), restriction.to_string());
+                    withheld_details.insert(identifier, waypoint);
+                }
+                None => {
+                    available.insert(identifier, waypoint);
+                }
+            }
+        }
+
+        let mut previous = self.withheld.write().await;
+        let mut unremoved = self.unremoved.write().await;
+        for (identifier, restriction) in withheld.iter() {
+            if !previous.contains_key(identifier) {
+                waypoints_info!(
+                    "(get_available) Withholding waypoint {} inside restriction {}.",
+                    identifier,
+                    restriction
+                );
+                unremoved.insert(identifier.clone());
+            }
+        }
+
+        for identifier in previous.keys() {
+            if !withheld.contains_key(identifier) {
+                waypoints_info!("(get_available) Restoring waypoint {}.", identifier);
+            }
+        }
+
+        // Restored waypoints are pushed again
+        unremoved.retain(|identifier| withheld.contains_key(identifier));
+        let withdrawn = withheld_details
+            .into_iter()
+            .filter(|(identifier, _)| unremoved.contains(identifier))
+            .collect();
+
+        *previous = withheld;
+        Availability {
+            available,
+            withdrawn,
+        }
+    }
+
+    /// Records that the provided withheld waypoints were removed from
+    ///  svc-gis
+    pub async fn removed<'a>(&self, identifiers: impl IntoIterator<Item = &'a String>) {
+        let mut unremoved = self.unremoved.write().await;
+        for identifier in identifiers {
+            unremoved.remove(identifier);
+        }
+    }
+
+    /// Returns the zones to push for the altitude limits of the available
+    ///  waypoints and to close the withheld ones, see [`zones`]
+    ///
+    /// Zones pushed before that are no longer needed are returned ended at
+    ///  the provided time, until [`WaypointStore::zones_pushed`] records
+    ///  that they reached svc-gis.
+    pub async fn waypoint_zones(
+        &self,
+        available: &HashMap<String, WaypointDetails>,
+        withheld: &HashMap<String, WaypointDetails>,
+        at: DateTime<Utc>,
+    ) -> HashMap<String, RestrictionDetails> {
+        let mut zones = zones::altitude_zones(available);
+        zones.extend(zones::closing_zones(withheld));
+        let mut pushed = self.zones.write().await;
+        for (identifier, zone) in pushed.iter() {
+            if zones.contains_key(identifier) {
+                continue;
+            }
+
+            let mut ended = zone.clone();
+            if !matches!(ended.timestamp_end, Some(end) if end <= at) {
+                ended.timestamp_end = Some(at);
+            }
+
+            zones.insert(identifier.clone(), ended);
+        }
+
+        *pushed = zones.clone();
+        zones
+    }
+
+    /// Forgets the zones that ended before the provided time, once they were
+    ///  pushed to svc-gis
+    pub async fn zones_pushed(&self, before: DateTime<Utc>) {
+        self.zones
+            .write()
+            .await
+            .retain(|_, zone| !matches!(zone.timestamp_end, Some(end) if end <= before));
+    }
+
+    /// Returns all waypoints, sorted by identifier
+    pub async fn list(&self) -> Vec<ListedWaypoint> {
+        let withheld = self.withheld.read().await;
+        let mut waypoints: Vec<_> = self
+            .waypoints
+            .read()
+            .await
+            .iter()
+            .map(|(identifier, waypoint)| ListedWaypoint {
+                identifier: identifier.clone(),
+                details: waypoint.clone(),
+                withheld_by: withheld.get(identifier).cloned(),
+            })
+            .collect();
+
+        waypoints.sort_by(|a, b| a.identifier.cmp(&b.identifier));
+        waypoints
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use svc_gis_client_grpc::prelude::gis::Coordinates;
+
+    #[tokio::test]
+    async fn test_waypoint_store() {
+        crate::get_log_handle().await;
+        ut_info!("(test_waypoint_store) Start.");
+
+        let store = WaypointStore::default();
+        let mut expired = WaypointDetails::fix(Coordinates {
+            latitude: 52.3745,
+            longitude: 4.9160,
+        });
+        expired.timestamp_end = Some(Utc::now() - Duration::try_hours(1).unwrap());
+
+        let mut waypoints = HashMap::new();
+        waypoints.insert("B".to_string(), expired);
+        waypoints.insert(
+            "A".to_string(),
+            WaypointDetails::fix(Coordinates {
+                latitude: 52.3749,
+                longitude: 4.9156,
+            }),
+        );
+        store.set(waypoints).await;
+
+        let list = store.list().await;
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].identifier, "A");
+        assert!(list[0].withheld_by.is_none());
+
+        let valid = store.get_valid(Utc::now()).await;
+        assert_eq!(valid.len(), 1);
+        assert!(valid.contains_key("A"));
+
+        ut_info!("(test_waypoint_store) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_withhold_waypoints() {
+        crate::get_log_handle().await;
+        ut_info!("(test_withhold_waypoints) Start.");
+
+        let now = Utc::now();
+        let vertices = vec![
+            (52.3745, 4.9150),
+            (52.3755, 4.9150),
+            (52.3755, 4.9170),
+            (52.3745, 4.9170),
+            (52.3745, 4.9150),
+        ];
+
+        let mut restriction = RestrictionDetails {
+            vertices: vertices
+                .into_iter()
+                .map(|(latitude, longitude)| Coordinates {
+                    latitude,
+                    longitude,
+                })
+                .collect(),
+            timestamp_start: Some(now - Duration::try_minutes(5).unwrap()),
+            timestamp_end: Some(now + Duration::try_minutes(5).unwrap()),
+            zone_type: svc_gis_client_grpc::prelude::gis::ZoneType::Restriction,
+            altitude_meters_min: 0.0,
+            altitude_meters_max: 300.0,
+            notam_id: None,
+        };
+
+        let inside = WaypointDetails::fix(Coordinates {
+            latitude: 52.3749,
+            longitude: 4.9160,
+        });
+
+        let mut above = inside.clone();
+        above.altitude_meters_min = Some(500.0);
+
+        let outside = WaypointDetails::fix(Coordinates {
+            latitude: 52.3800,
+            longitude: 4.9160,
+        });
+
+        let store = WaypointStore::default();
+        let mut waypoints = HashMap::new();
+        waypoints.insert("INSIDE".to_string(), inside);
+        waypoints.insert("ABOVE".to_string(), above);
+        waypoints.insert("OUTSIDE".to_string(), outside);
+        store.set(waypoints).await;
+
+        let mut restrictions = HashMap::new();
+        restrictions.insert("TFR".to_string(), restriction.clone());
+
+        let availability = store.get_available(now, &restrictions).await;
+        assert_eq!(availability.available.len(), 2);
+        assert!(!availability.available.contains_key("INSIDE"));
+        assert_eq!(
+            availability.withdrawn.keys().collect::<Vec<_>>(),
+            vec!["INSIDE"]
+        );
+
+        // Withdrawn until the removal from svc-gis is recorded
+        let availability = store.get_available(now, &restrictions).await;
+        assert_eq!(availability.withdrawn.len(), 1);
+        store.removed(availability.withdrawn.keys()).await;
+        let availability = store.get_available(now, &restrictions).await;
+        assert!(availability.withdrawn.is_empty());
+
+        let list = store.list().await;
+        let listed = list.iter().find(|w| w.identifier == "INSIDE").unwrap();
+        assert_eq!(listed.withheld_by.as_deref(), Some("TFR"));
+
+        // Restored once the restriction expires
+        restriction.timestamp_end = Some(now);
+        restrictions.insert("TFR".to_string(), restriction);
+        let availability = store.get_available(now, &restrictions).await;
+        assert_eq!(availability.available.len(), 3);
+        assert!(availability.withdrawn.is_empty());
+        assert!(store.list().await.iter().all(|w| w.withheld_by.is_none()));
+
+        ut_info!("(test_withhold_waypoints) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_altitude_zones() {
+        crate::get_log_handle().await;
+        ut_info!("(test_altitude_zones) Start.");
+
+        let now = Utc::now();
+        let mut limited = WaypointDetails::fix(Coordinates {
+            latitude: 52.3749,
+            longitude: 4.9160,
+        });
+        limited.altitude_meters_min = Some(100.0);
+        let mut waypoints = HashMap::from([("WP-1".to_string(), limited)]);
+
+        let store = WaypointStore::default();
+        let zones = store.waypoint_zones(&waypoints, &HashMap::new(), now).await;
+        assert_eq!(zones.len(), 1);
+        assert_eq!(zones["WP-1-BELOW"].timestamp_end, None);
+        store.zones_pushed(now).await;
+
+        // Ended once the limit is gone, until the end reached svc-gis
+        waypoints.get_mut("WP-1").unwrap().altitude_meters_min = None;
+        let later = now + Duration::try_minutes(1).unwrap();
+        let zones = store
+            .waypoint_zones(&waypoints, &HashMap::new(), later)
+            .await;
+        assert_eq!(zones["WP-1-BELOW"].timestamp_end, Some(later));
+        let zones = store
+            .waypoint_zones(&waypoints, &HashMap::new(), later)
+            .await;
+        assert_eq!(zones["WP-1-BELOW"].timestamp_end, Some(later));
+        store.zones_pushed(later).await;
+        assert!(store
+            .waypoint_zones(&waypoints, &HashMap::new(), later)
+            .await
+            .is_empty());
+
+        // Withheld waypoints are closed
+        let zones = store
+            .waypoint_zones(&HashMap::new(), &waypoints, later)
+            .await;
+        assert_eq!(zones["WP-1-WITHHELD"].altitude_meters_min, 0.0);
+
+        ut_info!("(test_altitude_zones) Success.");
+    }
+}
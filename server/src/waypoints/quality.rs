@@ -0,0 +1,373 @@
+//! Quality checks on imported waypoints
+//!
+//! Waypoints from the regional authority are checked before they are stored:
+//!  identifiers must follow the naming rules and be unique, and waypoints
+//!  closer to each other than the configured separation are rejected or
+//!  merged into the first occurrence.
+
+use crate::region::utils::LocalProjection;
+use crate::region::WaypointDetails;
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::sync::OnceLock;
+
+/// ICAO five-letter name codes, or identifiers in our own namespace
+const IDENTIFIER_PATTERN: &str = r"^([A-Z]{5}|ARROW(-[A-Z0-9]+)+)$";
+
+/// What to do with a waypoint too close to a preceding one
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NearDuplicateAction {
+    /// Drop the waypoint
+    Reject,
+
+    /// Drop the waypoint, completing the preceding one with its details
+    #[default]
+    Merge,
+}
+
+/// Waypoint quality settings
+#[derive(Debug, Copy, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct QualityConfig {
+    /// Waypoints closer than this distance in meters are near-duplicates
+    pub min_separation_meters: f64,
+
+    /// What to do with near-duplicates
+    pub near_duplicates: NearDuplicateAction,
+}
+
+impl Default for QualityConfig {
+    fn default() -> Self {
+        QualityConfig {
+            min_separation_meters: 10.0,
+            near_duplicates: NearDuplicateAction::default(),
+        }
+    }
+}
+
+/// Kind of quality issue
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum IssueKind {
+    /// Identifier does not follow the naming rules
+    InvalidIdentifier,
+
+    /// Identifier used by several waypoints at different locations
+    IdentifierCollision,
+
+    /// Waypoint within the minimum separation of another waypoint
+    NearDuplicate,
+}
+
+impl IssueKind {
+    /// Returns the name of the kind
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IssueKind::InvalidIdentifier => "invalid_identifier",
+            IssueKind::IdentifierCollision => "identifier_collision",
+            IssueKind::NearDuplicate => "near_duplicate",
+        }
+    }
+}
+
+impl Display for IssueKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// What happened to the waypoint with the issue
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum IssueAction {
+    /// The waypoint was dropped
+    Rejected,
+
+    /// The waypoint was merged into a related waypoint
+    Merged,
+}
+
+impl IssueAction {
+    /// Returns the name of the action
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IssueAction::Rejected => "rejected",
+            IssueAction::Merged => "merged",
+        }
+    }
+}
+
+impl Display for IssueAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A quality issue found on an imported waypoint
+#[derive(Debug, Clone, PartialEq)]
+pub struct QualityIssue {
+    /// Identifier of the waypoint
+    pub identifier: String,
+
+    /// Kind of issue
+    pub kind: IssueKind,
+
+    /// The other waypoint involved, if any
+    pub related: Option<String>,
+
+    /// Distance in meters to the related waypoint, for near-duplicates
+    pub distance_meters: Option<f64>,
+
+    /// What happened to the waypoint
+    pub action: IssueAction,
+}
+
+/// Outcome of checking a set of imported waypoints
+#[derive(Debug, Clone, PartialEq)]
+pub struct QualityReport {
+    /// When the check was performed
+    pub timestamp: DateTime<Utc>,
+
+    /// Number of waypoints received from the source
+    pub received: usize,
+
+    /// Number of waypoints accepted
+    pub accepted: usize,
+
+    /// Issues found, in source order
+    pub issues: Vec<QualityIssue>,
+}
+
+impl Default for QualityReport {
+    fn default() -> Self {
+        QualityReport {
+            timestamp: Utc::now(),
+            received: 0,
+            accepted: 0,
+            issues: vec![],
+        }
+    }
+}
+
+/// Returns true if the identifier follows the naming rules
+pub fn is_valid_identifier(identifier: &str) -> bool {
+    static PATTERN: OnceLock<Option<Regex>> = OnceLock::new();
+
+    // The pattern is a constant, compiling it can not fail
+    PATTERN
+        .get_or_init(|| Regex::new(IDENTIFIER_PATTERN).ok())
+        .as_ref()
+        .is_some_and(|pattern| pattern.is_match(identifier))
+}
+
+/// Distance in meters between two waypoints
+fn distance_meters(a: &WaypointDetails, b: &WaypointDetails) -> f64 {
+    let p = LocalProjection::new(&a.location).to_local(&b.location);
+    (p.x * p.x + p.y * p.y).sqrt()
+}
+
+/// Completes the missing details of a waypoint with those of a duplicate
+fn merge_details(into: &mut WaypointDetails, from: WaypointDetails) {
+    if into.name.is_none() {
+        into.name = from.name;
+    }
+
+    if into.altitude_meters_min.is_none() {
+        into.altitude_meters_min = from.altitude_meters_min;
+    }
+
+    if into.altitude_meters_max.is_none() {
+        into.altitude_meters_max = from.altitude_meters_max;
+    }
+}
+
+/// Checks the waypoints in source order, returning the accepted waypoints
+///  and a report of the issues found
+///
+/// Identifiers used for several different waypoints are rejected
+///  altogether, since there is no telling which one is right. Exact repeats
+///  of a waypoint are silently ignored.
+pub fn check(
+    waypoints: Vec<(String, WaypointDetails)>,
+    config: &QualityConfig,
+) -> (HashMap<String, WaypointDetails>, QualityReport) {
+    let mut report = QualityReport {
+        received: waypoints.len(),
+        ..Default::default()
+    };
+
+    // Identifier checks
+    let mut unique: Vec<(String, WaypointDetails)> = vec![];
+    let mut collisions: Vec<String> = vec![];
+    for (identifier, details) in waypoints {
+        if !is_valid_identifier(&identifier) {
+            report.issues.push(QualityIssue {
+                identifier,
+                kind: IssueKind::InvalidIdentifier,
+                related: None,
+                distance_meters: None,
+                action: IssueAction::Rejected,
+            });
+            continue;
+        }
+
+        match unique.iter().find(|(other, _)| *other == identifier) {
+            Some((_, other)) if *other == details => (),
+            Some(_) => {
+                if !collisions.contains(&identifier) {
+                    collisions.push(identifier);
+                }
+            }
+            None => unique.push((identifier, details)),
+        }
+    }
+
+    unique.retain(|(identifier, _)| !collisions.contains(identifier));
+    report
+        .issues
+        .extend(collisions.into_iter().map(|identifier| QualityIssue {
+            identifier,
+            kind: IssueKind::IdentifierCollision,
+            related: None,
+            distance_meters: None,
+            action: IssueAction::Rejected,
+        }));
+
+    // Separation checks
+    let mut accepted: Vec<(String, WaypointDetails)> = vec![];
+    for (identifier, details) in unique {
+        let nearest = accepted
+            .iter_mut()
+            .map(|(other, other_details)| {
+                let distance = distance_meters(other_details, &details);
+                (other, other_details, distance)
+            })
+            .filter(|(_, _, distance)| *distance < config.min_separation_meters)
+            .min_by(|a, b| a.2.total_cmp(&b.2));
+
+        let Some((related, related_details, distance)) = nearest else {
+            accepted.push((identifier, details));
+            continue;
+        };
+
+        let action = match config.near_duplicates {
+            NearDuplicateAction::Reject => IssueAction::Rejected,
+            NearDuplicateAction::Merge => {
+                merge_details(related_details, details);
+                IssueAction::Merged
+            }
+        };
+
+        report.issues.push(QualityIssue {
+            identifier,
+            kind: IssueKind::NearDuplicate,
+            related: Some(related.clone()),
+            distance_meters: Some(distance),
+            action,
+        });
+    }
+
+    for issue in report.issues.iter() {
+        waypoints_warn!(
+            "(check) Waypoint {} {}: {}{}.",
+            issue.identifier,
+            issue.action,
+            issue.kind,
+            issue
+                .related
+                .as_ref()
+                .map(|related| format!(" of {related}"))
+                .unwrap_or_default()
+        );
+    }
+
+    report.accepted = accepted.len();
+    (accepted.into_iter().collect(), report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svc_gis_client_grpc::prelude::gis::Coordinates;
+
+    fn waypoint(latitude: f64, longitude: f64) -> WaypointDetails {
+        WaypointDetails::fix(Coordinates {
+            latitude,
+            longitude,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_is_valid_identifier() {
+        crate::get_log_handle().await;
+        ut_info!("(test_is_valid_identifier) Start.");
+
+        assert!(is_valid_identifier("DAMRK"));
+        assert!(is_valid_identifier("ARROW-WEG-0"));
+        assert!(is_valid_identifier("ARROW-NL-STATIC-1A2B"));
+        assert!(!is_valid_identifier("DAMRAK"));
+        assert!(!is_valid_identifier("damrk"));
+        assert!(!is_valid_identifier("ARROW-"));
+        assert!(!is_valid_identifier("OTHER-WEG-0"));
+
+        ut_info!("(test_is_valid_identifier) Success.");
+    }
+
+    #[tokio::test]
+    async fn test_check() {
+        crate::get_log_handle().await;
+        ut_info!("(test_check) Start.");
+
+        // About 3 meters from ARROW-WEG-0
+        let mut named = waypoint(52.37452, 4.91603);
+        named.name = Some("DAMRAK".to_string());
+
+        let waypoints = vec![
+            ("ARROW-WEG-0".to_string(), waypoint(52.37450, 4.91600)),
+            ("ARROW-WEG-1".to_string(), named),
+            ("ARROW-WEG-2".to_string(), waypoint(52.38000, 4.92000)),
+            ("ARROW-WEG-2".to_string(), waypoint(52.39000, 4.93000)),
+            ("ARROW-WEG-3".to_string(), waypoint(52.40000, 4.94000)),
+            ("ARROW-WEG-3".to_string(), waypoint(52.40000, 4.94000)),
+            ("wp-4".to_string(), waypoint(52.41000, 4.95000)),
+        ];
+
+        let (accepted, report) = check(waypoints.clone(), &QualityConfig::default());
+        assert_eq!(report.received, 7);
+        assert_eq!(report.accepted, 2);
+        assert_eq!(accepted.len(), 2);
+        assert_eq!(accepted["ARROW-WEG-0"].name, Some("DAMRAK".to_string()));
+        assert!(accepted.contains_key("ARROW-WEG-3"));
+
+        let kinds: Vec<(&str, IssueKind, IssueAction)> = report
+            .issues
+            .iter()
+            .map(|issue| (issue.identifier.as_str(), issue.kind, issue.action))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("wp-4", IssueKind::InvalidIdentifier, IssueAction::Rejected),
+                (
+                    "ARROW-WEG-2",
+                    IssueKind::IdentifierCollision,
+                    IssueAction::Rejected
+                ),
+                ("ARROW-WEG-1", IssueKind::NearDuplicate, IssueAction::Merged),
+            ]
+        );
+        assert_eq!(report.issues[2].related, Some("ARROW-WEG-0".to_string()));
+
+        let config = QualityConfig {
+            near_duplicates: NearDuplicateAction::Reject,
+            ..Default::default()
+        };
+        let (accepted, report) = check(waypoints, &config);
+        assert_eq!(accepted["ARROW-WEG-0"].name, None);
+        assert_eq!(report.issues[2].action, IssueAction::Rejected);
+
+        ut_info!("(test_check) Success.");
+    }
+}
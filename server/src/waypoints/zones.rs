@@ -0,0 +1,151 @@
+//! Zones carrying the altitude limits of waypoints to svc-gis
+//!
+//! svc-gis accepts an identifier and a location per waypoint only, so the
+//!  altitude limits of a waypoint can not be pushed with it (R5). Until they
+//!  can, a constrained waypoint is pushed along with small restriction zones
+//!  around it, closing the altitudes below its minimum and above its
+//!  maximum, so routes through the waypoint keep to its limits.
+//!
+//! svc-gis can not remove waypoints either (R4), so a withheld waypoint it
+//!  still holds is closed at all altitudes the same way.
+
+use crate::region::utils::{LocalPoint, LocalProjection};
+use crate::region::{RestrictionDetails, WaypointDetails};
+use std::collections::HashMap;
+use svc_gis_client_grpc::prelude::gis;
+
+/// Suffix of the zone closing the altitudes below the minimum of a waypoint
+pub const BELOW_SUFFIX: &str = "-BELOW";
+
+/// Suffix of the zone closing the altitudes above the maximum of a waypoint
+pub const ABOVE_SUFFIX: &str = "-ABOVE";
+
+/// Suffix of the zone closing a withheld waypoint
+pub const WITHHELD_SUFFIX: &str = "-WITHHELD";
+
+/// Half the side of the square zones around a waypoint
+const HALF_SIDE_METERS: f64 = 25.0;
+
+/// Ceiling of the zones closing the altitudes above the maximum of a
+///  waypoint
+const CEILING_METERS: f32 = 10_000.0;
+
+/// Returns the closed square around a location
+fn square(location: &gis::Coordinates) -> Vec<gis::Coordinates> {
+    let projection = LocalProjection::new(location);
+    [
+        (-1.0, -1.0),
+        (1.0, -1.0),
+        (1.0, 1.0),
+        (-1.0, 1.0),
+        (-1.0, -1.0),
+    ]
+    .iter()
+    .map(|(x, y)| {
+        projection.to_coordinates(&LocalPoint {
+            x: x * HALF_SIDE_METERS,
+            y: y * HALF_SIDE_METERS,
+        })
+    })
+    .collect()
+}
+
+/// Returns the zones closing the altitudes outside the limits of the
+///  waypoints, by identifier
+///
+/// The zones are valid as long as their waypoint.
+pub fn altitude_zones(
+    waypoints: &HashMap<String, WaypointDetails>,
+) -> HashMap<String, RestrictionDetails> {
+    let mut zones = HashMap::new();
+    for (identifier, waypoint) in waypoints {
+        let zone = |altitude_meters_min, altitude_meters_max| RestrictionDetails {
+            vertices: square(&waypoint.location),
+            timestamp_start: waypoint.timestamp_start,
+            timestamp_end: waypoint.timestamp_end,
+            zone_type: gis::ZoneType::Restriction,
+            altitude_meters_max,
+            altitude_meters_min,
+            notam_id: None,
+        };
+
+        if let Some(min) = waypoint.altitude_meters_min.filter(|min| *min > 0.0) {
+            zones.insert(format!("{identifier}{BELOW_SUFFIX}"), zone(0.0, min));
+        }
+
+        if let Some(max) = waypoint
+            .altitude_meters_max
+            .filter(|max| *max < CEILING_METERS)
+        {
+            zones.insert(
+                format!("{identifier}{ABOVE_SUFFIX}"),
+                zone(max, CEILING_METERS),
+            );
+        }
+    }
+
+    zones
+}
+
+/// Returns the zones closing the withheld waypoints at all altitudes, by
+///  identifier
+pub fn closing_zones(
+    waypoints: &HashMap<String, WaypointDetails>,
+) -> HashMap<String, RestrictionDetails> {
+    waypoints
+        .iter()
+        .map(|(identifier, waypoint)| {
+            (
+                format!("{identifier}{WITHHELD_SUFFIX}"),
+                RestrictionDetails {
+                    vertices: square(&waypoint.location),
+                    timestamp_start: waypoint.timestamp_start,
+                    timestamp_end: waypoint.timestamp_end,
+                    zone_type: gis::ZoneType::Restriction,
+                    altitude_meters_max: CEILING_METERS,
+                    altitude_meters_min: 0.0,
+                    notam_id: None,
+                },
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_altitude_zones() {
+        let location = gis::Coordinates {
+            latitude: 52.37,
+            longitude: 4.89,
+        };
+        let waypoints = HashMap::from([
+            ("FREE".to_string(), WaypointDetails::fix(location)),
+            (
+                "LIMITED".to_string(),
+                WaypointDetails {
+                    altitude_meters_min: Some(150.0),
+                    altitude_meters_max: Some(400.0),
+                    ..WaypointDetails::fix(location)
+                },
+            ),
+        ]);
+
+        let zones = altitude_zones(&waypoints);
+        assert_eq!(zones.len(), 2);
+        let below = &zones["LIMITED-BELOW"];
+        assert_eq!(below.altitude_meters_min, 0.0);
+        assert_eq!(below.altitude_meters_max, 150.0);
+        assert!(below.contains(&location));
+        assert_eq!(below.vertices.len(), 5);
+        let corner = LocalProjection::new(&location).to_local(&below.vertices[2]);
+        assert!((corner.x - HALF_SIDE_METERS).abs() < 0.01);
+        assert!((corner.y - HALF_SIDE_METERS).abs() < 0.01);
+
+        let above = &zones["LIMITED-ABOVE"];
+        assert_eq!(above.altitude_meters_min, 400.0);
+        assert_eq!(above.altitude_meters_max, CEILING_METERS);
+    }
+}
@@ -0,0 +1,593 @@
+//! Decisions of the authority delivered over HTTP
+//!
+//! Authorities that notify decisions instead of being polled for them post
+//!  a JSON [`DecisionNotification`] to the webhook receiver, which runs next
+//!  to the gRPC server. Every notification is signed with a key shared with
+//!  the authority:
+//!
+//! - `X-Signature-Key`: identifier of the key, so keys can be rotated
+//! - `X-Signature-Timestamp`: unix time in seconds the notification was sent
+//! - `X-Signature-Nonce`: a value never reused by the sender
+//! - `X-Signature`: hex encoded HMAC-SHA256 of `{timestamp}.{nonce}.{body}`
+//!
+//! Notifications sent outside of the allowed clock skew are refused, and
+//!  nonces seen within it are remembered so a captured notification can't be
+//!  replayed. The decision is then handed to the [`DecisionResolver`], which
+//!  resolves a flight plan only once. A notification that could not be
+//!  resolved for now, see [`WebhookError::is_temporary`], can be sent again
+//!  with the same nonce.
+
+#[macro_use]
+pub mod macros;
+
+use crate::decisions::DecisionResolver;
+use crate::flight_plans::FlightPlanError;
+use crate::region::AuthorityDecision;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use hmac::{Hmac, Mac};
+use hyper::body::HttpBody;
+use hyper::header::{self, HeaderValue};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// Header holding the identifier of the signing key
+pub const HEADER_KEY: &str = "x-signature-key";
+
+/// Header holding the unix time in seconds the notification was sent
+pub const HEADER_TIMESTAMP: &str = "x-signature-timestamp";
+
+/// Header holding the single use value of the notification
+pub const HEADER_NONCE: &str = "x-signature-nonce";
+
+/// Header holding the signature of the notification
+pub const HEADER_SIGNATURE: &str = "x-signature";
+
+/// Seconds nonces are remembered beyond twice the allowed clock skew, for
+///  timestamps rounded to the second and slow deliveries
+const NONCE_MARGIN_SECONDS: i64 = 60;
+
+/// Webhook receiver settings
+#[derive(Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct WebhookConfig {
+    /// Whether the receiver is started
+    pub enabled: bool,
+
+    /// Port the receiver listens on
+    pub port: u16,
+
+    /// Path notifications are posted to
+    pub path: String,
+
+    /// Shared secrets by key identifier
+    pub keys: HashMap<String, String>,
+
+    /// Largest accepted difference in seconds between the timestamp of a
+    ///  notification and the time it is received
+    pub max_skew_seconds: u32,
+
+    /// Largest accepted body in bytes
+    pub max_bytes: usize,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        WebhookConfig {
+            enabled: false,
+            port: 8080,
+            path: String::from("/authority/decisions"),
+            keys: HashMap::new(),
+            max_skew_seconds: 300,
+            max_bytes: 65536,
+        }
+    }
+}
+
+impl std::fmt::Debug for WebhookConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut keys: Vec<&String> = self.keys.keys().collect();
+        keys.sort();
+
+        f.debug_struct("WebhookConfig")
+            .field("enabled", &self.enabled)
+            .field("port", &self.port)
+            .field("path", &self.path)
+            .field("keys", &keys)
+            .field("max_skew_seconds", &self.max_skew_seconds)
+            .field("max_bytes", &self.max_bytes)
+            .finish()
+    }
+}
+
+/// A decision of the authority as posted to the webhook
+///
+/// The flight plan is identified by its identifier or, for authorities that
+///  only know their own, by the reference returned on the submission.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DecisionNotification {
+    /// Identifier of the flight plan
+    #[serde(default)]
+    pub flight_plan_id: Option<String>,
+
+    /// Reference of the authority
+    #[serde(default)]
+    pub reference: Option<String>,
+
+    /// Whether the flight plan was accepted
+    pub granted: bool,
+
+    /// Explanation of the authority
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+/// Errors from receiving notifications
+#[derive(Debug, thiserror::Error)]
+pub enum WebhookError {
+    /// A signature header is absent or unreadable
+    #[error("missing header: {0}")]
+    MissingHeader(&'static str),
+
+    /// The key identifier is not configured
+    #[error("unknown key: {0}")]
+    UnknownKey(String),
+
+    /// The signature does not match the notification
+    #[error("invalid signature")]
+    InvalidSignature,
+
+    /// The timestamp is outside of the allowed clock skew
+    #[error("timestamp outside of the allowed skew: {0}")]
+    StaleTimestamp(String),
+
+    /// The nonce was already used
+    #[error("replayed nonce: {0}")]
+    Replayed(String),
+
+    /// The body is larger than the limit
+    #[error("body larger than {0} bytes")]
+    TooLarge(usize),
+
+    /// The body is not a notification
+    #[error("invalid notification: {0}")]
+    InvalidNotification(String),
+
+    /// No flight plan awaits a decision under the reference
+    #[error("unknown reference: {0}")]
+    UnknownReference(String),
+
+    /// The decision could not be recorded
+    #[error(transparent)]
+    Decision(#[from] FlightPlanError),
+
+    /// The receiver could not listen on the address
+    #[error("could not bind {0}: {1}")]
+    Bind(SocketAddr, String),
+}
+
+impl WebhookError {
+    /// Returns true if the notification may be resolved when sent again,
+    ///  as its reference may not be recorded yet or the decision could not
+    ///  be persisted
+    pub fn is_temporary(&self) -> bool {
+        matches!(
+            self,
+            WebhookError::UnknownReference(_)
+                | WebhookError::Decision(FlightPlanError::Persistence(_))
+        )
+    }
+
+    /// HTTP status answered for the error
+    pub fn status(&self) -> StatusCode {
+        match self {
+            WebhookError::MissingHeader(_)
+            | WebhookError::UnknownKey(_)
+            | WebhookError::InvalidSignature
+            | WebhookError::StaleTimestamp(_) => StatusCode::UNAUTHORIZED,
+            WebhookError::Replayed(_) => StatusCode::CONFLICT,
+            WebhookError::TooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            WebhookError::InvalidNotification(_) => StatusCode::BAD_REQUEST,
+            WebhookError::UnknownReference(_)
+            | WebhookError::Decision(FlightPlanError::NotFound(_)) => StatusCode::NOT_FOUND,
+            WebhookError::Decision(FlightPlanError::InvalidTransition { .. }) => {
+                StatusCode::CONFLICT
+            }
+            WebhookError::Decision(_) | WebhookError::Bind(..) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Returns the hex encoded signature of a notification
+pub fn signature(secret: &str, timestamp: i64, nonce: &str, body: &[u8]) -> String {
+    hex::encode(mac(secret, timestamp, nonce, body).finalize().into_bytes())
+}
+
+/// Returns the HMAC of a notification, ready to be finalized or verified
+fn mac(secret: &str, timestamp: i64, nonce: &str, body: &[u8]) -> Hmac<Sha256> {
+    // HMAC accepts keys of any length
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("(mac) expect HMAC to accept any key length.");
+    mac.update(format!("{timestamp}.{nonce}.").as_bytes());
+    mac.update(body);
+    mac
+}
+
+/// Reads the body, refusing bodies larger than the limit
+async fn read_body(mut body: Body, max_bytes: usize) -> Result<Vec<u8>, WebhookError> {
+    let mut contents = vec![];
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|e| WebhookError::InvalidNotification(e.to_string()))?;
+        if contents.len() + chunk.len() > max_bytes {
+            return Err(WebhookError::TooLarge(max_bytes));
+        }
+
+        contents.extend_from_slice(&chunk);
+    }
+
+    Ok(contents)
+}
+
+/// Returns the value of a header
+fn header<'a>(request: &'a Request<Body>, name: &'static str) -> Result<&'a str, WebhookError> {
+    request
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty())
+        .ok_or(WebhookError::MissingHeader(name))
+}
+
+/// Answers a notification with a JSON body
+fn respond(status: StatusCode, body: serde_json::Value) -> Response<Body> {
+    let mut response = Response::new(Body::from(body.to_string()));
+    *response.status_mut() = status;
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+    response
+}
+
+/// Receives signed decisions of the authority
+#[derive(Debug)]
+pub struct WebhookReceiver {
+    /// Settings
+    config: WebhookConfig,
+
+    /// Resolver of the notified decisions
+    resolver: Arc<DecisionResolver>,
+
+    /// Time each nonce was received
+    nonces: Mutex<HashMap<String, DateTime<Utc>>>,
+}
+
+impl WebhookReceiver {
+    /// Create a new receiver
+    pub fn new(config: WebhookConfig, resolver: Arc<DecisionResolver>) -> Self {
+        Self {
+            config,
+            resolver,
+            nonces: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Answers a request posted to the receiver
+    pub async fn handle(&self, request: Request<Body>) -> Response<Body> {
+        if request.uri().path() != self.config.path {
+            return respond(
+                StatusCode::NOT_FOUND,
+                serde_json::json!({ "error": "not found" }),
+            );
+        }
+
+        if request.method() != Method::POST {
+            return respond(
+                StatusCode::METHOD_NOT_ALLOWED,
+                serde_json::json!({ "error": "method not allowed" }),
+            );
+        }
+
+        match self.receive(request).await {
+            Ok((flight_plan_id, state)) => respond(
+                StatusCode::OK,
+                serde_json::json!({ "flight_plan_id": flight_plan_id, "state": state }),
+            ),
+            Err(e) => {
+                webhook_warn!("(handle) Refused notification: {}", e);
+                respond(e.status(), serde_json::json!({ "error": e.to_string() }))
+            }
+        }
+    }
+
+    /// Verifies a notification and resolves the flight plan it decides on,
+    ///  returning the identifier and new state of the flight plan
+    async fn receive(&self, request: Request<Body>) -> Result<(String, String), WebhookError> {
+        let key = header(&request, HEADER_KEY)?.to_string();
+        let timestamp = header(&request, HEADER_TIMESTAMP)?.to_string();
+        let nonce = header(&request, HEADER_NONCE)?.to_string();
+        let provided = header(&request, HEADER_SIGNATURE)?.to_string();
+
+        let Some(secret) = self.config.keys.get(&key.to_lowercase()) else {
+            return Err(WebhookError::UnknownKey(key));
+        };
+
+        let now = Utc::now();
+        let skew = Duration::seconds(self.config.max_skew_seconds as i64);
+        let sent_at = timestamp
+            .parse::<i64>()
+            .ok()
+            .and_then(|seconds| Utc.timestamp_opt(seconds, 0).single())
+            .ok_or_else(|| WebhookError::StaleTimestamp(timestamp.clone()))?;
+        if sent_at < now - skew || sent_at > now + skew {
+            return Err(WebhookError::StaleTimestamp(timestamp));
+        }
+
+        let body = read_body(request.into_body(), self.config.max_bytes).await?;
+        let provided = hex::decode(provided).map_err(|_| WebhookError::InvalidSignature)?;
+        mac(secret, sent_at.timestamp(), &nonce, &body)
+            .verify_slice(&provided)
+            .map_err(|_| WebhookError::InvalidSignature)?;
+
+        // Only authentic notifications are remembered, and for as long as
+        //  their timestamp can be accepted
+        {
+            let retention = skew * 2 + Duration::seconds(NONCE_MARGIN_SECONDS);
+            let mut nonces = self.nonces.lock().await;
+            nonces.retain(|_, received_at| *received_at >= now - retention);
+            if nonces.contains_key(&nonce) {
+                return Err(WebhookError::Replayed(nonce));
+            }
+
+            nonces.insert(nonce.clone(), now);
+        }
+
+        let result = self.decide(&body, &key).await;
+        if matches!(&result, Err(e) if e.is_temporary()) {
+            webhook_debug!("(receive) Nonce {} can be used again.", nonce);
+            self.nonces.lock().await.remove(&nonce);
+        }
+
+        result
+    }
+
+    /// Resolves the flight plan an authentic notification decides on,
+    ///  returning the identifier and new state of the flight plan
+    async fn decide(&self, body: &[u8], key: &str) -> Result<(String, String), WebhookError> {
+        let notification: DecisionNotification = serde_json::from_slice(body)
+            .map_err(|e| WebhookError::InvalidNotification(e.to_string()))?;
+
+        let flight_plan_id = match (&notification.flight_plan_id, &notification.reference) {
+            (Some(flight_plan_id), _) => flight_plan_id.clone(),
+            (None, Some(reference)) => self
+                .resolver
+                .find_by_reference(reference)
+                .await?
+                .ok_or_else(|| WebhookError::UnknownReference(reference.clone()))?,
+            (None, None) => {
+                return Err(WebhookError::InvalidNotification(
+                    "neither flight_plan_id nor reference provided".to_string(),
+                ))
+            }
+        };
+
+        let plan = self
+            .resolver
+            .resolve(
+                &flight_plan_id,
+                AuthorityDecision {
+                    granted: notification.granted,
+                    message: notification.message,
+                    reference: notification.reference,
+                },
+            )
+            .await?;
+
+        webhook_info!(
+            "(receive) Decision on {} received with key {}.",
+            flight_plan_id,
+            key
+        );
+
+        Ok((flight_plan_id, plan.state.to_string()))
+    }
+}
+
+/// Starts the receiver on the provided address, returning the address it
+///  listens on
+pub fn serve(
+    receiver: Arc<WebhookReceiver>,
+    address: SocketAddr,
+) -> Result<SocketAddr, WebhookError> {
+    let service = make_service_fn(move |_| {
+        let receiver = receiver.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let receiver = receiver.clone();
+                async move { Ok::<_, Infallible>(receiver.handle(request).await) }
+            }))
+        }
+    });
+
+    let server = Server::try_bind(&address)
+        .map_err(|e| WebhookError::Bind(address, e.to_string()))?
+        .serve(service);
+    let address = server.local_addr();
+
+    webhook_info!("(serve) Receiving authority decisions on {}.", address);
+    tokio::spawn(async move {
+        if let Err(e) = server.await {
+            webhook_error!("(serve) Webhook receiver stopped: {}", e);
+        }
+    });
+
+    Ok(address)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::flight_plans::{FlightPlanState, FlightPlanStore};
+    use crate::test_util::pending_plan;
+
+    const SECRET: &str = "shared-secret";
+
+    /// Starts a receiver on a free local port
+    fn receiver(flight_plans: Arc<FlightPlanStore>) -> SocketAddr {
+        let config = WebhookConfig {
+            enabled: true,
+            keys: HashMap::from([("authority-1".to_string(), SECRET.to_string())]),
+            ..Default::default()
+        };
+        let resolver = Arc::new(DecisionResolver::new(flight_plans, None));
+        let receiver = Arc::new(WebhookReceiver::new(config, resolver));
+        serve(receiver, ([127, 0, 0, 1], 0).into()).unwrap()
+    }
+
+    /// Posts a notification as a stand-in of the authority would
+    async fn post(
+        address: SocketAddr,
+        secret: &str,
+        timestamp: i64,
+        nonce: &str,
+        body: &str,
+    ) -> StatusCode {
+        let request = Request::post(format!("http://{address}/authority/decisions"))
+            .header(HEADER_KEY, "authority-1")
+            .header(HEADER_TIMESTAMP, timestamp.to_string())
+            .header(HEADER_NONCE, nonce)
+            .header(
+                HEADER_SIGNATURE,
+                signature(secret, timestamp, nonce, body.as_bytes()),
+            )
+            .body(Body::from(body.to_string()))
+            .unwrap();
+
+        hyper::Client::new()
+            .request(request)
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[test]
+    fn test_signature() {
+        let signature = signature(SECRET, 1700000000, "n-1", b"{}");
+        assert_eq!(signature.len(), 64);
+        assert!(mac(SECRET, 1700000000, "n-1", b"{}")
+            .verify_slice(&hex::decode(&signature).unwrap())
+            .is_ok());
+        assert!(mac(SECRET, 1700000001, "n-1", b"{}")
+            .verify_slice(&hex::decode(&signature).unwrap())
+            .is_err());
+
+        let config = WebhookConfig {
+            keys: HashMap::from([("authority-1".to_string(), SECRET.to_string())]),
+            ..Default::default()
+        };
+        assert!(!format!("{:?}", config).contains(SECRET));
+    }
+
+    #[tokio::test]
+    async fn test_webhook_receiver() {
+        crate::get_log_handle().await;
+        ut_info!("(test_webhook_receiver) Start.");
+
+        let flight_plans = Arc::new(FlightPlanStore::default());
+        pending_plan(&flight_plans, "FP-1", "REF-1").await;
+        pending_plan(&flight_plans, "FP-2", "REF-2").await;
+        let address = receiver(flight_plans.clone());
+        let now = Utc::now().timestamp();
+        let by_id = r#"{"flight_plan_id":"FP-1","granted":true}"#;
+
+        assert_eq!(
+            post(address, "wrong-secret", now, "n-1", by_id).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            post(address, SECRET, now - 3600, "n-2", by_id).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            flight_plans.get("FP-1").await.unwrap().state,
+            FlightPlanState::PendingAuthority
+        );
+
+        assert_eq!(
+            post(address, SECRET, now, "n-3", by_id).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            flight_plans.get("FP-1").await.unwrap().state,
+            FlightPlanState::Accepted
+        );
+
+        // A notification that can not be resolved yet can be sent again
+        let unknown = r#"{"reference":"REF-9","granted":true}"#;
+        assert_eq!(
+            post(address, SECRET, now, "n-8", unknown).await,
+            StatusCode::NOT_FOUND
+        );
+        pending_plan(&flight_plans, "FP-9", "REF-9").await;
+        assert_eq!(
+            post(address, SECRET, now, "n-8", unknown).await,
+            StatusCode::OK
+        );
+        assert_eq!(
+            post(address, SECRET, now, "n-8", unknown).await,
+            StatusCode::CONFLICT
+        );
+
+        // A replayed nonce is refused, even signed for another flight plan
+        let replayed = r#"{"flight_plan_id":"FP-2","granted":true}"#;
+        assert_eq!(
+            post(address, SECRET, now, "n-3", replayed).await,
+            StatusCode::CONFLICT
+        );
+        assert_eq!(
+            flight_plans.get("FP-2").await.unwrap().state,
+            FlightPlanState::PendingAuthority
+        );
+
+        // Resolved by the reference of the authority
+        let by_reference = r#"{"reference":"REF-2","granted":false,"message":"closed"}"#;
+        assert_eq!(
+            post(address, SECRET, now, "n-4", by_reference).await,
+            StatusCode::OK
+        );
+        let plan = flight_plans.get("FP-2").await.unwrap();
+        assert_eq!(plan.state, FlightPlanState::Rejected);
+        assert_eq!(
+            plan.history.last().unwrap().reason,
+            Some("closed".to_string())
+        );
+
+        assert_eq!(
+            post(address, SECRET, now, "n-5", by_reference).await,
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            post(
+                address,
+                SECRET,
+                now,
+                "n-6",
+                r#"{"flight_plan_id":"FP-3","granted":true}"#
+            )
+            .await,
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            post(address, SECRET, now, "n-7", "not json").await,
+            StatusCode::BAD_REQUEST
+        );
+
+        ut_info!("(test_webhook_receiver) Success.");
+    }
+}
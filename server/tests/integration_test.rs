@@ -31,8 +31,30 @@ async fn test_server_requests_and_logs() {
         let imp = ServerImpl {
             mq_channel: None,
             region: Box::<svc_compliance::region::RegionImpl>::default(),
+            restrictions: std::sync::Arc::new(
+                svc_compliance::restrictions::RestrictionStore::default(),
+            ),
+            waypoints: std::sync::Arc::new(svc_compliance::waypoints::WaypointStore::default()),
+            health: std::sync::Arc::new(svc_compliance::health::HealthState::new(
+                svc_compliance::health::HealthConfig {
+                    require_amqp: false,
+                    ..Default::default()
+                },
+            )),
+            flight_plans: std::sync::Arc::new(
+                svc_compliance::flight_plans::FlightPlanStore::default(),
+            ),
+            release: svc_compliance::releases::ReleaseConfig::default(),
+            safety_buffers: svc_compliance::restrictions::buffer::BufferConfig::default(),
+            release_signer: None,
         };
 
+        // Fresh data accepted by svc-gis
+        imp.health.restrictions_refreshed(chrono::Utc::now()).await;
+        imp.health.waypoints_refreshed(chrono::Utc::now()).await;
+        imp.health.restrictions_pushed(true).await;
+        imp.health.waypoints_pushed(true).await;
+
         let result = imp.is_ready(tonic::Request::new(ReadyRequest {})).await;
         assert!(result.is_ok());
         let result: ReadyResponse = result.unwrap().into_inner();